@@ -1,6 +1,6 @@
-use super::{event::AbiEvent, function::AbiFunction};
+use super::{error::AbiError, event::AbiEvent, function::AbiFunction};
 
-use serde::{ser::SerializeSeq, Serialize, Serializer};
+use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AbiContract {
@@ -9,11 +9,16 @@ pub struct AbiContract {
 
     /// Events emitted from the contract.
     events: Vec<AbiEvent>,
+
+    /// Errors the contract may revert with.
+    errors: Vec<AbiError>,
 }
 
 impl Serialize for AbiContract {
     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
-        let mut seq = s.serialize_seq(Some(self.funcs.len() + self.events.len()))?;
+        let mut seq = s.serialize_seq(Some(
+            self.funcs.len() + self.events.len() + self.errors.len(),
+        ))?;
         for func in &self.funcs {
             seq.serialize_element(func)?;
         }
@@ -22,12 +27,54 @@ impl Serialize for AbiContract {
             seq.serialize_element(event)?;
         }
 
+        for error in &self.errors {
+            seq.serialize_element(error)?;
+        }
+
         seq.end()
     }
 }
 
+impl<'de> Deserialize<'de> for AbiContract {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum AbiEntry {
+            Function(AbiFunction),
+            Event(AbiEvent),
+            Error(AbiError),
+        }
+
+        let mut contract = Self::new(vec![], vec![], vec![]);
+        for entry in Vec::<AbiEntry>::deserialize(d)? {
+            match entry {
+                AbiEntry::Function(func) => contract.funcs.push(func),
+                AbiEntry::Event(event) => contract.events.push(event),
+                AbiEntry::Error(error) => contract.errors.push(error),
+            }
+        }
+        Ok(contract)
+    }
+}
+
 impl AbiContract {
-    pub fn new(funcs: Vec<AbiFunction>, events: Vec<AbiEvent>) -> Self {
-        Self { funcs, events }
+    pub fn new(funcs: Vec<AbiFunction>, events: Vec<AbiEvent>, errors: Vec<AbiError>) -> Self {
+        Self {
+            funcs,
+            events,
+            errors,
+        }
+    }
+
+    pub fn functions(&self) -> &[AbiFunction] {
+        &self.funcs
+    }
+
+    pub fn events(&self) -> &[AbiEvent] {
+        &self.events
+    }
+
+    pub fn errors(&self) -> &[AbiError] {
+        &self.errors
     }
 }
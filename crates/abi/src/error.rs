@@ -0,0 +1,162 @@
+use super::types::AbiType;
+
+use fe_common::utils::keccak;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AbiError {
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    pub name: String,
+    pub inputs: Vec<AbiErrorField>,
+}
+
+impl<'de> Deserialize<'de> for AbiError {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct ErrorEntry {
+            #[serde(rename = "type")]
+            ty: String,
+            name: String,
+            #[serde(default)]
+            inputs: Vec<AbiErrorField>,
+        }
+
+        let entry = ErrorEntry::deserialize(d)?;
+        if entry.ty != "error" {
+            return Err(D::Error::custom(format!(
+                "expected an error, found `{}`",
+                entry.ty
+            )));
+        }
+        Ok(Self::new(entry.name, entry.inputs))
+    }
+}
+
+impl AbiError {
+    pub fn new(name: String, fields: Vec<AbiErrorField>) -> Self {
+        Self {
+            ty: "error",
+            name,
+            inputs: fields,
+        }
+    }
+
+    pub fn selector(&self) -> AbiErrorSelector {
+        AbiErrorSelector::new(self)
+    }
+}
+
+pub struct AbiErrorSelector {
+    selector_sig: String,
+}
+
+impl AbiErrorSelector {
+    pub fn selector_signature(&self) -> &str {
+        &self.selector_sig
+    }
+
+    pub fn selector_raw(&self) -> [u8; 4] {
+        keccak::full_as_bytes(self.selector_sig.as_bytes())[..4]
+            .try_into()
+            .unwrap()
+    }
+
+    /// Returns first 4 bytes of signature hash in hex.
+    pub fn hex(&self) -> String {
+        keccak::partial(self.selector_sig.as_bytes(), 4)
+    }
+
+    fn new(error: &AbiError) -> Self {
+        let selector_sig = format!(
+            "{}({})",
+            error.name,
+            error
+                .inputs
+                .iter()
+                .map(|input| input.ty.selector_type_name())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        Self { selector_sig }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiErrorField {
+    #[serde(default)]
+    pub name: String,
+    #[serde(flatten)]
+    pub ty: AbiType,
+}
+
+impl AbiErrorField {
+    pub fn new(name: String, ty: impl Into<AbiType>) -> Self {
+        Self {
+            name,
+            ty: ty.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_test::{assert_ser_tokens, Token};
+
+    fn test_error() -> AbiError {
+        let field1 = AbiErrorField::new("needed".into(), AbiType::UInt(256));
+        let field2 = AbiErrorField::new("have".into(), AbiType::UInt(256));
+
+        AbiError::new("InsufficientBalance".into(), vec![field1, field2])
+    }
+
+    #[test]
+    fn serialize_error() {
+        let error = test_error();
+
+        assert_ser_tokens(
+            &error,
+            &[
+                Token::Struct {
+                    name: "AbiError",
+                    len: 3,
+                },
+                Token::Str("type"),
+                Token::Str("error"),
+                Token::String("name"),
+                Token::String("InsufficientBalance"),
+                Token::Str("inputs"),
+                Token::Seq { len: Some(2) },
+                Token::Map { len: None },
+                Token::String("name"),
+                Token::String("needed"),
+                Token::String("type"),
+                Token::String("uint256"),
+                Token::MapEnd,
+                Token::Map { len: None },
+                Token::String("name"),
+                Token::String("have"),
+                Token::String("type"),
+                Token::String("uint256"),
+                Token::MapEnd,
+                Token::SeqEnd,
+                Token::StructEnd,
+            ],
+        )
+    }
+
+    #[test]
+    fn error_selector() {
+        let error = test_error();
+
+        let selector = error.selector();
+        debug_assert_eq!(
+            selector.selector_signature(),
+            "InsufficientBalance(uint256,uint256)"
+        );
+        debug_assert_eq!(selector.hex(), "cf479181");
+    }
+}
@@ -1,7 +1,7 @@
 use super::types::AbiType;
 
 use fe_common::utils::keccak;
-use serde::Serialize;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct AbiEvent {
@@ -12,6 +12,30 @@ pub struct AbiEvent {
     pub anonymous: bool,
 }
 
+impl<'de> Deserialize<'de> for AbiEvent {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct EventEntry {
+            #[serde(rename = "type")]
+            ty: String,
+            name: String,
+            #[serde(default)]
+            inputs: Vec<AbiEventField>,
+            #[serde(default)]
+            anonymous: bool,
+        }
+
+        let entry = EventEntry::deserialize(d)?;
+        if entry.ty != "event" {
+            return Err(D::Error::custom(format!(
+                "expected an event, found `{}`",
+                entry.ty
+            )));
+        }
+        Ok(Self::new(entry.name, entry.inputs, entry.anonymous))
+    }
+}
+
 impl AbiEvent {
     pub fn new(name: String, fields: Vec<AbiEventField>, anonymous: bool) -> Self {
         Self {
@@ -60,11 +84,13 @@ impl AbiEventSignature {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AbiEventField {
+    #[serde(default)]
     pub name: String,
     #[serde(flatten)]
     pub ty: AbiType,
+    #[serde(default)]
     pub indexed: bool,
 }
 
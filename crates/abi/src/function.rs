@@ -1,11 +1,11 @@
 use fe_common::utils::keccak;
 
-use serde::Serialize;
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
 use super::types::AbiType;
 
 /// The mutability of a public function.
-#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum StateMutability {
     Pure,
@@ -30,35 +30,93 @@ impl StateMutability {
         // Check ABI conformity
         // See https://github.com/ethereum/fe/issues/558
         //
-        //              no self   |   self   |  mut self  |
-        //           ......................................
-        // no ctx    :    pure    |   view    |  payable  |
-        // ctx       :    view    |   view    |  payable  |
-        // mut ctx   :   payable  |  payable  |  payable  |
+        //              no self   |   self     |  mut self   |
+        //           .........................................
+        // no ctx    :    pure    |   view     | nonpayable  |
+        // ctx       :    view    |   view     | nonpayable  |
+        // mut ctx   : nonpayable | nonpayable | nonpayable  |
+        //
+        // Functions marked `#payable` are `payable` regardless of their params,
+        // see `StateMutability::payable`.
 
         match (self_, ctx) {
             (SelfParam::None, CtxParam::None) => StateMutability::Pure,
             (SelfParam::None, CtxParam::Imm) => StateMutability::View,
-            (SelfParam::None, CtxParam::Mut) => StateMutability::Payable,
+            (SelfParam::None, CtxParam::Mut) => StateMutability::Nonpayable,
             (SelfParam::Imm, CtxParam::None) => StateMutability::View,
             (SelfParam::Imm, CtxParam::Imm) => StateMutability::View,
-            (SelfParam::Imm, CtxParam::Mut) => StateMutability::Payable,
-            (SelfParam::Mut, _) => StateMutability::Payable,
+            (SelfParam::Imm, CtxParam::Mut) => StateMutability::Nonpayable,
+            (SelfParam::Mut, _) => StateMutability::Nonpayable,
+        }
+    }
+
+    /// Returns `Payable` if `payable` is set, `self` otherwise.
+    pub fn payable(self, payable: bool) -> Self {
+        if payable {
+            StateMutability::Payable
+        } else {
+            self
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AbiFunction {
-    #[serde(rename = "type")]
     func_type: AbiFunctionType,
     name: String,
     inputs: Vec<AbiFunctionParamInner>,
     outputs: Vec<AbiFunctionParamInner>,
-    #[serde(rename = "stateMutability")]
     state_mutability: StateMutability,
 }
 
+impl Serialize for AbiFunction {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        // Receive and fallback functions have no name, inputs or outputs.
+        let is_unnamed = matches!(
+            self.func_type,
+            AbiFunctionType::Receive | AbiFunctionType::Fallback
+        );
+
+        let mut s = s.serialize_struct("AbiFunction", if is_unnamed { 2 } else { 5 })?;
+        s.serialize_field("type", &self.func_type)?;
+        if !is_unnamed {
+            s.serialize_field("name", &self.name)?;
+            s.serialize_field("inputs", &self.inputs)?;
+            s.serialize_field("outputs", &self.outputs)?;
+        }
+        s.serialize_field("stateMutability", &self.state_mutability)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for AbiFunction {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        // Receive and fallback functions have no name, inputs or outputs.
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct FunctionEntry {
+            #[serde(rename = "type")]
+            func_type: AbiFunctionType,
+            #[serde(default)]
+            name: String,
+            #[serde(default)]
+            inputs: Vec<AbiFunctionParamInner>,
+            #[serde(default)]
+            outputs: Vec<AbiFunctionParamInner>,
+            state_mutability: StateMutability,
+        }
+
+        let entry = FunctionEntry::deserialize(d)?;
+        Ok(Self {
+            func_type: entry.func_type,
+            name: entry.name,
+            inputs: entry.inputs,
+            outputs: entry.outputs,
+            state_mutability: entry.state_mutability,
+        })
+    }
+}
+
 impl AbiFunction {
     pub fn new(
         func_type: AbiFunctionType,
@@ -84,12 +142,38 @@ impl AbiFunction {
         }
     }
 
+    pub fn func_type(&self) -> AbiFunctionType {
+        self.func_type
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the names and types of the parameters.
+    pub fn inputs(&self) -> impl Iterator<Item = (&str, &AbiType)> {
+        self.inputs
+            .iter()
+            .map(|param| (param.name.as_str(), &param.ty))
+    }
+
+    /// Returns the names and types of the return values.
+    pub fn outputs(&self) -> impl Iterator<Item = (&str, &AbiType)> {
+        self.outputs
+            .iter()
+            .map(|param| (param.name.as_str(), &param.ty))
+    }
+
+    pub fn state_mutability(&self) -> &StateMutability {
+        &self.state_mutability
+    }
+
     pub fn selector(&self) -> AbiFunctionSelector {
         AbiFunctionSelector::new(self)
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AbiFunctionType {
     Function,
@@ -135,8 +219,9 @@ impl AbiFunctionSelector {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct AbiFunctionParamInner {
+    #[serde(default)]
     name: String,
     #[serde(flatten)]
     ty: AbiType,
@@ -153,7 +238,7 @@ mod tests {
     use crate::types::AbiTupleField;
 
     use super::*;
-    use serde_test::{assert_ser_tokens, Token};
+    use serde_test::{assert_de_tokens, assert_ser_tokens, Token};
 
     fn simple_tuple() -> AbiType {
         let u16_ty = AbiType::UInt(16);
@@ -245,6 +330,67 @@ mod tests {
         )
     }
 
+    #[test]
+    fn serialize_receive() {
+        let func = AbiFunction::new(
+            AbiFunctionType::Receive,
+            "__receive__".into(),
+            vec![],
+            None,
+            StateMutability::Payable,
+        );
+
+        assert_ser_tokens(
+            &func,
+            &[
+                Token::Struct {
+                    name: "AbiFunction",
+                    len: 2,
+                },
+                Token::Str("type"),
+                Token::UnitVariant {
+                    name: "AbiFunctionType",
+                    variant: "receive",
+                },
+                Token::Str("stateMutability"),
+                Token::UnitVariant {
+                    name: "StateMutability",
+                    variant: "payable",
+                },
+                Token::StructEnd,
+            ],
+        )
+    }
+
+    #[test]
+    fn deserialize_receive() {
+        let func = AbiFunction::new(
+            AbiFunctionType::Receive,
+            "".into(),
+            vec![],
+            None,
+            StateMutability::Payable,
+        );
+
+        assert_de_tokens(
+            &func,
+            &[
+                Token::Map { len: None },
+                Token::String("type"),
+                Token::UnitVariant {
+                    name: "AbiFunctionType",
+                    variant: "receive",
+                },
+                Token::String("stateMutability"),
+                Token::UnitVariant {
+                    name: "StateMutability",
+                    variant: "payable",
+                },
+                Token::MapEnd,
+            ],
+        )
+    }
+
     #[test]
     fn test_state_mutability() {
         assert_eq!(
@@ -257,7 +403,7 @@ mod tests {
         );
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::None, CtxParam::Mut),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
 
         assert_eq!(
@@ -270,21 +416,30 @@ mod tests {
         );
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::Imm, CtxParam::Mut),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
 
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::Mut, CtxParam::None),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::Mut, CtxParam::Imm),
-            StateMutability::Payable
+            StateMutability::Nonpayable
         );
         assert_eq!(
             StateMutability::from_self_and_ctx_params(SelfParam::Mut, CtxParam::Mut),
+            StateMutability::Nonpayable
+        );
+
+        assert_eq!(
+            StateMutability::View.payable(true),
             StateMutability::Payable
         );
+        assert_eq!(
+            StateMutability::Nonpayable.payable(false),
+            StateMutability::Nonpayable
+        );
 
         let pure_func = test_func(StateMutability::Pure);
         assert_eq!(pure_func.state_mutability, StateMutability::Pure);
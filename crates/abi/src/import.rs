@@ -0,0 +1,392 @@
+//! Generates Fe source for calling an existing contract from its ABI JSON, e.g.
+//! the ABI of a contract written in Solidity.
+
+use std::collections::HashSet;
+
+use serde::{de::Error as _, Deserialize};
+use serde_json::Value;
+
+use crate::{
+    error::AbiError,
+    event::AbiEvent,
+    function::{AbiFunction, AbiFunctionType, StateMutability},
+    types::{AbiTupleField, AbiType},
+};
+
+const KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "break", "const", "continue", "contract", "else", "enum", "false", "fn",
+    "for", "idx", "if", "impl", "in", "let", "match", "mut", "not", "or", "pragma", "pub",
+    "return", "revert", "Self", "self", "struct", "trait", "true", "type", "unsafe", "use",
+    "while",
+];
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// The maximum length of `string` and `bytes` values, which are represented
+    /// by the fixed-size `String<N>` and `Array<u8, N>` types in Fe.
+    pub max_dynamic_len: usize,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            max_dynamic_len: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedInterface {
+    /// Fe source of the interface, along with the structs, events and errors
+    /// it uses.
+    pub source: String,
+
+    /// Describes the ABI entries that can't be represented in Fe, and are
+    /// missing from `source`.
+    pub skipped: Vec<String>,
+}
+
+/// Generates the Fe source of an interface named `name` from a JSON ABI.
+///
+/// `json` is either the ABI array itself, or an object with an `abi` field like
+/// the artifacts of most Solidity toolchains. Constructors, `receive` and
+/// `fallback` functions are ignored. Other entries that can't be represented
+/// in Fe are left out and reported in [`ImportedInterface::skipped`].
+pub fn import_interface(
+    name: &str,
+    json: &str,
+    options: &ImportOptions,
+) -> serde_json::Result<ImportedInterface> {
+    if !is_ident(name) || is_keyword(name) {
+        return Err(serde_json::Error::custom(format!(
+            "`{name}` is not a valid interface name"
+        )));
+    }
+
+    let entries = match serde_json::from_str(json)? {
+        Value::Object(mut artifact) => artifact.remove("abi").unwrap_or(Value::Null),
+        abi => abi,
+    };
+    let entries = match entries {
+        Value::Array(entries) => entries,
+        _ => {
+            return Err(serde_json::Error::custom(
+                "expected an ABI array, or an object with an `abi` array",
+            ))
+        }
+    };
+
+    let mut emitter = Emitter::new(name, options, &entries);
+    for entry in &entries {
+        emitter.entry(entry);
+    }
+    Ok(emitter.finish())
+}
+
+struct Emitter<'a> {
+    name: &'a str,
+    options: &'a ImportOptions,
+    /// Names of the events and errors in the ABI, which tuple structs must not
+    /// use.
+    reserved: HashSet<String>,
+    /// Names of the module items emitted so far.
+    item_names: HashSet<String>,
+    function_names: HashSet<String>,
+    tuple_structs: Vec<(Vec<AbiTupleField>, String)>,
+    structs: Vec<String>,
+    events: Vec<String>,
+    errors: Vec<String>,
+    functions: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl<'a> Emitter<'a> {
+    fn new(name: &'a str, options: &'a ImportOptions, entries: &[Value]) -> Self {
+        let reserved = entries
+            .iter()
+            .filter(|entry| matches!(entry["type"].as_str(), Some("event" | "error")))
+            .filter_map(|entry| entry["name"].as_str())
+            .chain([name])
+            .map(str::to_string)
+            .collect();
+
+        Self {
+            name,
+            options,
+            reserved,
+            item_names: HashSet::from([name.to_string()]),
+            function_names: HashSet::new(),
+            tuple_structs: vec![],
+            structs: vec![],
+            events: vec![],
+            errors: vec![],
+            functions: vec![],
+            skipped: vec![],
+        }
+    }
+
+    fn entry(&mut self, entry: &Value) {
+        let kind = entry["type"].as_str().unwrap_or("function");
+        let name = entry["name"].as_str().unwrap_or_default();
+
+        let result = match kind {
+            "constructor" | "receive" | "fallback" => Ok(()),
+            "function" => AbiFunction::deserialize(entry)
+                .map_err(|err| err.to_string())
+                .and_then(|func| self.function(&func)),
+            "event" => AbiEvent::deserialize(entry)
+                .map_err(|err| err.to_string())
+                .and_then(|event| self.event(&event)),
+            "error" => AbiError::deserialize(entry)
+                .map_err(|err| err.to_string())
+                .and_then(|error| self.error(&error)),
+            _ => Err(format!("unknown ABI entry type `{kind}`")),
+        };
+
+        if let Err(reason) = result {
+            self.skipped.push(format!("{kind} `{name}`: {reason}"));
+        }
+    }
+
+    fn function(&mut self, func: &AbiFunction) -> Result<(), String> {
+        debug_assert_eq!(func.func_type(), AbiFunctionType::Function);
+        let name = func.name();
+        if !is_ident(name) || is_keyword(name) || name.starts_with("__") {
+            return Err(format!("`{name}` is not a valid Fe function name"));
+        }
+        if self.function_names.contains(name) {
+            return Err("overloaded functions are not supported".into());
+        }
+        for (_, ty) in func.inputs().chain(func.outputs()) {
+            check_type(ty)?;
+        }
+
+        let self_param = match func.state_mutability() {
+            StateMutability::Pure | StateMutability::View => "self",
+            StateMutability::Nonpayable | StateMutability::Payable => "mut self",
+        };
+        let params: Vec<_> = func
+            .inputs()
+            .enumerate()
+            .map(|(idx, (param, ty))| {
+                let param = ident(param, "arg", idx);
+                let ty = self.fe_type(ty, &param);
+                format!("{param}: {ty}")
+            })
+            .collect();
+        let mut returns: Vec<_> = func
+            .outputs()
+            .map(|(output, ty)| self.fe_type(ty, output))
+            .collect();
+        let ret = match returns.len() {
+            0 => String::new(),
+            1 => format!(" -> {}", returns.remove(0)),
+            _ => format!(" -> ({})", returns.join(", ")),
+        };
+
+        self.function_names.insert(name.to_string());
+        self.functions.push(format!(
+            "fn {name}({}){ret};",
+            [self_param.to_string()]
+                .into_iter()
+                .chain(params)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        Ok(())
+    }
+
+    fn event(&mut self, event: &AbiEvent) -> Result<(), String> {
+        if event.anonymous {
+            return Err("anonymous events are not supported".into());
+        }
+        self.check_item_name(&event.name)?;
+        for field in &event.inputs {
+            check_type(&field.ty)?;
+        }
+
+        let mut fields = String::new();
+        for (idx, field) in event.inputs.iter().enumerate() {
+            let name = ident(&field.name, "field", idx);
+            let ty = self.fe_type(&field.ty, &name);
+            if field.indexed {
+                fields.push_str("    #indexed\n");
+            }
+            fields.push_str(&format!("    pub {name}: {ty}\n"));
+        }
+
+        self.item_names.insert(event.name.clone());
+        self.events.push(if fields.is_empty() {
+            format!("pub struct {} {{}}", event.name)
+        } else {
+            format!("pub struct {} {{\n{fields}}}", event.name)
+        });
+        Ok(())
+    }
+
+    fn error(&mut self, error: &AbiError) -> Result<(), String> {
+        self.check_item_name(&error.name)?;
+        for field in &error.inputs {
+            check_type(&field.ty)?;
+        }
+
+        let fields: Vec<_> = error
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let name = ident(&field.name, "arg", idx);
+                let ty = self.fe_type(&field.ty, &name);
+                format!("{name}: {ty}")
+            })
+            .collect();
+
+        self.item_names.insert(error.name.clone());
+        self.errors
+            .push(format!("pub error {}({})", error.name, fields.join(", ")));
+        Ok(())
+    }
+
+    fn check_item_name(&self, name: &str) -> Result<(), String> {
+        if !is_ident(name) || is_keyword(name) {
+            Err(format!("`{name}` is not a valid Fe item name"))
+        } else if self.item_names.contains(name) {
+            Err(format!("`{name}` is already defined"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the Fe type of a type accepted by `check_type`. A struct is
+    /// generated for each distinct tuple type, named after `hint`.
+    fn fe_type(&mut self, ty: &AbiType, hint: &str) -> String {
+        match ty {
+            AbiType::UInt(bits) => format!("u{bits}"),
+            AbiType::Int(bits) => format!("i{bits}"),
+            AbiType::Address => "address".into(),
+            AbiType::Bool => "bool".into(),
+            AbiType::Bytes => format!("Array<u8, {}>", self.options.max_dynamic_len),
+            AbiType::String => format!("String<{}>", self.options.max_dynamic_len),
+            AbiType::Array { elem_ty, len } => {
+                format!("Array<{}, {len}>", self.fe_type(elem_ty, hint))
+            }
+            AbiType::Tuple(fields) => self.tuple_struct(fields, hint),
+            AbiType::Function => unreachable!(),
+        }
+    }
+
+    fn tuple_struct(&mut self, fields: &[AbiTupleField], hint: &str) -> String {
+        if let Some((_, name)) = self.tuple_structs.iter().find(|(f, _)| f == fields) {
+            return name.clone();
+        }
+
+        let mut source = String::new();
+        for (idx, field) in fields.iter().enumerate() {
+            let field_name = ident(&field.name, "field", idx);
+            let ty = self.fe_type(&field.ty, &field_name);
+            source.push_str(&format!("    pub {field_name}: {ty}\n"));
+        }
+
+        let base = match camel_case(hint) {
+            base if base.is_empty() || is_keyword(&base) => "Tuple".to_string(),
+            base => base,
+        };
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while self.reserved.contains(&name) || self.item_names.contains(&name) {
+            suffix += 1;
+            name = format!("{base}{suffix}");
+        }
+
+        self.item_names.insert(name.clone());
+        self.tuple_structs.push((fields.to_vec(), name.clone()));
+        self.structs
+            .push(format!("pub struct {name} {{\n{source}}}"));
+        name
+    }
+
+    fn finish(self) -> ImportedInterface {
+        let interface = if self.functions.is_empty() {
+            format!("pub interface {} {{}}", self.name)
+        } else {
+            let functions: String = self
+                .functions
+                .iter()
+                .map(|func| format!("    {func}\n"))
+                .collect();
+            format!("pub interface {} {{\n{functions}}}", self.name)
+        };
+
+        let mut items = self.structs;
+        items.extend(self.events);
+        if !self.errors.is_empty() {
+            items.push(self.errors.join("\n"));
+        }
+        items.push(interface);
+
+        ImportedInterface {
+            source: items.join("\n\n") + "\n",
+            skipped: self.skipped,
+        }
+    }
+}
+
+/// Checks that `ty` can be represented in Fe.
+fn check_type(ty: &AbiType) -> Result<(), String> {
+    match ty {
+        AbiType::UInt(bits) | AbiType::Int(bits)
+            if !matches!(bits, 8 | 16 | 32 | 64 | 128 | 256) =>
+        {
+            Err(format!(
+                "Fe has no equivalent of `{}`",
+                ty.selector_type_name()
+            ))
+        }
+        AbiType::Function => Err("function types are not supported".into()),
+        AbiType::Array { elem_ty, .. } if elem_ty.as_ref() == &AbiType::UInt(8) => {
+            Err("`uint8` arrays are not supported, Fe encodes `Array<u8, N>` as `bytes`".into())
+        }
+        AbiType::Array { elem_ty, .. } => check_type(elem_ty),
+        AbiType::Tuple(fields) if fields.is_empty() => Err("empty tuples are not supported".into()),
+        AbiType::Tuple(fields) => fields.iter().try_for_each(|field| check_type(&field.ty)),
+        _ => Ok(()),
+    }
+}
+
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_keyword(name: &str) -> bool {
+    KEYWORDS.contains(&name)
+}
+
+/// Turns a parameter or field name into a Fe identifier. Unnamed ones are
+/// called `{prefix}{idx}`, and a `_` is appended to keywords and `ctx`, which
+/// can only be used for the `Context` parameter.
+fn ident(name: &str, prefix: &str, idx: usize) -> String {
+    if !is_ident(name) || name == "_" {
+        format!("{prefix}{idx}")
+    } else if is_keyword(name) || name == "ctx" {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+                .collect::<String>()
+        })
+        .collect()
+}
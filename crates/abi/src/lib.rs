@@ -1,4 +1,7 @@
 pub mod contract;
+pub mod error;
 pub mod event;
 pub mod function;
+pub mod import;
+pub mod storage_layout;
 pub mod types;
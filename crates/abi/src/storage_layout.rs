@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Serializer};
+
+/// The storage layout of a contract in the shape of the `storageLayout`
+/// output of solc.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct StorageLayout {
+    /// Fields of the contract in declaration order.
+    pub storage: Vec<StorageItem>,
+
+    /// Definitions of the types referred to by `storage`, keyed by type id.
+    pub types: BTreeMap<String, StorageType>,
+}
+
+/// A contract field, or a member of a struct or tuple type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageItem {
+    pub label: String,
+
+    /// The slot the item starts at. The slot of a member is relative to the
+    /// slot its parent starts at.
+    #[serde(serialize_with = "serialize_decimal")]
+    pub slot: usize,
+
+    /// The byte offset of the item in its slot, counted from the lower-order
+    /// end of the slot.
+    pub offset: usize,
+
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+impl StorageItem {
+    pub fn new(label: String, slot: usize, offset: usize, ty: String) -> Self {
+        Self {
+            label,
+            slot,
+            offset,
+            ty,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageType {
+    pub encoding: StorageEncoding,
+    pub label: String,
+    #[serde(serialize_with = "serialize_decimal")]
+    pub number_of_bytes: usize,
+
+    /// The element type of an array.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+
+    /// The key type of a map.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// The value type of a map.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+
+    /// How the slot of a map value is derived from its key and the slot of
+    /// the map.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashing: Option<String>,
+
+    /// The members of a struct or tuple.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<StorageItem>>,
+}
+
+impl StorageType {
+    pub fn inplace(label: String, number_of_bytes: usize) -> Self {
+        Self {
+            encoding: StorageEncoding::Inplace,
+            label,
+            number_of_bytes,
+            base: None,
+            key: None,
+            value: None,
+            hashing: None,
+            members: None,
+        }
+    }
+
+    pub fn array(label: String, number_of_bytes: usize, base: String) -> Self {
+        Self {
+            base: Some(base),
+            ..Self::inplace(label, number_of_bytes)
+        }
+    }
+
+    pub fn composite(label: String, number_of_bytes: usize, members: Vec<StorageItem>) -> Self {
+        Self {
+            members: Some(members),
+            ..Self::inplace(label, number_of_bytes)
+        }
+    }
+
+    pub fn mapping(
+        label: String,
+        number_of_bytes: usize,
+        key: String,
+        value: String,
+        hashing: String,
+    ) -> Self {
+        Self {
+            encoding: StorageEncoding::Mapping,
+            key: Some(key),
+            value: Some(value),
+            hashing: Some(hashing),
+            ..Self::inplace(label, number_of_bytes)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageEncoding {
+    /// The value is laid out contiguously from the slot of the item.
+    Inplace,
+    /// The values are stored at slots derived from their keys.
+    Mapping,
+}
+
+/// Solc writes slots and sizes as decimal strings because they may not fit
+/// in a JSON number.
+fn serialize_decimal<S: Serializer>(value: &usize, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_test::{assert_ser_tokens, Token};
+
+    #[test]
+    fn serialize_storage_item() {
+        let item = StorageItem::new("owner".into(), 1, 20, "t_address".into());
+
+        assert_ser_tokens(
+            &item,
+            &[
+                Token::Struct {
+                    name: "StorageItem",
+                    len: 4,
+                },
+                Token::Str("label"),
+                Token::String("owner"),
+                Token::Str("slot"),
+                Token::Str("1"),
+                Token::Str("offset"),
+                Token::U64(20),
+                Token::Str("type"),
+                Token::String("t_address"),
+                Token::StructEnd,
+            ],
+        )
+    }
+
+    #[test]
+    fn serialize_mapping_type() {
+        let ty = StorageType::mapping(
+            "Map<address, u256>".into(),
+            32,
+            "t_address".into(),
+            "t_uint256".into(),
+            "hash".into(),
+        );
+
+        assert_ser_tokens(
+            &ty,
+            &[
+                Token::Struct {
+                    name: "StorageType",
+                    len: 6,
+                },
+                Token::Str("encoding"),
+                Token::UnitVariant {
+                    name: "StorageEncoding",
+                    variant: "mapping",
+                },
+                Token::Str("label"),
+                Token::String("Map<address, u256>"),
+                Token::Str("numberOfBytes"),
+                Token::Str("32"),
+                Token::Str("key"),
+                Token::Some,
+                Token::String("t_address"),
+                Token::Str("value"),
+                Token::Some,
+                Token::String("t_uint256"),
+                Token::Str("hashing"),
+                Token::Some,
+                Token::String("hash"),
+                Token::StructEnd,
+            ],
+        )
+    }
+}
@@ -1,4 +1,4 @@
-use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde::{de::Error as _, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AbiType {
@@ -120,6 +120,58 @@ impl AbiType {
         }
     }
 
+    /// Parses an ABI type name, e.g. `uint256` or `tuple[2]`. `components` are
+    /// the fields of the innermost tuple type, if any.
+    ///
+    /// Dynamic arrays and fixed-size byte types (`bytes32` etc.) have no
+    /// equivalent in Fe and are rejected.
+    pub fn from_type_name(name: &str, components: Vec<AbiTupleField>) -> Result<Self, String> {
+        if let Some(prefix) = name.strip_suffix(']') {
+            let (elem, len) = prefix
+                .rsplit_once('[')
+                .ok_or_else(|| format!("invalid type `{name}`"))?;
+            if len.is_empty() {
+                return Err(format!("dynamic array type `{name}` is not supported"));
+            }
+            let len = len
+                .parse()
+                .map_err(|_| format!("invalid array length in `{name}`"))?;
+            let elem_ty = Self::from_type_name(elem, components)?;
+            return Ok(Self::Array {
+                elem_ty: elem_ty.into(),
+                len,
+            });
+        }
+
+        let bits = |digits: &str| -> Result<usize, String> {
+            if digits.is_empty() {
+                return Ok(256);
+            }
+            match digits.parse() {
+                Ok(bits) if bits % 8 == 0 && (8..=256).contains(&bits) => Ok(bits),
+                _ => Err(format!("invalid type `{name}`")),
+            }
+        };
+
+        match name {
+            "address" => Ok(Self::Address),
+            "bool" => Ok(Self::Bool),
+            "function" => Ok(Self::Function),
+            "bytes" => Ok(Self::Bytes),
+            "string" => Ok(Self::String),
+            "tuple" => Ok(Self::Tuple(components)),
+            _ => {
+                if let Some(digits) = name.strip_prefix("uint") {
+                    bits(digits).map(Self::UInt)
+                } else if let Some(digits) = name.strip_prefix("int") {
+                    bits(digits).map(Self::Int)
+                } else {
+                    Err(format!("unsupported type `{name}`"))
+                }
+            }
+        }
+    }
+
     fn serialize_component<S: SerializeMap>(&self, s: &mut S) -> Result<(), S::Error> {
         match self {
             Self::Tuple(entry) => s.serialize_entry("components", entry),
@@ -141,8 +193,24 @@ impl Serialize for AbiType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+impl<'de> Deserialize<'de> for AbiType {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct TypeEntry {
+            #[serde(rename = "type")]
+            name: String,
+            #[serde(default)]
+            components: Vec<AbiTupleField>,
+        }
+
+        let entry = TypeEntry::deserialize(d)?;
+        Self::from_type_name(&entry.name, entry.components).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AbiTupleField {
+    #[serde(default)]
     pub name: String,
     #[serde(flatten)]
     pub ty: AbiType,
@@ -161,7 +229,7 @@ impl AbiTupleField {
 mod tests {
     use super::*;
 
-    use serde_test::{assert_ser_tokens, Token};
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, Token};
 
     #[test]
     fn primitive() {
@@ -371,4 +439,56 @@ mod tests {
             ],
         )
     }
+
+    #[test]
+    fn deserialize_tuple_array() {
+        let field = AbiTupleField::new("field1".into(), AbiType::Int(64));
+        let tuple_array_ty = AbiType::Array {
+            elem_ty: AbiType::Tuple(vec![field]).into(),
+            len: 2,
+        };
+
+        assert_de_tokens(
+            &tuple_array_ty,
+            &[
+                Token::Map { len: None },
+                Token::String("type"),
+                Token::String("tuple[2]"),
+                Token::String("internalType"),
+                Token::String("struct Foo[2]"),
+                Token::String("components"),
+                Token::Seq { len: Some(1) },
+                Token::Map { len: None },
+                Token::String("name"),
+                Token::String("field1"),
+                Token::String("type"),
+                Token::String("int64"),
+                Token::MapEnd,
+                Token::SeqEnd,
+                Token::MapEnd,
+            ],
+        )
+    }
+
+    #[test]
+    fn deserialize_unsupported() {
+        for (name, err) in [
+            (
+                "uint256[]",
+                "dynamic array type `uint256[]` is not supported",
+            ),
+            ("bytes32", "unsupported type `bytes32`"),
+            ("uint7", "invalid type `uint7`"),
+        ] {
+            assert_de_tokens_error::<AbiType>(
+                &[
+                    Token::Map { len: None },
+                    Token::String("type"),
+                    Token::String(name),
+                    Token::MapEnd,
+                ],
+                err,
+            )
+        }
+    }
 }
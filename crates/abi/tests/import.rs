@@ -0,0 +1,54 @@
+use fe_abi::import::{import_interface, ImportOptions};
+use fe_test_files::fixture;
+
+fn import(name: &str, path: &str) -> (String, Vec<String>) {
+    let imported = import_interface(name, fixture(path), &ImportOptions::default())
+        .unwrap_or_else(|err| panic!("failed to import `{path}`: {err}"));
+    (imported.source, imported.skipped)
+}
+
+#[test]
+fn erc20() {
+    let (source, skipped) = import("ERC20", "abi/erc20.json");
+    assert_eq!(source, fixture("abi/erc20.fe"));
+    assert!(skipped.is_empty(), "{skipped:?}");
+}
+
+#[test]
+fn marketplace() {
+    let (source, skipped) = import("Marketplace", "abi/marketplace.json");
+    assert_eq!(source, fixture("abi/marketplace.fe"));
+    assert_eq!(
+        skipped,
+        [
+            "event `Ping`: anonymous events are not supported",
+            "function `buy`: overloaded functions are not supported",
+            "function `cancelAll`: dynamic array type `uint256[]` is not supported",
+            "function `setRoot`: unsupported type `bytes32`",
+            "function `fee`: Fe has no equivalent of `uint24`",
+        ]
+    );
+}
+
+#[test]
+fn max_dynamic_len() {
+    let options = ImportOptions {
+        max_dynamic_len: 32,
+    };
+    let source = import_interface("ERC20", fixture("abi/erc20.json"), &options)
+        .unwrap()
+        .source;
+    assert!(source.contains("fn name(self) -> String<32>;"));
+}
+
+#[test]
+fn invalid_abi() {
+    let options = ImportOptions::default();
+    for (name, json) in [
+        ("Foo", "{\"contractName\": \"Foo\"}"),
+        ("Foo", "[{\"type\": \"function\""),
+        ("contract", "[]"),
+    ] {
+        assert!(import_interface(name, json, &options).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::namespace::types::Base;
+use fe_common::EvmVersion;
 use strum::{AsRefStr, EnumIter, EnumString};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumString, AsRefStr)]
@@ -72,6 +73,9 @@ pub enum Intrinsic {
     __mstore8,        // (p, v) -> ()
     __sload,          // (p)
     __sstore,         // (p, v) -> ()
+    __tload,          // (p)
+    __tstore,         // (p, v) -> ()
+    __mcopy,          // (t, f, s) -> ()
     __msize,          // ()
     __gas,            // ()
     __address,        // ()
@@ -112,6 +116,7 @@ pub enum Intrinsic {
     __coinbase,       // ()
     __timestamp,      // ()
     __number,         // ()
+    __difficulty,     // ()
     __prevrandao,     // ()
     __gaslimit,       // ()
 }
@@ -121,20 +126,20 @@ impl Intrinsic {
         use Intrinsic::*;
         match self {
             __stop | __basefee | __origin | __gasprice | __coinbase | __timestamp | __number
-            | __prevrandao | __gaslimit | __pc | __msize | __gas | __address | __selfbalance
-            | __caller | __callvalue | __calldatasize | __codesize | __returndatasize
-            | __invalid | __chainid => 0,
+            | __difficulty | __prevrandao | __gaslimit | __pc | __msize | __gas | __address
+            | __selfbalance | __caller | __callvalue | __calldatasize | __codesize
+            | __returndatasize | __invalid | __chainid => 0,
 
-            __not | __iszero | __pop | __mload | __balance | __sload | __calldataload
+            __not | __iszero | __pop | __mload | __balance | __sload | __tload | __calldataload
             | __extcodesize | __extcodehash | __selfdestruct | __blockhash => 1,
 
             __add | __sub | __mul | __div | __sdiv | __mod | __smod | __exp | __lt | __gt
             | __slt | __sgt | __eq | __and | __or | __xor | __byte | __shl | __shr | __sar
-            | __signextend | __keccak256 | __mstore | __mstore8 | __sstore | __return
-            | __revert | __log0 => 2,
+            | __signextend | __keccak256 | __mstore | __mstore8 | __sstore | __tstore
+            | __return | __revert | __log0 => 2,
 
-            __addmod | __mulmod | __calldatacopy | __codecopy | __returndatacopy | __create
-            | __log1 => 3,
+            __addmod | __mulmod | __calldatacopy | __codecopy | __returndatacopy | __mcopy
+            | __create | __log1 => 3,
             __extcodecopy | __create2 | __log2 => 4,
             __log3 => 5,
             __delegatecall | __staticcall | __log4 => 6,
@@ -145,10 +150,45 @@ impl Intrinsic {
     pub fn return_type(&self) -> Base {
         use Intrinsic::*;
         match self {
-            __stop | __pop | __mstore | __mstore8 | __sstore | __calldatacopy | __codecopy
-            | __extcodecopy | __returndatacopy | __return | __revert | __selfdestruct
-            | __invalid | __log0 | __log1 | __log2 | __log3 | __log4 => Base::Unit,
+            __stop | __pop | __mstore | __mstore8 | __sstore | __tstore | __mcopy
+            | __calldatacopy | __codecopy | __extcodecopy | __returndatacopy | __return
+            | __revert | __selfdestruct | __invalid | __log0 | __log1 | __log2 | __log3
+            | __log4 => Base::Unit,
             _ => Base::u256(),
         }
     }
+
+    /// The first EVM version that supports the intrinsic, if older versions
+    /// don't.
+    pub fn introduced_in(&self) -> Option<EvmVersion> {
+        use Intrinsic::*;
+        match self {
+            __prevrandao => Some(EvmVersion::Paris),
+            __tload | __tstore | __mcopy => Some(EvmVersion::Cancun),
+            _ => None,
+        }
+    }
+
+    /// The first EVM version that no longer supports the intrinsic, if any.
+    pub fn removed_in(&self) -> Option<EvmVersion> {
+        match self {
+            Intrinsic::__difficulty => Some(EvmVersion::Paris),
+            _ => None,
+        }
+    }
+
+    pub fn is_available(&self, version: EvmVersion) -> bool {
+        self.introduced_in().is_none_or(|since| version >= since)
+            && self.removed_in().is_none_or(|until| version < until)
+    }
+
+    /// The intrinsic that computes the same value on the EVM versions that
+    /// don't support this one.
+    pub fn replacement(&self) -> Option<Intrinsic> {
+        match self {
+            Intrinsic::__difficulty => Some(Intrinsic::__prevrandao),
+            Intrinsic::__prevrandao => Some(Intrinsic::__difficulty),
+            _ => None,
+        }
+    }
 }
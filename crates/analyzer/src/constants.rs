@@ -1,4 +1,20 @@
 pub const EMITTABLE_TRAIT_NAME: &str = "Emittable";
 pub const EMIT_FN_NAME: &str = "emit";
+pub const INIT_FN_NAME: &str = "__init__";
+pub const CALL_FN_NAME: &str = "__call__";
+pub const RECEIVE_FN_NAME: &str = "__receive__";
+pub const FALLBACK_FN_NAME: &str = "__fallback__";
+pub const HOT: &str = "hot";
 pub const INDEXED: &str = "indexed";
 pub const MAX_INDEXED_EVENT_FIELDS: usize = 3;
+pub const PAYABLE: &str = "payable";
+pub const TRANSIENT: &str = "Transient";
+
+/// `true` for the functions that aren't called by name: `__init__`,
+/// `__call__`, `__receive__` and `__fallback__`.
+pub fn is_special_fn_name(name: &str) -> bool {
+    matches!(
+        name,
+        INIT_FN_NAME | CALL_FN_NAME | RECEIVE_FN_NAME | FALLBACK_FN_NAME
+    )
+}
@@ -6,7 +6,7 @@ use crate::{
 };
 
 use crate::namespace::items::{
-    ContractId, DiagnosticSink, FunctionId, FunctionSigId, Item, TraitId,
+    ContractId, DiagnosticSink, FunctionId, FunctionSigId, Item, StructId, TraitId,
 };
 use crate::namespace::types::{Generic, SelfDecl, Type, TypeId};
 use crate::AnalyzerDb;
@@ -162,6 +162,15 @@ pub trait AnalyzerContext {
     /// Returns the `Context` type, if it is defined.
     fn get_context_type(&self) -> Option<TypeId>;
 
+    /// Returns the type that the generic parameter `name` of the enclosing
+    /// struct or enum stands for, if there is such a parameter.
+    fn generic_param_type(&self, name: &str) -> Option<TypeId> {
+        match self.root_item() {
+            Item::Type(def) => def.generic_param_type(self.db(), name),
+            _ => None,
+        }
+    }
+
     fn type_error(
         &self,
         message: &str,
@@ -400,6 +409,10 @@ impl AnalyzerContext for TempContext {
     fn get_context_type(&self) -> Option<TypeId> {
         panic!("TempContext can't resolve Context")
     }
+
+    fn generic_param_type(&self, _name: &str) -> Option<TypeId> {
+        None
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
@@ -593,3 +606,15 @@ pub enum Constant {
     Bool(bool),
     Str(SmolStr),
 }
+
+/// The revert that a test function marked `#test(should_revert)` expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpectedRevert {
+    /// Any revert passes the test.
+    Any,
+    /// A `Panic(uint256)` revert with the given code.
+    Panic(BigInt),
+    /// A revert with an instance of the struct or `error`, whose fields have
+    /// the given values.
+    Error { ty: StructId, fields: Vec<Constant> },
+}
@@ -6,7 +6,7 @@ use crate::namespace::items::{
 };
 use crate::namespace::types::{self, Type, TypeId};
 use crate::{
-    context::{Analysis, Constant, FunctionBody},
+    context::{Analysis, Constant, ExpectedRevert, FunctionBody},
     namespace::items::EnumId,
 };
 use crate::{
@@ -14,7 +14,7 @@ use crate::{
     namespace::items::EnumVariantId,
 };
 use fe_common::db::{SourceDb, SourceDbStorage, Upcast, UpcastMut};
-use fe_common::{SourceFileId, Span};
+use fe_common::{EvmVersion, SourceFileId, Span};
 use fe_parser::ast;
 use indexmap::map::IndexMap;
 use smol_str::SmolStr;
@@ -70,6 +70,10 @@ pub trait AnalyzerDb: SourceDb + Upcast<dyn SourceDb> + UpcastMut<dyn SourceDb>
     // that just need the global entrypoint
     #[salsa::input]
     fn root_ingot(&self) -> IngotId;
+    /// The EVM version that the code is compiled for, which determines the
+    /// intrinsics that are available.
+    #[salsa::input]
+    fn evm_version(&self) -> EvmVersion;
 
     #[salsa::invoke(queries::ingots::ingot_modules)]
     fn ingot_modules(&self, ingot: IngotId) -> Rc<[ModuleId]>;
@@ -134,6 +138,10 @@ pub trait AnalyzerDb: SourceDb + Upcast<dyn SourceDb> + UpcastMut<dyn SourceDb>
     fn contract_init_function(&self, id: ContractId) -> Analysis<Option<FunctionId>>;
     #[salsa::invoke(queries::contracts::contract_call_function)]
     fn contract_call_function(&self, id: ContractId) -> Analysis<Option<FunctionId>>;
+    #[salsa::invoke(queries::contracts::contract_receive_function)]
+    fn contract_receive_function(&self, id: ContractId) -> Analysis<Option<FunctionId>>;
+    #[salsa::invoke(queries::contracts::contract_fallback_function)]
+    fn contract_fallback_function(&self, id: ContractId) -> Analysis<Option<FunctionId>>;
 
     #[salsa::invoke(queries::contracts::contract_all_fields)]
     fn contract_all_fields(&self, id: ContractId) -> Rc<[ContractFieldId]>;
@@ -156,6 +164,8 @@ pub trait AnalyzerDb: SourceDb + Upcast<dyn SourceDb> + UpcastMut<dyn SourceDb>
     fn function_signature(&self, id: FunctionSigId) -> Analysis<Rc<types::FunctionSignature>>;
     #[salsa::invoke(queries::functions::function_body)]
     fn function_body(&self, id: FunctionId) -> Analysis<Rc<FunctionBody>>;
+    #[salsa::invoke(queries::functions::function_expected_revert)]
+    fn function_expected_revert(&self, id: FunctionId) -> Analysis<Option<Rc<ExpectedRevert>>>;
     #[salsa::cycle(queries::functions::function_dependency_graph_cycle)]
     #[salsa::invoke(queries::functions::function_dependency_graph)]
     fn function_dependency_graph(&self, id: FunctionId) -> DepGraphWrapper;
@@ -206,11 +216,11 @@ pub trait AnalyzerDb: SourceDb + Upcast<dyn SourceDb> + UpcastMut<dyn SourceDb>
 
     // Type
     #[salsa::invoke(queries::types::all_impls)]
-    fn all_impls(&self, ty: TypeId) -> Rc<[ImplId]>;
+    fn all_impls(&self, ty: TypeId, ingot: IngotId) -> Rc<[ImplId]>;
     #[salsa::invoke(queries::types::impl_for)]
-    fn impl_for(&self, ty: TypeId, treit: TraitId) -> Option<ImplId>;
+    fn impl_for(&self, ty: TypeId, treit: TraitId, ingot: IngotId) -> Option<ImplId>;
     #[salsa::invoke(queries::types::function_sigs)]
-    fn function_sigs(&self, ty: TypeId, name: SmolStr) -> Rc<[FunctionSigId]>;
+    fn function_sigs(&self, ty: TypeId, name: SmolStr, ingot: IngotId) -> Rc<[FunctionSigId]>;
 
     // Type alias
     #[salsa::invoke(queries::types::type_alias_type)]
@@ -219,12 +229,21 @@ pub trait AnalyzerDb: SourceDb + Upcast<dyn SourceDb> + UpcastMut<dyn SourceDb>
 }
 
 #[salsa::database(AnalyzerDbStorage, SourceDbStorage)]
-#[derive(Default)]
 pub struct TestDb {
     storage: salsa::Storage<TestDb>,
 }
 impl salsa::Database for TestDb {}
 
+impl Default for TestDb {
+    fn default() -> Self {
+        let mut db = Self {
+            storage: Default::default(),
+        };
+        db.set_evm_version(EvmVersion::default());
+        db
+    }
+}
+
 impl Upcast<dyn SourceDb> for TestDb {
     fn upcast(&self) -> &(dyn SourceDb + 'static) {
         self
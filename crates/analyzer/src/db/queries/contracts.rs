@@ -1,15 +1,20 @@
+use crate::constants::{is_special_fn_name, HOT, PAYABLE, TRANSIENT};
 use crate::context::AnalyzerContext;
 use crate::db::{Analysis, AnalyzerDb};
+use crate::display::Displayable;
 use crate::errors;
 use crate::namespace::items::{
     self, ContractFieldId, ContractId, DepGraph, DepGraphWrapper, DepLocality, FunctionId, Item,
     TypeDef,
 };
 use crate::namespace::scopes::ItemScope;
-use crate::namespace::types::{self, Type};
+use crate::namespace::types::{self, TraitOrType, Type};
+use crate::traversal::call_args::validate_arg_count;
 use crate::traversal::types::type_desc;
 use fe_common::diagnostics::Label;
+use fe_common::{EvmVersion, Spanned};
 use fe_parser::ast;
+use fe_parser::node::Node;
 use indexmap::map::{Entry, IndexMap};
 use smol_str::SmolStr;
 use std::rc::Rc;
@@ -20,13 +25,16 @@ pub fn contract_all_functions(db: &dyn AnalyzerDb, contract: ContractId) -> Rc<[
     let module = contract.module(db);
     let body = &contract.data(db).ast.kind.body;
     body.iter()
-        .map(|stmt| match stmt {
-            ast::ContractStmt::Function(node) => db.intern_function(Rc::new(items::Function::new(
-                db,
-                node,
-                Some(Item::Type(TypeDef::Contract(contract))),
-                module,
-            ))),
+        .filter_map(|stmt| match stmt {
+            ast::ContractStmt::Function(node) => {
+                Some(db.intern_function(Rc::new(items::Function::new(
+                    db,
+                    node,
+                    Some(Item::Type(TypeDef::Contract(contract))),
+                    module,
+                ))))
+            }
+            ast::ContractStmt::Attribute(_) => None,
         })
         .collect()
 }
@@ -41,7 +49,76 @@ pub fn contract_function_map(
     for func in db.contract_all_functions(contract).iter() {
         let def = &func.data(db).ast;
         let def_name = def.name();
-        if def_name == "__init__" || def_name == "__call__" {
+
+        let is_dispatched = func.is_public(db) && !is_special_fn_name(def_name);
+        for attr in func.contract_attributes(db) {
+            let is_valid = match attr.kind.as_str() {
+                HOT => is_dispatched,
+                PAYABLE => is_dispatched || def_name == "__init__" || def_name == "__fallback__",
+                _ => {
+                    scope.error(
+                        "Invalid attribute",
+                        attr.span,
+                        "illegal name. Only `hot` and `payable` supported.",
+                    );
+                    continue;
+                }
+            };
+            if !is_valid {
+                let message = if attr.kind == PAYABLE {
+                    "`payable` can only be applied to `__init__`, `__fallback__` and functions called through the dispatcher"
+                } else {
+                    "`hot` can only be applied to functions called through the dispatcher"
+                };
+                let reason = if def_name == "__receive__" && attr.kind == PAYABLE {
+                    "`__receive__` is always payable".to_string()
+                } else if func.is_public(db) {
+                    format!("`{def_name}` isn't called through the dispatcher")
+                } else {
+                    format!("`{def_name}` isn't public")
+                };
+                scope.fancy_error(
+                    message,
+                    vec![
+                        Label::primary(attr.span, "invalid attribute"),
+                        Label::secondary(def.kind.sig.kind.name.span, reason),
+                    ],
+                    vec![],
+                );
+            }
+        }
+
+        if contract.is_interface(db) {
+            if is_special_fn_name(def_name) {
+                scope.fancy_error(
+                    &format!("`{def_name}` can't be declared in an interface"),
+                    vec![Label::primary(
+                        def.kind.sig.kind.name.span,
+                        "not allowed here",
+                    )],
+                    vec![
+                        "Note: an interface only declares the public functions of a contract"
+                            .into(),
+                    ],
+                );
+                continue;
+            }
+            if !func.takes_self(db) {
+                scope.fancy_error(
+                    "interface functions must take `self`",
+                    vec![Label::primary(
+                        def.kind.sig.kind.name.span,
+                        format!("`{def_name}` doesn't take `self`"),
+                    )],
+                    vec![format!(
+                        "Hint: interface functions are called on a contract, e.g. `fn {def_name}(self)`"
+                    )],
+                );
+                continue;
+            }
+        }
+
+        if is_special_fn_name(def_name) {
             continue;
         }
 
@@ -229,7 +306,7 @@ pub fn contract_call_function(
     if let Some((_id, init_span)) = first_def {
         for func in all_fns.iter() {
             let name = func.name(db);
-            if func.is_public(db) && name != "__init__" && name != "__call__" {
+            if func.is_public(db) && !is_special_fn_name(&name) {
                 diagnostics.push(errors::fancy_error(
                     "`pub` not allowed if `__call__` is defined",
                     vec![
@@ -251,6 +328,118 @@ pub fn contract_call_function(
     }
 }
 
+pub fn contract_receive_function(
+    db: &dyn AnalyzerDb,
+    contract: ContractId,
+) -> Analysis<Option<FunctionId>> {
+    contract_entry_point(db, contract, "__receive__")
+}
+
+pub fn contract_fallback_function(
+    db: &dyn AnalyzerDb,
+    contract: ContractId,
+) -> Analysis<Option<FunctionId>> {
+    contract_entry_point(db, contract, "__fallback__")
+}
+
+/// Finds `__receive__` or `__fallback__`, which the dispatcher runs when the
+/// call data is empty or its selector doesn't match any public function.
+fn contract_entry_point(
+    db: &dyn AnalyzerDb,
+    contract: ContractId,
+    name: &str,
+) -> Analysis<Option<FunctionId>> {
+    let all_fns = db.contract_all_functions(contract);
+    let mut entry_fns = all_fns.iter().filter_map(|func| {
+        let def = &func.data(db).ast;
+        (def.name() == name).then_some((func, def.span))
+    });
+
+    let mut diagnostics = vec![];
+
+    let first_def = entry_fns.next();
+    if let Some((_, dupe_span)) = entry_fns.next() {
+        let mut labels = vec![
+            Label::primary(first_def.unwrap().1, format!("`{name}` first defined here")),
+            Label::secondary(dupe_span, format!("`{name}` redefined here")),
+        ];
+        for (_, dupe_span) in entry_fns {
+            labels.push(Label::secondary(
+                dupe_span,
+                format!("`{name}` redefined here"),
+            ));
+        }
+        diagnostics.push(errors::fancy_error(
+            format!(
+                "`fn {name}()` is defined multiple times in `contract {}`",
+                contract.name(db),
+            ),
+            labels,
+            vec![],
+        ));
+    }
+
+    if let Some((id, span)) = first_def {
+        // Return type is checked in `queries::functions::function_signature`.
+        if !id.is_public(db) {
+            diagnostics.push(errors::fancy_error(
+                format!("`{name}` function is not public"),
+                vec![Label::primary(
+                    span,
+                    format!("`{name}` function must be public"),
+                )],
+                vec![
+                    "Hint: Add the `pub` modifier.".to_string(),
+                    format!("Example: `pub fn {name}():`"),
+                ],
+            ));
+        }
+
+        if let Some(call_fn) = all_fns.iter().find(|func| func.name(db) == "__call__") {
+            diagnostics.push(errors::fancy_error(
+                format!("`{name}` not allowed if `__call__` is defined"),
+                vec![
+                    Label::primary(id.name_span(db), format!("`{name}` is never run")),
+                    Label::secondary(call_fn.name_span(db), "`__call__` defined here"),
+                ],
+                vec![format!(
+                    "The `__call__` function replaces the default function dispatcher, which runs `{name}`."
+                )],
+            ));
+        }
+
+        // The call data isn't decoded, so there's nothing to pass to the
+        // function besides `self` and `ctx`.
+        let labels: Vec<_> = id
+            .data(db)
+            .ast
+            .kind
+            .sig
+            .kind
+            .args
+            .iter()
+            .filter(|arg| {
+                matches!(&arg.kind, ast::FunctionArg::Regular { name, .. } if name.kind != "ctx")
+            })
+            .map(|arg| Label::primary(arg.span, "remove this parameter"))
+            .collect();
+        if !labels.is_empty() {
+            diagnostics.push(errors::fancy_error(
+                format!("`{name}` function can't take parameters"),
+                labels,
+                vec![format!(
+                    "Note: `{name}` can only take `self` and `ctx: Context`."
+                )],
+            ));
+        }
+    }
+
+    Analysis {
+        value: first_def.map(|(id, _span)| *id),
+        diagnostics: diagnostics.into(),
+    }
+}
+
 /// All field ids, including those with duplicate names
 pub fn contract_all_fields(db: &dyn AnalyzerDb, contract: ContractId) -> Rc<[ContractFieldId]> {
     contract
@@ -306,9 +495,21 @@ pub fn contract_field_type(
 ) -> Analysis<Result<types::TypeId, errors::TypeError>> {
     let mut scope = ItemScope::new(db, field.data(db).parent.module(db));
     let self_ty = Some(field.data(db).parent.as_type(db).as_trait_or_type());
-    let typ = type_desc(&mut scope, &field.data(db).ast.kind.typ, self_ty);
-
     let node = &field.data(db).ast;
+    let typ = match items::transient_type_args(&node.kind.typ.kind) {
+        Some(args) => {
+            let evm_version = db.evm_version();
+            if evm_version < EvmVersion::Cancun {
+                scope.error(
+                    &format!("transient storage is not available in EVM version `{evm_version}`"),
+                    node.kind.typ.span,
+                    &format!("requires `{}` or later", EvmVersion::Cancun),
+                );
+            }
+            transient_value_type(&mut scope, &node.kind.typ, args, self_ty)
+        }
+        None => type_desc(&mut scope, &node.kind.typ, self_ty),
+    };
 
     if node.kind.is_pub {
         scope.not_yet_implemented("contract `pub` fields", node.span);
@@ -326,6 +527,48 @@ pub fn contract_field_type(
     }
 }
 
+/// Resolves the value type `T` of a `Transient<T>` field. Transient storage
+/// slots are accessed one word at a time, so `T` must be a primitive type.
+fn transient_value_type(
+    scope: &mut ItemScope,
+    desc: &Node<ast::TypeDesc>,
+    args: &Node<Vec<ast::GenericArg>>,
+    self_ty: Option<TraitOrType>,
+) -> Result<types::TypeId, errors::TypeError> {
+    if let Some(diag) = validate_arg_count(scope, TRANSIENT, desc.span, args, 1, "generic argument")
+    {
+        return Err(errors::TypeError::new(diag));
+    }
+
+    let arg = match &args.kind[0] {
+        ast::GenericArg::TypeDesc(arg) => arg,
+        arg => {
+            return Err(errors::TypeError::new(scope.error(
+                "`Transient` expects a type argument",
+                arg.span(),
+                "expected a primitive type",
+            )))
+        }
+    };
+
+    let typ = type_desc(scope, arg, self_ty)?;
+    if typ.is_primitive(scope.db()) && !typ.typ(scope.db()).is_unit() {
+        Ok(typ)
+    } else {
+        Err(errors::TypeError::new(scope.fancy_error(
+            "invalid transient field type",
+            vec![Label::primary(
+                arg.span,
+                format!(
+                    "`{}` can't be stored in transient storage",
+                    typ.display(scope.db())
+                ),
+            )],
+            vec!["Note: transient fields must have a primitive type, like `bool` or `u256`".into()],
+        )))
+    }
+}
+
 pub fn contract_dependency_graph(db: &dyn AnalyzerDb, contract: ContractId) -> DepGraphWrapper {
     // A contract depends on the types of its fields, and the things those types
     // depend on. Note that this *does not* include the contract's public
@@ -378,7 +621,13 @@ pub fn contract_runtime_dependency_graph(
     let root_fns = if let Some(call_id) = contract.call_function(db) {
         vec![call_id]
     } else {
-        contract.public_functions(db).values().copied().collect()
+        contract
+            .public_functions(db)
+            .values()
+            .copied()
+            .chain(contract.receive_function(db))
+            .chain(contract.fallback_function(db))
+            .collect()
     }
     .into_iter()
     .map(|fun| (root, Item::Function(fun), DepLocality::Local))
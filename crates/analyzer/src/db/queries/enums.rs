@@ -17,7 +17,7 @@ use crate::{
         scopes::ItemScope,
         types::Type,
     },
-    traversal::types::type_desc,
+    traversal::types::{check_type_generic_params, type_desc},
     AnalyzerDb,
 };
 
@@ -44,6 +44,7 @@ pub fn enum_variant_map(
     enum_: EnumId,
 ) -> Analysis<Rc<IndexMap<SmolStr, EnumVariantId>>> {
     let scope = ItemScope::new(db, enum_.module(db));
+    check_type_generic_params(&scope, &enum_.data(db).ast.kind.generic_params.kind);
     let mut variants = IndexMap::<SmolStr, EnumVariantId>::new();
 
     for &variant in db.enum_all_variants(enum_).iter() {
@@ -73,7 +74,11 @@ pub fn enum_variant_kind(
     variant: EnumVariantId,
 ) -> Analysis<Result<EnumVariantKind, TypeError>> {
     let variant_data = variant.data(db);
-    let mut scope = ItemScope::new(db, variant_data.parent.module(db));
+    let mut scope = ItemScope::with_generic_owner(
+        db,
+        variant_data.parent.module(db),
+        TypeDef::Enum(variant_data.parent),
+    );
     let self_ty = Some(variant.parent(db).as_type(db).as_trait_or_type());
     let kind = match &variant_data.ast.kind.kind {
         ast::VariantKind::Unit => Ok(EnumVariantKind::Unit),
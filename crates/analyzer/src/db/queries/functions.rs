@@ -1,18 +1,24 @@
-use crate::context::{AnalyzerContext, CallType, FunctionBody};
+use crate::constants::is_special_fn_name;
+use crate::context::{
+    AnalyzerContext, CallType, Constant, ExpectedRevert, FunctionBody, NamedThing,
+};
 use crate::db::{Analysis, AnalyzerDb};
 use crate::display::Displayable;
-use crate::errors::TypeError;
+use crate::errors::{self, TypeError};
 use crate::namespace::items::{
     DepGraph, DepGraphWrapper, DepLocality, FunctionId, FunctionSigId, Item, TypeDef,
 };
 use crate::namespace::scopes::{BlockScope, BlockScopeType, FunctionScope, ItemScope};
-use crate::namespace::types::{self, CtxDecl, Generic, SelfDecl, Type, TypeId};
+use crate::namespace::types::{self, Base, CtxDecl, Generic, SelfDecl, Type, TypeId};
 use crate::traversal::functions::traverse_statements;
 use crate::traversal::types::{type_desc, type_desc_to_trait};
-use fe_common::diagnostics::Label;
+use fe_common::diagnostics::{Diagnostic, Label};
+use fe_common::numeric;
+use fe_common::Span;
 use fe_parser::ast::{self, GenericParameter};
 use fe_parser::node::Node;
 use if_chain::if_chain;
+use num_bigint::BigInt;
 use smol_str::SmolStr;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -25,8 +31,11 @@ pub fn function_signature(
 ) -> Analysis<Rc<types::FunctionSignature>> {
     let def = &function.data(db).ast;
 
-    let mut scope = ItemScope::new(db, function.module(db));
     let fn_parent = function.parent(db);
+    let mut scope = match fn_parent {
+        Item::Type(def) => ItemScope::with_generic_owner(db, function.module(db), def),
+        _ => ItemScope::new(db, function.module(db)),
+    };
 
     let mut self_decl = None;
     let mut ctx_decl = None;
@@ -221,8 +230,9 @@ pub fn function_signature(
         .as_ref()
         .map(|type_node| {
             let fn_name = &function.name(db);
-            if fn_name == "__init__" || fn_name == "__call__" {
-                // `__init__` and `__call__` must not return any type other than `()`.
+            if is_special_fn_name(fn_name) {
+                // `__init__`, `__call__`, `__receive__` and `__fallback__` must
+                // not return any type other than `()`.
                 if type_node.kind != ast::TypeDesc::Unit {
                     scope.fancy_error(
                         &format!("`{fn_name}` function has incorrect return type"),
@@ -302,6 +312,11 @@ pub fn function_body(db: &dyn AnalyzerDb, function: FunctionId) -> Analysis<Rc<F
     let def = &function.data(db).ast.kind;
     let scope = FunctionScope::new(db, function);
 
+    // Interface functions are implemented by the called contract.
+    if function.is_interface_fn(db) {
+        return Analysis::new(Rc::new(scope.body.into_inner()), Rc::new([]));
+    }
+
     // If the return type is unit, explicit return or no return (implicit) is valid,
     // so no scanning is necessary.
     // If the return type is anything else, we need to ensure that all code paths
@@ -325,6 +340,35 @@ pub fn function_body(db: &dyn AnalyzerDb, function: FunctionId) -> Analysis<Rc<F
         }
     }
 
+    // The arguments of a test are generated by the fuzzer of the test runner.
+    if function.is_test(db) {
+        let sig = function.signature(db);
+        for arg in &def.sig.kind.args {
+            let name = match &arg.kind {
+                ast::FunctionArg::Regular { name, .. } if name.kind != "ctx" => &name.kind,
+                _ => continue,
+            };
+            let param = sig.params.iter().find(|param| &param.name == name);
+            if let Some(Ok(typ)) = param.map(|param| &param.typ) {
+                if !is_test_arg_type(db, *typ) {
+                    scope.fancy_error(
+                        &format!(
+                            "parameters of type `{}` are not supported on test functions",
+                            typ.display(db)
+                        ),
+                        vec![Label::primary(arg.span, "unsupported parameter type")],
+                        vec![
+                            "Hint: test arguments are generated by the fuzzer, which supports \
+                              integers, `bool`, `address`, and arrays (except of `u8`), tuples \
+                              and structs of them"
+                                .into(),
+                        ],
+                    );
+                }
+            }
+        }
+    }
+
     let mut block_scope = BlockScope::new(
         &scope,
         if function.is_unsafe(db) {
@@ -345,6 +389,222 @@ pub fn function_body(db: &dyn AnalyzerDb, function: FunctionId) -> Analysis<Rc<F
     }
 }
 
+/// Returns `true` if the fuzzer can generate arguments of type `typ` for
+/// test functions. Arrays of `u8` are excluded, since their ABI type is
+/// `bytes`, which doesn't carry the length of the array.
+fn is_test_arg_type(db: &dyn AnalyzerDb, typ: TypeId) -> bool {
+    match typ.typ(db) {
+        Type::Base(base) => base != Base::Unit,
+        Type::Array(array) => {
+            array.inner.typ(db) != Type::u8() && is_test_arg_type(db, array.inner)
+        }
+        Type::Tuple(tuple) => tuple.items.iter().all(|item| is_test_arg_type(db, *item)),
+        Type::Struct(id) => {
+            db.struct_dependency_graph(id).diagnostics.is_empty()
+                && id.fields(db).values().all(|field| match field.typ(db) {
+                    Ok(typ) => is_test_arg_type(db, typ),
+                    Err(_) => false,
+                })
+        }
+        Type::Mut(inner) => is_test_arg_type(db, inner),
+        _ => false,
+    }
+}
+
+/// Resolve the revert that a test function expects, given by the
+/// `should_revert` argument of its `#test` attribute.
+pub fn function_expected_revert(
+    db: &dyn AnalyzerDb,
+    function: FunctionId,
+) -> Analysis<Option<Rc<ExpectedRevert>>> {
+    let mut diagnostics = vec![];
+    let mut expected: Option<(Span, ExpectedRevert)> = None;
+
+    for attr in Item::Function(function).attributes(db) {
+        let attr = attr.data(db);
+        let args = match (&attr.ast.kind.args, attr.ast.kind.name.kind.as_str()) {
+            (Some(args), "test") => args,
+            _ => continue,
+        };
+
+        for arg in &args.kind {
+            let value = match (&arg.kind.label, &arg.kind.value.kind) {
+                (None, ast::Expr::Name(name)) if name == "should_revert" => ExpectedRevert::Any,
+                (Some(label), _) if label.kind == "should_revert" => {
+                    match expected_error(db, function, &arg.kind.value, &mut diagnostics) {
+                        Some(value) => value,
+                        None => continue,
+                    }
+                }
+                _ => {
+                    diagnostics.push(errors::fancy_error(
+                        "invalid argument of the `test` attribute",
+                        vec![Label::primary(arg.span, "unknown argument")],
+                        vec!["Note: expected `should_revert` or `should_revert: <error>`".into()],
+                    ));
+                    continue;
+                }
+            };
+
+            if let Some((span, _)) = &expected {
+                diagnostics.push(errors::fancy_error(
+                    "duplicate `should_revert` argument",
+                    vec![
+                        Label::primary(*span, "`should_revert` first given here"),
+                        Label::secondary(arg.span, "given again here"),
+                    ],
+                    vec![],
+                ));
+            } else {
+                expected = Some((arg.span, value));
+            }
+        }
+    }
+
+    Analysis::new(
+        expected.map(|(_, value)| Rc::new(value)),
+        diagnostics.into(),
+    )
+}
+
+/// Resolve the error value of `should_revert: <error>`, which is either
+/// `Panic(code: <int>)` or an instance of a struct or `error` whose fields
+/// are literals.
+fn expected_error(
+    db: &dyn AnalyzerDb,
+    function: FunctionId,
+    expr: &Node<ast::Expr>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<ExpectedRevert> {
+    let (func, args) = match &expr.kind {
+        ast::Expr::Call {
+            func,
+            generic_args: None,
+            args,
+        } => (func, args),
+        _ => {
+            diagnostics.push(errors::fancy_error(
+                "invalid `should_revert` value",
+                vec![Label::primary(expr.span, "expected an error")],
+                vec!["Example: `should_revert: Error(code: 42)`".into()],
+            ));
+            return None;
+        }
+    };
+
+    let module = function.module(db);
+    let resolved = match &func.kind {
+        ast::Expr::Name(name) if name == "Panic" => {
+            let code = match args.kind.as_slice() {
+                [arg] => match &arg.kind.label {
+                    Some(label) if label.kind != "code" => None,
+                    _ => literal_int(&arg.kind.value),
+                },
+                _ => None,
+            };
+            if code.is_none() {
+                diagnostics.push(errors::error(
+                    "invalid `Panic` value",
+                    args.span,
+                    "expected `(code: <integer literal>)`",
+                ));
+            }
+            return code.map(ExpectedRevert::Panic);
+        }
+        ast::Expr::Name(name) => module.resolve_name(db, name).ok().flatten(),
+        ast::Expr::Path(path) => {
+            let resolved = module.resolve_path(db, path);
+            diagnostics.extend(resolved.diagnostics.iter().cloned());
+            resolved.value
+        }
+        _ => None,
+    };
+
+    let struct_ = match resolved {
+        Some(NamedThing::Item(Item::Type(TypeDef::Struct(struct_)))) => struct_,
+        _ => {
+            diagnostics.push(errors::fancy_error(
+                format!("`{}` is not a struct or error", func.kind),
+                vec![Label::primary(func.span, "expected a struct or error")],
+                vec!["Note: `Panic(code: <integer>)` matches panics of the compiler".into()],
+            ));
+            return None;
+        }
+    };
+
+    let fields = struct_.fields(db);
+    if fields.len() != args.kind.len() {
+        diagnostics.push(errors::error(
+            format!(
+                "`{}` has {} fields, but {} were given",
+                struct_.name(db),
+                fields.len(),
+                args.kind.len()
+            ),
+            args.span,
+            "wrong number of fields",
+        ));
+        return None;
+    }
+
+    let mut values = vec![];
+    for ((name, field), arg) in fields.iter().zip(&args.kind) {
+        if let Some(label) = &arg.kind.label {
+            if &label.kind != name {
+                diagnostics.push(errors::error(
+                    "argument label mismatch",
+                    label.span,
+                    format!("expected `{name}`"),
+                ));
+                return None;
+            }
+        }
+
+        let typ = field.typ(db).ok()?;
+        let value = &arg.kind.value;
+        let constant = match (&value.kind, typ.typ(db)) {
+            (ast::Expr::Bool(value), Type::Base(Base::Bool)) => Some(Constant::Bool(*value)),
+            (ast::Expr::Num(_), Type::Base(Base::Address)) => {
+                literal_int(value).map(Constant::Address)
+            }
+            (ast::Expr::Str(text), Type::String(string)) if text.len() <= string.max_size => {
+                Some(Constant::Str(text.clone()))
+            }
+            (_, Type::Base(Base::Numeric(int))) => literal_int(value)
+                .filter(|num| int.fits(num.clone()))
+                .map(Constant::Int),
+            _ => None,
+        };
+        match constant {
+            Some(constant) => values.push(constant),
+            None => {
+                diagnostics.push(errors::error(
+                    format!("expected a literal of type `{}`", typ.display(db)),
+                    value.span,
+                    "invalid field value",
+                ));
+                return None;
+            }
+        }
+    }
+
+    Some(ExpectedRevert::Error {
+        ty: struct_,
+        fields: values,
+    })
+}
+
+/// Returns the value of an integer literal, which may be negated.
+fn literal_int(expr: &Node<ast::Expr>) -> Option<BigInt> {
+    match &expr.kind {
+        ast::Expr::Num(num) => numeric::Literal::new(num).parse::<BigInt>().ok(),
+        ast::Expr::UnaryOperation { op, operand } if op.kind == ast::UnaryOperator::USub => {
+            literal_int(operand).map(|num| -num)
+        }
+        _ => None,
+    }
+}
+
 fn all_paths_return_or_revert(block: &[Node<ast::FuncStmt>]) -> bool {
     for statement in block.iter().rev() {
         match &statement.kind {
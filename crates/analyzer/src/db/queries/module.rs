@@ -74,18 +74,38 @@ pub fn module_all_items(db: &dyn AnalyzerDb, module: ModuleId) -> Rc<[Item]> {
                     name: node.name().into(),
                     ast: node.clone(),
                     module,
+                    is_interface: false,
+                })),
+            ))),
+            ast::ModuleStmt::Interface(node) => Some(Item::Type(TypeDef::Contract(
+                db.intern_contract(Rc::new(Contract {
+                    name: node.name().into(),
+                    ast: interface_contract_ast(node),
+                    module,
+                    is_interface: true,
                 })),
             ))),
             ast::ModuleStmt::Struct(node) => Some(Item::Type(TypeDef::Struct(db.intern_struct(
                 Rc::new(Struct {
                     ast: node.clone(),
                     module,
+                    is_error: false,
+                    generic_args: vec![],
+                }),
+            )))),
+            ast::ModuleStmt::Error(node) => Some(Item::Type(TypeDef::Struct(db.intern_struct(
+                Rc::new(Struct {
+                    ast: node.clone(),
+                    module,
+                    is_error: true,
+                    generic_args: vec![],
                 }),
             )))),
             ast::ModuleStmt::Enum(node) => {
                 Some(Item::Type(TypeDef::Enum(db.intern_enum(Rc::new(Enum {
                     ast: node.clone(),
                     module,
+                    generic_args: vec![],
                 })))))
             }
             ast::ModuleStmt::Constant(node) => Some(Item::Constant(db.intern_module_const(
@@ -113,6 +133,38 @@ pub fn module_all_items(db: &dyn AnalyzerDb, module: ModuleId) -> Rc<[Item]> {
         .collect()
 }
 
+/// An interface is analyzed as a contract without fields, whose functions have
+/// empty bodies. The nodes keep the ids of the interface and its function
+/// signatures.
+fn interface_contract_ast(node: &Node<ast::Interface>) -> Node<ast::Contract> {
+    let body = node
+        .kind
+        .functions
+        .iter()
+        .map(|sig| {
+            ast::ContractStmt::Function(Node {
+                kind: ast::Function {
+                    sig: sig.clone(),
+                    body: vec![],
+                },
+                id: sig.id,
+                span: sig.span,
+            })
+        })
+        .collect();
+
+    Node {
+        kind: ast::Contract {
+            name: node.kind.name.clone(),
+            fields: vec![],
+            body,
+            pub_qual: node.kind.pub_qual,
+        },
+        id: node.id,
+        span: node.span,
+    }
+}
+
 pub fn module_all_impls(db: &dyn AnalyzerDb, module: ModuleId) -> Analysis<Rc<[ImplId]>> {
     let body = &module.ast(db).body;
     let mut scope = ItemScope::new(db, module);
@@ -168,7 +220,15 @@ pub fn module_item_map(
     let mut map = IndexMap::<SmolStr, Item>::new();
 
     for item in module.all_items(db).iter() {
-        if matches!(item, Item::Attribute(_)) {
+        if let Item::Attribute(attr) = item {
+            let ast = &attr.data(db).ast.kind;
+            if let (Some(args), false) = (&ast.args, ast.name.kind == "test") {
+                diagnostics.push(errors::error(
+                    format!("the `{}` attribute doesn't take arguments", ast.name.kind),
+                    args.span,
+                    "unexpected arguments",
+                ));
+            }
             continue;
         }
 
@@ -185,26 +245,6 @@ pub fn module_item_map(
                         vec!["Hint: remove the generic parameters".into()],
                     ));
                 }
-
-                if let Some(arg) = sig_ast.args.first() {
-                    if arg.name() != "ctx" {
-                        diagnostics.push(errors::fancy_error(
-                            "function parameters other than `ctx` are not supported on test functions",
-                            vec![Label::primary(arg.span, "invalid function parameter")],
-                            vec!["Hint: remove the parameter".into()],
-                        ));
-                    }
-                }
-
-                for arg in sig_ast.args.iter().skip(1) {
-                    if arg.name() != "ctx" {
-                        diagnostics.push(errors::fancy_error(
-                            "function parameters other than `ctx` are not supported on test functions",
-                            vec![Label::primary(arg.span, "invalid function parameter")],
-                            vec!["Hint: remove the parameter".into()],
-                        ));
-                    }
-                }
             }
         }
 
@@ -325,12 +365,14 @@ pub fn module_impl_map(
     )
 }
 
+/// The contracts defined in the module. Interfaces aren't included, as they're
+/// implemented by contracts deployed elsewhere.
 pub fn module_contracts(db: &dyn AnalyzerDb, module: ModuleId) -> Rc<[ContractId]> {
     module
         .all_items(db)
         .iter()
         .filter_map(|item| match item {
-            Item::Type(TypeDef::Contract(id)) => Some(*id),
+            Item::Type(TypeDef::Contract(id)) if !id.is_interface(db) => Some(*id),
             _ => None,
         })
         .collect()
@@ -9,7 +9,7 @@ use crate::namespace::items::{
 };
 use crate::namespace::scopes::ItemScope;
 use crate::namespace::types::{Type, TypeId};
-use crate::traversal::types::type_desc;
+use crate::traversal::types::{check_type_generic_params, type_desc};
 use crate::AnalyzerDb;
 use fe_common::utils::humanize::pluralize_conditionally;
 use fe_parser::{ast, Label};
@@ -39,6 +39,7 @@ pub fn struct_field_map(
     struct_: StructId,
 ) -> Analysis<Rc<IndexMap<SmolStr, StructFieldId>>> {
     let scope = ItemScope::new(db, struct_.module(db));
+    check_type_generic_params(&scope, &struct_.data(db).ast.kind.generic_params.kind);
     let mut fields = IndexMap::<SmolStr, StructFieldId>::new();
 
     let mut indexed_count = 0;
@@ -108,7 +109,11 @@ pub fn struct_field_type(
     field: StructFieldId,
 ) -> Analysis<Result<TypeId, TypeError>> {
     let field_data = field.data(db);
-    let mut scope = ItemScope::new(db, field_data.parent.module(db));
+    let mut scope = ItemScope::with_generic_owner(
+        db,
+        field_data.parent.module(db),
+        TypeDef::Struct(field_data.parent),
+    );
 
     let ast::Field {
         attributes: _,
@@ -5,24 +5,23 @@ use smol_str::SmolStr;
 use crate::context::{AnalyzerContext, TempContext};
 use crate::db::Analysis;
 use crate::errors::TypeError;
-use crate::namespace::items::{FunctionSigId, ImplId, TraitId, TypeAliasId};
+use crate::namespace::items::{FunctionSigId, ImplId, IngotId, TraitId, TypeAliasId};
 use crate::namespace::scopes::ItemScope;
 use crate::namespace::types::{self, TypeId};
 use crate::traversal::types::type_desc;
 use crate::AnalyzerDb;
 
-/// Returns all `impl` for the given type from the current ingot as well as
+/// Returns all `impl` for the given type from the given ingot as well as its
 /// dependency ingots
-pub fn all_impls(db: &dyn AnalyzerDb, ty: TypeId) -> Rc<[ImplId]> {
+pub fn all_impls(db: &dyn AnalyzerDb, ty: TypeId, ingot: IngotId) -> Rc<[ImplId]> {
     let ty = ty.deref(db);
 
-    let ingot_modules = db
-        .root_ingot()
+    let ingot_modules = ingot
         .all_modules(db)
         .iter()
         .flat_map(|module_id| module_id.all_impls(db).to_vec())
         .collect::<Vec<_>>();
-    db.ingot_external_ingots(db.root_ingot())
+    db.ingot_external_ingots(ingot)
         .values()
         .flat_map(|ingot| ingot.all_modules(db).to_vec())
         .flat_map(|module_id| module_id.all_impls(db).to_vec())
@@ -31,15 +30,20 @@ pub fn all_impls(db: &dyn AnalyzerDb, ty: TypeId) -> Rc<[ImplId]> {
         .collect()
 }
 
-pub fn impl_for(db: &dyn AnalyzerDb, ty: TypeId, treit: TraitId) -> Option<ImplId> {
-    db.all_impls(ty)
+pub fn impl_for(db: &dyn AnalyzerDb, ty: TypeId, treit: TraitId, ingot: IngotId) -> Option<ImplId> {
+    db.all_impls(ty, ingot)
         .iter()
         .find(|impl_| impl_.trait_id(db) == treit)
         .cloned()
 }
 
-pub fn function_sigs(db: &dyn AnalyzerDb, ty: TypeId, name: SmolStr) -> Rc<[FunctionSigId]> {
-    db.all_impls(ty)
+pub fn function_sigs(
+    db: &dyn AnalyzerDb,
+    ty: TypeId,
+    name: SmolStr,
+    ingot: IngotId,
+) -> Rc<[FunctionSigId]> {
+    db.all_impls(ty, ingot)
         .iter()
         .filter_map(|impl_| impl_.function(db, &name))
         .map(|fun| fun.sig(db))
@@ -1,5 +1,7 @@
-use crate::constants::{EMITTABLE_TRAIT_NAME, INDEXED};
-use crate::context::{self, Analysis, Constant, NamedThing};
+use crate::constants::{
+    EMITTABLE_TRAIT_NAME, FALLBACK_FN_NAME, HOT, INDEXED, PAYABLE, RECEIVE_FN_NAME, TRANSIENT,
+};
+use crate::context::{self, Analysis, Constant, ExpectedRevert, NamedThing};
 use crate::display::{DisplayWithDb, Displayable};
 use crate::errors::{self, IncompleteItem, TypeError};
 use crate::namespace::types::{self, GenericType, Type, TypeId};
@@ -964,6 +966,76 @@ impl TypeDef {
         }
     }
 
+    /// Returns the generic parameters of a struct or enum definition.
+    pub fn generic_params(&self, db: &dyn AnalyzerDb) -> Vec<GenericParameter> {
+        match self {
+            TypeDef::Struct(id) => id.data(db).ast.kind.generic_params.kind.clone(),
+            TypeDef::Enum(id) => id.data(db).ast.kind.generic_params.kind.clone(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the types the generic parameters are bound to. This is empty
+    /// for a generic definition, which isn't bound to any types yet.
+    pub fn generic_args(&self, db: &dyn AnalyzerDb) -> Vec<TypeId> {
+        match self {
+            TypeDef::Struct(id) => id.data(db).generic_args.clone(),
+            TypeDef::Enum(id) => id.data(db).generic_args.clone(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns `true` if the type is a generic struct or enum definition,
+    /// rather than an instance of one.
+    pub fn is_generic(&self, db: &dyn AnalyzerDb) -> bool {
+        !self.generic_params(db).is_empty() && self.generic_args(db).is_empty()
+    }
+
+    /// Returns the type that the generic parameter `name` stands for. In a
+    /// generic definition this is the unbounded [`Type::Generic`] parameter
+    /// itself, in an instance it is the type the parameter is bound to.
+    pub fn generic_param_type(&self, db: &dyn AnalyzerDb, name: &str) -> Option<TypeId> {
+        let idx = self
+            .generic_params(db)
+            .iter()
+            .position(|param| param.name() == name)?;
+
+        Some(match self.generic_args(db).get(idx) {
+            Some(arg) => *arg,
+            None => db.intern_type(Type::Generic(types::Generic {
+                name: name.into(),
+                bounds: Rc::new([]),
+            })),
+        })
+    }
+
+    /// Binds the generic parameters of a struct or enum definition to `args`.
+    ///
+    /// Binding the parameters to themselves, as in `Pair<T, U>` inside the
+    /// definition of `Pair`, returns the definition.
+    pub fn instantiate(&self, db: &dyn AnalyzerDb, args: Vec<TypeId>) -> TypeDef {
+        let params = self.generic_params(db);
+        let is_identity = params.iter().zip(&args).all(|(param, arg)| {
+            matches!(
+                arg.typ(db),
+                Type::Generic(generic) if generic.name == param.name() && generic.bounds.is_empty()
+            )
+        });
+        let args = if is_identity { vec![] } else { args };
+
+        match self {
+            TypeDef::Struct(id) => TypeDef::Struct(db.intern_struct(Rc::new(Struct {
+                generic_args: args,
+                ..id.data(db).as_ref().clone()
+            }))),
+            TypeDef::Enum(id) => TypeDef::Enum(db.intern_enum(Rc::new(Enum {
+                generic_args: args,
+                ..id.data(db).as_ref().clone()
+            }))),
+            _ => *self,
+        }
+    }
+
     pub fn parent(&self, db: &dyn AnalyzerDb) -> Option<Item> {
         match self {
             TypeDef::Alias(id) => Some(id.parent(db)),
@@ -1030,6 +1102,9 @@ pub struct Contract {
     pub name: SmolStr,
     pub ast: Node<ast::Contract>,
     pub module: ModuleId,
+    /// `true` if the contract is declared as an `interface`, whose functions
+    /// have no body and are only called through the contract ABI.
+    pub is_interface: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
@@ -1051,6 +1126,9 @@ impl ContractId {
     pub fn is_public(&self, db: &dyn AnalyzerDb) -> bool {
         self.data(db).ast.kind.pub_qual.is_some()
     }
+    pub fn is_interface(&self, db: &dyn AnalyzerDb) -> bool {
+        self.data(db).is_interface
+    }
     pub fn name_span(&self, db: &dyn AnalyzerDb) -> Span {
         self.data(db).ast.kind.name.span
     }
@@ -1097,22 +1175,35 @@ impl ContractId {
         db.contract_call_function(*self).value
     }
 
+    /// The `__receive__` function, run by the dispatcher if the call data is
+    /// empty.
+    pub fn receive_function(&self, db: &dyn AnalyzerDb) -> Option<FunctionId> {
+        db.contract_receive_function(*self).value
+    }
+
+    /// The `__fallback__` function, run by the dispatcher if the selector in
+    /// the call data doesn't match any public function.
+    pub fn fallback_function(&self, db: &dyn AnalyzerDb) -> Option<FunctionId> {
+        db.contract_fallback_function(*self).value
+    }
+
     pub fn all_functions(&self, db: &dyn AnalyzerDb) -> Rc<[FunctionId]> {
         db.contract_all_functions(*self)
     }
 
-    /// User functions, public and not. Excludes `__init__` and `__call__`.
+    /// User functions, public and not. Excludes `__init__`, `__call__`,
+    /// `__receive__` and `__fallback__`.
     pub fn functions(&self, db: &dyn AnalyzerDb) -> Rc<IndexMap<SmolStr, FunctionId>> {
         db.contract_function_map(*self).value
     }
 
     /// Lookup a function by name. Searches all user functions, private or not.
-    /// Excludes `__init__` and `__call__`.
+    /// Excludes `__init__`, `__call__`, `__receive__` and `__fallback__`.
     pub fn function(&self, db: &dyn AnalyzerDb, name: &str) -> Option<FunctionId> {
         self.functions(db).get(name).copied()
     }
 
-    /// Excludes `__init__` and `__call__`.
+    /// Excludes `__init__`, `__call__`, `__receive__` and `__fallback__`.
     pub fn public_functions(&self, db: &dyn AnalyzerDb) -> Rc<IndexMap<SmolStr, FunctionId>> {
         db.contract_public_function_map(*self)
     }
@@ -1130,7 +1221,8 @@ impl ContractId {
     }
 
     /// Dependency graph of the (imaginary) `__call__` function, which
-    /// dispatches to the contract's public functions.
+    /// dispatches to the contract's public functions, `__receive__` and
+    /// `__fallback__`.
     pub fn runtime_dependency_graph(&self, db: &dyn AnalyzerDb) -> Rc<DepGraph> {
         db.contract_runtime_dependency_graph(*self).0
     }
@@ -1145,6 +1237,8 @@ impl ContractId {
         // functions
         db.contract_init_function(*self).sink_diagnostics(sink);
         db.contract_call_function(*self).sink_diagnostics(sink);
+        db.contract_receive_function(*self).sink_diagnostics(sink);
+        db.contract_fallback_function(*self).sink_diagnostics(sink);
         db.contract_function_map(*self).sink_diagnostics(sink);
         db.contract_all_functions(*self)
             .iter()
@@ -1168,14 +1262,32 @@ impl ContractFieldId {
     pub fn data(&self, db: &dyn AnalyzerDb) -> Rc<ContractField> {
         db.lookup_intern_contract_field(*self)
     }
+    /// The type of the field. For a transient field this is the type of the
+    /// value, without the `Transient` wrapper.
     pub fn typ(&self, db: &dyn AnalyzerDb) -> Result<types::TypeId, TypeError> {
         db.contract_field_type(*self).value
     }
+    /// `true` if the field is declared as `Transient<T>`, in which case it
+    /// lives in transient storage instead of storage.
+    pub fn is_transient(&self, db: &dyn AnalyzerDb) -> bool {
+        transient_type_args(&self.data(db).ast.kind.typ.kind).is_some()
+    }
     pub fn sink_diagnostics(&self, db: &dyn AnalyzerDb, sink: &mut impl DiagnosticSink) {
         sink.push_all(db.contract_field_type(*self).diagnostics.iter())
     }
 }
 
+/// Returns the generic arguments of `typ` if it is the `Transient` wrapper of
+/// a transient contract field.
+pub(crate) fn transient_type_args(typ: &ast::TypeDesc) -> Option<&Node<Vec<ast::GenericArg>>> {
+    match typ {
+        ast::TypeDesc::Generic { base, args } if matches!(base.kind.segments.as_slice(), [name] if name.kind == TRANSIENT) => {
+            Some(args)
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct FunctionSig {
     pub ast: Node<ast::FunctionSignature>,
@@ -1214,7 +1326,10 @@ impl FunctionSigId {
     }
 
     pub fn is_public(&self, db: &dyn AnalyzerDb) -> bool {
-        self.is_trait_fn(db) || self.is_impl_fn(db) || self.pub_span(db).is_some()
+        self.is_trait_fn(db)
+            || self.is_impl_fn(db)
+            || self.is_interface_fn(db)
+            || self.pub_span(db).is_some()
     }
     pub fn name(&self, db: &dyn AnalyzerDb) -> SmolStr {
         self.data(db).ast.kind.name.kind.clone()
@@ -1228,6 +1343,11 @@ impl FunctionSigId {
     pub fn is_constructor(&self, db: &dyn AnalyzerDb) -> bool {
         self.name(db) == "__init__"
     }
+    /// `true` for `__receive__` and `__fallback__`, which the dispatcher runs
+    /// without matching a selector.
+    pub fn is_receive_or_fallback(&self, db: &dyn AnalyzerDb) -> bool {
+        matches!(self.name(db).as_str(), RECEIVE_FN_NAME | FALLBACK_FN_NAME)
+    }
     pub fn pub_span(&self, db: &dyn AnalyzerDb) -> Option<Span> {
         self.data(db).ast.kind.pub_
     }
@@ -1292,6 +1412,11 @@ impl FunctionSigId {
         matches! {self.parent(db), Item::Type(TypeDef::Contract(_))}
     }
 
+    /// `true` if the function is declared in an `interface`.
+    pub fn is_interface_fn(&self, db: &dyn AnalyzerDb) -> bool {
+        matches!(self.parent(db), Item::Type(TypeDef::Contract(id)) if id.is_interface(db))
+    }
+
     pub fn sink_diagnostics(&self, db: &dyn AnalyzerDb, sink: &mut impl DiagnosticSink) {
         sink.push_all(db.function_signature(*self).diagnostics.iter());
     }
@@ -1363,6 +1488,9 @@ impl FunctionId {
     pub fn is_constructor(&self, db: &dyn AnalyzerDb) -> bool {
         self.sig(db).is_constructor(db)
     }
+    pub fn is_receive_or_fallback(&self, db: &dyn AnalyzerDb) -> bool {
+        self.sig(db).is_receive_or_fallback(db)
+    }
     pub fn is_unsafe(&self, db: &dyn AnalyzerDb) -> bool {
         self.unsafe_span(db).is_some()
     }
@@ -1384,10 +1512,24 @@ impl FunctionId {
     pub fn sink_diagnostics(&self, db: &dyn AnalyzerDb, sink: &mut impl DiagnosticSink) {
         sink.push_all(db.function_signature(self.data(db).sig).diagnostics.iter());
         sink.push_all(db.function_body(*self).diagnostics.iter());
+        sink.push_all(db.function_expected_revert(*self).diagnostics.iter());
     }
     pub fn is_contract_func(self, db: &dyn AnalyzerDb) -> bool {
         self.sig(db).is_contract_func(db)
     }
+    pub fn is_interface_fn(&self, db: &dyn AnalyzerDb) -> bool {
+        self.sig(db).is_interface_fn(db)
+    }
+
+    /// `true` if the function is declared in an `interface` and takes neither
+    /// `mut self` nor `mut ctx`. It's called with `staticcall`, which reverts
+    /// if the called contract modifies any state.
+    pub fn is_static_interface_fn(&self, db: &dyn AnalyzerDb) -> bool {
+        let sig = self.signature(db);
+        self.is_interface_fn(db)
+            && !matches!(sig.self_decl, Some(decl) if decl.is_mut())
+            && !matches!(sig.ctx_decl, Some(decl) if decl.mut_.is_some())
+    }
 
     pub fn is_test(&self, db: &dyn AnalyzerDb) -> bool {
         Item::Function(*self)
@@ -1395,6 +1537,51 @@ impl FunctionId {
             .iter()
             .any(|attribute| attribute.name(db) == "test")
     }
+
+    /// The revert that the test function expects, if it's marked
+    /// `#test(should_revert)` or `#test(should_revert: <error>)`.
+    pub fn expected_revert(&self, db: &dyn AnalyzerDb) -> Option<Rc<ExpectedRevert>> {
+        db.function_expected_revert(*self).value
+    }
+
+    /// The attributes that precede the function in the body of a contract,
+    /// e.g. `#hot`.
+    pub fn contract_attributes(&self, db: &dyn AnalyzerDb) -> Vec<Node<SmolStr>> {
+        let contract = match self.parent(db) {
+            Item::Type(TypeDef::Contract(contract)) => contract,
+            _ => return vec![],
+        };
+
+        let func_id = self.data(db).ast.id;
+        let mut attributes = vec![];
+        for stmt in &contract.data(db).ast.kind.body {
+            match stmt {
+                ast::ContractStmt::Attribute(attr) => attributes.push(attr.clone()),
+                ast::ContractStmt::Function(func) if func.id == func_id => return attributes,
+                ast::ContractStmt::Function(_) => attributes.clear(),
+            }
+        }
+        vec![]
+    }
+
+    /// `true` if the function is marked `#hot`, in which case the dispatcher
+    /// checks its selector before the selectors of other functions.
+    pub fn is_hot(&self, db: &dyn AnalyzerDb) -> bool {
+        self.contract_attributes(db)
+            .iter()
+            .any(|attr| attr.kind == HOT)
+    }
+
+    /// `true` if the function is marked `#payable` or is `__receive__`. Calls
+    /// to any other public function or `__init__` revert if they come with a
+    /// value.
+    pub fn is_payable(&self, db: &dyn AnalyzerDb) -> bool {
+        self.name(db) == "__receive__"
+            || self
+                .contract_attributes(db)
+                .iter()
+                .any(|attr| attr.kind == PAYABLE)
+    }
 }
 
 trait FunctionsAsItems {
@@ -1438,6 +1625,11 @@ impl FunctionsAsItems for ContractId {
 pub struct Struct {
     pub ast: Node<ast::Struct>,
     pub module: ModuleId,
+    /// `true` if the struct was defined with `error`.
+    pub is_error: bool,
+    /// The types the generic parameters of the struct are bound to. Empty
+    /// unless the struct is an instance of a generic struct.
+    pub generic_args: Vec<TypeId>,
 }
 
 #[derive(Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
@@ -1461,6 +1653,28 @@ impl StructId {
         self.data(db).ast.kind.pub_qual.is_some()
     }
 
+    /// Returns `true` if the struct is an `error` definition.
+    pub fn is_error(&self, db: &dyn AnalyzerDb) -> bool {
+        self.data(db).is_error
+    }
+
+    pub fn is_generic(&self, db: &dyn AnalyzerDb) -> bool {
+        TypeDef::Struct(*self).is_generic(db)
+    }
+
+    pub fn generic_args(&self, db: &dyn AnalyzerDb) -> Vec<TypeId> {
+        self.data(db).generic_args.clone()
+    }
+
+    /// Returns the generic definition the struct is an instance of, or the
+    /// struct itself if it isn't an instance.
+    pub fn definition(&self, db: &dyn AnalyzerDb) -> StructId {
+        db.intern_struct(Rc::new(Struct {
+            generic_args: vec![],
+            ..self.data(db).as_ref().clone()
+        }))
+    }
+
     pub fn module(&self, db: &dyn AnalyzerDb) -> ModuleId {
         self.data(db).module
     }
@@ -1505,6 +1719,20 @@ impl StructId {
             .iter()
             .for_each(|id| id.sink_diagnostics(db, sink));
 
+        if self.is_error(db) {
+            for field in db.struct_all_fields(*self).iter() {
+                if let Ok(typ) = field.typ(db) {
+                    if typ.is_encodable(db) == Ok(false) {
+                        sink.push(&errors::error(
+                            "error field type must be encodable in the ABI",
+                            field.span(db),
+                            format!("`{}` can't be ABI encoded", typ.display(db)),
+                        ));
+                    }
+                }
+            }
+        }
+
         db.struct_function_map(*self).sink_diagnostics(sink);
         db.struct_all_functions(*self)
             .iter()
@@ -1559,7 +1787,7 @@ impl StructFieldId {
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Attribute {
-    pub ast: Node<SmolStr>,
+    pub ast: Node<ast::Attribute>,
     pub module: ModuleId,
 }
 #[derive(Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
@@ -1574,7 +1802,7 @@ impl AttributeId {
         self.data(db).ast.span
     }
     pub fn name(self, db: &dyn AnalyzerDb) -> SmolStr {
-        self.data(db).ast.kind.to_owned()
+        self.data(db).ast.kind.name.kind.to_owned()
     }
 
     pub fn module(self, db: &dyn AnalyzerDb) -> ModuleId {
@@ -1590,6 +1818,9 @@ impl AttributeId {
 pub struct Enum {
     pub ast: Node<ast::Enum>,
     pub module: ModuleId,
+    /// The types the generic parameters of the enum are bound to. Empty
+    /// unless the enum is an instance of a generic enum.
+    pub generic_args: Vec<TypeId>,
 }
 #[derive(Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
 pub struct EnumId(pub(crate) u32);
@@ -1613,10 +1844,33 @@ impl EnumId {
         db.intern_type(Type::Enum(self))
     }
 
+    pub fn is_generic(self, db: &dyn AnalyzerDb) -> bool {
+        TypeDef::Enum(self).is_generic(db)
+    }
+
+    pub fn generic_args(self, db: &dyn AnalyzerDb) -> Vec<TypeId> {
+        self.data(db).generic_args.clone()
+    }
+
+    /// Returns the generic definition the enum is an instance of, or the enum
+    /// itself if it isn't an instance.
+    pub fn definition(self, db: &dyn AnalyzerDb) -> EnumId {
+        db.intern_enum(Rc::new(Enum {
+            generic_args: vec![],
+            ..self.data(db).as_ref().clone()
+        }))
+    }
+
     pub fn is_public(self, db: &dyn AnalyzerDb) -> bool {
         self.data(db).ast.kind.pub_qual.is_some()
     }
 
+    /// Returns `true` if the enum is the enum `name` of `std`, or an instance
+    /// of it.
+    pub fn is_std_enum(self, db: &dyn AnalyzerDb, name: &str) -> bool {
+        self.module(db).is_in_std(db) && self.name(db) == name
+    }
+
     pub fn variant(self, db: &dyn AnalyzerDb, name: &str) -> Option<EnumVariantId> {
         self.variants(db).get(name).copied()
     }
@@ -1704,6 +1958,18 @@ impl EnumVariantId {
     pub fn parent(self, db: &dyn AnalyzerDb) -> EnumId {
         self.data(db).parent
     }
+
+    /// Returns the variant of the same name in `ty` if `ty` is an instance of
+    /// the generic enum the variant belongs to. Otherwise, returns the variant
+    /// itself.
+    pub fn instantiate(self, db: &dyn AnalyzerDb, ty: TypeId) -> EnumVariantId {
+        match ty.deref(db).typ(db) {
+            Type::Enum(id) if id != self.parent(db) && id.definition(db) == self.parent(db) => {
+                id.variant(db, &self.name(db)).unwrap_or(self)
+            }
+            _ => self,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -2076,14 +2342,18 @@ impl TraitId {
     pub fn as_trait_or_type(&self) -> TraitOrType {
         TraitOrType::TraitId(*self)
     }
-    pub fn is_implemented_for(&self, db: &dyn AnalyzerDb, ty: TypeId) -> bool {
+    /// Returns `true` if `ty` implements the trait in `ingot` or its
+    /// dependencies.
+    pub fn is_implemented_for(&self, db: &dyn AnalyzerDb, ty: TypeId, ingot: IngotId) -> bool {
         // All encodable structs automagically implement the Emittable trait
         // TODO: Remove this when we have the `Encode / Decode` trait.
         if self.is_std_trait(db, EMITTABLE_TRAIT_NAME) && ty.is_emittable(db) {
             return true;
         }
 
-        db.all_impls(ty).iter().any(|val| &val.trait_id(db) == self)
+        db.all_impls(ty, ingot)
+            .iter()
+            .any(|val| &val.trait_id(db) == self)
     }
 
     pub fn is_in_std(&self, db: &dyn AnalyzerDb) -> bool {
@@ -20,6 +20,8 @@ use std::collections::BTreeMap;
 pub struct ItemScope<'a> {
     db: &'a dyn AnalyzerDb,
     module: ModuleId,
+    /// The struct or enum whose generic parameters are in scope.
+    generic_owner: Option<TypeDef>,
     expressions: RefCell<IndexMap<NodeId, ExpressionAttributes>>,
     pub diagnostics: RefCell<Vec<Diagnostic>>,
 }
@@ -28,10 +30,20 @@ impl<'a> ItemScope<'a> {
         Self {
             db,
             module,
+            generic_owner: None,
             expressions: RefCell::new(IndexMap::default()),
             diagnostics: RefCell::new(vec![]),
         }
     }
+
+    /// Creates a scope for a member of `owner`, in which the generic
+    /// parameters of `owner` can be used as types.
+    pub fn with_generic_owner(db: &'a dyn AnalyzerDb, module: ModuleId, owner: TypeDef) -> Self {
+        Self {
+            generic_owner: Some(owner),
+            ..Self::new(db, module)
+        }
+    }
 }
 
 impl<'a> AnalyzerContext for ItemScope<'a> {
@@ -184,6 +196,10 @@ impl<'a> AnalyzerContext for ItemScope<'a> {
 
         None
     }
+
+    fn generic_param_type(&self, name: &str) -> Option<TypeId> {
+        self.generic_owner?.generic_param_type(self.db, name)
+    }
 }
 
 pub struct FunctionScope<'a> {
@@ -3,7 +3,7 @@ use crate::display::DisplayWithDb;
 use crate::display::Displayable;
 use crate::errors::TypeError;
 use crate::namespace::items::{
-    ContractId, EnumId, FunctionId, FunctionSigId, ImplId, Item, StructId, TraitId,
+    ContractId, EnumId, FunctionId, FunctionSigId, ImplId, IngotId, Item, StructId, TraitId,
 };
 use crate::AnalyzerDb;
 
@@ -183,10 +183,16 @@ impl TypeId {
         }
     }
 
-    /// Return the `impl` for the given trait. There can only ever be a single
-    /// implementation per concrete type and trait.
-    pub fn get_impl_for(&self, db: &dyn AnalyzerDb, trait_: TraitId) -> Option<ImplId> {
-        db.impl_for(*self, trait_)
+    /// Return the `impl` for the given trait that is visible from `ingot`.
+    /// There can only ever be a single implementation per concrete type and
+    /// trait.
+    pub fn get_impl_for(
+        &self,
+        db: &dyn AnalyzerDb,
+        trait_: TraitId,
+        ingot: IngotId,
+    ) -> Option<ImplId> {
+        db.impl_for(*self, trait_, ingot)
     }
 
     /// Looks up all possible candidates of the given function name that are implemented via traits.
@@ -199,7 +205,7 @@ impl TypeId {
     ) -> TraitFunctionLookup {
         let candidates = context
             .db()
-            .all_impls(*self)
+            .all_impls(*self, context.module().ingot(context.db()))
             .iter()
             .cloned()
             .filter_map(|_impl| {
@@ -242,9 +248,14 @@ impl TypeId {
 
     /// Like `function_sig` but returns a `Vec<FunctionSigId>` which not only
     /// considers functions natively implemented on the type but also those
-    /// that are provided by implemented traits on the type.
-    pub fn function_sigs(&self, db: &dyn AnalyzerDb, name: &str) -> Rc<[FunctionSigId]> {
-        db.function_sigs(*self, name.into())
+    /// that are provided by traits implemented in `ingot` or its dependencies.
+    pub fn function_sigs(
+        &self,
+        db: &dyn AnalyzerDb,
+        name: &str,
+        ingot: IngotId,
+    ) -> Rc<[FunctionSigId]> {
+        db.function_sigs(*self, name.into(), ingot)
     }
 
     pub fn self_function(&self, db: &dyn AnalyzerDb, name: &str) -> Option<FunctionSigId> {
@@ -256,7 +267,8 @@ impl TypeId {
     /// TODO: This function should be removed when we add `Encode / Decode`
     /// trait
     pub fn is_emittable(self, db: &dyn AnalyzerDb) -> bool {
-        matches!(self.typ(db), Type::Struct(_)) && self.is_encodable(db).unwrap_or(false)
+        matches!(self.typ(db), Type::Struct(id) if !id.is_error(db))
+            && self.is_encodable(db).unwrap_or(false)
     }
 
     /// Returns `true` if the type is encodable in Solidity ABI.
@@ -750,8 +762,14 @@ impl DisplayWithDb for Type {
                 write!(f, ")")
             }
             Type::Contract(id) | Type::SelfContract(id) => write!(f, "{}", id.name(db)),
-            Type::Struct(id) => write!(f, "{}", id.name(db)),
-            Type::Enum(id) => write!(f, "{}", id.name(db)),
+            Type::Struct(id) => {
+                write!(f, "{}", id.name(db))?;
+                format_generic_args(&id.generic_args(db), db, f)
+            }
+            Type::Enum(id) => {
+                write!(f, "{}", id.name(db))?;
+                format_generic_args(&id.generic_args(db), db, f)
+            }
             Type::Generic(inner) => inner.fmt(f),
             Type::SPtr(inner) => write!(f, "SPtr<{}>", inner.display(db)),
             Type::Mut(inner) => write!(f, "mut {}", inner.display(db)),
@@ -759,6 +777,24 @@ impl DisplayWithDb for Type {
         }
     }
 }
+/// Writes the generic arguments of a struct or enum instance, if there are any.
+fn format_generic_args(
+    args: &[TypeId],
+    db: &dyn AnalyzerDb,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    if args.is_empty() {
+        return Ok(());
+    }
+    write!(f, "<")?;
+    let mut delim = "";
+    for arg in args {
+        write!(f, "{}{}", delim, arg.display(db))?;
+        delim = ", ";
+    }
+    write!(f, ">")
+}
+
 impl DisplayWithDb for TypeId {
     fn format(&self, db: &dyn AnalyzerDb, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.typ(db).format(db, f)
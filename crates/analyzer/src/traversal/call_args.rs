@@ -166,8 +166,9 @@ pub fn validate_named_args(
         let arg_type =
             if let Type::Generic(Generic { bounds, .. }) = param_type.deref_typ(context.db()) {
                 let arg_type = expr_type(context, &arg.kind.value)?;
+                let ingot = context.module().ingot(context.db());
                 for bound in bounds.iter() {
-                    if !bound.is_implemented_for(context.db(), arg_type) {
+                    if !bound.is_implemented_for(context.db(), arg_type, ingot) {
                         context.error(
                             &format!(
                                 "the trait bound `{}: {}` is not satisfied",
@@ -55,6 +55,7 @@ pub(crate) fn eval_expr(
 
         // TODO: Need to evaluate attribute getter, constant constructor and const fn call.
         ast::Expr::Subscript { .. }
+        | ast::Expr::Try { .. }
         | ast::Expr::Path(_)
         | ast::Expr::Attribute { .. }
         | ast::Expr::Call { .. }
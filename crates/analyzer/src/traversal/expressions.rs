@@ -1,22 +1,27 @@
 use super::borrowck;
 use crate::builtins::{ContractTypeMethod, GlobalFunction, Intrinsic, ValueMethod};
+use crate::constants::{is_special_fn_name, CALL_FN_NAME, INIT_FN_NAME};
 use crate::context::{AnalyzerContext, CallType, Constant, ExpressionAttributes, NamedThing};
 use crate::display::Displayable;
 use crate::errors::{self, FatalError, IndexingError, TypeCoercionError};
 use crate::namespace::items::{
-    EnumVariantId, EnumVariantKind, FunctionId, FunctionSigId, ImplId, Item, StructId, TypeDef,
+    ContractId, EnumId, EnumVariantId, EnumVariantKind, FunctionId, FunctionSigId, ImplId, Item,
+    StructId, TypeDef,
 };
 use crate::namespace::scopes::{check_visibility, BlockScopeType};
 use crate::namespace::types::{
-    self, Array, Base, FeString, Integer, TraitOrType, Tuple, Type, TypeDowncast, TypeId,
+    self, Array, Base, FeString, FunctionParam, Integer, TraitOrType, Tuple, Type, TypeDowncast,
+    TypeId,
 };
 use crate::operations;
 use crate::traversal::call_args::{validate_arg_count, validate_named_args};
 use crate::traversal::const_expr::eval_expr;
 use crate::traversal::types::{
-    apply_generic_type_args, deref_type, try_cast_type, try_coerce_type,
+    apply_generic_type_args, apply_type_def_generic_args, deref_type, try_cast_type,
+    try_coerce_type,
 };
 use crate::traversal::utils::add_bin_operations_errors;
+use crate::AnalyzerDb;
 
 use fe_common::diagnostics::Label;
 use fe_common::{numeric, Span};
@@ -90,6 +95,7 @@ pub fn expr(
         fe::Expr::Num(_) => Ok(expr_num(context, exp, expected)),
 
         fe::Expr::Subscript { .. } => expr_subscript(context, exp, expected),
+        fe::Expr::Try { .. } => expr_try(context, exp),
         fe::Expr::Attribute { .. } => expr_attribute(context, exp, expected),
         fe::Expr::Ternary { .. } => expr_ternary(context, exp, expected),
         fe::Expr::BoolOperation { .. } => expr_bool_operation(context, exp),
@@ -416,6 +422,7 @@ fn expr_named_thing(
             Ok(typ)
         }
         Some(NamedThing::EnumVariant(variant)) => {
+            let variant = instantiate_enum_variant(context, variant, expected_type, exp.span)?;
             if let Ok(EnumVariantKind::Tuple(_)) = variant.kind(context.db()) {
                 let name = variant.name_with_parent(context.db());
                 context.fancy_error(
@@ -623,6 +630,95 @@ fn expr_subscript(
     unreachable!()
 }
 
+/// Analyzes `value?`. The value must be an `Option` or a `Result`, and the
+/// function must return an `Option` or a `Result` with the same error type
+/// respectively, since the `None` or `Err` value is returned as is.
+fn expr_try(
+    context: &mut dyn AnalyzerContext,
+    exp: &Node<fe::Expr>,
+) -> Result<ExpressionAttributes, FatalError> {
+    let value = match &exp.kind {
+        fe::Expr::Try { value } => value,
+        _ => unreachable!(),
+    };
+
+    let value_ty = expr_type(context, value)?;
+    let value_ty = deref_type(context, value, value_ty);
+    let (enum_, ok_variant, err_variant) = match value_ty.typ(context.db()) {
+        Type::Enum(id) if id.is_std_enum(context.db(), "Option") => (id, "Some", "None"),
+        Type::Enum(id) if id.is_std_enum(context.db(), "Result") => (id, "Ok", "Err"),
+        _ => {
+            return Err(FatalError::new(context.fancy_error(
+                "the `?` operator can only be applied to an `Option` or a `Result`",
+                vec![Label::primary(
+                    value.span,
+                    format!("this has type `{}`", value_ty.display(context.db())),
+                )],
+                vec![],
+            )))
+        }
+    };
+
+    let payload_ty = variant_payload(context, enum_, ok_variant)
+        .ok_or_else(|| FatalError::new(context.error("invalid `?` operand", value.span, "")))?;
+    let err_ty = variant_payload(context, enum_, err_variant);
+
+    let return_ty = match context.parent() {
+        Item::Function(function) => match function.signature(context.db()).return_type.clone() {
+            Ok(ty) => ty.deref(context.db()),
+            Err(_) => return Ok(ExpressionAttributes::new(payload_ty)),
+        },
+        _ => {
+            context.error(
+                "the `?` operator can only be used in a function body",
+                exp.span,
+                "",
+            );
+            return Ok(ExpressionAttributes::new(payload_ty));
+        }
+    };
+
+    let is_compatible = match return_ty.typ(context.db()) {
+        Type::Enum(id) if id.definition(context.db()) == enum_.definition(context.db()) => {
+            variant_payload(context, id, err_variant) == err_ty
+        }
+        _ => false,
+    };
+    if !is_compatible {
+        let expected_return_ty = match err_ty {
+            Some(err_ty) => format!("Result<.., {}>", err_ty.display(context.db())),
+            None => "Option<..>".into(),
+        };
+        context.fancy_error(
+            &format!(
+                "the `?` operator can't be used in a function that returns `{}`",
+                return_ty.display(context.db())
+            ),
+            vec![Label::primary(
+                exp.span,
+                format!(
+                    "this returns the `{}` value of `{}` from the function",
+                    err_variant,
+                    value_ty.display(context.db())
+                ),
+            )],
+            vec![format!(
+                "Hint: the function must return `{expected_return_ty}`"
+            )],
+        );
+    }
+
+    Ok(ExpressionAttributes::new(payload_ty))
+}
+
+/// Returns the type of the first field of the variant `name` of `enum_`.
+fn variant_payload(context: &dyn AnalyzerContext, enum_: EnumId, name: &str) -> Option<TypeId> {
+    match enum_.variant(context.db(), name)?.kind(context.db()) {
+        Ok(EnumVariantKind::Tuple(elts)) => elts.first().copied(),
+        _ => None,
+    }
+}
+
 fn expr_attribute(
     context: &mut dyn AnalyzerContext,
     exp: &Node<fe::Expr>,
@@ -664,8 +760,11 @@ fn field_type(
             field_type(context, inner, field_name, field_span)?,
         ))
         .id(context.db())),
-        Type::SelfContract(id) => match id.field_type(context.db(), field_name) {
-            Some(typ) => Ok(typ?.make_sptr(context.db())),
+        // Transient fields are read and written as values, they are not
+        // addressable like fields in storage.
+        Type::SelfContract(id) => match id.fields(context.db()).get(field_name) {
+            Some(field) if field.is_transient(context.db()) => Ok(field.typ(context.db())?),
+            Some(field) => Ok(field.typ(context.db())?.make_sptr(context.db())),
             None => Err(FatalError::new(context.fancy_error(
                 &format!("No field `{field_name}` exists on this contract"),
                 vec![Label::primary(field_span, "undefined field")],
@@ -875,8 +974,12 @@ fn expr_call(
     expected_type: Option<TypeId>,
 ) -> Result<ExpressionAttributes, FatalError> {
     let (attributes, call_type) = match &func.kind {
-        fe::Expr::Name(name) => expr_call_name(context, name, func, generic_args, args)?,
-        fe::Expr::Path(path) => expr_call_path(context, path, func, generic_args, args)?,
+        fe::Expr::Name(name) => {
+            expr_call_name(context, name, func, generic_args, args, expected_type)?
+        }
+        fe::Expr::Path(path) => {
+            expr_call_path(context, path, func, generic_args, args, expected_type)?
+        }
         fe::Expr::Attribute { value, attr } => {
             // TODO: err if there are generic args
             expr_call_method(context, value, attr, generic_args, args)?
@@ -940,6 +1043,7 @@ fn expr_call_name<T: std::fmt::Display>(
     func: &Node<T>,
     generic_args: &Option<Node<Vec<fe::GenericArg>>>,
     args: &Node<Vec<Node<fe::CallArg>>>,
+    expected_type: Option<TypeId>,
 ) -> Result<(ExpressionAttributes, CallType), FatalError> {
     check_for_call_to_special_fns(context, name, func.span)?;
 
@@ -986,7 +1090,14 @@ fn expr_call_name<T: std::fmt::Display>(
         }
     })?;
 
-    expr_call_named_thing(context, named_thing, func, generic_args, args)
+    expr_call_named_thing(
+        context,
+        named_thing,
+        func,
+        generic_args,
+        args,
+        expected_type,
+    )
 }
 
 fn expr_call_path<T: std::fmt::Display>(
@@ -995,12 +1106,20 @@ fn expr_call_path<T: std::fmt::Display>(
     func: &Node<T>,
     generic_args: &Option<Node<Vec<fe::GenericArg>>>,
     args: &Node<Vec<Node<fe::CallArg>>>,
+    expected_type: Option<TypeId>,
 ) -> Result<(ExpressionAttributes, CallType), FatalError> {
     match context.resolve_visible_path(path) {
         Some(named_thing) => {
             check_visibility(context, &named_thing, func.span);
             validate_has_no_conflicting_trait_in_scope(context, &named_thing, path, func)?;
-            expr_call_named_thing(context, named_thing, func, generic_args, args)
+            expr_call_named_thing(
+                context,
+                named_thing,
+                func,
+                generic_args,
+                args,
+                expected_type,
+            )
         }
         // If we we can't resolve a call to a path e.g. `foo::Bar::do_thing()` there is a chance that `do_thing`
         // still exists as as a trait associated function for `foo::Bar`.
@@ -1141,6 +1260,7 @@ fn expr_call_named_thing<T: std::fmt::Display>(
     func: &Node<T>,
     generic_args: &Option<Node<Vec<fe::GenericArg>>>,
     args: &Node<Vec<Node<fe::CallArg>>>,
+    expected_type: Option<TypeId>,
 ) -> Result<(ExpressionAttributes, CallType), FatalError> {
     match named_thing {
         NamedThing::Item(Item::BuiltinFunction(function)) => {
@@ -1152,6 +1272,23 @@ fn expr_call_named_thing<T: std::fmt::Display>(
         NamedThing::Item(Item::Function(function)) => {
             expr_call_pure(context, function, func.span, generic_args, args)
         }
+        NamedThing::Item(Item::Type(def)) if def.is_generic(context.db()) => {
+            // The generic arguments of a struct may be left out if the expected type is an
+            // instance of it.
+            let typ = match expected_type.map(|typ| typ.deref(context.db())) {
+                Some(expected)
+                    if generic_args.is_none()
+                        && matches!(
+                            expected.typ(context.db()),
+                            Type::Struct(id) if TypeDef::Struct(id.definition(context.db())) == def
+                        ) =>
+                {
+                    expected
+                }
+                _ => apply_type_def_generic_args(context, def, func.span, generic_args.as_ref())?,
+            };
+            expr_call_type_constructor(context, typ, func.span, args)
+        }
         NamedThing::Item(Item::Type(def)) => {
             if let Some(args) = generic_args {
                 context.fancy_error(
@@ -1208,6 +1345,7 @@ fn expr_call_named_thing<T: std::fmt::Display>(
         ))),
 
         NamedThing::EnumVariant(variant) => {
+            let variant = instantiate_enum_variant(context, variant, expected_type, func.span)?;
             expr_call_enum_constructor(context, func.span, variant, args)
         }
 
@@ -1303,6 +1441,32 @@ fn expr_call_intrinsic(
         );
     }
 
+    // The std library wraps intrinsics for all EVM versions, so only the calls
+    // of user code are checked.
+    let evm_version = context.db().evm_version();
+    if !function.is_available(evm_version) && !context.module().is_in_std(context.db()) {
+        let label = match (function.introduced_in(), function.removed_in()) {
+            (_, Some(until)) if evm_version >= until => format!("removed in `{until}`"),
+            (Some(since), _) => format!("requires `{since}` or later"),
+            _ => unreachable!(),
+        };
+        let notes = match function.replacement() {
+            Some(replacement) if replacement.is_available(evm_version) => vec![format!(
+                "Hint: use `{}`, which computes the same value",
+                replacement.as_ref()
+            )],
+            _ => vec![],
+        };
+        context.fancy_error(
+            &format!(
+                "`{}` is not available in EVM version `{evm_version}`",
+                function.as_ref()
+            ),
+            vec![Label::primary(name_span, label)],
+            notes,
+        );
+    }
+
     let argument_attributes = expr_call_args(context, args)?;
 
     validate_arg_count(
@@ -1383,6 +1547,13 @@ fn expr_call_pure(
     let sig = function.sig(context.db());
     validate_visibility_of_called_fn(context, call_span, sig);
 
+    if matches!(function.parent(context.db()), Item::Type(def) if def.is_generic(context.db())) {
+        return Err(FatalError::new(context.not_yet_implemented(
+            "calling associated functions of generic types",
+            call_span,
+        )));
+    }
+
     let fn_name = function.name(context.db());
     if let Some(args) = generic_args {
         context.fancy_error(
@@ -1509,6 +1680,44 @@ fn expr_call_struct_constructor(
     ))
 }
 
+/// Returns the variant of the generic enum instance that is expected at this
+/// point. Variants of non-generic enums are returned as they are.
+fn instantiate_enum_variant(
+    context: &mut dyn AnalyzerContext,
+    variant: EnumVariantId,
+    expected_type: Option<TypeId>,
+    span: Span,
+) -> Result<EnumVariantId, FatalError> {
+    let db = context.db();
+    let parent = variant.parent(db);
+    if !parent.is_generic(db) {
+        return Ok(variant);
+    }
+
+    match expected_type.map(|typ| typ.deref(db).typ(db)) {
+        Some(Type::Enum(id)) if id.definition(db) == parent => {
+            Ok(variant.instantiate(db, id.as_type(db)))
+        }
+        _ => Err(FatalError::new(context.fancy_error(
+            &format!(
+                "cannot infer the generic arguments of `{}`",
+                parent.name(db)
+            ),
+            vec![Label::primary(
+                span,
+                format!(
+                    "`{}` is used here without an expected type",
+                    variant.name_with_parent(db)
+                ),
+            )],
+            vec![format!(
+                "Hint: annotate the expected type, e.g. `let x: {}<u256> = ...`",
+                parent.name(db)
+            )],
+        ))),
+    }
+}
+
 fn expr_call_enum_constructor(
     context: &mut dyn AnalyzerContext,
     name_span: Span,
@@ -1578,7 +1787,11 @@ fn expr_call_method(
         check_for_call_to_special_fns(context, &field.kind, field.span)?;
     }
 
-    match obj_type.function_sigs(context.db(), &field.kind).as_ref() {
+    let ingot = context.module().ingot(context.db());
+    match obj_type
+        .function_sigs(context.db(), &field.kind, ingot)
+        .as_ref()
+    {
         [] => Err(FatalError::new(context.fancy_error(
             &format!(
                 "No function `{}` exists on type `{}`",
@@ -1898,7 +2111,21 @@ fn expr_call_type_attribute(
         );
     }
 
-    let arg_attributes = expr_call_args(context, args)?;
+    // The arguments that `create` and `create2` pass on to `__init__` are
+    // checked against its parameters below.
+    let builtin_arg_count = match typ {
+        Type::Contract(_) => ContractTypeMethod::from_str(&field.kind)
+            .map(|function| function.arg_count().min(args.kind.len()))
+            .ok(),
+        _ => None,
+    };
+    let (builtin_args, init_args) = args
+        .kind
+        .split_at(builtin_arg_count.unwrap_or(args.kind.len()));
+    let arg_attributes = builtin_args
+        .iter()
+        .map(|arg| expr(context, &arg.kind.value, None))
+        .collect::<Result<Vec<_>, _>>()?;
 
     let target_type = typ.id(context.db());
     let target_name = typ.name(context.db());
@@ -1907,6 +2134,20 @@ fn expr_call_type_attribute(
         // Check for Foo.create/create2 (this will go away when the context object is
         // ready)
         if let Ok(function) = ContractTypeMethod::from_str(&field.kind) {
+            if contract.is_interface(context.db()) {
+                return Err(FatalError::new(context.fancy_error(
+                    &format!(
+                        "`{}.{}(...)` can't be called on an interface",
+                        &target_name,
+                        function.as_ref()
+                    ),
+                    vec![Label::primary(field.span, "interfaces can't be deployed")],
+                    vec![format!(
+                        "Hint: use `{}(addr)` to call a contract that's deployed at `addr`",
+                        &target_name
+                    )],
+                )));
+            }
             if context.root_item() == Item::Type(TypeDef::Contract(contract)) {
                 context.fancy_error(
                         &format!("`{contract}.{}(...)` called within `{contract}` creates an illegal circular dependency", function.as_ref(), contract=&target_name),
@@ -1914,12 +2155,13 @@ fn expr_call_type_attribute(
                         vec![format!("Note: Consider using a dedicated factory contract to create instances of `{}`", &target_name)]);
             }
             let arg_count = function.arg_count();
+            let init_params = contract_init_params(context.db(), contract);
             validate_arg_count(
                 context,
                 &field.kind,
                 field.span,
                 args,
-                arg_count,
+                arg_count + init_params.len(),
                 "argument",
             );
 
@@ -1983,6 +2225,16 @@ fn expr_call_type_attribute(
                     }
                 }
             }
+
+            if init_args.len() == init_params.len() {
+                let init_args = Node::new(init_args.to_vec(), args.span);
+                let name = format!("{}.{}", &target_name, function.as_ref());
+                validate_named_args(context, &name, field.span, &init_args, &init_params)?;
+            } else {
+                for arg in init_args {
+                    expr(context, &arg.kind.value, None)?;
+                }
+            }
             return Ok((
                 ExpressionAttributes::new(context.db().intern_type(typ)),
                 CallType::BuiltinAssociatedFunction { contract, function },
@@ -2046,6 +2298,19 @@ fn expr_call_type_attribute(
     )))
 }
 
+/// The parameters of the `__init__` function of `contract` that are passed
+/// to `create` and `create2`, i.e. all but `ctx`.
+fn contract_init_params(db: &dyn AnalyzerDb, contract: ContractId) -> Vec<FunctionParam> {
+    match contract.init_function(db) {
+        Some(init) => {
+            let sig = init.signature(db);
+            let ctx_params = usize::from(sig.ctx_decl.is_some());
+            sig.params[ctx_params..].to_vec()
+        }
+        None => vec![],
+    }
+}
+
 fn expr_call_args(
     context: &mut dyn AnalyzerContext,
     args: &Node<Vec<Node<fe::CallArg>>>,
@@ -2079,13 +2344,17 @@ fn check_for_call_to_special_fns(
     name: &str,
     span: Span,
 ) -> Result<(), FatalError> {
-    if name == "__init__" || name == "__call__" {
-        let label = if name == "__init__" {
-            "Note: `__init__` is the constructor function, and can't be called at runtime."
-        } else {
+    if is_special_fn_name(name) {
+        let label = match name {
+            INIT_FN_NAME => {
+                "Note: `__init__` is the constructor function, and can't be called at runtime."
+            }
             // TODO: add a hint label explaining how to call contracts directly
             // with `Context` (not yet supported).
-            "Note: `__call__` is not part of the contract's interface, and can't be called."
+            CALL_FN_NAME => {
+                "Note: `__call__` is not part of the contract's interface, and can't be called."
+            }
+            _ => "Note: `__receive__` and `__fallback__` are run by the function dispatcher, and can't be called.",
         };
         Err(FatalError::new(context.fancy_error(
             &format!("`{name}()` is not directly callable"),
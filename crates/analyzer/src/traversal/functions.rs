@@ -208,6 +208,7 @@ fn match_pattern(
         Pattern::Path(path) => match scope.resolve_visible_path(&path.kind) {
             Some(NamedThing::EnumVariant(variant)) => {
                 let db = scope.db();
+                let variant = variant.instantiate(db, expected_type);
                 let parent_type = variant.parent(db).as_type(db);
                 let kind = variant.kind(db)?;
                 if kind != EnumVariantKind::Unit {
@@ -283,7 +284,7 @@ fn match_pattern(
 
         Pattern::PathTuple(path, pat_elts) => {
             let variant = match scope.resolve_path(&path.kind, path.span)? {
-                NamedThing::EnumVariant(variant) => variant,
+                NamedThing::EnumVariant(variant) => variant.instantiate(scope.db(), expected_type),
                 _ => {
                     let err = scope.fancy_error(
                         "expected enum variant",
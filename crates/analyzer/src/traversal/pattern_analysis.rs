@@ -640,7 +640,7 @@ fn simplify_pattern(
 
         Pattern::Path(path) => match scope.resolve_visible_path(&path.kind) {
             Some(NamedThing::EnumVariant(variant)) => SimplifiedPatternKind::Constructor {
-                kind: ConstructorKind::Enum(variant),
+                kind: ConstructorKind::Enum(variant.instantiate(scope.db(), ty)),
                 fields: vec![],
             },
             _ => {
@@ -651,7 +651,7 @@ fn simplify_pattern(
 
         Pattern::PathTuple(path, elts) => {
             let variant = match scope.resolve_visible_path(&path.kind).unwrap() {
-                NamedThing::EnumVariant(variant) => variant,
+                NamedThing::EnumVariant(variant) => variant.instantiate(scope.db(), ty),
                 _ => unreachable!(),
             };
             let ctor_kind = ConstructorKind::Enum(variant);
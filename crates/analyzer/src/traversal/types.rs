@@ -5,7 +5,7 @@ use crate::context::{
 };
 use crate::display::Displayable;
 use crate::errors::{TypeCoercionError, TypeError};
-use crate::namespace::items::{Item, TraitId};
+use crate::namespace::items::{Item, TraitId, TypeDef};
 use crate::namespace::types::{
     Base, FeString, GenericArg, GenericParamKind, GenericType, Integer, TraitOrType, Tuple, Type,
     TypeId,
@@ -16,7 +16,9 @@ use fe_common::utils::humanize::pluralize_conditionally;
 use fe_common::Spanned;
 use fe_parser::ast;
 use fe_parser::node::{Node, Span};
+use smol_str::SmolStr;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 
 /// Try to perform an explicit type cast, eg `u256(my_address)` or `address(my_contract)`.
 /// Returns nothing. Emits an error if the cast fails; explicit cast failures are not fatal.
@@ -441,6 +443,103 @@ pub fn apply_generic_type_args(
         .expect("failed to construct generic type after checking args"))
 }
 
+/// Checks the generic parameter list of a struct or enum definition.
+pub fn check_type_generic_params(context: &dyn AnalyzerContext, params: &[ast::GenericParameter]) {
+    let mut names = HashMap::<SmolStr, Span>::new();
+    for param in params {
+        let name = param.name_node();
+        if let Some(previous) = names.get(&name.kind) {
+            context.duplicate_name_error(
+                "duplicate generic parameter",
+                &name.kind,
+                *previous,
+                name.span,
+            );
+        } else {
+            names.insert(name.kind, name.span);
+        }
+
+        if let ast::GenericParameter::Bounded { bound, .. } = param {
+            context.not_yet_implemented("trait bounds on struct or enum parameters", bound.span);
+        }
+    }
+}
+
+/// Binds the generic parameters of a struct or enum definition to the given
+/// type arguments, e.g. `Pair<u8, bool>`.
+pub fn apply_type_def_generic_args(
+    context: &mut dyn AnalyzerContext,
+    def: TypeDef,
+    name_span: Span,
+    args: Option<&Node<Vec<ast::GenericArg>>>,
+) -> Result<TypeId, TypeError> {
+    let params = def.generic_params(context.db());
+    let name = def.name(context.db());
+
+    let args = args.ok_or_else(|| {
+        TypeError::new(context.fancy_error(
+            &format!(
+                "missing generic {} for type `{}`",
+                pluralize_conditionally("argument", params.len()),
+                name
+            ),
+            vec![Label::primary(
+                name_span,
+                format!(
+                    "expected {} generic {}",
+                    params.len(),
+                    pluralize_conditionally("argument", params.len())
+                ),
+            )],
+            vec![format!(
+                "Example: `{}<{}>`",
+                name,
+                vec!["u256"; params.len()].join(", ")
+            )],
+        ))
+    })?;
+
+    if let Some(diag) = validate_arg_count(
+        context,
+        &name,
+        name_span,
+        args,
+        params.len(),
+        "generic argument",
+    ) {
+        return Err(TypeError::new(diag));
+    }
+
+    let concrete_args = params
+        .iter()
+        .zip(args.kind.iter())
+        .map(|(param, arg)| match arg {
+            ast::GenericArg::TypeDesc(type_node) => {
+                let typ = type_desc(context, type_node, None)?;
+                if typ.has_fixed_size(context.db()) {
+                    Ok(typ)
+                } else {
+                    Err(TypeError::new(context.error(
+                        &format!("`{}` {} must have a fixed size", name, param.name()),
+                        type_node.span,
+                        &format!("`{}` doesn't have a fixed size", typ.display(context.db())),
+                    )))
+                }
+            }
+            ast::GenericArg::Int(_) | ast::GenericArg::ConstExpr(_) => {
+                Err(TypeError::new(context.fancy_error(
+                    &format!("`{}` {} must be a type", name, param.name()),
+                    vec![Label::primary(arg.span(), "expected a type name")],
+                    vec![],
+                )))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    def.instantiate(context.db(), concrete_args)
+        .type_id(context.db())
+}
+
 fn friendly_generic_arg_example_string(generic: GenericType) -> String {
     let example_args = generic
         .params()
@@ -482,6 +581,9 @@ pub fn resolve_concrete_type_named_thing<T: std::fmt::Display>(
     generic_args: Option<&Node<Vec<ast::GenericArg>>>,
 ) -> Result<TypeId, TypeError> {
     match named_thing {
+        Some(NamedThing::Item(Item::Type(id))) if id.is_generic(context.db()) => {
+            apply_type_def_generic_args(context, id, base_desc.span, generic_args)
+        }
         Some(NamedThing::Item(Item::Type(id))) => {
             if let Some(args) = generic_args {
                 context.fancy_error(
@@ -542,12 +644,15 @@ pub fn type_desc(
     self_type: Option<TraitOrType>,
 ) -> Result<TypeId, TypeError> {
     match &desc.kind {
-        ast::TypeDesc::Base { base } => resolve_concrete_type_name(context, base, desc, None),
+        ast::TypeDesc::Base { base } => match context.generic_param_type(base) {
+            Some(typ) => Ok(typ),
+            None => resolve_concrete_type_name(context, base, desc, None),
+        },
         ast::TypeDesc::Path(path) => resolve_concrete_type_path(context, path, desc, None),
-        // generic will need to allow for paths too
-        ast::TypeDesc::Generic { base, args } => {
-            resolve_concrete_type_name(context, &base.kind, base, Some(args))
-        }
+        ast::TypeDesc::Generic { base, args } => match base.kind.segments.as_slice() {
+            [name] => resolve_concrete_type_name(context, &name.kind, base, Some(args)),
+            _ => resolve_concrete_type_path(context, &base.kind, base, Some(args)),
+        },
         ast::TypeDesc::Tuple { items } => {
             let types = items
                 .iter()
@@ -595,9 +700,11 @@ pub fn type_desc_to_trait(
             let named_thing = context.resolve_path(path, desc.span)?;
             resolve_concrete_trait_named_thing(context, Some(named_thing), desc)
         }
-        // generic will need to allow for paths too
         ast::TypeDesc::Generic { base, .. } => {
-            let named_thing = context.resolve_name(&base.kind, desc.span)?;
+            let named_thing = match base.kind.segments.as_slice() {
+                [name] => context.resolve_name(&name.kind, desc.span)?,
+                _ => Some(context.resolve_path(&base.kind, desc.span)?),
+            };
             resolve_concrete_trait_named_thing(context, named_thing, desc)
         }
         _ => panic!("Should be rejected by parser"),
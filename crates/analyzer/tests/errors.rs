@@ -1,14 +1,20 @@
 //! Tests for contracts that should cause compile errors
 
 use fe_analyzer::namespace::items::{IngotId, ModuleId};
-use fe_analyzer::TestDb;
+use fe_analyzer::{AnalyzerDb, TestDb};
 use fe_common::diagnostics::diagnostics_string;
 use fe_common::utils::files::BuildFiles;
+use fe_common::EvmVersion;
 use insta::assert_snapshot;
 use wasm_bindgen_test::wasm_bindgen_test;
 
 fn error_string(path: &str, src: &str) -> String {
+    error_string_for_evm_version(path, src, EvmVersion::default())
+}
+
+fn error_string_for_evm_version(path: &str, src: &str, evm_version: EvmVersion) -> String {
     let mut db = TestDb::default();
+    db.set_evm_version(evm_version);
     let module = ModuleId::new_standalone(&mut db, path, src);
 
     let diags = module.diagnostics(&db);
@@ -236,6 +242,7 @@ test_file! { cannot_move }
 test_file! { cannot_move2 }
 test_file! { circular_dependency_create }
 test_file! { circular_dependency_create2 }
+test_file! { create_init_args }
 test_file! { circular_type_alias }
 test_file! { const_assign }
 test_file! { const_generics_param }
@@ -251,6 +258,8 @@ test_file! { duplicate_var_in_child_scope }
 test_file! { duplicate_var_in_contract_method }
 test_file! { duplicate_var_in_for_loop }
 test_file! { duplicate_generic_params }
+test_file! { generic_type_args }
+test_file! { error_field_not_encodable }
 test_file! { external_call_type_error }
 test_file! { external_call_wrong_number_of_params }
 test_file! { emittable_not_implementable }
@@ -363,6 +372,25 @@ test_file! { invalid_repeat_length }
 test_file! { invalid_struct_pub_qualifier }
 test_file! { mut_mistakes }
 test_file! { invalid_comparisons }
+test_file! { try_operator }
+test_file! { transient_field_type }
+test_file! { contract_fn_attributes }
+test_file! { receive_fallback }
+test_file! { interface }
+test_stmt! { difficulty_after_paris, "unsafe { __difficulty() }" }
+
+#[test]
+#[wasm_bindgen_test]
+fn evm_version_london() {
+    let path = "compile_errors/evm_version_london.fe";
+    let errors = error_string_for_evm_version(path, test_files::fixture(path), EvmVersion::London);
+    if cfg!(target_arch = "wasm32") {
+        fe_common::assert_snapshot_wasm!("snapshots/errors__evm_version_london.snap", errors);
+    } else {
+        assert_snapshot!(errors);
+    }
+}
 
 test_file! { _test_fn_call }
 test_file! { _test_fn_params }
+test_file! { _test_fn_should_revert }
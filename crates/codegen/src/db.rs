@@ -1,13 +1,17 @@
 #![allow(clippy::arc_with_non_send_sync)]
 use std::rc::Rc;
 
-use fe_abi::{contract::AbiContract, event::AbiEvent, function::AbiFunction, types::AbiType};
+use fe_abi::{
+    contract::AbiContract, error::AbiError, event::AbiEvent, function::AbiFunction,
+    storage_layout::StorageLayout, types::AbiType,
+};
 use fe_analyzer::{
     db::AnalyzerDbStorage,
     namespace::items::{ContractId, ModuleId},
     AnalyzerDb,
 };
 use fe_common::db::{SourceDb, SourceDbStorage, Upcast, UpcastMut};
+use fe_common::EvmVersion;
 use fe_mir::{
     db::{MirDb, MirDbStorage},
     ir::{FunctionBody, FunctionId, FunctionSignature, TypeId},
@@ -17,6 +21,25 @@ mod queries;
 
 #[salsa::query_group(CodegenDbStorage)]
 pub trait CodegenDb: MirDb + Upcast<dyn MirDb> + UpcastMut<dyn MirDb> {
+    /// Whether function bodies are run through the MIR optimization pipeline
+    /// before they are legalized. The driver sets it together with the solc
+    /// optimizer.
+    #[salsa::input]
+    fn codegen_optimize_mir(&self) -> bool;
+    /// Whether Yul statements are annotated with `@src` comments, which make
+    /// solc emit a source map of the bytecode.
+    #[salsa::input]
+    fn codegen_source_map(&self) -> bool;
+    /// Whether contracts are compiled even if their bytecode exceeds the code
+    /// size limits of EIP-170 and EIP-3860.
+    #[salsa::input]
+    fn codegen_allow_oversize(&self) -> bool;
+    /// Whether the start of the allocated memory is passed through
+    /// `memoryguard`. Only the native assembler is given such Yul, since it
+    /// makes solc assume that the code is memory-safe.
+    #[salsa::input]
+    fn codegen_memory_guard(&self) -> bool;
+
     #[salsa::invoke(queries::function::legalized_signature)]
     fn codegen_legalized_signature(&self, function_id: FunctionId) -> Rc<FunctionSignature>;
     #[salsa::invoke(queries::function::legalized_body)]
@@ -33,10 +56,14 @@ pub trait CodegenDb: MirDb + Upcast<dyn MirDb> + UpcastMut<dyn MirDb> {
     fn codegen_abi_function(&self, function_id: FunctionId) -> AbiFunction;
     #[salsa::invoke(queries::abi::abi_event)]
     fn codegen_abi_event(&self, ty: TypeId) -> AbiEvent;
+    #[salsa::invoke(queries::abi::abi_error)]
+    fn codegen_abi_error(&self, ty: TypeId) -> AbiError;
     #[salsa::invoke(queries::abi::abi_contract)]
     fn codegen_abi_contract(&self, contract: ContractId) -> AbiContract;
     #[salsa::invoke(queries::abi::abi_module_events)]
     fn codegen_abi_module_events(&self, module: ModuleId) -> Vec<AbiEvent>;
+    #[salsa::invoke(queries::abi::abi_module_errors)]
+    fn codegen_abi_module_errors(&self, module: ModuleId) -> Vec<AbiError>;
     #[salsa::invoke(queries::abi::abi_type_maximum_size)]
     fn codegen_abi_type_maximum_size(&self, ty: TypeId) -> usize;
     #[salsa::invoke(queries::abi::abi_type_minimum_size)]
@@ -50,6 +77,8 @@ pub trait CodegenDb: MirDb + Upcast<dyn MirDb> + UpcastMut<dyn MirDb> {
     fn codegen_contract_symbol_name(&self, contract: ContractId) -> Rc<String>;
     #[salsa::invoke(queries::contract::deployer_symbol_name)]
     fn codegen_contract_deployer_symbol_name(&self, contract: ContractId) -> Rc<String>;
+    #[salsa::invoke(queries::contract::storage_layout)]
+    fn codegen_contract_storage_layout(&self, contract: ContractId) -> StorageLayout;
 
     #[salsa::invoke(queries::constant::string_symbol_name)]
     fn codegen_constant_string_symbol_name(&self, data: String) -> Rc<String>;
@@ -57,12 +86,25 @@ pub trait CodegenDb: MirDb + Upcast<dyn MirDb> + UpcastMut<dyn MirDb> {
 
 // TODO: Move this to driver.
 #[salsa::database(SourceDbStorage, AnalyzerDbStorage, MirDbStorage, CodegenDbStorage)]
-#[derive(Default)]
 pub struct Db {
     storage: salsa::Storage<Db>,
 }
 impl salsa::Database for Db {}
 
+impl Default for Db {
+    fn default() -> Self {
+        let mut db = Self {
+            storage: Default::default(),
+        };
+        db.set_codegen_optimize_mir(false);
+        db.set_codegen_source_map(false);
+        db.set_codegen_allow_oversize(false);
+        db.set_codegen_memory_guard(false);
+        db.set_evm_version(EvmVersion::default());
+        db
+    }
+}
+
 impl Upcast<dyn MirDb> for Db {
     fn upcast(&self) -> &(dyn MirDb + 'static) {
         self
@@ -1,5 +1,6 @@
 use fe_abi::{
     contract::AbiContract,
+    error::{AbiError, AbiErrorField},
     event::{AbiEvent, AbiEventField},
     function::{AbiFunction, AbiFunctionType, CtxParam, SelfParam, StateMutability},
     types::{AbiTupleField, AbiType},
@@ -32,9 +33,22 @@ pub fn abi_contract(db: &dyn CodegenDb, contract: ContractId) -> AbiContract {
         }
     }
 
+    // `__receive__` and `__fallback__` are only run through the dispatcher.
+    if contract.call_function(db.upcast()).is_none() {
+        let entry_points = contract
+            .receive_function(db.upcast())
+            .into_iter()
+            .chain(contract.fallback_function(db.upcast()));
+        for func in entry_points {
+            let mir_func = db.mir_lowered_func_signature(func);
+            funcs.push(db.codegen_abi_function(mir_func));
+        }
+    }
+
     let events = abi_module_events(db, contract.module(db.upcast()));
+    let errors = abi_module_errors(db, contract.module(db.upcast()));
 
-    AbiContract::new(funcs, events)
+    AbiContract::new(funcs, events, errors)
 }
 
 pub fn abi_module_events(db: &dyn CodegenDb, module: ModuleId) -> Vec<AbiEvent> {
@@ -52,6 +66,17 @@ pub fn abi_module_events(db: &dyn CodegenDb, module: ModuleId) -> Vec<AbiEvent>
     events
 }
 
+pub fn abi_module_errors(db: &dyn CodegenDb, module: ModuleId) -> Vec<AbiError> {
+    db.module_structs(module)
+        .iter()
+        .filter(|s| s.is_error(db.upcast()))
+        .map(|s| {
+            let mir_error = db.mir_lowered_type(s.as_type(db.upcast()));
+            db.codegen_abi_error(mir_error)
+        })
+        .collect()
+}
+
 pub fn abi_function(db: &dyn CodegenDb, function: FunctionId) -> AbiFunction {
     // We use a legalized signature.
     let sig = db.codegen_legalized_signature(function);
@@ -66,13 +91,19 @@ pub fn abi_function(db: &dyn CodegenDb, function: FunctionId) -> AbiFunction {
 
     let func_type = if function.is_contract_init(db.upcast()) {
         AbiFunctionType::Constructor
+    } else if name == "__receive__" {
+        AbiFunctionType::Receive
+    } else if name == "__fallback__" {
+        AbiFunctionType::Fallback
     } else {
         AbiFunctionType::Function
     };
 
-    // The "stateMutability" field is derived from the presence & mutability of
-    // `self` and `ctx` params in the analyzer fn sig.
-    let analyzer_sig = sig.analyzer_func_id.signature(db.upcast());
+    // The "stateMutability" field is derived from the `#payable` attribute and
+    // the presence & mutability of `self` and `ctx` params in the analyzer fn
+    // sig.
+    let analyzer_func = sig.analyzer_func_id;
+    let analyzer_sig = analyzer_func.signature(db.upcast());
     let self_param = match analyzer_sig.self_decl {
         None => SelfParam::None,
         Some(SelfDecl { mut_: None, .. }) => SelfParam::Imm,
@@ -84,11 +115,13 @@ pub fn abi_function(db: &dyn CodegenDb, function: FunctionId) -> AbiFunction {
         Some(CtxDecl { mut_: Some(_), .. }) => CtxParam::Mut,
     };
 
-    let state_mutability = if name == "__init__" {
-        StateMutability::Payable
+    let state_mutability = if name == "__init__" || name == "__receive__" || name == "__fallback__"
+    {
+        StateMutability::Nonpayable
     } else {
         StateMutability::from_self_and_ctx_params(self_param, ctx_param)
-    };
+    }
+    .payable(analyzer_func.is_payable(db.upcast()));
 
     AbiFunction::new(func_type, name.to_string(), args, ret_ty, state_mutability)
 }
@@ -274,6 +307,28 @@ pub fn abi_event(db: &dyn CodegenDb, ty: TypeId) -> AbiEvent {
     AbiEvent::new(event_def.name.to_string(), fields, false)
 }
 
+/// Returns the ABI of an `error`, or of a struct as it's reverted with. A
+/// struct has the same selector as an `error`, but its fields are encoded as
+/// a single tuple.
+pub fn abi_error(db: &dyn CodegenDb, ty: TypeId) -> AbiError {
+    debug_assert!(ty.is_struct(db.upcast()));
+
+    let legalized_ty = db.codegen_legalized_type(ty);
+    let legalized_ty_data = legalized_ty.data(db.upcast());
+    let error_def = match &legalized_ty_data.kind {
+        ir::TypeKind::Struct(def) => def,
+        _ => unreachable!(),
+    };
+
+    let fields = error_def
+        .fields
+        .iter()
+        .map(|(name, ty)| AbiErrorField::new(name.to_string(), db.codegen_abi_type(*ty)))
+        .collect();
+
+    AbiError::new(error_def.name.to_string(), fields)
+}
+
 fn ceil_32(value: usize) -> usize {
     ((value + 31) / 32) * 32
 }
@@ -1,8 +1,10 @@
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc};
 
-use fe_analyzer::namespace::items::ContractId;
+use fe_abi::storage_layout::{StorageItem, StorageLayout, StorageType};
+use fe_analyzer::namespace::{items::ContractId, types::Type};
+use fe_mir::ir::{TypeId, TypeKind};
 
-use crate::db::CodegenDb;
+use crate::{db::CodegenDb, yul::slot_size::SLOT_SIZE};
 
 pub fn symbol_name(db: &dyn CodegenDb, contract: ContractId) -> Rc<String> {
     let module = contract.module(db.upcast());
@@ -18,3 +20,161 @@ pub fn symbol_name(db: &dyn CodegenDb, contract: ContractId) -> Rc<String> {
 pub fn deployer_symbol_name(db: &dyn CodegenDb, contract: ContractId) -> Rc<String> {
     format!("deploy_{}", symbol_name(db, contract).as_ref()).into()
 }
+
+/// Returns the storage layout of the contract. The contract fields are laid
+/// out from the storage pointer `0`, which is the pointer the Yul backend
+/// passes as `self`, with the same offsets the generated code uses to access
+/// them.
+pub fn storage_layout(db: &dyn CodegenDb, contract: ContractId) -> StorageLayout {
+    let contract_ty = Type::SelfContract(contract).id(db.upcast());
+    let contract_ty = db.mir_lowered_type(contract_ty);
+
+    let mut types = BTreeMap::new();
+    let storage = storage_members(db, contract_ty, &mut types);
+    StorageLayout { storage, types }
+}
+
+/// Returns the fields of the aggregate `ty` and registers their types in
+/// `types`.
+fn storage_members(
+    db: &dyn CodegenDb,
+    ty: TypeId,
+    types: &mut BTreeMap<String, StorageType>,
+) -> Vec<StorageItem> {
+    let labels: Vec<String> = match &ty.data(db.upcast()).kind {
+        TypeKind::Struct(def) | TypeKind::Contract(def) => def
+            .fields
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        TypeKind::Tuple(def) => (0..def.items.len()).map(|i| format!("item{i}")).collect(),
+        _ => unreachable!(),
+    };
+
+    labels
+        .into_iter()
+        .enumerate()
+        .map(|(idx, label)| {
+            let field_ty = ty.projection_ty_imm(db.upcast(), idx);
+            let offset = ty.aggregate_elem_offset(db.upcast(), idx, SLOT_SIZE);
+            let ty_id = register_storage_type(db, field_ty, types);
+            StorageItem::new(label, offset / SLOT_SIZE, offset % SLOT_SIZE, ty_id)
+        })
+        .collect()
+}
+
+/// Registers the definition of `ty` and the types it refers to in `types`,
+/// and returns the id of `ty`. The ids follow the naming of solc.
+fn register_storage_type(
+    db: &dyn CodegenDb,
+    ty: TypeId,
+    types: &mut BTreeMap<String, StorageType>,
+) -> String {
+    let size = ty.size_of(db.upcast(), SLOT_SIZE);
+    let label = storage_type_label(db, ty);
+    let (id, def) = match &ty.data(db.upcast()).kind {
+        TypeKind::Array(def) => {
+            let base = register_storage_type(db, def.elem_ty, types);
+            (
+                format!("t_array({base}){}_storage", def.len),
+                StorageType::array(label, round_up_to_slot(size), base),
+            )
+        }
+
+        TypeKind::Struct(def) => {
+            let members = storage_members(db, ty, types);
+            (
+                format!("t_struct({})_storage", def.name),
+                StorageType::composite(label, round_up_to_slot(size), members),
+            )
+        }
+
+        TypeKind::Tuple(def) => {
+            let items: Vec<_> = def
+                .items
+                .iter()
+                .map(|item| register_storage_type(db, *item, types))
+                .collect();
+            let members = storage_members(db, ty, types);
+            (
+                format!("t_tuple({})_storage", items.join(",")),
+                StorageType::composite(label, round_up_to_slot(size), members),
+            )
+        }
+
+        TypeKind::Enum(def) => (
+            format!("t_enum({})", def.name),
+            StorageType::inplace(label, round_up_to_slot(size)),
+        ),
+
+        TypeKind::Map(def) => {
+            let key = register_storage_type(db, def.key_ty, types);
+            let value = register_storage_type(db, def.value_ty, types);
+            // See `make_map_value_ptr_with_primitive_key` and
+            // `make_map_value_ptr_with_ptr_key` in the Yul runtime.
+            let hashing = if def.key_ty.is_primitive(db.upcast()) {
+                "(keccak256(key . slot * 32) & ~0xff) / 32"
+            } else {
+                "(keccak256(keccak256(key) . slot * 32) & ~0xff) / 32"
+            };
+            (
+                format!("t_mapping({key},{value})"),
+                StorageType::mapping(label, size, key, value, hashing.to_string()),
+            )
+        }
+
+        TypeKind::String(len) => (
+            format!("t_string{len}_storage"),
+            StorageType::inplace(label, round_up_to_slot(size)),
+        ),
+
+        TypeKind::Bool => ("t_bool".to_string(), StorageType::inplace(label, size)),
+        TypeKind::Address => ("t_address".to_string(), StorageType::inplace(label, size)),
+        TypeKind::Unit => ("t_unit".to_string(), StorageType::inplace(label, size)),
+        _ if ty.is_signed(db.upcast()) => (
+            format!("t_int{}", size * 8),
+            StorageType::inplace(label, size),
+        ),
+        _ => (
+            format!("t_uint{}", size * 8),
+            StorageType::inplace(label, size),
+        ),
+    };
+
+    types.entry(id.clone()).or_insert(def);
+    id
+}
+
+/// Returns the name of `ty` in Fe syntax.
+fn storage_type_label(db: &dyn CodegenDb, ty: TypeId) -> String {
+    match &ty.data(db.upcast()).kind {
+        TypeKind::Array(def) => {
+            format!(
+                "Array<{}, {}>",
+                storage_type_label(db, def.elem_ty),
+                def.len
+            )
+        }
+        TypeKind::Tuple(def) => {
+            let items: Vec<_> = def
+                .items
+                .iter()
+                .map(|item| storage_type_label(db, *item))
+                .collect();
+            format!("({})", items.join(", "))
+        }
+        TypeKind::Map(def) => format!(
+            "Map<{}, {}>",
+            storage_type_label(db, def.key_ty),
+            storage_type_label(db, def.value_ty)
+        ),
+        TypeKind::String(len) => format!("String<{len}>"),
+        _ => ty.as_string(db.upcast()),
+    }
+}
+
+/// Aggregates occupy whole slots since the element that follows an aggregate
+/// starts a new slot.
+fn round_up_to_slot(size: usize) -> usize {
+    (size + SLOT_SIZE - 1) / SLOT_SIZE * SLOT_SIZE
+}
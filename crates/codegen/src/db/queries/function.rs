@@ -20,7 +20,12 @@ pub fn legalized_signature(db: &dyn CodegenDb, function: FunctionId) -> Rc<Funct
 }
 
 pub fn legalized_body(db: &dyn CodegenDb, function: FunctionId) -> Rc<FunctionBody> {
-    let mut body = function.body(db.upcast()).as_ref().clone();
+    let body = if db.codegen_optimize_mir() {
+        function.optimized_body(db.upcast())
+    } else {
+        function.body(db.upcast())
+    };
+    let mut body = body.as_ref().clone();
     legalize::legalize_func_body(db, &mut body);
     body.into()
 }
@@ -45,6 +50,15 @@ pub fn symbol_name(db: &dyn CodegenDb, function: FunctionId) -> Rc<String> {
             );
             format!("{class_name}${func_name}")
         }
+        Some(Item::Type(def)) => {
+            // Instances of a generic struct or enum share the names of their functions.
+            let class_name = format!(
+                "{}{}",
+                def.name(db.upcast()),
+                generic_args_suffix(db, &def.generic_args(db.upcast()))
+            );
+            format!("{class_name}${func_name}")
+        }
         Some(class) => {
             let class_name = class.name(db.upcast());
             format!("{class_name}${func_name}")
@@ -66,11 +80,30 @@ fn type_suffix(function: FunctionId, db: &dyn CodegenDb) -> SmolStr {
         .into()
 }
 
+fn generic_args_suffix(db: &dyn CodegenDb, args: &[TypeId]) -> String {
+    args.iter().fold(String::new(), |acc, arg| {
+        format!("{}_{}", acc, safe_name(db, *arg))
+    })
+}
+
 fn safe_name(db: &dyn CodegenDb, ty: TypeId) -> SmolStr {
     match ty.typ(db.upcast()) {
         // TODO: Would be nice to get more human friendly names here
         Type::Array(_) => format!("array_{:?}", ty.as_intern_id()).into(),
         Type::Tuple(_) => format!("tuple_{:?}", ty.as_intern_id()).into(),
+        Type::String(string) => format!("string_{}", string.max_size).into(),
+        Type::Struct(id) => format!(
+            "{}{}",
+            id.name(db.upcast()),
+            generic_args_suffix(db, &id.generic_args(db.upcast()))
+        )
+        .into(),
+        Type::Enum(id) => format!(
+            "{}{}",
+            id.name(db.upcast()),
+            generic_args_suffix(db, &id.generic_args(db.upcast()))
+        )
+        .into(),
         _ => format!("{}", ty.display(db.upcast())).into(),
     }
 }
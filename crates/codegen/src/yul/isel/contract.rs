@@ -16,7 +16,8 @@ pub fn lower_contract_deployable(db: &dyn CodegenDb, contract: ContractId) -> yu
         let init = db.mir_lowered_func_signature(init);
         make_init(db, &mut context, contract, init)
     } else {
-        statements! {}
+        // A contract without `__init__` can't be deployed with a value.
+        check_callvalue(false)
     };
 
     let deploy_code = make_deploy(db, contract);
@@ -83,7 +84,7 @@ pub fn lower_contract(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
             ([call_symbol]())
         }
     } else {
-        make_dispatcher(db, &mut context, &exported_funcs)
+        make_dispatcher(db, &mut context, contract, &exported_funcs)
     };
 
     let dep_functions: Vec<_> = context
@@ -119,33 +120,142 @@ pub fn lower_contract(db: &dyn CodegenDb, contract: ContractId) -> yul::Object {
     }
 }
 
+/// Contracts with more public functions than this find the function of a
+/// selector with a binary search over the sorted selectors, down to a `switch`
+/// over at most this many of them. A `switch` compares the selector with its
+/// cases one by one.
+const MAX_SWITCH_CASES: usize = 4;
+
 fn make_dispatcher(
     db: &dyn CodegenDb,
     context: &mut Context,
+    contract: ContractId,
     funcs: &[FunctionId],
 ) -> yul::Statement {
-    let arms = funcs
+    let receive = contract
+        .receive_function(db.upcast())
+        .map(|func| db.mir_lowered_func_signature(func));
+    let fallback = contract
+        .fallback_function(db.upcast())
+        .map(|func| db.mir_lowered_func_signature(func));
+
+    // `__fallback__` runs if no selector matches, and for empty call data if
+    // there's no `__receive__`.
+    let default = match fallback {
+        Some(fallback) => call_entry_point(db, context, fallback),
+        None => statements! { (return(0, 0)) },
+    };
+
+    let mut stmts = vec![];
+    if let Some(receive) = receive {
+        let call_receive = call_entry_point(db, context, receive);
+        stmts.push(yul::Statement::If(yul::If {
+            expression: expression! { iszero((calldatasize())) },
+            block: block! { [call_receive...] },
+        }));
+    }
+
+    if funcs.is_empty() {
+        stmts.extend(default);
+        return block_statement! { [stmts...] };
+    }
+
+    // The selectors of `#hot` functions are checked first, in declaration
+    // order.
+    let (hot, mut cold): (Vec<_>, Vec<_>) = funcs
         .iter()
-        .map(|func| dispatch_arm(db, context, *func))
-        .collect::<Vec<_>>();
+        .map(|func| (selector(db, *func), *func))
+        .partition(|(_, func)| {
+            func.signature(db.upcast())
+                .analyzer_func_id
+                .is_hot(db.upcast())
+        });
+    cold.sort_by_key(|(selector, _)| *selector);
 
-    if arms.is_empty() {
-        statement! { return(0, 0) }
-    } else {
-        let selector = expression! {
-            and((shr((sub(256, 32)), (calldataload(0)))), 0xffffffff)
-        };
-        switch! {
-            switch ([selector])
-            [arms...]
-            (default { (return(0, 0)) })
-        }
+    let selector = YulVariable::new("$selector");
+    let mut dispatch = dispatch_sorted(db, context, &selector, &cold, &default);
+    if !hot.is_empty() {
+        dispatch = vec![make_switch(db, context, &selector, &hot, dispatch)];
+    }
+
+    block_statement! {
+        [stmts...]
+        (let [selector.ident()] := and((shr((sub(256, 32)), (calldataload(0)))), 0xffffffff))
+        [dispatch...]
+    }
+}
+
+/// Dispatches to the function of `selector` among `funcs`, which are sorted by
+/// selector, or runs `default` if there's none.
+fn dispatch_sorted(
+    db: &dyn CodegenDb,
+    context: &mut Context,
+    selector: &YulVariable,
+    funcs: &[(u32, FunctionId)],
+    default: &[yul::Statement],
+) -> Vec<yul::Statement> {
+    if funcs.is_empty() {
+        return default.to_vec();
+    } else if funcs.len() <= MAX_SWITCH_CASES {
+        return vec![make_switch(db, context, selector, funcs, default.to_vec())];
+    }
+
+    // Every branch of the lower half returns, so the upper half is only
+    // reached if the selector isn't less than the pivot.
+    let (lower, upper) = funcs.split_at(funcs.len() / 2);
+    let pivot = literal_expression! { (format!("{:#010x}", upper[0].0)) };
+    let lower = dispatch_sorted(db, context, selector, lower, default);
+    let mut stmts = vec![yul::Statement::If(yul::If {
+        expression: expression! { lt([selector.expr()], [pivot]) },
+        block: block! { [lower...] },
+    })];
+    stmts.extend(dispatch_sorted(db, context, selector, upper, default));
+    stmts
+}
+
+/// Runs `__receive__` or `__fallback__` and returns without any data.
+fn call_entry_point(
+    db: &dyn CodegenDb,
+    context: &mut Context,
+    func: FunctionId,
+) -> Vec<yul::Statement> {
+    context.function_dependency.insert(func);
+    let check_value = check_callvalue(is_payable(db, func));
+    let name = identifier! { (db.codegen_function_symbol_name(func)) };
+    statements! {
+        [check_value...]
+        ([name]())
+        (return(0, 0))
     }
 }
 
+fn make_switch(
+    db: &dyn CodegenDb,
+    context: &mut Context,
+    selector: &YulVariable,
+    funcs: &[(u32, FunctionId)],
+    default: Vec<yul::Statement>,
+) -> yul::Statement {
+    let mut arms: Vec<_> = funcs
+        .iter()
+        .map(|(_, func)| dispatch_arm(db, context, *func))
+        .collect();
+    arms.push(case! { default { [default...] } });
+
+    switch! {
+        switch ([selector.expr()])
+        [arms...]
+    }
+}
+
+fn selector(db: &dyn CodegenDb, func: FunctionId) -> u32 {
+    u32::from_be_bytes(db.codegen_abi_function(func).selector().selector_raw())
+}
+
 fn dispatch_arm(db: &dyn CodegenDb, context: &mut Context, func: FunctionId) -> yul::Case {
     context.function_dependency.insert(func);
     let func_sig = db.codegen_legalized_signature(func);
+    let check_value = check_callvalue(is_payable(db, func));
     let mut param_vars = Vec::with_capacity(func_sig.params.len());
     let mut param_tys = Vec::with_capacity(func_sig.params.len());
     func_sig.params.iter().for_each(|param| {
@@ -201,6 +311,7 @@ fn dispatch_arm(db: &dyn CodegenDb, context: &mut Context, func: FunctionId) ->
     let selector = literal! { (format!("0x{}", abi_sig.selector().hex())) };
     case! {
         case [selector] {
+            [check_value...]
             [decode_params...]
             [call_and_encode_return...]
         }
@@ -238,8 +349,10 @@ fn make_init(
         }
     };
 
+    let check_value = check_callvalue(is_payable(db, init));
     let call = expression! {[init_func_name]([(param_vars.iter().map(YulVariable::expr).collect::<Vec<_>>())...])};
     statements! {
+        [check_value...]
         (let [program_size.ident()] := datasize([contract_name]))
         (let [code_size.ident()] := codesize())
         (let [arg_size.ident()] := sub([code_size.expr()], [program_size.expr()]))
@@ -250,6 +363,23 @@ fn make_init(
     }
 }
 
+fn is_payable(db: &dyn CodegenDb, func: FunctionId) -> bool {
+    func.signature(db.upcast())
+        .analyzer_func_id
+        .is_payable(db.upcast())
+}
+
+/// Reverts if a value is sent to an entry point that isn't `#payable`.
+fn check_callvalue(payable: bool) -> Vec<yul::Statement> {
+    if payable {
+        statements! {}
+    } else {
+        statements! {
+            (if (callvalue()) { (revert(0, 0)) })
+        }
+    }
+}
+
 fn make_deploy(db: &dyn CodegenDb, contract: ContractId) -> Vec<yul::Statement> {
     let contract_symbol =
         identifier_expression! { (format!{r#""{}""#, db.codegen_contract_symbol_name(contract)}) };
@@ -5,15 +5,15 @@ use super::{context::Context, inst_order::InstSerializer};
 use fe_common::numeric::to_hex_str;
 
 use fe_abi::function::{AbiFunction, AbiFunctionType};
-use fe_common::db::Upcast;
+use fe_common::{db::Upcast, EvmVersion, Span};
 use fe_mir::{
     ir::{
         self,
         constant::ConstantValue,
-        inst::{BinOp, CallType, CastKind, InstKind, UnOp},
+        inst::{BinOp, CallType, CastKind, InstKind, UnOp, YulIntrinsicOp},
         value::AssignableValue,
-        Constant, FunctionBody, FunctionId, FunctionSignature, InstId, Type, TypeId, TypeKind,
-        Value, ValueId,
+        Constant, FunctionBody, FunctionId, FunctionSignature, InstId, SourceInfo, Type, TypeId,
+        TypeKind, Value, ValueId,
     },
     pretty_print::PrettyPrint,
 };
@@ -30,7 +30,7 @@ use crate::{
     yul::slot_size::{function_hash_type, yul_primitive_type, SLOT_SIZE},
     yul::{
         runtime::{self, RuntimeProvider},
-        YulVariable,
+        source_map, YulVariable,
     },
 };
 
@@ -55,6 +55,9 @@ struct FuncLowerHelper<'db, 'a> {
     body: &'a FunctionBody,
     ret_value: Option<yul::Identifier>,
     sink: Vec<yul::Statement>,
+    /// The span of the last `@src` comment in `sink`, if source maps are
+    /// enabled.
+    source: Option<Span>,
 }
 
 impl<'db, 'a> FuncLowerHelper<'db, 'a> {
@@ -92,6 +95,7 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
             body,
             ret_value,
             sink: Vec::new(),
+            source: None,
         }
     }
 
@@ -124,9 +128,15 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
     fn lower_body(mut self) -> yul::Block {
         let inst_order = InstSerializer::new(self.body).serialize();
 
+        let body = self.body;
+        self.set_source(&body.source);
         for inst in inst_order {
             self.lower_structural_inst(inst)
         }
+        // Don't attribute the functions that follow to this one.
+        if self.source.is_some() {
+            self.sink.push(source_map::src_comment(None));
+        }
 
         yul::Block {
             statements: self.sink,
@@ -158,6 +168,8 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
     }
 
     fn lower_inst(&mut self, inst: InstId) {
+        let body = self.body;
+        self.set_source(&body.store.inst_data(inst).source);
         if let Some(lhs) = self.body.store.inst_result(inst) {
             self.declare_assignable_value(lhs)
         }
@@ -257,13 +269,12 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                 let dst_ptr_ty = lhs.ty(self.db.upcast(), &self.body.store);
                 let src_ptr = self.value_expr(*src);
                 let src_ptr_ty = self.body.store.value_ty(*src);
-                let ty_size = literal_expression! { (self.value_ty_size_deref(*src)) };
                 self.sink
-                    .push(yul::Statement::Expression(self.ctx.runtime.ptr_copy(
+                    .push(yul::Statement::Expression(self.ctx.runtime.aggregate_copy(
                         self.db,
                         src_ptr,
                         dst_ptr,
-                        ty_size,
+                        src_ptr_ty.deref(self.db.upcast()),
                         src_ptr_ty.is_sptr(self.db.upcast()),
                         dst_ptr_ty.is_sptr(self.db.upcast()),
                     )))
@@ -403,11 +414,19 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                 });
             }
 
-            InstKind::Create { value, contract } => {
+            InstKind::Create {
+                value,
+                contract,
+                args,
+            } => {
                 self.ctx.contract_dependency.insert(*contract);
 
                 let value_expr = self.value_expr(*value);
-                let result = self.ctx.runtime.create(self.db, *contract, value_expr);
+                let args = args.iter().map(|arg| self.value_expr(*arg)).collect();
+                let result = self
+                    .ctx
+                    .runtime
+                    .create(self.db, *contract, value_expr, args);
                 let u256_ty = yul_primitive_type(self.db);
                 self.assign_inst_result(inst, result, u256_ty)
             }
@@ -416,22 +435,30 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                 value,
                 salt,
                 contract,
+                args,
             } => {
                 self.ctx.contract_dependency.insert(*contract);
 
                 let value_expr = self.value_expr(*value);
                 let salt_expr = self.value_expr(*salt);
+                let args = args.iter().map(|arg| self.value_expr(*arg)).collect();
                 let result = self
                     .ctx
                     .runtime
-                    .create2(self.db, *contract, value_expr, salt_expr);
+                    .create2(self.db, *contract, value_expr, salt_expr, args);
                 let u256_ty = yul_primitive_type(self.db);
                 self.assign_inst_result(inst, result, u256_ty)
             }
 
             InstKind::YulIntrinsic { op, args } => {
                 let args: Vec<_> = args.iter().map(|arg| self.value_expr(*arg)).collect();
-                let op_name = identifier! { (format!("{op}").strip_prefix("__").unwrap()) };
+                let op_name = match op {
+                    // Solc names the opcode `difficulty` before Paris.
+                    YulIntrinsicOp::Prevrandao if self.db.evm_version() < EvmVersion::Paris => {
+                        identifier! { difficulty }
+                    }
+                    _ => identifier! { (format!("{op}").strip_prefix("__").unwrap()) },
+                };
                 let result = expression! { [op_name]([args...]) };
                 // Intrinsic operation never returns ptr type, so we can use u256_ty as a dummy
                 // type for the result.
@@ -448,6 +475,16 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
         }
     }
 
+    /// Attributes the statements that follow to `source` if source maps are
+    /// enabled.
+    fn set_source(&mut self, source: &SourceInfo) {
+        if !self.db.codegen_source_map() || source.is_dummy() || self.source == Some(source.span) {
+            return;
+        }
+        self.source = Some(source.span);
+        self.sink.push(source_map::src_comment(self.source));
+    }
+
     fn lower_if(
         &mut self,
         cond: ValueId,
@@ -554,13 +591,11 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                 let src = self.value_expr(rhs);
 
                 if src_ty.is_ptr(self.db.upcast()) {
-                    let ty_size = literal_expression! { (self.value_ty_size_deref(rhs)) };
-
-                    let expr = self.ctx.runtime.ptr_copy(
+                    let expr = self.ctx.runtime.aggregate_copy(
                         self.db,
                         src,
                         dst,
-                        ty_size,
+                        src_ty.deref(self.db.upcast()),
                         src_ty.is_sptr(self.db.upcast()),
                         dst_ty.is_sptr(self.db.upcast()),
                     );
@@ -690,14 +725,11 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                                 let lhs_ident = self.value_ident(*value);
                                 statement! { [lhs_ident] := [rhs] }
                             } else {
-                                let ty_size = rhs_ty
-                                    .deref(self.db.upcast())
-                                    .size_of(self.db.upcast(), SLOT_SIZE);
-                                yul::Statement::Expression(self.ctx.runtime.ptr_copy(
+                                yul::Statement::Expression(self.ctx.runtime.aggregate_copy(
                                     self.db,
                                     rhs,
                                     lhs,
-                                    literal_expression! { (ty_size) },
+                                    rhs_ty.deref(self.db.upcast()),
                                     rhs_ty.is_sptr(self.db.upcast()),
                                     lhs_ty.is_sptr(self.db.upcast()),
                                 ))
@@ -722,14 +754,11 @@ impl<'db, 'a> FuncLowerHelper<'db, 'a> {
                 }
                 AssignableValue::Aggregate { .. } | AssignableValue::Map { .. } => {
                     let expr = if rhs_ty.is_ptr(self.db.upcast()) {
-                        let ty_size = rhs_ty
-                            .deref(self.db.upcast())
-                            .size_of(self.db.upcast(), SLOT_SIZE);
-                        self.ctx.runtime.ptr_copy(
+                        self.ctx.runtime.aggregate_copy(
                             self.db,
                             rhs,
                             lhs,
-                            literal_expression! { (ty_size) },
+                            rhs_ty.deref(self.db.upcast()),
                             rhs_ty.is_sptr(self.db.upcast()),
                             lhs_ty.is_sptr(self.db.upcast()),
                         )
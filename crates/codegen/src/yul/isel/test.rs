@@ -1,5 +1,8 @@
 use super::context::Context;
-use crate::db::CodegenDb;
+use crate::{
+    db::CodegenDb,
+    yul::{runtime::AbiSrcLocation, YulVariable},
+};
 use fe_analyzer::namespace::items::FunctionId;
 use yultsur::{yul, *};
 
@@ -8,6 +11,23 @@ pub fn lower_test(db: &dyn CodegenDb, test: FunctionId) -> yul::Object {
     let test = db.mir_lowered_func_signature(test);
     context.function_dependency.insert(test);
 
+    // The arguments of a fuzz test are ABI encoded in the calldata.
+    let test_sig = db.codegen_legalized_signature(test);
+    let param_vars: Vec<_> = test_sig
+        .params
+        .iter()
+        .map(|param| YulVariable::new(param.name.as_str()))
+        .collect();
+    let decode_params = if test_sig.params.is_empty() {
+        statements! {}
+    } else {
+        let ident_params: Vec<_> = param_vars.iter().map(YulVariable::ident).collect();
+        let param_tys: Vec<_> = test_sig.params.iter().map(|param| param.ty).collect();
+        statements! {
+            (let [ident_params...] := [context.runtime.abi_decode(db, expression! { 0 }, expression! { calldatasize() }, &param_tys, AbiSrcLocation::CallData)])
+        }
+    };
+
     let dep_functions: Vec<_> = context
         .resolve_function_dependency(db)
         .into_iter()
@@ -22,11 +42,13 @@ pub fn lower_test(db: &dyn CodegenDb, test: FunctionId) -> yul::Object {
         .map(yul::Statement::FunctionDefinition)
         .collect();
     let test_func_name = identifier! { (db.codegen_function_symbol_name(test)) };
-    let call = function_call_statement! {[test_func_name]()};
+    let args: Vec<_> = param_vars.iter().map(YulVariable::expr).collect();
+    let call = yul::Statement::Expression(expression! {[test_func_name]([args...])});
 
     let code = code! {
         [dep_functions...]
         [runtime_funcs...]
+        [decode_params...]
         [call]
         (stop())
     };
@@ -100,7 +100,9 @@ fn legalize_inst_arg(db: &dyn CodegenDb, body: &mut FunctionBody, inst_id: InstI
             args.retain(|arg| !is_value_zst(db, body, *arg));
         }
 
-        InstKind::Call { args, .. } => {
+        InstKind::Call { args, .. }
+        | InstKind::Create { args, .. }
+        | InstKind::Create2 { args, .. } => {
             args.retain(|arg| !is_value_zst(db, body, *arg) && !is_value_contract(db, body, *arg))
         }
 
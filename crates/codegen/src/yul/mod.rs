@@ -3,6 +3,7 @@ use std::borrow::Cow;
 pub mod isel;
 pub mod legalize;
 pub mod runtime;
+pub mod source_map;
 
 mod slot_size;
 
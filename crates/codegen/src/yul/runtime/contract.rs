@@ -21,14 +21,18 @@ pub(super) fn make_create(
         (format!(r#""{}""#, db.codegen_contract_deployer_symbol_name(contract)))
     };
 
-    let size = YulVariable::new("size");
-    let value = YulVariable::new("value");
+    // The locals are prefixed with `$` so that they don't clash with the
+    // parameters of `__init__`.
+    let size = YulVariable::new("$size");
+    let value = YulVariable::new("$value");
+    let (params, encode_args) = encode_init_args(provider, db, contract, &size);
     let func = function_definition! {
-        function [func_name.ident()]([value.ident()]) -> addr {
+        function [func_name.ident()]([value.ident()], [params...]) -> addr {
             (let [size.ident()] := datasize([contract_symbol.clone()]))
             (let mem_ptr := [provider.avail(db)])
             (let contract_ptr := dataoffset([contract_symbol]))
             (datacopy(mem_ptr, contract_ptr, [size.expr()]))
+            [encode_args...]
             (addr := create([value.expr()], mem_ptr, [size.expr()]))
         }
     };
@@ -47,14 +51,16 @@ pub(super) fn make_create2(
         (format!(r#""{}""#, db.codegen_contract_deployer_symbol_name(contract)))
     };
 
-    let size = YulVariable::new("size");
-    let value = YulVariable::new("value");
+    let size = YulVariable::new("$size");
+    let value = YulVariable::new("$value");
+    let (params, encode_args) = encode_init_args(provider, db, contract, &size);
     let func = function_definition! {
-        function [func_name.ident()]([value.ident()], salt) -> addr {
+        function [func_name.ident()]([value.ident()], salt, [params...]) -> addr {
             (let [size.ident()] := datasize([contract_symbol.clone()]))
             (let mem_ptr := [provider.avail(db)])
             (let contract_ptr := dataoffset([contract_symbol]))
             (datacopy(mem_ptr, contract_ptr, [size.expr()]))
+            [encode_args...]
             (addr := create2([value.expr()], mem_ptr, [size.expr()], salt))
         }
     };
@@ -62,6 +68,45 @@ pub(super) fn make_create2(
     RuntimeFunction::from_statement(func)
 }
 
+/// Returns the parameters of the `__init__` function of `contract`, and the
+/// statements that ABI encode them right after the deployer code, which ends
+/// at `mem_ptr + size`; `size` is updated to include the encoded arguments.
+fn encode_init_args(
+    provider: &mut DefaultRuntimeProvider,
+    db: &dyn CodegenDb,
+    contract: ContractId,
+    size: &YulVariable,
+) -> (Vec<yul::Identifier>, Vec<yul::Statement>) {
+    let init = match contract.init_function(db.upcast()) {
+        Some(init) => db.mir_lowered_func_signature(init),
+        None => return (vec![], vec![]),
+    };
+    let sig = db.codegen_legalized_signature(init);
+    if sig.params.is_empty() {
+        return (vec![], vec![]);
+    }
+
+    let args: Vec<_> = sig
+        .params
+        .iter()
+        .map(|param| YulVariable::new(param.name.as_str()))
+        .collect();
+    let arg_tys: Vec<_> = sig.params.iter().map(|param| param.ty).collect();
+    let params = args.iter().map(YulVariable::ident).collect();
+    let args: Vec<_> = args.iter().map(YulVariable::expr).collect();
+    let encode = provider.abi_encode_seq(
+        db,
+        &args,
+        expression! { add(mem_ptr, [size.expr()]) },
+        &arg_tys,
+        false,
+    );
+    let stmts = statements! {
+        ([size.ident()] := add([size.expr()], [encode]))
+    };
+    (params, stmts)
+}
+
 pub(super) fn make_external_call(
     provider: &mut DefaultRuntimeProvider,
     db: &dyn CodegenDb,
@@ -91,12 +136,20 @@ pub(super) fn make_external_call(
     let func_selector = literal_expression! { (format!{"0x{}", db.codegen_abi_function(function).selector().hex()}) };
     let selector_ty = db.mir_intern_type(Type::new(TypeKind::U32, None).into());
 
+    // Functions of an interface that can't modify state are called with
+    // `staticcall`.
+    let call = if sig.analyzer_func_id.is_static_interface_fn(db.upcast()) {
+        expression! { staticcall((gas()), [func_addr.expr()], [input.expr()], [input_size.expr()], 0, 0) }
+    } else {
+        expression! { call((gas()), [func_addr.expr()], 0, [input.expr()], [input_size.expr()], 0, 0) }
+    };
+
     let mut body = statements! {
             (let [input.ident()] := [provider.avail(db)])
             [yul::Statement::Expression(provider.ptr_store(db, input.expr(), func_selector, selector_ty.make_mptr(db.upcast())))]
             (let [input_size.ident()] := add(4, [provider.abi_encode_seq(db, &params_expr, expression!{ add([input.expr()], 4) }, &arg_tys, false)]))
             (let [output.ident()] := add([provider.avail(db)], [input_size.expr()]))
-            (let success := call((gas()), [func_addr.expr()], 0, [input.expr()], [input_size.expr()], 0, 0))
+            (let success := [call])
             (let [output_size.ident()] := returndatasize())
             (returndatacopy([output.expr()], 0, [output_size.expr()]))
             (if (iszero(success)) {
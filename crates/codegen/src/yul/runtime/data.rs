@@ -18,10 +18,23 @@ const HASH_SCRATCH_SPACE_SIZE: usize = 64;
 const FREE_MEMORY_ADDRESS_STORE: usize = HASH_SCRATCH_SPACE_START + HASH_SCRATCH_SPACE_SIZE;
 const FREE_MEMORY_START: usize = FREE_MEMORY_ADDRESS_STORE + 32;
 
-pub(super) fn make_alloc(func_name: &str) -> RuntimeFunction {
+/// Returns the start of the memory that is allocated. With `memory_guard`,
+/// it's passed through `memoryguard`, which lets the native assembler reserve
+/// memory below the allocated memory for variables that don't fit in the
+/// stack.
+fn free_memory_start(memory_guard: bool) -> yul::Expression {
+    let free_memory_start = literal_expression! {(FREE_MEMORY_START)};
+    if memory_guard {
+        expression! { memoryguard([free_memory_start]) }
+    } else {
+        free_memory_start
+    }
+}
+
+pub(super) fn make_alloc(func_name: &str, memory_guard: bool) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let free_address_ptr = literal_expression! {(FREE_MEMORY_ADDRESS_STORE)};
-    let free_memory_start = literal_expression! {(FREE_MEMORY_START)};
+    let free_memory_start = free_memory_start(memory_guard);
     let func = function_definition! {
         function [func_name.ident()](size) -> ptr {
             (ptr := mload([free_address_ptr.clone()]))
@@ -33,10 +46,10 @@ pub(super) fn make_alloc(func_name: &str) -> RuntimeFunction {
     RuntimeFunction::from_statement(func)
 }
 
-pub(super) fn make_avail(func_name: &str) -> RuntimeFunction {
+pub(super) fn make_avail(func_name: &str, memory_guard: bool) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let free_address_ptr = literal_expression! {(FREE_MEMORY_ADDRESS_STORE)};
-    let free_memory_start = literal_expression! {(FREE_MEMORY_START)};
+    let free_memory_start = free_memory_start(memory_guard);
     let func = function_definition! {
         function [func_name.ident()]() -> ptr {
             (ptr := mload([free_address_ptr]))
@@ -171,8 +184,7 @@ pub(super) fn make_sptr_store(func_name: &str) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let func = function_definition! {
         function [func_name.ident()](ptr, value, size_bits) {
-            (let rem_bits := mul((mod(ptr, 32)), 8))
-            (let shift_bits := sub(256, (add(rem_bits, size_bits))))
+            (let shift_bits := mul((mod(ptr, 32)), 8))
             (let mask := (shl(shift_bits, (sub((shl(size_bits, 1)), 1)))))
             (let inv_mask := not(mask))
             (let slot := div(ptr, 32))
@@ -202,10 +214,9 @@ pub(super) fn make_sptr_load(func_name: &str) -> RuntimeFunction {
     let func_name = YulVariable::new(func_name);
     let func = function_definition! {
         function [func_name.ident()](ptr, size_bits) -> ret {
-            (let rem_bits := mul((mod(ptr, 32)), 8))
-            (let shift_num := sub(256, (add(rem_bits, size_bits))))
+            (let shift_num := mul((mod(ptr, 32)), 8))
             (let slot := div(ptr, 32))
-            (ret := shr(shift_num, (sload(slot))))
+            (ret := and((shr(shift_num, (sload(slot)))), (sub((shl(size_bits, 1)), 1))))
         }
     };
 
@@ -223,6 +234,141 @@ pub(super) fn make_mptr_load(func_name: &str) -> RuntimeFunction {
     RuntimeFunction::from_statement(func)
 }
 
+/// Makes a function that copies a value of `ty` between memory and storage.
+/// The value is copied primitive by primitive because a primitive that doesn't
+/// fill a whole slot is at a different position in a storage slot than in a
+/// memory word.
+pub(super) fn make_aggregate_copy(
+    provider: &mut DefaultRuntimeProvider,
+    db: &dyn CodegenDb,
+    func_name: &str,
+    ty: TypeId,
+    is_src_storage: bool,
+) -> RuntimeFunction {
+    let func_name = YulVariable::new(func_name);
+    let src = YulVariable::new("src");
+    let dst = YulVariable::new("dst");
+
+    let body = match &ty.data(db.upcast()).kind {
+        TypeKind::Array(def) => {
+            let elem_size = literal_expression! {(ty.array_elem_size(db.upcast(), SLOT_SIZE))};
+            let iter_count = literal_expression! {(def.len)};
+            let copy_elem = make_value_copy(
+                provider,
+                db,
+                src.expr(),
+                dst.expr(),
+                def.elem_ty,
+                is_src_storage,
+            );
+            statements! {
+                (for {(let i := 0)} (lt(i, [iter_count])) {(i := (add(i, 1)))}
+                {
+                    [copy_elem...]
+                    ([src.ident()] := add([src.expr()], [elem_size.clone()]))
+                    ([dst.ident()] := add([dst.expr()], [elem_size]))
+                })
+            }
+        }
+
+        TypeKind::Enum(def) => {
+            let disc = YulVariable::new("disc");
+            let disc_ty = ty.enum_disc_type(db.upcast());
+            let data_offset = literal_expression! {(ty.enum_data_offset(db.upcast(), SLOT_SIZE))};
+            let mut cases = vec![];
+            for (tag, variant) in def.variants.iter().enumerate() {
+                if variant.ty.is_zero_sized(db.upcast()) {
+                    continue;
+                }
+                let copy_data = make_value_copy(
+                    provider,
+                    db,
+                    expression! { add([src.expr()], [data_offset.clone()]) },
+                    expression! { add([dst.expr()], [data_offset.clone()]) },
+                    variant.ty,
+                    is_src_storage,
+                );
+                let tag = literal! {(tag)};
+                cases.push(case! {
+                    case [tag] {
+                        [copy_data...]
+                    }
+                });
+            }
+
+            let load_disc =
+                provider.ptr_load(db, src.expr(), make_ptr(db, disc_ty, is_src_storage));
+            let store_disc = provider.ptr_store(
+                db,
+                dst.expr(),
+                disc.expr(),
+                make_ptr(db, disc_ty, !is_src_storage),
+            );
+            let mut body = statements! {
+                (let [disc.ident()] := [load_disc])
+                ([yul::Statement::Expression(store_disc)])
+            };
+            if !cases.is_empty() {
+                body.push(switch! {
+                    switch ([disc.expr()])
+                    [cases...]
+                });
+            }
+            body
+        }
+
+        _ if ty.is_aggregate(db.upcast()) => {
+            let mut body = vec![];
+            for idx in 0..ty.aggregate_field_num(db.upcast()) {
+                let field_ty = ty.projection_ty_imm(db.upcast(), idx);
+                let offset =
+                    literal_expression! {(ty.aggregate_elem_offset(db.upcast(), idx, SLOT_SIZE))};
+                body.extend(make_value_copy(
+                    provider,
+                    db,
+                    expression! { add([src.expr()], [offset.clone()]) },
+                    expression! { add([dst.expr()], [offset]) },
+                    field_ty,
+                    is_src_storage,
+                ));
+            }
+            body
+        }
+
+        _ => make_value_copy(provider, db, src.expr(), dst.expr(), ty, is_src_storage),
+    };
+
+    let func_def = yul::FunctionDefinition {
+        name: func_name.ident(),
+        parameters: vec![src.ident(), dst.ident()],
+        returns: vec![],
+        block: yul::Block { statements: body },
+    };
+
+    RuntimeFunction(func_def)
+}
+
+/// Returns statements that copy a value of `ty` between memory and storage.
+fn make_value_copy(
+    provider: &mut DefaultRuntimeProvider,
+    db: &dyn CodegenDb,
+    src: yul::Expression,
+    dst: yul::Expression,
+    ty: TypeId,
+    is_src_storage: bool,
+) -> Vec<yul::Statement> {
+    if ty.is_zero_sized(db.upcast()) {
+        vec![]
+    } else if ty.is_primitive(db.upcast()) {
+        let value = provider.ptr_load(db, src, make_ptr(db, ty, is_src_storage));
+        let store = provider.ptr_store(db, dst, value, make_ptr(db, ty, !is_src_storage));
+        vec![yul::Statement::Expression(store)]
+    } else {
+        let copy = provider.aggregate_copy(db, src, dst, ty, is_src_storage, !is_src_storage);
+        vec![yul::Statement::Expression(copy)]
+    }
+}
+
 // TODO: We can optimize aggregate initialization by combining multiple
 // `ptr_store` operations into single `ptr_store` operation.
 pub(super) fn make_aggregate_init(
@@ -246,20 +392,19 @@ pub(super) fn make_aggregate_init(
         let field_ty = inner_ty
             .projection_ty_imm(db.upcast(), idx)
             .deref(db.upcast());
-        let field_ty_size = field_ty.size_of(db.upcast(), SLOT_SIZE);
         let field_ptr_ty = make_ptr(db, field_ty, is_sptr);
         let field_offset =
             literal_expression! {(inner_ty.aggregate_elem_offset(db.upcast(), idx, SLOT_SIZE))};
 
         let field_ptr = expression! { add([ptr.expr()], [field_offset] )};
         let copy_expr = if field_ty.is_aggregate(db.upcast()) || field_ty.is_string(db.upcast()) {
-            // Call ptr copy function if field type is aggregate.
+            // Call aggregate copy function if field type is aggregate.
             debug_assert!(field_arg_ty.is_ptr(db.upcast()));
-            provider.ptr_copy(
+            provider.aggregate_copy(
                 db,
                 field_arg.expr(),
                 field_ptr,
-                literal_expression! {(field_ty_size)},
+                field_ty,
                 field_arg_ty.is_sptr(db.upcast()),
                 is_sptr,
             )
@@ -9,6 +9,7 @@ use std::fmt::Write;
 
 use fe_abi::types::AbiType;
 use fe_analyzer::namespace::items::ContractId;
+use fe_common::EvmVersion;
 use fe_mir::ir::{types::ArrayDef, FunctionId, TypeId, TypeKind};
 use indexmap::IndexMap;
 use yultsur::*;
@@ -31,6 +32,7 @@ pub trait RuntimeProvider {
         db: &dyn CodegenDb,
         contract: ContractId,
         value: yul::Expression,
+        args: Vec<yul::Expression>,
     ) -> yul::Expression;
 
     fn create2(
@@ -39,6 +41,7 @@ pub trait RuntimeProvider {
         contract: ContractId,
         value: yul::Expression,
         salt: yul::Expression,
+        args: Vec<yul::Expression>,
     ) -> yul::Expression;
 
     fn emit(
@@ -107,6 +110,19 @@ pub trait RuntimeProvider {
         is_dst_storage: bool,
     ) -> yul::Expression;
 
+    /// Copy a value of `ty` from `src` to `dst`. Unlike `ptr_copy`, this
+    /// moves the packed primitives in the value to their positions in the
+    /// destination when the value is copied between memory and storage.
+    fn aggregate_copy(
+        &mut self,
+        db: &dyn CodegenDb,
+        src: yul::Expression,
+        dst: yul::Expression,
+        ty: TypeId,
+        is_src_storage: bool,
+        is_dst_storage: bool,
+    ) -> yul::Expression;
+
     fn ptr_store(
         &mut self,
         db: &dyn CodegenDb,
@@ -257,16 +273,18 @@ impl RuntimeProvider for DefaultRuntimeProvider {
             .collect()
     }
 
-    fn alloc(&mut self, _db: &dyn CodegenDb, bytes: yul::Expression) -> yul::Expression {
+    fn alloc(&mut self, db: &dyn CodegenDb, bytes: yul::Expression) -> yul::Expression {
         let name = "$alloc";
         let arg = vec![bytes];
-        self.create_then_call(name, arg, |_| data::make_alloc(name))
+        let memory_guard = db.codegen_memory_guard();
+        self.create_then_call(name, arg, |_| data::make_alloc(name, memory_guard))
     }
 
-    fn avail(&mut self, _db: &dyn CodegenDb) -> yul::Expression {
+    fn avail(&mut self, db: &dyn CodegenDb) -> yul::Expression {
         let name = "$avail";
         let arg = vec![];
-        self.create_then_call(name, arg, |_| data::make_avail(name))
+        let memory_guard = db.codegen_memory_guard();
+        self.create_then_call(name, arg, |_| data::make_avail(name, memory_guard))
     }
 
     fn create(
@@ -274,9 +292,10 @@ impl RuntimeProvider for DefaultRuntimeProvider {
         db: &dyn CodegenDb,
         contract: ContractId,
         value: yul::Expression,
+        args: Vec<yul::Expression>,
     ) -> yul::Expression {
         let name = format!("$create_{}", db.codegen_contract_symbol_name(contract));
-        let arg = vec![value];
+        let arg = std::iter::once(value).chain(args).collect();
         self.create_then_call(&name, arg, |provider| {
             contract::make_create(provider, db, &name, contract)
         })
@@ -288,9 +307,10 @@ impl RuntimeProvider for DefaultRuntimeProvider {
         contract: ContractId,
         value: yul::Expression,
         salt: yul::Expression,
+        args: Vec<yul::Expression>,
     ) -> yul::Expression {
         let name = format!("$create2_{}", db.codegen_contract_symbol_name(contract));
-        let arg = vec![value, salt];
+        let arg = [value, salt].into_iter().chain(args).collect();
         self.create_then_call(&name, arg, |provider| {
             contract::make_create2(provider, db, &name, contract)
         })
@@ -441,13 +461,18 @@ impl RuntimeProvider for DefaultRuntimeProvider {
 
     fn ptr_copy(
         &mut self,
-        _db: &dyn CodegenDb,
+        db: &dyn CodegenDb,
         src: yul::Expression,
         dst: yul::Expression,
         size: yul::Expression,
         is_src_storage: bool,
         is_dst_storage: bool,
     ) -> yul::Expression {
+        if !is_src_storage && !is_dst_storage && db.evm_version() >= EvmVersion::Cancun {
+            // `mcopy` copies exactly `size` bytes like `mcopym`, without a loop.
+            return expression! { mcopy([dst], [src], [size]) };
+        }
+
         let args = vec![src, dst, size];
         match (is_src_storage, is_dst_storage) {
             (true, true) => {
@@ -469,6 +494,30 @@ impl RuntimeProvider for DefaultRuntimeProvider {
         }
     }
 
+    fn aggregate_copy(
+        &mut self,
+        db: &dyn CodegenDb,
+        src: yul::Expression,
+        dst: yul::Expression,
+        ty: TypeId,
+        is_src_storage: bool,
+        is_dst_storage: bool,
+    ) -> yul::Expression {
+        if is_src_storage == is_dst_storage || !ty.has_packed_fields(db.upcast(), SLOT_SIZE) {
+            let size = literal_expression! {(ty.size_of(db.upcast(), SLOT_SIZE))};
+            return self.ptr_copy(db, src, dst, size, is_src_storage, is_dst_storage);
+        }
+
+        let name = if is_src_storage {
+            format!("$aggregate_copy_{}_storage_to_memory", ty.0)
+        } else {
+            format!("$aggregate_copy_{}_memory_to_storage", ty.0)
+        };
+        self.create_then_call(&name, vec![src, dst], |provider| {
+            data::make_aggregate_copy(provider, db, &name, ty, is_src_storage)
+        })
+    }
+
     fn ptr_store(
         &mut self,
         db: &dyn CodegenDb,
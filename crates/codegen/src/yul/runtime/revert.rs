@@ -39,13 +39,25 @@ pub(super) fn make_revert(
             }
         }
     } else {
-        let encode = provider.abi_encode_seq(
-            db,
-            &[arg.expr()],
-            expression! { add([abi_tmp_ptr.expr()], 4) },
-            &[arg_ty],
-            false,
-        );
+        let encode = if arg_ty.deref(db.upcast()).is_error(db.upcast()) {
+            // The fields of an error are encoded as the arguments of the
+            // signature, rather than as a single tuple argument.
+            provider.abi_encode(
+                db,
+                arg.expr(),
+                expression! { add([abi_tmp_ptr.expr()], 4) },
+                arg_ty,
+                false,
+            )
+        } else {
+            provider.abi_encode_seq(
+                db,
+                &[arg.expr()],
+                expression! { add([abi_tmp_ptr.expr()], 4) },
+                &[arg_ty],
+                false,
+            )
+        };
 
         function_definition! {
             function [func_name.ident()]([arg.ident()]) {
@@ -63,6 +75,11 @@ pub(super) fn make_revert(
 /// Returns signature hash of the type.
 fn type_signature_for_revert(db: &dyn CodegenDb, name: &str, ty: TypeId) -> yul::Expression {
     let deref_ty = ty.deref(db.upcast());
+    if deref_ty.is_error(db.upcast()) {
+        let type_sig = db.codegen_abi_error(deref_ty).selector().hex();
+        return literal_expression! {(format!{"0x{type_sig}" })};
+    }
+
     let ty_data = deref_ty.data(db.upcast());
     let args = match &ty_data.kind {
         ir::TypeKind::Struct(def) => def
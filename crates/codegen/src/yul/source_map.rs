@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+
+use fe_common::{db::SourceDb, SourceFileId, Span};
+use salsa::{InternId, InternKey};
+use yultsur::*;
+
+const SRC_PREFIX: &str = "/** @src ";
+
+/// Returns a statement that only consists of a `@src` comment, which makes
+/// solc attribute the following statements to `span`, or to no source if
+/// `span` is `None`.
+///
+/// The comment refers to the file by its id, which [`render_object`] replaces
+/// with an index into the `@use-src` comment of the object.
+pub(super) fn src_comment(span: Option<Span>) -> yul::Statement {
+    let comment = match span {
+        Some(span) => format!(
+            "{SRC_PREFIX}{}:{}:{} */",
+            span.file_id.as_intern_id().as_u32(),
+            span.start,
+            span.end
+        ),
+        None => format!("{SRC_PREFIX}-1:-1:-1 */"),
+    };
+    yul::Statement::Expression(identifier_expression! {(comment)})
+}
+
+/// Renders a Yul object, and prefixes it and each of its nested objects with a
+/// `@use-src` comment that maps the indices of the `@src` comments to file
+/// paths. Returns the Yul code and the files in the order of their indices.
+///
+/// The Yul code is returned unchanged if it has no `@src` comments.
+pub fn render_object(db: &dyn SourceDb, object: &yul::Object) -> (String, Vec<SourceFileId>) {
+    let yul = object.to_string();
+
+    let file_ids: BTreeSet<u32> = yul
+        .match_indices(SRC_PREFIX)
+        .filter_map(|(pos, _)| {
+            let location = &yul[pos + SRC_PREFIX.len()..];
+            location.split(':').next()?.parse().ok()
+        })
+        .collect();
+    if file_ids.is_empty() {
+        return (yul, vec![]);
+    }
+    let file_ids: Vec<_> = file_ids.into_iter().collect();
+
+    let mut annotated = String::with_capacity(yul.len());
+    let mut rest = yul.as_str();
+    while let Some(pos) = rest.find(SRC_PREFIX) {
+        let (head, tail) = rest.split_at(pos + SRC_PREFIX.len());
+        let id_len = tail.find(':').unwrap_or_default();
+        annotated.push_str(head);
+        match tail[..id_len].parse() {
+            Ok(id) => annotated.push_str(&file_ids.binary_search(&id).unwrap().to_string()),
+            Err(_) => annotated.push_str(&tail[..id_len]),
+        }
+        rest = &tail[id_len..];
+    }
+    annotated.push_str(rest);
+
+    let files: Vec<_> = file_ids
+        .into_iter()
+        .map(|id| SourceFileId::from_intern_id(InternId::from(id)))
+        .collect();
+    let use_src = files
+        .iter()
+        .enumerate()
+        .map(|(idx, file)| format!("{idx}:\"{}\"", file.path(db)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut names = BTreeSet::new();
+    collect_object_names(object, &mut names);
+    for name in names {
+        let header = format!("object \"{name}\"");
+        annotated = annotated.replace(&header, &format!("/** @use-src {use_src} */ {header}"));
+    }
+
+    (annotated, files)
+}
+
+fn collect_object_names(object: &yul::Object, names: &mut BTreeSet<String>) {
+    names.insert(object.name.to_string());
+    for object in &object.objects {
+        collect_object_names(object, names);
+    }
+}
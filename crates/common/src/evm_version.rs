@@ -0,0 +1,81 @@
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// The hardfork of the EVM that compiled contracts are deployed to. Versions
+/// are ordered, so that `version >= EvmVersion::Shanghai` checks whether the
+/// features of Shanghai are available.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EvmVersion {
+    London,
+    /// Replaces `difficulty` with `prevrandao`.
+    Paris,
+    /// Adds `PUSH0`.
+    Shanghai,
+    /// Adds transient storage (`tload` and `tstore`) and `mcopy`.
+    #[default]
+    Cancun,
+}
+
+impl EvmVersion {
+    pub const ALL: [EvmVersion; 4] = [
+        EvmVersion::London,
+        EvmVersion::Paris,
+        EvmVersion::Shanghai,
+        EvmVersion::Cancun,
+    ];
+
+    /// Returns the name of the version, as used by `fe.toml` and solc.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EvmVersion::London => "london",
+            EvmVersion::Paris => "paris",
+            EvmVersion::Shanghai => "shanghai",
+            EvmVersion::Cancun => "cancun",
+        }
+    }
+}
+
+impl fmt::Display for EvmVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EvmVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EvmVersion::ALL
+            .into_iter()
+            .find(|version| version.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = EvmVersion::ALL.iter().map(|v| v.as_str()).collect();
+                format!(
+                    "unknown EVM version `{s}`, expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_evm_version() {
+        for version in EvmVersion::ALL {
+            assert_eq!(version.as_str().parse(), Ok(version));
+        }
+        assert_eq!(
+            "frontier".parse::<EvmVersion>(),
+            Err(
+                "unknown EVM version `frontier`, expected one of: london, paris, shanghai, cancun"
+                    .into()
+            )
+        );
+        assert!(EvmVersion::London < EvmVersion::Cancun);
+    }
+}
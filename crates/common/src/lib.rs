@@ -1,11 +1,13 @@
 pub mod db;
 pub mod diagnostics;
+mod evm_version;
 pub mod files;
 pub mod numeric;
 pub mod panic;
 mod span;
 pub mod utils;
 
+pub use evm_version::EvmVersion;
 pub use files::{File, FileKind, SourceFileId};
 pub use span::{Span, Spanned};
 
@@ -9,6 +9,7 @@ use walkdir::WalkDir;
 
 use crate::utils::dirs::get_fe_deps;
 use crate::utils::git;
+use crate::EvmVersion;
 
 const FE_TOML: &str = "fe.toml";
 
@@ -94,6 +95,12 @@ impl BuildFiles {
         self.project_files[&self.root_project_path].mode
     }
 
+    /// The EVM version that the `fe.toml` of the root project targets, if it
+    /// sets one.
+    pub fn root_project_evm_version(&self) -> Option<EvmVersion> {
+        self.project_files[&self.root_project_path].evm_version
+    }
+
     /// Build files are loaded from the file system.
     pub fn load_fs(root_path: &str) -> Result<Self, String> {
         Self::load(&FileLoader::Fs, root_path)
@@ -141,6 +148,7 @@ pub struct ProjectFiles {
     pub name: SmolStr,
     pub version: SmolStr,
     pub mode: ProjectMode,
+    pub evm_version: Option<EvmVersion>,
     pub dependencies: Vec<Dependency>,
     pub src: Vec<(String, String)>,
 }
@@ -155,6 +163,7 @@ impl ProjectFiles {
         let manifest = Manifest::load(loader, &manifest_path)?;
         let name = manifest.name;
         let version = manifest.version;
+        let evm_version = manifest.evm_version;
 
         let mut dependencies = vec![];
         let mut errors = vec![];
@@ -199,6 +208,7 @@ impl ProjectFiles {
             name,
             version,
             mode,
+            evm_version,
             dependencies,
             src,
         })
@@ -379,6 +389,8 @@ impl Dependency {
 struct Manifest {
     pub name: SmolStr,
     pub version: SmolStr,
+    #[serde(rename = "evm-version")]
+    pub evm_version: Option<EvmVersion>,
     dependencies: Option<Table>,
 }
 
@@ -1,19 +1,28 @@
 #![allow(unused_imports, dead_code)]
 
+use fe_abi::error::AbiError;
 use fe_abi::event::AbiEvent;
+use fe_abi::function::AbiFunctionType;
 use fe_abi::types::{AbiTupleField, AbiType};
+pub use fe_analyzer::AnalyzerDb;
 pub use fe_codegen::db::{CodegenDb, Db};
+pub use fe_mir::interpreter::InterpreterError;
 
-use fe_analyzer::namespace::items::{ContractId, FunctionId, IngotId, IngotMode, ModuleId};
-use fe_common::diagnostics::Diagnostic;
-use fe_common::files::FileKind;
+use fe_analyzer::context::{self, Constant};
+use fe_analyzer::namespace::items::{ContractId, FunctionId, IngotId, IngotMode, Item, ModuleId};
+use fe_common::diagnostics::{Diagnostic, Label, Severity};
+use fe_common::files::{FileKind, SourceFileId};
+use fe_common::EvmVersion;
 use fe_common::{db::Upcast, utils::files::BuildFiles};
+use fe_mir::db::MirDb;
+use fe_mir::interpreter::{self, Outcome};
 use fe_parser::ast::SmolStr;
-use fe_test_runner::ethabi::{Event, EventParam, ParamType};
-use fe_test_runner::TestSink;
+use fe_test_runner::ethabi::{self, Event, EventParam, Param, ParamType, Token, Uint};
+use fe_test_runner::{ExpectedRevert, FuzzConfig, TestLog, TestOutcome, TestSink};
 use indexmap::{indexmap, IndexMap};
+use num_bigint::{BigInt, Sign};
 use serde_json::Value;
-use std::fmt::Display;
+use std::fmt::{Display, Write};
 
 /// The artifacts of a compiled module.
 pub struct CompiledModule {
@@ -25,35 +34,211 @@ pub struct CompiledModule {
 /// The artifacts of a compiled contract.
 pub struct CompiledContract {
     pub json_abi: String,
+    pub storage_layout: String,
     pub yul: String,
     pub origin: ContractId,
-    #[cfg(feature = "solc-backend")]
+    /// The bytecode, which is compiled by solc with the `solc-backend`
+    /// feature, and by the native assembler without it.
     pub bytecode: String,
-    #[cfg(feature = "solc-backend")]
     pub runtime_bytecode: String,
+    /// A JSON mapping from the offsets of the instructions in `bytecode` and
+    /// `runtime_bytecode` to the source spans they derive from. Empty unless
+    /// source maps are enabled with `Db::set_codegen_source_map`.
+    #[cfg(feature = "solc-backend")]
+    pub source_map: String,
 }
 
 #[cfg(feature = "solc-backend")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CompiledTest {
     pub name: SmolStr,
+    /// The name of the test qualified by its module, e.g. `foo::test_bar`,
+    /// which identifies it in gas reports and snapshots.
+    path: String,
+    /// The parameters of the test other than `ctx`, whose arguments are
+    /// generated by the fuzzer.
+    params: Vec<Param>,
     events: Vec<AbiEvent>,
+    errors: Vec<AbiError>,
+    /// The names of the contract functions the test can call, by selector.
+    functions: IndexMap<[u8; 4], String>,
+    expected_revert: Option<ExpectedRevert>,
     bytecode: String,
+    evm_version: EvmVersion,
 }
 
 #[cfg(feature = "solc-backend")]
 impl CompiledTest {
-    pub fn new(name: SmolStr, events: Vec<AbiEvent>, bytecode: String) -> Self {
+    /// The parameters of the test other than `ctx`.
+    pub fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    /// Runs the test with the ABI encoded arguments in `calldata`, which is
+    /// empty if the test takes none.
+    pub fn run(&self, calldata: Vec<u8>) -> TestOutcome {
+        fe_test_runner::run(&self.bytecode, calldata, self.evm_version).0
+    }
+
+    /// Executes the test, and records its outcome and the gas it used in
+    /// `sink`. A test that takes arguments is fuzzed with `fuzz` instead, and
+    /// its gas isn't recorded since it depends on the arguments.
+    pub fn execute(&self, sink: &mut TestSink, fuzz: FuzzConfig) -> bool {
+        let errors = map_abi_errors(&self.errors);
+        if !self.params.is_empty() {
+            return fe_test_runner::fuzz(
+                &self.name,
+                &self.params,
+                &errors,
+                self.expected_revert.as_ref(),
+                fuzz,
+                sink,
+                |calldata| Ok(self.run(calldata)),
+            );
+        }
+
+        let events = map_abi_events(&self.events);
+        let (passed, gas) = fe_test_runner::execute(
+            &self.name,
+            &events,
+            &errors,
+            self.expected_revert.as_ref(),
+            &self.bytecode,
+            self.evm_version,
+            sink,
+        );
+        sink.insert_gas(&self.path, &gas, |selector| {
+            match self.functions.get(&selector) {
+                Some(name) => name.clone(),
+                None => format!("0x{:08x}", u32::from_be_bytes(selector)),
+            }
+        });
+        passed
+    }
+}
+
+/// A test that is run by the MIR interpreter instead of being compiled to
+/// bytecode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpretedTest {
+    pub name: SmolStr,
+    /// The parameters of the test other than `ctx`, whose arguments are
+    /// generated by the fuzzer.
+    params: Vec<Param>,
+    events: Vec<AbiEvent>,
+    errors: Vec<AbiError>,
+    expected_revert: Option<ExpectedRevert>,
+    function: fe_mir::ir::FunctionId,
+}
+
+impl InterpretedTest {
+    pub fn new(
+        name: SmolStr,
+        params: Vec<Param>,
+        events: Vec<AbiEvent>,
+        errors: Vec<AbiError>,
+        expected_revert: Option<ExpectedRevert>,
+        function: fe_mir::ir::FunctionId,
+    ) -> Self {
         Self {
             name,
+            params,
             events,
-            bytecode,
+            errors,
+            expected_revert,
+            function,
         }
     }
 
-    pub fn execute(&self, sink: &mut TestSink) -> bool {
-        let events = map_abi_events(&self.events);
-        fe_test_runner::execute(&self.name, &events, &self.bytecode, sink)
+    /// Returns `true` if the test takes arguments, so that it's fuzzed by
+    /// [`Self::execute`] rather than run once.
+    pub fn is_fuzz_test(&self) -> bool {
+        !self.params.is_empty()
+    }
+
+    /// Runs the test, which must take no arguments other than `ctx`.
+    pub fn run(&self, db: &Db) -> Result<TestOutcome, InterpreterError> {
+        Ok(test_outcome(interpreter::run_test(db, self.function)?))
+    }
+
+    /// The parameters of the test other than `ctx`.
+    pub fn params(&self) -> &[Param] {
+        &self.params
+    }
+
+    /// Runs the test with the ABI encoded arguments in `calldata`, which is
+    /// empty if the test takes none.
+    pub fn run_with(&self, db: &Db, calldata: Vec<u8>) -> Result<TestOutcome, InterpreterError> {
+        Ok(test_outcome(interpreter::run_fuzz_test(
+            db,
+            self.function,
+            calldata,
+        )?))
+    }
+
+    /// Runs the test, or fuzzes it with `fuzz` if it takes arguments, and
+    /// records its outcome in `sink`.
+    pub fn execute(&self, db: &Db, sink: &mut TestSink, fuzz: FuzzConfig) -> bool {
+        if !self.is_fuzz_test() {
+            return self.report(self.run(db), sink);
+        }
+
+        let errors = map_abi_errors(&self.errors);
+        fe_test_runner::fuzz(
+            &self.name,
+            &self.params,
+            &errors,
+            self.expected_revert.as_ref(),
+            fuzz,
+            sink,
+            |calldata| {
+                self.run_with(db, calldata)
+                    .map_err(|err| format!("  {err}"))
+            },
+        )
+    }
+
+    /// Records the result of [`Self::run`] in `sink` and returns whether the
+    /// test passed.
+    pub fn report(
+        &self,
+        result: Result<TestOutcome, InterpreterError>,
+        sink: &mut TestSink,
+    ) -> bool {
+        match result {
+            Ok(outcome) => {
+                let events = map_abi_events(&self.events);
+                let errors = map_abi_errors(&self.errors);
+                fe_test_runner::report(
+                    &self.name,
+                    &events,
+                    &errors,
+                    self.expected_revert.as_ref(),
+                    outcome,
+                    sink,
+                )
+            }
+            Err(err) => {
+                sink.insert_failure(&self.name, &format!("  {err}"));
+                false
+            }
+        }
+    }
+}
+
+fn test_outcome(outcome: Outcome) -> TestOutcome {
+    match outcome {
+        Outcome::Success { logs } => TestOutcome::Success {
+            logs: logs
+                .into_iter()
+                .map(|log| TestLog {
+                    address: log.address,
+                    topics: log.topics,
+                    data: log.data,
+                })
+                .collect(),
+        },
+        Outcome::Revert { data } => TestOutcome::Revert { output: data },
     }
 }
 
@@ -81,6 +266,26 @@ fn map_abi_event(event: &AbiEvent) -> Event {
     }
 }
 
+fn map_abi_errors(errors: &[AbiError]) -> Vec<ethabi::AbiError> {
+    errors.iter().map(map_abi_error).collect()
+}
+
+fn map_abi_error(error: &AbiError) -> ethabi::AbiError {
+    let inputs = error
+        .inputs
+        .iter()
+        .map(|input| Param {
+            name: input.name.to_owned(),
+            kind: map_abi_type(&input.ty),
+            internal_type: None,
+        })
+        .collect();
+    ethabi::AbiError {
+        name: error.name.to_owned(),
+        inputs,
+    }
+}
+
 fn map_abi_type(typ: &AbiType) -> ParamType {
     match typ {
         AbiType::UInt(value) => ParamType::Uint(*value),
@@ -144,6 +349,23 @@ pub fn compile_single_file_tests(
     }
 }
 
+/// Lowers the tests of a single file to MIR so that they can be run by the
+/// interpreter.
+pub fn lower_single_file_tests(
+    db: &mut Db,
+    path: &str,
+    src: &str,
+) -> Result<(SmolStr, Vec<InterpretedTest>), CompileError> {
+    let module = ModuleId::new_standalone(db, path, src);
+    let diags = module.diagnostics(db);
+
+    if diags.is_empty() {
+        Ok((module.name(db), lower_module_tests(db, module)))
+    } else {
+        Err(CompileError(diags))
+    }
+}
+
 // Run analysis with ingot
 // Return vector error,waring...
 pub fn check_ingot(db: &mut Db, build_files: &BuildFiles) -> Vec<Diagnostic> {
@@ -211,6 +433,27 @@ pub fn compile_ingot_tests(
     }
 }
 
+/// Lowers the tests of all modules of an ingot to MIR so that they can be run
+/// by the interpreter.
+pub fn lower_ingot_tests(
+    db: &mut Db,
+    build_files: &BuildFiles,
+) -> Result<Vec<(SmolStr, Vec<InterpretedTest>)>, CompileError> {
+    let ingot = IngotId::from_build_files(db, build_files);
+
+    let mut diags = ingot.diagnostics(db);
+    ingot.sink_external_ingot_diagnostics(db, &mut diags);
+    if !diags.is_empty() {
+        return Err(CompileError(diags));
+    }
+
+    Ok(ingot
+        .all_modules(db)
+        .iter()
+        .map(|module| (module.name(db), lower_module_tests(db, *module)))
+        .collect())
+}
+
 /// Returns graphviz string.
 // TODO: This is temporary function for debugging.
 pub fn dump_mir_single_file(db: &mut Db, path: &str, src: &str) -> Result<String, CompileError> {
@@ -231,13 +474,53 @@ fn compile_test(db: &mut Db, test: FunctionId, optimize: bool) -> CompiledTest {
     let yul_test = fe_codegen::yul::isel::lower_test(db, test)
         .to_string()
         .replace('"', "\\\"");
-    let bytecode = compile_to_evm("test", &yul_test, optimize, false).bytecode;
+    let evm_version = db.evm_version();
+    let bytecode = compile_to_evm("test", &yul_test, optimize, evm_version, false).bytecode;
     let events = db.codegen_abi_module_events(test.module(db));
-    CompiledTest::new(test.name(db), events, bytecode)
+    let errors = db.codegen_abi_module_errors(test.module(db));
+    CompiledTest {
+        name: test.name(db),
+        path: Item::Function(test).path(db).join("::"),
+        params: fuzz_params(db, test),
+        events,
+        errors,
+        functions: function_names(db, test.module(db)),
+        expected_revert: expected_revert(db, test),
+        bytecode,
+        evm_version,
+    }
+}
+
+/// Maps the selectors of the public functions of the contracts in the ingot
+/// of `module` to names like `Token.transfer`. A function that several
+/// contracts share is named after all of them, e.g. `Token/Vault.transfer`.
+#[cfg(feature = "solc-backend")]
+fn function_names(db: &Db, module: ModuleId) -> IndexMap<[u8; 4], String> {
+    let mut functions: IndexMap<[u8; 4], (Vec<SmolStr>, String)> = IndexMap::new();
+    for module in module.ingot(db).all_modules(db).iter() {
+        for &contract in db.module_contracts(*module).iter() {
+            let abi = db.codegen_abi_contract(contract);
+            for func in abi.functions() {
+                if func.func_type() != AbiFunctionType::Function {
+                    continue;
+                }
+                let (contracts, _) = functions
+                    .entry(func.selector().selector_raw())
+                    .or_insert_with(|| (vec![], func.name().to_string()));
+                contracts.push(contract.name(db));
+            }
+        }
+    }
+
+    functions
+        .into_iter()
+        .map(|(selector, (contracts, name))| (selector, format!("{}.{name}", contracts.join("/"))))
+        .collect()
 }
 
 #[cfg(feature = "solc-backend")]
 fn compile_module_tests(db: &mut Db, module_id: ModuleId, optimize: bool) -> Vec<CompiledTest> {
+    db.set_codegen_optimize_mir(optimize);
     module_id
         .tests(db)
         .iter()
@@ -245,6 +528,87 @@ fn compile_module_tests(db: &mut Db, module_id: ModuleId, optimize: bool) -> Vec
         .collect()
 }
 
+fn lower_module_tests(db: &mut Db, module_id: ModuleId) -> Vec<InterpretedTest> {
+    let events = db.codegen_abi_module_events(module_id);
+    let errors = db.codegen_abi_module_errors(module_id);
+    module_id
+        .tests(db)
+        .iter()
+        .map(|test| {
+            let function = db.mir_lowered_func_signature(*test);
+            InterpretedTest::new(
+                test.name(db),
+                fuzz_params(db, *test),
+                events.clone(),
+                errors.clone(),
+                expected_revert(db, *test),
+                function,
+            )
+        })
+        .collect()
+}
+
+/// Returns the parameters of `test` other than `ctx`, whose arguments are
+/// generated by the fuzzer.
+fn fuzz_params(db: &Db, test: FunctionId) -> Vec<Param> {
+    let test = db.mir_lowered_func_signature(test);
+    db.codegen_abi_function(test)
+        .inputs()
+        .map(|(name, ty)| Param {
+            name: name.to_string(),
+            kind: map_abi_type(ty),
+            internal_type: None,
+        })
+        .collect()
+}
+
+/// Returns the revert that `test` expects if it's marked
+/// `#test(should_revert)`, with the error's fields as ABI tokens.
+fn expected_revert(db: &Db, test: FunctionId) -> Option<ExpectedRevert> {
+    Some(match test.expected_revert(db)?.as_ref() {
+        context::ExpectedRevert::Any => ExpectedRevert::Any,
+        context::ExpectedRevert::Panic(code) => ExpectedRevert::panic(uint(code)),
+        context::ExpectedRevert::Error { ty, fields } => {
+            let mir_ty = db.mir_lowered_type(ty.as_type(db));
+            let error = map_abi_error(&db.codegen_abi_error(mir_ty));
+            let mut args: Vec<_> = fields
+                .iter()
+                .zip(&error.inputs)
+                .map(|(field, input)| constant_token(field, &input.kind))
+                .collect();
+            if !ty.is_error(db) {
+                args = vec![Token::Tuple(args)];
+            }
+            ExpectedRevert::Error { error, args }
+        }
+    })
+}
+
+fn constant_token(constant: &Constant, typ: &ParamType) -> Token {
+    match (constant, typ) {
+        (Constant::Int(value), ParamType::Int(_)) => Token::Int(uint(value)),
+        (Constant::Int(value), _) => Token::Uint(uint(value)),
+        (Constant::Address(value), _) => {
+            let mut word = [0; 32];
+            uint(value).to_big_endian(&mut word);
+            Token::Address(ethabi::Address::from_slice(&word[12..]))
+        }
+        (Constant::Bool(value), _) => Token::Bool(*value),
+        (Constant::Str(value), _) => Token::String(value.to_string()),
+    }
+}
+
+/// Returns `value` as a 256-bit word, in two's complement if it's negative.
+fn uint(value: &BigInt) -> Uint {
+    let (sign, bytes) = value.to_bytes_be();
+    let magnitude = Uint::from_big_endian(&bytes);
+    if sign == Sign::Minus {
+        (!magnitude).overflowing_add(Uint::one()).0
+    } else {
+        magnitude
+    }
+}
+
 #[cfg(feature = "solc-backend")]
 fn compile_module(
     db: &mut Db,
@@ -253,39 +617,63 @@ fn compile_module(
     with_runtime_bytecode: bool,
     optimize: bool,
 ) -> Result<CompiledModule, CompileError> {
+    db.set_codegen_optimize_mir(optimize);
     let mut contracts = IndexMap::default();
+    let mut diags = vec![];
 
     for contract in module_id.all_contracts(db.upcast()) {
         let name = &contract.data(db.upcast()).name;
         let abi = db.codegen_abi_contract(contract);
-        let yul_contract = compile_to_yul(db, contract);
-
-        let (bytecode, runtime_bytecode) = if with_bytecode || with_runtime_bytecode {
-            let deployable_name = db.codegen_contract_deployer_symbol_name(contract);
-            let bytecode = compile_to_evm(
-                deployable_name.as_str(),
-                &yul_contract,
-                optimize,
-                with_runtime_bytecode,
-            );
-            (bytecode.bytecode, bytecode.runtime_bytecode)
-        } else {
-            ("".to_string(), "".to_string())
-        };
+        let storage_layout = db.codegen_contract_storage_layout(contract);
+        let (yul_contract, source_files) = compile_to_yul(db, contract);
+
+        let (bytecode, runtime_bytecode, source_map) =
+            if with_bytecode || with_runtime_bytecode || !source_files.is_empty() {
+                let deployable_name = db.codegen_contract_deployer_symbol_name(contract);
+                let bytecode = compile_to_evm(
+                    deployable_name.as_str(),
+                    &yul_contract,
+                    optimize,
+                    db.evm_version(),
+                    with_runtime_bytecode,
+                );
+                let source_map = if source_files.is_empty() {
+                    "".to_string()
+                } else {
+                    serde_json::to_string_pretty(&source_map_json(db, &source_files, &bytecode))
+                        .unwrap()
+                };
+                (bytecode.bytecode, bytecode.runtime_bytecode, source_map)
+            } else {
+                ("".to_string(), "".to_string(), "".to_string())
+            };
+        diags.extend(check_code_size(
+            db,
+            contract,
+            &yul_contract,
+            &bytecode,
+            &runtime_bytecode,
+        ));
 
         contracts.insert(
             name.to_string(),
             // Maybe put the ContractID here so we can trace it back to the source file
             CompiledContract {
                 json_abi: serde_json::to_string_pretty(&abi).unwrap(),
+                storage_layout: serde_json::to_string_pretty(&storage_layout).unwrap(),
                 yul: yul_contract,
                 origin: contract,
                 bytecode,
                 runtime_bytecode,
+                source_map,
             },
         );
     }
 
+    if !diags.is_empty() {
+        return Err(CompileError(diags));
+    }
+
     Ok(CompiledModule {
         src_ast: format!("{:#?}", module_id.ast(db)),
         lowered_ast: format!("{:#?}", module_id.ast(db)),
@@ -297,26 +685,59 @@ fn compile_module(
 fn compile_module(
     db: &mut Db,
     module_id: ModuleId,
-    _with_bytecode: bool,
-    _with_runtime_bytecode: bool,
-    _optimize: bool,
+    with_bytecode: bool,
+    with_runtime_bytecode: bool,
+    optimize: bool,
 ) -> Result<CompiledModule, CompileError> {
+    db.set_codegen_optimize_mir(optimize);
+    // The native assembler moves variables that are too deep in the stack to
+    // the memory that `memoryguard` reserves.
+    db.set_codegen_memory_guard(true);
     let mut contracts = IndexMap::default();
+    let mut diags = vec![];
     for contract in module_id.all_contracts(db.upcast()) {
         let name = &contract.data(db.upcast()).name;
         let abi = db.codegen_abi_contract(contract);
-        let yul_contract = compile_to_yul(db, contract);
+        let storage_layout = db.codegen_contract_storage_layout(contract);
+        let (yul_contract, _) = compile_to_yul(db, contract);
+
+        let (bytecode, runtime_bytecode) = if with_bytecode || with_runtime_bytecode {
+            let deployable_name = db.codegen_contract_deployer_symbol_name(contract);
+            let bytecode = assemble_to_evm(
+                deployable_name.as_str(),
+                &yul_contract,
+                db.evm_version(),
+                with_runtime_bytecode,
+            )?;
+            (bytecode.bytecode, bytecode.runtime_bytecode)
+        } else {
+            ("".to_string(), "".to_string())
+        };
+        diags.extend(check_code_size(
+            db,
+            contract,
+            &yul_contract,
+            &bytecode,
+            &runtime_bytecode,
+        ));
 
         contracts.insert(
             name.to_string(),
             CompiledContract {
                 json_abi: serde_json::to_string_pretty(&abi).unwrap(),
+                storage_layout: serde_json::to_string_pretty(&storage_layout).unwrap(),
                 yul: yul_contract,
                 origin: contract,
+                bytecode,
+                runtime_bytecode,
             },
         );
     }
 
+    if !diags.is_empty() {
+        return Err(CompileError(diags));
+    }
+
     Ok(CompiledModule {
         src_ast: format!("{:#?}", module_id.ast(db)),
         lowered_ast: format!("{:#?}", module_id.ast(db)),
@@ -324,9 +745,168 @@ fn compile_module(
     })
 }
 
-fn compile_to_yul(db: &mut Db, contract: ContractId) -> String {
+/// Returns the Yul code of a contract, and the files its `@src` comments refer
+/// to if source maps are enabled.
+fn compile_to_yul(db: &mut Db, contract: ContractId) -> (String, Vec<SourceFileId>) {
     let yul_contract = fe_codegen::yul::isel::lower_contract_deployable(db, contract);
-    yul_contract.to_string().replace('"', "\\\"")
+    let (yul, source_files) = fe_codegen::yul::source_map::render_object(db, &yul_contract);
+    (yul.replace('"', "\\\""), source_files)
+}
+
+/// Returns the Yul code of [`CompiledContract::yul`] with its double quotes
+/// unescaped. They're escaped for the code to be embedded in the JSON input of
+/// solc, but the native assembler reads the code as is.
+pub fn unescape_yul(yul: &str) -> String {
+    yul.replace("\\\"", "\"")
+}
+
+/// The maximum size of the runtime bytecode of a contract, from EIP-170.
+const MAX_CODE_SIZE: usize = 0x6000;
+/// The maximum size of the init code of a contract, from EIP-3860, which
+/// applies from Shanghai on.
+const MAX_INIT_CODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+/// The number of functions that the size breakdown of an oversized contract
+/// lists.
+const LISTED_FUNCTIONS: usize = 10;
+
+/// Checks the hex encoded bytecode of `contract` against the code size
+/// limits, unless oversized contracts are allowed.
+fn check_code_size(
+    db: &Db,
+    contract: ContractId,
+    yul: &str,
+    bytecode: &str,
+    runtime_bytecode: &str,
+) -> Vec<Diagnostic> {
+    if db.codegen_allow_oversize() {
+        return vec![];
+    }
+
+    let mut diags = vec![];
+    let runtime_size = runtime_bytecode.len() / 2;
+    if runtime_size > MAX_CODE_SIZE {
+        diags.push(code_size_error(db, contract, yul, true, runtime_size));
+    }
+    let init_code_size = bytecode.len() / 2;
+    if db.evm_version() >= EvmVersion::Shanghai && init_code_size > MAX_INIT_CODE_SIZE {
+        diags.push(code_size_error(db, contract, yul, false, init_code_size));
+    }
+    diags
+}
+
+fn code_size_error(
+    db: &Db,
+    contract: ContractId,
+    yul: &str,
+    is_runtime: bool,
+    size: usize,
+) -> Diagnostic {
+    let (code, limit, eip) = if is_runtime {
+        ("runtime bytecode", MAX_CODE_SIZE, "EIP-170")
+    } else {
+        ("init code", MAX_INIT_CODE_SIZE, "EIP-3860")
+    };
+
+    let mut notes: Vec<_> = function_size_breakdown(db, yul, is_runtime)
+        .into_iter()
+        .collect();
+    notes.push(
+        "Hint: move functions into another contract, or build it anyway with `--allow-oversize`"
+            .into(),
+    );
+
+    Diagnostic {
+        severity: Severity::Error,
+        message: format!(
+            "the {code} of contract `{}` is {size} bytes, which exceeds the limit of {limit} bytes of {eip}",
+            contract.name(db)
+        ),
+        labels: vec![Label::primary(
+            contract.name_span(db),
+            format!("{code} is too large to deploy"),
+        )],
+        notes,
+    }
+}
+
+/// Lists the largest Yul functions of the runtime object of a contract, or of
+/// its deploy object if `is_runtime` is false. The sizes are those of the
+/// native assembler, which doesn't optimize, so they're only estimates of the
+/// share of each function in the bytecode that solc optimizes.
+fn function_size_breakdown(db: &Db, yul: &str, is_runtime: bool) -> Option<String> {
+    let object = fe_evm_asm::parse_object(&unescape_yul(yul)).ok()?;
+    let object = if is_runtime {
+        object.objects.first()?
+    } else {
+        &object
+    };
+    let sizes = fe_evm_asm::function_sizes(object, db.evm_version()).ok()?;
+
+    let mut breakdown =
+        "the largest Yul functions, by their estimated size before optimization:".to_string();
+    for (name, size) in sizes.iter().take(LISTED_FUNCTIONS) {
+        write!(breakdown, "\n  {name}: ~{size} bytes (estimate)").unwrap();
+    }
+    if sizes.len() > LISTED_FUNCTIONS {
+        write!(breakdown, "\n  and {} more", sizes.len() - LISTED_FUNCTIONS).unwrap();
+    }
+    Some(breakdown)
+}
+
+/// Maps the offset of each instruction in the bytecode and runtime bytecode to
+/// the file and span it derives from.
+#[cfg(feature = "solc-backend")]
+fn source_map_json(
+    db: &Db,
+    source_files: &[SourceFileId],
+    bytecode: &fe_yulc::ContractBytecode,
+) -> Value {
+    let instructions = |bytecode: &str, source_map: &str| -> Vec<Value> {
+        fe_yulc::decode_source_map(bytecode, source_map)
+            .into_iter()
+            .filter_map(|inst| {
+                let file = source_files.get(inst.source_index)?;
+                Some(serde_json::json!({
+                    "pc": inst.pc,
+                    "file": file.path(db).as_str(),
+                    "start": inst.start,
+                    "end": inst.end,
+                }))
+            })
+            .collect()
+    };
+
+    serde_json::json!({
+        "bytecode": instructions(&bytecode.bytecode, &bytecode.source_map),
+        "runtime_bytecode": instructions(
+            &bytecode.runtime_bytecode,
+            &bytecode.runtime_source_map
+        ),
+    })
+}
+
+/// Compiles a Yul object to bytecode with the native assembler, which doesn't
+/// need solc.
+#[cfg(not(feature = "solc-backend"))]
+fn assemble_to_evm(
+    name: &str,
+    yul_object: &str,
+    evm_version: EvmVersion,
+    verify_runtime_bytecode: bool,
+) -> Result<fe_evm_asm::ContractBytecode, CompileError> {
+    let error = |message: String| {
+        CompileError(vec![Diagnostic::error(format!(
+            "failed to assemble `{name}`: {message}"
+        ))])
+    };
+
+    fe_evm_asm::compile_single_contract(
+        name,
+        &unescape_yul(yul_object),
+        evm_version,
+        verify_runtime_bytecode,
+    )
+    .map_err(|err| error(err.to_string()))
 }
 
 #[cfg(feature = "solc-backend")]
@@ -334,9 +914,16 @@ fn compile_to_evm(
     name: &str,
     yul_object: &str,
     optimize: bool,
+    evm_version: EvmVersion,
     verify_runtime_bytecode: bool,
 ) -> fe_yulc::ContractBytecode {
-    match fe_yulc::compile_single_contract(name, yul_object, optimize, verify_runtime_bytecode) {
+    match fe_yulc::compile_single_contract(
+        name,
+        yul_object,
+        optimize,
+        evm_version,
+        verify_runtime_bytecode,
+    ) {
         Ok(bytecode) => bytecode,
 
         Err(error) => {
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use fe_common::EvmVersion;
+
+use crate::{
+    opcode::{JUMPDEST, PUSH0, PUSH1, PUSH2, PUSH32},
+    AsmError,
+};
+
+/// A label of a `JUMPDEST`.
+pub type Tag = usize;
+
+/// An instruction of an object's code whose immediate value may only be known
+/// once the object is laid out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Op(u8),
+    /// Pushes a big-endian value without leading zeros. Zero is pushed with
+    /// `PUSH0` from Shanghai on.
+    Push(Vec<u8>),
+    PushTag(Tag),
+    Tag(Tag),
+    PushDataOffset(DataRef),
+    PushDataSize(DataRef),
+    /// Pushes a placeholder that the deploy code replaces with the value of an
+    /// immutable.
+    PushImmutable(String),
+}
+
+/// A part of an assembled object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataRef {
+    /// The whole object, including its sub-objects and data.
+    Object,
+    Sub(usize),
+    Data(usize),
+}
+
+/// The bytecode of an object.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Assembled {
+    pub bytecode: Vec<u8>,
+    /// The offsets of the placeholders of each immutable in the bytecode.
+    pub immutables: BTreeMap<String, Vec<usize>>,
+}
+
+impl Item {
+    pub fn size(&self, evm_version: EvmVersion) -> usize {
+        match self {
+            Item::Op(_) | Item::Tag(_) => 1,
+            Item::Push(value) if value.is_empty() && evm_version >= EvmVersion::Shanghai => 1,
+            Item::Push(value) => 1 + value.len().max(1),
+            Item::PushTag(_) | Item::PushDataOffset(_) | Item::PushDataSize(_) => 3,
+            Item::PushImmutable(_) => 33,
+        }
+    }
+}
+
+/// Lays out the code, followed by the sub-objects and the data, and resolves
+/// the tags and data references of the code.
+///
+/// Tags and data references are pushed with two bytes, which is enough for
+/// any object that fits the init code size limit.
+pub fn link(
+    items: &[Item],
+    subs: &[Assembled],
+    data: &[Vec<u8>],
+    evm_version: EvmVersion,
+) -> Result<Assembled, AsmError> {
+    let mut tags = BTreeMap::new();
+    let mut code_size = 0;
+    for item in items {
+        if let Item::Tag(tag) = item {
+            tags.insert(*tag, code_size);
+        }
+        code_size += item.size(evm_version);
+    }
+
+    let sections: Vec<&[u8]> = subs
+        .iter()
+        .map(|sub| sub.bytecode.as_slice())
+        .chain(data.iter().map(Vec::as_slice))
+        .collect();
+    let mut offsets = Vec::with_capacity(sections.len());
+    let mut size = code_size;
+    for section in &sections {
+        offsets.push(size);
+        size += section.len();
+    }
+
+    let section = |data_ref: &DataRef| match data_ref {
+        DataRef::Object => (0, size),
+        DataRef::Sub(idx) => (offsets[*idx], sections[*idx].len()),
+        DataRef::Data(idx) => (offsets[subs.len() + idx], sections[subs.len() + idx].len()),
+    };
+
+    let mut bytecode = Vec::with_capacity(size);
+    let mut immutables = BTreeMap::<_, Vec<_>>::new();
+    for item in items {
+        match item {
+            Item::Op(opcode) => bytecode.push(*opcode),
+            Item::Push(value) if value.is_empty() && evm_version >= EvmVersion::Shanghai => {
+                bytecode.push(PUSH0)
+            }
+            Item::Push(value) if value.is_empty() => bytecode.extend([PUSH1, 0]),
+            Item::Push(value) => {
+                bytecode.push(PUSH1 + value.len() as u8 - 1);
+                bytecode.extend_from_slice(value);
+            }
+            Item::PushTag(tag) => push_u16(&mut bytecode, tags[tag])?,
+            Item::Tag(_) => bytecode.push(JUMPDEST),
+            Item::PushDataOffset(data_ref) => push_u16(&mut bytecode, section(data_ref).0)?,
+            Item::PushDataSize(data_ref) => push_u16(&mut bytecode, section(data_ref).1)?,
+            Item::PushImmutable(name) => {
+                bytecode.push(PUSH32);
+                immutables
+                    .entry(name.clone())
+                    .or_default()
+                    .push(bytecode.len());
+                bytecode.extend([0; 32]);
+            }
+        }
+    }
+    for section in sections {
+        bytecode.extend_from_slice(section);
+    }
+
+    Ok(Assembled {
+        bytecode,
+        immutables,
+    })
+}
+
+fn push_u16(bytecode: &mut Vec<u8>, value: usize) -> Result<(), AsmError> {
+    let value = u16::try_from(value).map_err(|_| {
+        AsmError(format!(
+            "object is too large: offset {value} doesn't fit in two bytes"
+        ))
+    })?;
+    bytecode.push(PUSH2);
+    bytecode.extend(value.to_be_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::{JUMP, STOP};
+
+    #[test]
+    fn link_tags_and_data() {
+        let sub = Assembled {
+            bytecode: vec![0xaa, 0xbb],
+            immutables: BTreeMap::new(),
+        };
+        let items = [
+            Item::PushTag(0),
+            Item::Op(JUMP),
+            Item::Tag(0),
+            Item::PushDataOffset(DataRef::Data(0)),
+            Item::PushDataSize(DataRef::Object),
+            Item::Push(vec![]),
+            Item::Push(vec![0x01, 0x02]),
+            Item::Op(STOP),
+        ];
+
+        let assembled = link(&items, &[sub.clone()], &[vec![0xcc]], EvmVersion::London).unwrap();
+        assert_eq!(
+            hex::encode(assembled.bytecode),
+            "610004565b610013610014600061010200aabbcc"
+        );
+
+        let assembled = link(&items, &[sub], &[vec![0xcc]], EvmVersion::Shanghai).unwrap();
+        assert_eq!(
+            hex::encode(assembled.bytecode),
+            "610004565b6100126100135f61010200aabbcc"
+        );
+    }
+
+    #[test]
+    fn link_immutables() {
+        let items = [
+            Item::PushImmutable("a".into()),
+            Item::PushImmutable("b".into()),
+            Item::PushImmutable("a".into()),
+        ];
+
+        let assembled = link(&items, &[], &[], EvmVersion::default()).unwrap();
+        assert_eq!(assembled.bytecode.len(), 99);
+        assert_eq!(assembled.immutables["a"], vec![1, 67]);
+        assert_eq!(assembled.immutables["b"], vec![34]);
+    }
+}
@@ -0,0 +1,171 @@
+/// A Yul object, which consists of code, nested objects and data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    pub name: String,
+    pub code: Block,
+    pub objects: Vec<Object>,
+    pub data: Vec<Data>,
+}
+
+/// A named data item of an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Data {
+    pub name: String,
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Statement {
+    Block(Block),
+    FunctionDefinition(FunctionDefinition),
+    VariableDeclaration {
+        names: Vec<String>,
+        value: Option<Expression>,
+    },
+    Assignment {
+        names: Vec<String>,
+        value: Expression,
+    },
+    If {
+        condition: Expression,
+        body: Block,
+    },
+    Switch {
+        expression: Expression,
+        cases: Vec<Case>,
+        default: Option<Block>,
+    },
+    ForLoop {
+        init: Block,
+        condition: Expression,
+        post: Block,
+        body: Block,
+    },
+    Break,
+    Continue,
+    Leave,
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub params: Vec<String>,
+    pub returns: Vec<String>,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub value: Literal,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    Literal(Literal),
+    Identifier(String),
+    Call { name: String, args: Vec<Expression> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal {
+    /// A number or boolean as a big-endian word.
+    Number([u8; 32]),
+    /// The unescaped bytes of a string literal.
+    String(Vec<u8>),
+}
+
+impl Literal {
+    /// Returns the value of the literal as a big-endian word. Strings are
+    /// left-aligned and must not be longer than 32 bytes.
+    pub fn to_word(&self) -> Option<[u8; 32]> {
+        match self {
+            Literal::Number(word) => Some(*word),
+            Literal::String(bytes) if bytes.len() <= 32 => {
+                let mut word = [0; 32];
+                word[..bytes.len()].copy_from_slice(bytes);
+                Some(word)
+            }
+            Literal::String(_) => None,
+        }
+    }
+}
+
+impl Block {
+    /// Returns the number of references to the variable `name` in the block,
+    /// including assignments. Functions defined in the block are skipped,
+    /// since they can't access outer variables.
+    pub fn count_references(&self, name: &str) -> usize {
+        count_in_statements(&self.statements, name)
+    }
+}
+
+pub(crate) fn count_in_statements(statements: &[Statement], name: &str) -> usize {
+    statements
+        .iter()
+        .map(|statement| count_in_statement(statement, name))
+        .sum()
+}
+
+fn count_in_statement(statement: &Statement, name: &str) -> usize {
+    match statement {
+        Statement::Block(block) => block.count_references(name),
+        Statement::FunctionDefinition(_)
+        | Statement::Break
+        | Statement::Continue
+        | Statement::Leave => 0,
+        Statement::VariableDeclaration { value, .. } => value
+            .as_ref()
+            .map_or(0, |value| value.count_references(name)),
+        Statement::Assignment { names, value } => {
+            names.iter().filter(|target| *target == name).count() + value.count_references(name)
+        }
+        Statement::If { condition, body } => {
+            condition.count_references(name) + body.count_references(name)
+        }
+        Statement::Switch {
+            expression,
+            cases,
+            default,
+        } => {
+            expression.count_references(name)
+                + cases
+                    .iter()
+                    .map(|case| case.body.count_references(name))
+                    .sum::<usize>()
+                + default
+                    .as_ref()
+                    .map_or(0, |block| block.count_references(name))
+        }
+        Statement::ForLoop {
+            init,
+            condition,
+            post,
+            body,
+        } => {
+            init.count_references(name)
+                + condition.count_references(name)
+                + post.count_references(name)
+                + body.count_references(name)
+        }
+        Statement::Expression(expression) => expression.count_references(name),
+    }
+}
+
+impl Expression {
+    pub(crate) fn count_references(&self, name: &str) -> usize {
+        match self {
+            Expression::Literal(_) => 0,
+            Expression::Identifier(ident) => usize::from(ident == name),
+            Expression::Call { args, .. } => {
+                args.iter().map(|arg| arg.count_references(name)).sum()
+            }
+        }
+    }
+}
@@ -0,0 +1,1123 @@
+//! Lowers the code of a Yul object to EVM instructions.
+//!
+//! Variables live in stack slots, which are accessed with `DUP` and `SWAP`.
+//! Every variable counts its remaining references, and the slot of a
+//! variable that won't be referenced again is reused for the next variable
+//! declaration, or popped if it's at the top of the stack.
+//!
+//! A function is called by pushing the return tag and the arguments, with the
+//! first argument on top, and jumping to the function. The function pushes
+//! its return variables, and when it returns it shuffles them to the position
+//! of the return tag and jumps back.
+//!
+//! If a variable of a function, or of the object code, ends up too deep in the
+//! stack to be reached, the code is lowered again with all of its variables,
+//! and the return tag, spilled to memory. Like solc, the memory is reserved by
+//! raising the value of `memoryguard`, so spilling requires the object to call
+//! it, always with the same literal. Since every spilled function has a fixed
+//! memory area, recursive functions can't be spilled.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::rc::Rc;
+
+use fe_common::EvmVersion;
+
+use crate::{
+    assembly::{DataRef, Item, Tag},
+    ast::{count_in_statements, Block, Expression, FunctionDefinition, Literal, Object, Statement},
+    opcode::{self, MAX_STACK_ACCESS},
+    AsmError,
+};
+
+/// The instructions of an object's code.
+pub struct Code {
+    pub items: Vec<Item>,
+    /// The name of each function with the index of its first item. Functions
+    /// are laid out in this order after the code of the object.
+    pub functions: Vec<(String, usize)>,
+}
+
+/// Lowers the code of `object`. `immutables` are the offsets of the
+/// immutables in the sub-objects, which `setimmutable` writes to.
+pub fn generate(
+    object: &Object,
+    immutables: &BTreeMap<String, Vec<usize>>,
+    evm_version: EvmVersion,
+) -> Result<Code, AsmError> {
+    let mut guards = vec![];
+    find_memory_guards(&object.code.statements, &mut guards);
+    let memory_guard = match guards.split_first() {
+        Some((first, rest)) if rest.iter().all(|guard| guard == first) => *first,
+        _ => None,
+    };
+
+    let mut codegen = CodeGen {
+        object,
+        immutables,
+        evm_version,
+        items: vec![],
+        tags: 0,
+        functions: vec![],
+        function_starts: vec![],
+        function_scopes: vec![],
+        pending: VecDeque::new(),
+        frame: Frame::default(),
+        memory_guard,
+        memory_guard_items: vec![],
+        spill_size: 0,
+        spilled: vec![],
+        calls: vec![],
+    };
+
+    codegen.object_code()?;
+    while let Some((id, definition, scopes)) = codegen.pending.pop_front() {
+        codegen.function(id, definition, scopes)?;
+    }
+    codegen.check_spilled_recursion()?;
+
+    // The memory of the spilled variables starts at the value that
+    // `memoryguard` is called with, and the code gets the memory above it.
+    if let Some(guard) = codegen.memory_guard {
+        let value = guard + codegen.spill_size as u64;
+        for idx in &codegen.memory_guard_items {
+            codegen.items[*idx] = Item::Push(trim_bytes(&value.to_be_bytes()));
+        }
+    }
+
+    Ok(Code {
+        items: codegen.items,
+        functions: codegen.function_starts,
+    })
+}
+
+/// Collects the argument of every call to `memoryguard` in `statements`,
+/// including the functions they define, or `None` for an argument that isn't
+/// a number literal that fits in 64 bits.
+fn find_memory_guards(statements: &[Statement], guards: &mut Vec<Option<u64>>) {
+    fn expression(expr: &Expression, guards: &mut Vec<Option<u64>>) {
+        if let Expression::Call { name, args } = expr {
+            if name == "memoryguard" {
+                guards.push(match &args[..] {
+                    [Expression::Literal(Literal::Number(word))]
+                        if word[..24].iter().all(|byte| *byte == 0) =>
+                    {
+                        Some(u64::from_be_bytes(word[24..].try_into().unwrap()))
+                    }
+                    _ => None,
+                });
+            }
+            for arg in args {
+                expression(arg, guards);
+            }
+        }
+    }
+
+    for statement in statements {
+        match statement {
+            Statement::Block(block) => find_memory_guards(&block.statements, guards),
+            Statement::FunctionDefinition(definition) => {
+                find_memory_guards(&definition.body.statements, guards)
+            }
+            Statement::VariableDeclaration { value, .. } => {
+                if let Some(value) = value {
+                    expression(value, guards);
+                }
+            }
+            Statement::Assignment { value, .. } | Statement::Expression(value) => {
+                expression(value, guards)
+            }
+            Statement::If { condition, body } => {
+                expression(condition, guards);
+                find_memory_guards(&body.statements, guards);
+            }
+            Statement::Switch {
+                expression: value,
+                cases,
+                default,
+            } => {
+                expression(value, guards);
+                for case in cases {
+                    find_memory_guards(&case.body.statements, guards);
+                }
+                if let Some(default) = default {
+                    find_memory_guards(&default.statements, guards);
+                }
+            }
+            Statement::ForLoop {
+                init,
+                condition,
+                post,
+                body,
+            } => {
+                find_memory_guards(&init.statements, guards);
+                expression(condition, guards);
+                find_memory_guards(&post.statements, guards);
+                find_memory_guards(&body.statements, guards);
+            }
+            Statement::Break | Statement::Continue | Statement::Leave => {}
+        }
+    }
+}
+
+type FunctionScope<'a> = Rc<HashMap<&'a str, usize>>;
+
+struct CodeGen<'a> {
+    object: &'a Object,
+    immutables: &'a BTreeMap<String, Vec<usize>>,
+    evm_version: EvmVersion,
+    items: Vec<Item>,
+    tags: usize,
+    functions: Vec<Function>,
+    function_starts: Vec<(String, usize)>,
+    /// The functions that are visible in the current block.
+    function_scopes: Vec<FunctionScope<'a>>,
+    /// The functions whose bodies still have to be lowered, with the functions
+    /// that are visible in them.
+    pending: VecDeque<(usize, &'a FunctionDefinition, Vec<FunctionScope<'a>>)>,
+    frame: Frame<'a>,
+    /// The value that `memoryguard` is called with, if variables can be
+    /// spilled to memory.
+    memory_guard: Option<u64>,
+    /// The items that push the value of `memoryguard`, which is only known
+    /// once all functions are lowered.
+    memory_guard_items: Vec<usize>,
+    /// The size of the memory of the variables spilled so far.
+    spill_size: usize,
+    /// The functions whose variables are spilled to memory.
+    spilled: Vec<usize>,
+    /// The calls between functions, by the ids of the caller and the callee.
+    calls: Vec<(usize, usize)>,
+}
+
+struct Function {
+    name: String,
+    tag: Tag,
+    params: usize,
+    returns: usize,
+}
+
+/// The lengths of the output of the code generator before a function, or the
+/// object code, is lowered, which are restored to lower it again.
+struct Checkpoint {
+    items: usize,
+    tags: usize,
+    functions: usize,
+    function_starts: usize,
+    pending: usize,
+    memory_guard_items: usize,
+    calls: usize,
+}
+
+/// The state of the function, or the object code, that is being lowered.
+#[derive(Default)]
+struct Frame<'a> {
+    function: Option<(usize, &'a str)>,
+    stack: Vec<Slot>,
+    variables: Vec<Variable>,
+    scopes: Vec<Scope<'a>>,
+    loops: Vec<Loop>,
+    returns: Vec<usize>,
+    /// Whether the variables are spilled to memory instead of living on the
+    /// stack.
+    spill: bool,
+    /// The memory address of the return tag, if it's spilled.
+    return_tag: Option<u64>,
+    /// Whether a variable was too deep in the stack to be reached.
+    too_deep: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    ReturnTag,
+    Variable(usize),
+    /// The slot of a variable that isn't referenced anymore.
+    Free,
+    /// An intermediate value of an expression.
+    Temp,
+}
+
+struct Variable {
+    /// The number of references that haven't been lowered yet.
+    refs: usize,
+    /// The number of enclosing loops that may still reference the variable.
+    pinned: usize,
+    slot: Option<usize>,
+    /// The memory address of the variable, if it's spilled.
+    memory: Option<u64>,
+}
+
+struct Scope<'a> {
+    variables: HashMap<&'a str, usize>,
+    /// The stack height when the scope was entered.
+    height: usize,
+}
+
+struct Loop {
+    continue_tag: Tag,
+    break_tag: Tag,
+    height: usize,
+}
+
+/// The code that follows a statement in its block, which may reference the
+/// variables it declares.
+#[derive(Default, Clone, Copy)]
+struct Following<'a> {
+    statements: &'a [Statement],
+    /// The condition, post block and body of a loop whose init block is being
+    /// lowered.
+    loop_parts: Option<(&'a Expression, &'a Block, &'a Block)>,
+}
+
+impl<'a> Following<'a> {
+    fn count_references(&self, name: &str) -> usize {
+        let in_loop = self.loop_parts.map_or(0, |(condition, post, body)| {
+            condition.count_references(name)
+                + post.count_references(name)
+                + body.count_references(name)
+        });
+        count_in_statements(self.statements, name) + in_loop
+    }
+}
+
+impl<'a> CodeGen<'a> {
+    fn new_tag(&mut self) -> Tag {
+        self.tags += 1;
+        self.tags
+    }
+
+    fn error(&self, message: String) -> AsmError {
+        match self.frame.function {
+            Some((_, function)) => AsmError(format!("{message} in function `{function}`")),
+            None => AsmError(format!("{message} in object `{}`", self.object.name)),
+        }
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            items: self.items.len(),
+            tags: self.tags,
+            functions: self.functions.len(),
+            function_starts: self.function_starts.len(),
+            pending: self.pending.len(),
+            memory_guard_items: self.memory_guard_items.len(),
+            calls: self.calls.len(),
+        }
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.items.truncate(checkpoint.items);
+        self.tags = checkpoint.tags;
+        self.functions.truncate(checkpoint.functions);
+        self.function_starts.truncate(checkpoint.function_starts);
+        self.pending.truncate(checkpoint.pending);
+        self.memory_guard_items
+            .truncate(checkpoint.memory_guard_items);
+        self.calls.truncate(checkpoint.calls);
+    }
+
+    /// Returns whether the code that failed with `result` should be lowered
+    /// again with its variables spilled to memory.
+    fn should_spill(&self, result: &Result<(), AsmError>) -> bool {
+        result.is_err() && self.frame.too_deep && self.memory_guard.is_some()
+    }
+
+    /// Lowers the code of the object, which is followed by its functions.
+    fn object_code(&mut self) -> Result<(), AsmError> {
+        let checkpoint = self.checkpoint();
+        let function_scopes = self.function_scopes.clone();
+        let mut result = self.block(&self.object.code, Following::default());
+        if self.should_spill(&result) {
+            self.restore(checkpoint);
+            self.function_scopes = function_scopes;
+            self.frame = Frame {
+                spill: true,
+                ..Frame::default()
+            };
+            result = self.block(&self.object.code, Following::default());
+        }
+        result?;
+        self.items.push(Item::Op(opcode::STOP));
+        Ok(())
+    }
+
+    fn function(
+        &mut self,
+        id: usize,
+        definition: &'a FunctionDefinition,
+        scopes: Vec<FunctionScope<'a>>,
+    ) -> Result<(), AsmError> {
+        let checkpoint = self.checkpoint();
+        let result = self.function_body(id, definition, scopes.clone(), false);
+        if self.should_spill(&result) {
+            self.restore(checkpoint);
+            self.spilled.push(id);
+            return self.function_body(id, definition, scopes, true);
+        }
+        result
+    }
+
+    fn function_body(
+        &mut self,
+        id: usize,
+        definition: &'a FunctionDefinition,
+        scopes: Vec<FunctionScope<'a>>,
+        spill: bool,
+    ) -> Result<(), AsmError> {
+        self.function_scopes = scopes;
+        self.frame = Frame {
+            function: Some((id, &definition.name)),
+            stack: vec![Slot::ReturnTag],
+            scopes: vec![Scope {
+                variables: HashMap::new(),
+                height: 1,
+            }],
+            spill,
+            ..Frame::default()
+        };
+        self.function_starts
+            .push((definition.name.clone(), self.items.len()));
+        self.items.push(Item::Tag(self.functions[id].tag));
+
+        if spill {
+            // The arguments are stored from the first one, which is on top,
+            // followed by the return tag.
+            for param in &definition.params {
+                self.frame.stack.push(Slot::Temp);
+                let refs = definition.body.count_references(param);
+                let var = self.declare(param, refs);
+                self.store(var);
+            }
+            let address = self.spill_address();
+            self.frame.return_tag = Some(address);
+            self.items
+                .push(Item::Push(trim_bytes(&address.to_be_bytes())));
+            self.items.push(Item::Op(opcode::MSTORE));
+            self.frame.stack.pop();
+        } else {
+            // The caller pushes the arguments in reverse order.
+            for param in definition.params.iter().rev() {
+                let refs = definition.body.count_references(param);
+                let var = self.declare(param, refs);
+                self.frame.variables[var].slot = Some(self.frame.stack.len());
+                self.frame.stack.push(Slot::Variable(var));
+            }
+        }
+        for ret in &definition.returns {
+            let refs = definition.body.count_references(ret);
+            let var = self.declare(ret, refs);
+            self.frame.returns.push(var);
+            if spill {
+                // The memory may hold a value from a previous call.
+                self.push(Item::Push(vec![]));
+                self.store(var);
+                continue;
+            }
+            // Return variables are kept until the function returns.
+            self.frame.variables[var].pinned = 1;
+            self.frame.variables[var].slot = Some(self.frame.stack.len());
+            self.items.push(Item::Push(vec![]));
+            self.frame.stack.push(Slot::Variable(var));
+        }
+        self.free_unused();
+
+        self.block(&definition.body, Following::default())?;
+        self.exit_function()
+    }
+
+    /// Fails if a spilled function may call itself, which would overwrite the
+    /// memory of its variables.
+    fn check_spilled_recursion(&self) -> Result<(), AsmError> {
+        for &function in &self.spilled {
+            let mut visited = BTreeSet::new();
+            let mut callers = vec![function];
+            while let Some(caller) = callers.pop() {
+                for &(_, callee) in self.calls.iter().filter(|(from, _)| *from == caller) {
+                    if callee == function {
+                        return Err(AsmError(format!(
+                            "stack too deep in function `{}`, whose variables can't be moved to memory since it's recursive",
+                            self.functions[function].name
+                        )));
+                    }
+                    if visited.insert(callee) {
+                        callers.push(callee);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the return variables to the position of the return tag, pops
+    /// everything else, and jumps to the return tag.
+    fn exit_function(&mut self) -> Result<(), AsmError> {
+        if let Some(return_tag) = self.frame.return_tag {
+            for _ in 0..self.frame.stack.len() {
+                self.items.push(Item::Op(opcode::POP));
+            }
+            let addresses: Vec<_> = self
+                .frame
+                .returns
+                .iter()
+                .map(|ret| self.frame.variables[*ret].memory.unwrap())
+                .chain([return_tag])
+                .collect();
+            for address in addresses {
+                self.items
+                    .push(Item::Push(trim_bytes(&address.to_be_bytes())));
+                self.items.push(Item::Op(opcode::MLOAD));
+            }
+            self.items.push(Item::Op(opcode::JUMP));
+            return Ok(());
+        }
+
+        let returns = &self.frame.returns;
+        let mut layout: Vec<_> = self
+            .frame
+            .stack
+            .iter()
+            .map(|slot| match slot {
+                Slot::ReturnTag => Some(returns.len()),
+                Slot::Variable(var) => returns.iter().position(|ret| ret == var),
+                Slot::Free | Slot::Temp => None,
+            })
+            .collect();
+
+        while let Some(&target) = layout.last() {
+            let top = layout.len() - 1;
+            match target {
+                None => {
+                    self.items.push(Item::Op(opcode::POP));
+                    layout.pop();
+                }
+                Some(target) if target == top => break,
+                Some(target) => {
+                    let depth = top - target;
+                    if depth > MAX_STACK_ACCESS {
+                        self.frame.too_deep = true;
+                        return Err(self.error(format!(
+                            "stack too deep: a return value is {depth} slots below the top of the stack"
+                        )));
+                    }
+                    self.items.push(Item::Op(opcode::SWAP1 + depth as u8 - 1));
+                    layout.swap(target, top);
+                }
+            }
+        }
+        debug_assert!(layout
+            .iter()
+            .enumerate()
+            .all(|(idx, target)| *target == Some(idx)));
+
+        self.items.push(Item::Op(opcode::JUMP));
+        Ok(())
+    }
+
+    fn block(&mut self, block: &'a Block, following: Following<'a>) -> Result<(), AsmError> {
+        self.enter_scope(block);
+        self.statements(&block.statements, following)?;
+        self.exit_scope();
+        Ok(())
+    }
+
+    /// Enters the scope of `block`, and makes the functions that are defined
+    /// in it visible.
+    fn enter_scope(&mut self, block: &'a Block) {
+        let mut functions = HashMap::new();
+        let mut definitions = vec![];
+        for statement in &block.statements {
+            if let Statement::FunctionDefinition(definition) = statement {
+                let id = self.functions.len();
+                let tag = self.new_tag();
+                self.functions.push(Function {
+                    name: definition.name.clone(),
+                    tag,
+                    params: definition.params.len(),
+                    returns: definition.returns.len(),
+                });
+                functions.insert(definition.name.as_str(), id);
+                definitions.push((id, definition));
+            }
+        }
+
+        self.function_scopes.push(Rc::new(functions));
+        for (id, definition) in definitions {
+            self.pending
+                .push_back((id, definition, self.function_scopes.clone()));
+        }
+
+        self.frame.scopes.push(Scope {
+            variables: HashMap::new(),
+            height: self.frame.stack.len(),
+        });
+    }
+
+    fn exit_scope(&mut self) {
+        self.function_scopes.pop();
+        let scope = self.frame.scopes.pop().unwrap();
+        for var in scope.variables.values() {
+            if let Some(slot) = self.frame.variables[*var].slot.take() {
+                self.frame.stack[slot] = Slot::Free;
+            }
+        }
+        while self.frame.stack.len() > scope.height {
+            self.pop();
+        }
+    }
+
+    fn statements(
+        &mut self,
+        statements: &'a [Statement],
+        following: Following<'a>,
+    ) -> Result<(), AsmError> {
+        for (idx, statement) in statements.iter().enumerate() {
+            let following = Following {
+                statements: &statements[idx + 1..],
+                ..following
+            };
+            self.statement(statement, following)?;
+            self.free_unused();
+        }
+        Ok(())
+    }
+
+    fn statement(
+        &mut self,
+        statement: &'a Statement,
+        following: Following<'a>,
+    ) -> Result<(), AsmError> {
+        match statement {
+            Statement::Block(block) => self.block(block, Following::default())?,
+
+            // Functions are lowered after the code of the object.
+            Statement::FunctionDefinition(_) => {}
+
+            Statement::VariableDeclaration { names, value } => {
+                match value {
+                    Some(value) => self.expression_values(value, names.len())?,
+                    None => {
+                        for _ in names {
+                            self.push(Item::Push(vec![]));
+                        }
+                    }
+                }
+
+                let vars: Vec<_> = names
+                    .iter()
+                    .map(|name| self.declare(name, following.count_references(name)))
+                    .collect();
+                if self.frame.spill {
+                    for var in vars.into_iter().rev() {
+                        self.store(var);
+                    }
+                } else if let [var] = vars[..] {
+                    self.assign_slot(var);
+                } else {
+                    let height = self.frame.stack.len();
+                    for (idx, var) in vars.into_iter().enumerate() {
+                        let slot = height - names.len() + idx;
+                        self.frame.stack[slot] = Slot::Variable(var);
+                        self.frame.variables[var].slot = Some(slot);
+                    }
+                }
+            }
+
+            Statement::Assignment { names, value } => {
+                self.expression_values(value, names.len())?;
+                for name in names.iter().rev() {
+                    let var = self.lookup_variable(name)?;
+                    if self.frame.spill {
+                        self.store(var);
+                        continue;
+                    }
+                    let slot = self.frame.variables[var].slot.unwrap();
+                    self.swap_top(slot, name)?;
+                    self.pop();
+                }
+            }
+
+            Statement::If { condition, body } => {
+                let end = self.new_tag();
+                self.expression_values(condition, 1)?;
+                self.items.push(Item::Op(opcode::ISZERO));
+                self.items.push(Item::PushTag(end));
+                self.items.push(Item::Op(opcode::JUMPI));
+                self.frame.stack.pop();
+                self.block(body, Following::default())?;
+                self.items.push(Item::Tag(end));
+            }
+
+            Statement::Switch {
+                expression,
+                cases,
+                default,
+            } => {
+                let end = self.new_tag();
+                self.expression_values(expression, 1)?;
+
+                let mut case_tags = vec![];
+                for case in cases {
+                    let word = match case.value.to_word() {
+                        Some(word) => word,
+                        None => return Err(self.error("string literal is too long".into())),
+                    };
+                    let tag = self.new_tag();
+                    self.items.push(Item::Op(opcode::DUP1));
+                    self.items.push(Item::Push(trim_bytes(&word)));
+                    self.items.push(Item::Op(opcode::EQ));
+                    self.items.push(Item::PushTag(tag));
+                    self.items.push(Item::Op(opcode::JUMPI));
+                    case_tags.push(tag);
+                }
+
+                self.pop();
+                if let Some(default) = default {
+                    self.block(default, Following::default())?;
+                }
+                self.items.push(Item::PushTag(end));
+                self.items.push(Item::Op(opcode::JUMP));
+
+                for (case, tag) in cases.iter().zip(case_tags) {
+                    self.items.push(Item::Tag(tag));
+                    self.frame.stack.push(Slot::Temp);
+                    self.pop();
+                    self.block(&case.body, Following::default())?;
+                    self.items.push(Item::PushTag(end));
+                    self.items.push(Item::Op(opcode::JUMP));
+                }
+                self.items.push(Item::Tag(end));
+            }
+
+            Statement::ForLoop {
+                init,
+                condition,
+                post,
+                body,
+            } => {
+                self.enter_scope(init);
+                self.statements(
+                    &init.statements,
+                    Following {
+                        statements: &[],
+                        loop_parts: Some((condition, post, body)),
+                    },
+                )?;
+
+                // The variables that are referenced in the loop must be kept
+                // until the loop ends, since it may run again.
+                let pinned: Vec<_> = (0..self.frame.variables.len())
+                    .filter(|var| self.frame.variables[*var].slot.is_some())
+                    .collect();
+                for var in &pinned {
+                    self.frame.variables[*var].pinned += 1;
+                }
+
+                let start = self.new_tag();
+                let continue_tag = self.new_tag();
+                let break_tag = self.new_tag();
+                self.items.push(Item::Tag(start));
+                self.expression_values(condition, 1)?;
+                self.items.push(Item::Op(opcode::ISZERO));
+                self.items.push(Item::PushTag(break_tag));
+                self.items.push(Item::Op(opcode::JUMPI));
+                self.frame.stack.pop();
+
+                self.frame.loops.push(Loop {
+                    continue_tag,
+                    break_tag,
+                    height: self.frame.stack.len(),
+                });
+                self.block(body, Following::default())?;
+                self.frame.loops.pop();
+
+                self.items.push(Item::Tag(continue_tag));
+                self.block(post, Following::default())?;
+                self.items.push(Item::PushTag(start));
+                self.items.push(Item::Op(opcode::JUMP));
+                self.items.push(Item::Tag(break_tag));
+
+                for var in pinned {
+                    self.frame.variables[var].pinned -= 1;
+                }
+                self.free_unused();
+                self.exit_scope();
+            }
+
+            Statement::Break | Statement::Continue => {
+                let (height, tag) = match self.frame.loops.last() {
+                    Some(Loop {
+                        height,
+                        continue_tag,
+                        break_tag,
+                    }) => (
+                        *height,
+                        if matches!(statement, Statement::Break) {
+                            *break_tag
+                        } else {
+                            *continue_tag
+                        },
+                    ),
+                    None => {
+                        return Err(self.error("`break` or `continue` outside of a loop".into()))
+                    }
+                };
+                for _ in height..self.frame.stack.len() {
+                    self.items.push(Item::Op(opcode::POP));
+                }
+                self.items.push(Item::PushTag(tag));
+                self.items.push(Item::Op(opcode::JUMP));
+            }
+
+            Statement::Leave => {
+                if self.frame.function.is_none() {
+                    return Err(self.error("`leave` outside of a function".into()));
+                }
+                self.exit_function()?;
+            }
+
+            Statement::Expression(expression) => {
+                let values = self.expression(expression)?;
+                for _ in 0..values {
+                    self.pop();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lowers `expression`, which has to result in `count` values.
+    fn expression_values(
+        &mut self,
+        expression: &'a Expression,
+        count: usize,
+    ) -> Result<(), AsmError> {
+        let values = self.expression(expression)?;
+        if values == count {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {count} values, found {values}")))
+        }
+    }
+
+    /// Lowers `expression` and returns the number of values it pushes.
+    fn expression(&mut self, expression: &'a Expression) -> Result<usize, AsmError> {
+        match expression {
+            Expression::Literal(literal) => {
+                let word = match literal.to_word() {
+                    Some(word) => word,
+                    None => return Err(self.error("string literal is too long".into())),
+                };
+                self.push(Item::Push(trim_bytes(&word)));
+                Ok(1)
+            }
+
+            Expression::Identifier(name) => {
+                let var = self.lookup_variable(name)?;
+                if let Some(address) = self.frame.variables[var].memory {
+                    self.push(Item::Push(trim_bytes(&address.to_be_bytes())));
+                    self.items.push(Item::Op(opcode::MLOAD));
+                    return Ok(1);
+                }
+                let slot = self.frame.variables[var].slot.unwrap();
+                let depth = self.frame.stack.len() - slot;
+                if depth > MAX_STACK_ACCESS {
+                    return Err(self.stack_too_deep(name, depth));
+                }
+                self.push(Item::Op(opcode::DUP1 + depth as u8 - 1));
+                Ok(1)
+            }
+
+            Expression::Call { name, args } => self.call(name, args),
+        }
+    }
+
+    fn call(&mut self, name: &str, args: &'a [Expression]) -> Result<usize, AsmError> {
+        match name {
+            "datasize" | "dataoffset" => {
+                let data_ref = self.data_ref(self.literal_arg(name, args, 0, 1)?)?;
+                self.push(if name == "datasize" {
+                    Item::PushDataSize(data_ref)
+                } else {
+                    Item::PushDataOffset(data_ref)
+                });
+                return Ok(1);
+            }
+            "loadimmutable" => {
+                let name = self.literal_arg(name, args, 0, 1)?;
+                self.push(Item::PushImmutable(
+                    String::from_utf8_lossy(name).into_owned(),
+                ));
+                return Ok(1);
+            }
+            "setimmutable" => {
+                let immutable = self.literal_arg(name, args, 1, 3)?;
+                let offsets = self
+                    .immutables
+                    .get(String::from_utf8_lossy(immutable).as_ref())
+                    .cloned()
+                    .unwrap_or_default();
+                self.expression_values(&args[2], 1)?;
+                self.expression_values(&args[0], 1)?;
+                // Stack: value, offset
+                for offset in offsets {
+                    self.items.push(Item::Op(opcode::DUP1 + 1));
+                    self.items.push(Item::Op(opcode::DUP1 + 1));
+                    self.items
+                        .push(Item::Push(trim_bytes(&offset.to_be_bytes())));
+                    self.items.push(Item::Op(opcode::ADD));
+                    self.items.push(Item::Op(opcode::MSTORE));
+                }
+                self.pop();
+                self.pop();
+                return Ok(0);
+            }
+            "memoryguard" => {
+                self.check_arg_count(name, args, 1)?;
+                if self.memory_guard.is_none() {
+                    return self.expression(&args[0]);
+                }
+                // The value is raised by the size of the spilled variables.
+                self.memory_guard_items.push(self.items.len());
+                self.push(Item::Push(vec![]));
+                return Ok(1);
+            }
+            _ => {}
+        }
+
+        if let Some(builtin) = opcode::builtin(name) {
+            if !opcode::is_available(name, self.evm_version) {
+                return Err(self.error(format!(
+                    "`{name}` is not available in EVM version `{}`",
+                    self.evm_version
+                )));
+            }
+            self.check_arg_count(name, args, builtin.args)?;
+            for arg in args.iter().rev() {
+                self.expression_values(arg, 1)?;
+            }
+            self.items.push(Item::Op(builtin.opcode));
+            self.frame
+                .stack
+                .truncate(self.frame.stack.len() - builtin.args);
+            for _ in 0..builtin.returns {
+                self.frame.stack.push(Slot::Temp);
+            }
+            return Ok(builtin.returns);
+        }
+
+        let id = match self
+            .function_scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+        {
+            Some(id) => *id,
+            None => return Err(self.error(format!("unknown function `{name}`"))),
+        };
+        let (tag, params, returns) = {
+            let function = &self.functions[id];
+            (function.tag, function.params, function.returns)
+        };
+        self.check_arg_count(name, args, params)?;
+        if let Some((caller, _)) = self.frame.function {
+            self.calls.push((caller, id));
+        }
+
+        let return_tag = self.new_tag();
+        self.push(Item::PushTag(return_tag));
+        for arg in args.iter().rev() {
+            self.expression_values(arg, 1)?;
+        }
+        self.items.push(Item::PushTag(tag));
+        self.items.push(Item::Op(opcode::JUMP));
+        self.items.push(Item::Tag(return_tag));
+
+        self.frame
+            .stack
+            .truncate(self.frame.stack.len() - params - 1);
+        for _ in 0..returns {
+            self.frame.stack.push(Slot::Temp);
+        }
+        Ok(returns)
+    }
+
+    fn check_arg_count(
+        &self,
+        name: &str,
+        args: &[Expression],
+        count: usize,
+    ) -> Result<(), AsmError> {
+        if args.len() == count {
+            Ok(())
+        } else {
+            Err(self.error(format!(
+                "`{name}` takes {count} arguments, but {} were given",
+                args.len()
+            )))
+        }
+    }
+
+    /// Returns the string literal at `idx` of the `count` arguments of the
+    /// builtin `name`.
+    fn literal_arg(
+        &self,
+        name: &str,
+        args: &'a [Expression],
+        idx: usize,
+        count: usize,
+    ) -> Result<&'a [u8], AsmError> {
+        self.check_arg_count(name, args, count)?;
+        match &args[idx] {
+            Expression::Literal(Literal::String(value)) => Ok(value),
+            _ => Err(self.error(format!(
+                "argument {} of `{name}` must be a literal",
+                idx + 1
+            ))),
+        }
+    }
+
+    fn data_ref(&self, name: &[u8]) -> Result<DataRef, AsmError> {
+        let name = String::from_utf8_lossy(name);
+        if name == self.object.name {
+            Ok(DataRef::Object)
+        } else if let Some(idx) = self.object.objects.iter().position(|o| o.name == name) {
+            Ok(DataRef::Sub(idx))
+        } else if let Some(idx) = self.object.data.iter().position(|d| d.name == name) {
+            Ok(DataRef::Data(idx))
+        } else {
+            Err(self.error(format!("unknown object or data `{name}`")))
+        }
+    }
+
+    fn declare(&mut self, name: &'a str, refs: usize) -> usize {
+        let var = self.frame.variables.len();
+        let memory = self.frame.spill.then(|| self.spill_address());
+        self.frame.variables.push(Variable {
+            refs,
+            pinned: 0,
+            slot: None,
+            memory,
+        });
+        self.frame
+            .scopes
+            .last_mut()
+            .unwrap()
+            .variables
+            .insert(name, var);
+        var
+    }
+
+    /// Makes the value on top of the stack the value of `var`. The value is
+    /// moved to a free slot if there is one that can be reached.
+    fn assign_slot(&mut self, var: usize) {
+        let top = self.frame.stack.len() - 1;
+        let free = (top.saturating_sub(MAX_STACK_ACCESS)..top)
+            .rev()
+            .find(|slot| self.frame.stack[*slot] == Slot::Free);
+
+        let slot = match free {
+            Some(slot) => {
+                self.items
+                    .push(Item::Op(opcode::SWAP1 + (top - slot) as u8 - 1));
+                self.pop();
+                slot
+            }
+            None => top,
+        };
+        self.frame.stack[slot] = Slot::Variable(var);
+        self.frame.variables[var].slot = Some(slot);
+    }
+
+    /// Looks up the variable `name` and counts the reference.
+    fn lookup_variable(&mut self, name: &str) -> Result<usize, AsmError> {
+        let var = self
+            .frame
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.variables.get(name).copied());
+        match var {
+            Some(var)
+                if self.frame.variables[var].slot.is_some()
+                    || self.frame.variables[var].memory.is_some() =>
+            {
+                let var_data = &mut self.frame.variables[var];
+                var_data.refs = var_data.refs.saturating_sub(1);
+                Ok(var)
+            }
+            _ => Err(self.error(format!("unknown variable `{name}`"))),
+        }
+    }
+
+    /// Frees the slots of the variables that aren't referenced anymore, and
+    /// pops the free slots of the current scope from the top of the stack.
+    fn free_unused(&mut self) {
+        for var in &mut self.frame.variables {
+            if var.refs == 0 && var.pinned == 0 {
+                if let Some(slot) = var.slot.take() {
+                    self.frame.stack[slot] = Slot::Free;
+                }
+            }
+        }
+
+        let height = self.frame.scopes.last().map_or(0, |scope| scope.height);
+        while self.frame.stack.len() > height && self.frame.stack.last() == Some(&Slot::Free) {
+            self.pop();
+        }
+    }
+
+    /// Swaps the value on top of the stack with `slot`.
+    fn swap_top(&mut self, slot: usize, name: &str) -> Result<(), AsmError> {
+        let depth = self.frame.stack.len() - 1 - slot;
+        if depth > MAX_STACK_ACCESS {
+            return Err(self.stack_too_deep(name, depth));
+        }
+        self.items.push(Item::Op(opcode::SWAP1 + depth as u8 - 1));
+        Ok(())
+    }
+
+    /// Reserves a word of memory for a spilled variable, and returns its
+    /// address.
+    fn spill_address(&mut self) -> u64 {
+        let address = self.memory_guard.unwrap() + self.spill_size as u64;
+        self.spill_size += 32;
+        address
+    }
+
+    /// Stores the value on top of the stack in the memory of the spilled
+    /// variable `var`.
+    fn store(&mut self, var: usize) {
+        let address = self.frame.variables[var].memory.unwrap();
+        self.items
+            .push(Item::Push(trim_bytes(&address.to_be_bytes())));
+        self.items.push(Item::Op(opcode::MSTORE));
+        self.frame.stack.pop();
+    }
+
+    fn stack_too_deep(&mut self, name: &str, depth: usize) -> AsmError {
+        self.frame.too_deep = true;
+        self.error(format!(
+            "stack too deep: variable `{name}` is {depth} slots below the top of the stack, but only {MAX_STACK_ACCESS} can be reached"
+        ))
+    }
+
+    /// Adds an instruction that pushes one value.
+    fn push(&mut self, item: Item) {
+        self.items.push(item);
+        self.frame.stack.push(Slot::Temp);
+    }
+
+    fn pop(&mut self) {
+        self.items.push(Item::Op(opcode::POP));
+        self.frame.stack.pop();
+    }
+}
+
+/// Strips the leading zeros of a big-endian value.
+fn trim_bytes(bytes: &[u8]) -> Vec<u8> {
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len());
+    bytes[start..].to_vec()
+}
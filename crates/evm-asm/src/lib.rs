@@ -0,0 +1,132 @@
+//! An assembler that compiles Yul objects to EVM bytecode without solc.
+
+use std::fmt;
+
+use fe_common::EvmVersion;
+
+mod assembly;
+pub mod ast;
+mod codegen;
+mod opcode;
+mod parser;
+
+pub use parser::parse_object;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError(pub String);
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+pub struct ContractBytecode {
+    pub bytecode: String,
+    pub runtime_bytecode: String,
+}
+
+/// Compiles a single Yul contract to bytecode.
+///
+/// Like solc, the runtime bytecode is the bytecode of the only sub-object of
+/// the contract, and is empty if it doesn't have exactly one.
+pub fn compile_single_contract(
+    name: &str,
+    yul_src: &str,
+    evm_version: EvmVersion,
+    verify_runtime_bytecode: bool,
+) -> Result<ContractBytecode, AsmError> {
+    let object = parse_object(yul_src)?;
+    if object.name != name {
+        return Err(AsmError(format!(
+            "expected an object named `{name}`, found `{}`",
+            object.name
+        )));
+    }
+
+    let runtime_bytecode = match &object.objects[..] {
+        [runtime] => hex::encode(assemble(runtime, evm_version)?),
+        _ if verify_runtime_bytecode => {
+            return Err(AsmError(format!(
+                "object `{name}` has no single sub-object to deploy"
+            )))
+        }
+        _ => String::new(),
+    };
+
+    Ok(ContractBytecode {
+        bytecode: hex::encode(assemble(&object, evm_version)?),
+        runtime_bytecode,
+    })
+}
+
+/// Assembles a Yul object, with its sub-objects and data appended to its code.
+/// The builtins that `evm_version` doesn't support are rejected.
+pub fn assemble(object: &ast::Object, evm_version: EvmVersion) -> Result<Vec<u8>, AsmError> {
+    assemble_object(object, evm_version).map(|assembled| assembled.bytecode)
+}
+
+/// Returns the size of the code of each function of `object`, largest first.
+/// The sizes don't include the sub-objects, and since the native assembler
+/// doesn't optimize, they only approximate the sizes of the code of solc.
+pub fn function_sizes(
+    object: &ast::Object,
+    evm_version: EvmVersion,
+) -> Result<Vec<(String, usize)>, AsmError> {
+    let (_, code) = generate(object, evm_version)?;
+    let ends = code
+        .functions
+        .iter()
+        .skip(1)
+        .map(|(_, start)| *start)
+        .chain([code.items.len()]);
+    let mut sizes: Vec<_> = code
+        .functions
+        .iter()
+        .zip(ends)
+        .map(|((name, start), end)| {
+            let size: usize = code.items[*start..end]
+                .iter()
+                .map(|item| item.size(evm_version))
+                .sum();
+            (name.clone(), size)
+        })
+        .collect();
+    sizes.sort_by(|(_, a), (_, b)| b.cmp(a));
+    Ok(sizes)
+}
+
+fn assemble_object(
+    object: &ast::Object,
+    evm_version: EvmVersion,
+) -> Result<assembly::Assembled, AsmError> {
+    let (subs, code) = generate(object, evm_version)?;
+    let data: Vec<_> = object.data.iter().map(|data| data.value.clone()).collect();
+    assembly::link(&code.items, &subs, &data, evm_version)
+}
+
+/// Assembles the sub-objects of `object` and lowers its code.
+fn generate(
+    object: &ast::Object,
+    evm_version: EvmVersion,
+) -> Result<(Vec<assembly::Assembled>, codegen::Code), AsmError> {
+    let subs = object
+        .objects
+        .iter()
+        .map(|sub| assemble_object(sub, evm_version))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // `setimmutable` writes to the immutables of the sub-objects, which the
+    // deploy code copies to memory.
+    let mut immutables = std::collections::BTreeMap::<_, Vec<_>>::new();
+    for sub in &subs {
+        for (name, offsets) in &sub.immutables {
+            immutables.entry(name.clone()).or_default().extend(offsets);
+        }
+    }
+
+    let code = codegen::generate(object, &immutables, evm_version)?;
+    Ok((subs, code))
+}
@@ -0,0 +1,677 @@
+use crate::{
+    ast::{Block, Case, Data, Expression, FunctionDefinition, Literal, Object, Statement},
+    AsmError,
+};
+
+/// Parses the source of a Yul object. A source that only consists of a block
+/// is treated as an object named `object`, like solc does.
+pub fn parse_object(src: &str) -> Result<Object, AsmError> {
+    let mut parser = Parser::new(src)?;
+    let object = if parser.peek_keyword("object") {
+        parser.object()?
+    } else {
+        Object {
+            name: "object".to_string(),
+            code: parser.block()?,
+            objects: vec![],
+            data: vec![],
+        }
+    };
+
+    match parser.peek() {
+        None => Ok(object),
+        Some(_) => Err(parser.unexpected("end of input")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    Assign,
+    Arrow,
+    Ident(String),
+    Number([u8; 32]),
+    String(Vec<u8>),
+    HexString(Vec<u8>),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::LBrace => write!(f, "`{{`"),
+            Token::RBrace => write!(f, "`}}`"),
+            Token::LParen => write!(f, "`(`"),
+            Token::RParen => write!(f, "`)`"),
+            Token::Comma => write!(f, "`,`"),
+            Token::Colon => write!(f, "`:`"),
+            Token::Assign => write!(f, "`:=`"),
+            Token::Arrow => write!(f, "`->`"),
+            Token::Ident(name) => write!(f, "`{name}`"),
+            Token::Number(_) => write!(f, "number"),
+            Token::String(_) => write!(f, "string"),
+            Token::HexString(_) => write!(f, "hex string"),
+        }
+    }
+}
+
+struct Parser {
+    /// The tokens in reverse order, each with the line and column it starts
+    /// at.
+    tokens: Vec<(Token, usize, usize)>,
+    /// The position of the end of the input.
+    end: (usize, usize),
+}
+
+impl Parser {
+    fn new(src: &str) -> Result<Self, AsmError> {
+        let mut tokens = Lexer::new(src).tokenize()?;
+        let end = line_col(src, src.len());
+        tokens.reverse();
+        Ok(Self { tokens, end })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.last().map(|(token, ..)| token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.tokens.pop();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), AsmError> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&token.to_string()))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), AsmError> {
+        if self.peek_keyword(keyword) {
+            self.tokens.pop();
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{keyword}`")))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> AsmError {
+        match self.tokens.last() {
+            Some((token, line, col)) => {
+                AsmError(format!("{line}:{col}: expected {expected}, found {token}"))
+            }
+            None => AsmError(format!(
+                "{}:{}: expected {expected}, found end of input",
+                self.end.0, self.end.1
+            )),
+        }
+    }
+
+    fn object(&mut self) -> Result<Object, AsmError> {
+        self.expect_keyword("object")?;
+        let name = self.name()?;
+        self.expect(Token::LBrace)?;
+        self.expect_keyword("code")?;
+        let code = self.block()?;
+
+        let mut objects = vec![];
+        let mut data = vec![];
+        loop {
+            if self.peek_keyword("object") {
+                objects.push(self.object()?);
+            } else if self.peek_keyword("data") {
+                self.tokens.pop();
+                let name = self.name()?;
+                let value = match self.peek() {
+                    Some(Token::String(value) | Token::HexString(value)) => value.clone(),
+                    _ => return Err(self.unexpected("string or hex string")),
+                };
+                self.tokens.pop();
+                data.push(Data { name, value });
+            } else {
+                self.expect(Token::RBrace)?;
+                break;
+            }
+        }
+
+        Ok(Object {
+            name,
+            code,
+            objects,
+            data,
+        })
+    }
+
+    /// Parses the string literal that names an object or data item.
+    fn name(&mut self) -> Result<String, AsmError> {
+        match self.peek() {
+            Some(Token::String(name)) => {
+                let name = String::from_utf8_lossy(name).into_owned();
+                self.tokens.pop();
+                Ok(name)
+            }
+            _ => Err(self.unexpected("name")),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, AsmError> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let ident = ident.clone();
+                self.tokens.pop();
+                // Skip a type annotation, e.g. `x:u256`.
+                if self.eat(&Token::Colon) {
+                    self.ident()?;
+                }
+                Ok(ident)
+            }
+            _ => Err(self.unexpected("identifier")),
+        }
+    }
+
+    fn ident_list(&mut self) -> Result<Vec<String>, AsmError> {
+        let mut idents = vec![self.ident()?];
+        while self.eat(&Token::Comma) {
+            idents.push(self.ident()?);
+        }
+        Ok(idents)
+    }
+
+    fn block(&mut self) -> Result<Block, AsmError> {
+        self.expect(Token::LBrace)?;
+        let mut statements = vec![];
+        while !self.eat(&Token::RBrace) {
+            statements.push(self.statement()?);
+        }
+        Ok(Block { statements })
+    }
+
+    fn statement(&mut self) -> Result<Statement, AsmError> {
+        let keyword = match self.peek() {
+            Some(Token::LBrace) => return Ok(Statement::Block(self.block()?)),
+            Some(Token::Ident(ident)) => ident.clone(),
+            _ => return Err(self.unexpected("statement")),
+        };
+
+        let statement = match keyword.as_str() {
+            "function" => {
+                self.tokens.pop();
+                let name = self.ident()?;
+                self.expect(Token::LParen)?;
+                let params = if self.eat(&Token::RParen) {
+                    vec![]
+                } else {
+                    let params = self.ident_list()?;
+                    self.expect(Token::RParen)?;
+                    params
+                };
+                let returns = if self.eat(&Token::Arrow) {
+                    self.ident_list()?
+                } else {
+                    vec![]
+                };
+                Statement::FunctionDefinition(FunctionDefinition {
+                    name,
+                    params,
+                    returns,
+                    body: self.block()?,
+                })
+            }
+            "let" => {
+                self.tokens.pop();
+                let names = self.ident_list()?;
+                let value = if self.eat(&Token::Assign) {
+                    Some(self.expression()?)
+                } else {
+                    None
+                };
+                Statement::VariableDeclaration { names, value }
+            }
+            "if" => {
+                self.tokens.pop();
+                Statement::If {
+                    condition: self.expression()?,
+                    body: self.block()?,
+                }
+            }
+            "switch" => {
+                self.tokens.pop();
+                let expression = self.expression()?;
+                let mut cases = vec![];
+                while self.peek_keyword("case") {
+                    self.tokens.pop();
+                    let value = self.literal()?;
+                    cases.push(Case {
+                        value,
+                        body: self.block()?,
+                    });
+                }
+                let default = if self.peek_keyword("default") {
+                    self.tokens.pop();
+                    Some(self.block()?)
+                } else {
+                    None
+                };
+                if cases.is_empty() && default.is_none() {
+                    return Err(self.unexpected("`case` or `default`"));
+                }
+                Statement::Switch {
+                    expression,
+                    cases,
+                    default,
+                }
+            }
+            "for" => {
+                self.tokens.pop();
+                Statement::ForLoop {
+                    init: self.block()?,
+                    condition: self.expression()?,
+                    post: self.block()?,
+                    body: self.block()?,
+                }
+            }
+            "break" => {
+                self.tokens.pop();
+                Statement::Break
+            }
+            "continue" => {
+                self.tokens.pop();
+                Statement::Continue
+            }
+            "leave" => {
+                self.tokens.pop();
+                Statement::Leave
+            }
+            _ => {
+                let ident = self.ident()?;
+                match self.peek() {
+                    Some(Token::LParen) => {
+                        let args = self.args()?;
+                        Statement::Expression(Expression::Call { name: ident, args })
+                    }
+                    Some(Token::Comma | Token::Assign) => {
+                        let mut names = vec![ident];
+                        while self.eat(&Token::Comma) {
+                            names.push(self.ident()?);
+                        }
+                        self.expect(Token::Assign)?;
+                        Statement::Assignment {
+                            names,
+                            value: self.expression()?,
+                        }
+                    }
+                    _ => return Err(self.unexpected("`(` or `:=`")),
+                }
+            }
+        };
+
+        Ok(statement)
+    }
+
+    fn expression(&mut self) -> Result<Expression, AsmError> {
+        match self.peek() {
+            Some(Token::Ident(ident)) if ident != "true" && ident != "false" => {
+                let ident = ident.clone();
+                self.tokens.pop();
+                if self.peek() == Some(&Token::LParen) {
+                    Ok(Expression::Call {
+                        name: ident,
+                        args: self.args()?,
+                    })
+                } else {
+                    Ok(Expression::Identifier(ident))
+                }
+            }
+            _ => Ok(Expression::Literal(self.literal()?)),
+        }
+    }
+
+    fn args(&mut self) -> Result<Vec<Expression>, AsmError> {
+        self.expect(Token::LParen)?;
+        let mut args = vec![];
+        if !self.eat(&Token::RParen) {
+            args.push(self.expression()?);
+            while self.eat(&Token::Comma) {
+                args.push(self.expression()?);
+            }
+            self.expect(Token::RParen)?;
+        }
+        Ok(args)
+    }
+
+    fn literal(&mut self) -> Result<Literal, AsmError> {
+        let literal = match self.peek() {
+            Some(Token::Number(word)) => Literal::Number(*word),
+            Some(Token::String(bytes)) => Literal::String(bytes.clone()),
+            Some(Token::Ident(ident)) if ident == "true" || ident == "false" => {
+                let mut word = [0; 32];
+                word[31] = u8::from(ident == "true");
+                Literal::Number(word)
+            }
+            _ => return Err(self.unexpected("literal")),
+        };
+        self.tokens.pop();
+
+        // Skip a type annotation, e.g. `1:u256`.
+        if self.eat(&Token::Colon) {
+            self.ident()?;
+        }
+        Ok(literal)
+    }
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn error(&self, pos: usize, message: &str) -> AsmError {
+        let (line, col) = line_col(self.src, pos);
+        AsmError(format!("{line}:{col}: {message}"))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, usize, usize)>, AsmError> {
+        let mut tokens = vec![];
+        loop {
+            self.skip_trivia()?;
+            let start = self.pos;
+            let rest = self.rest();
+            let c = match rest.chars().next() {
+                Some(c) => c,
+                None => return Ok(tokens),
+            };
+
+            let token = if rest.starts_with(":=") {
+                self.pos += 2;
+                Token::Assign
+            } else if rest.starts_with("->") {
+                self.pos += 2;
+                Token::Arrow
+            } else if rest.starts_with("hex\"") || rest.starts_with("hex'") {
+                self.pos += 3;
+                let value = self.string()?;
+                let digits: String = String::from_utf8_lossy(&value)
+                    .chars()
+                    .filter(|c| *c != '_')
+                    .collect();
+                Token::HexString(
+                    hex::decode(digits).map_err(|_| self.error(start, "invalid hex string"))?,
+                )
+            } else {
+                match c {
+                    '{' => self.single(Token::LBrace),
+                    '}' => self.single(Token::RBrace),
+                    '(' => self.single(Token::LParen),
+                    ')' => self.single(Token::RParen),
+                    ',' => self.single(Token::Comma),
+                    ':' => self.single(Token::Colon),
+                    '"' | '\'' => Token::String(self.string()?),
+                    '0'..='9' => self.number()?,
+                    c if is_ident_start(c) => {
+                        let len = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+                        self.pos += len;
+                        Token::Ident(rest[..len].to_string())
+                    }
+                    c => return Err(self.error(start, &format!("unexpected character `{c}`"))),
+                }
+            };
+
+            let (line, col) = line_col(self.src, start);
+            tokens.push((token, line, col));
+        }
+    }
+
+    fn single(&mut self, token: Token) -> Token {
+        self.pos += 1;
+        token
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), AsmError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                match trimmed.find("*/") {
+                    Some(end) => self.pos += end + 2,
+                    None => return Err(self.error(self.pos, "unterminated comment")),
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Token, AsmError> {
+        let start = self.pos;
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        self.pos += len;
+
+        let literal = &rest[..len];
+        let word = match literal.strip_prefix("0x") {
+            Some(digits) => parse_word(digits, 16),
+            None => parse_word(literal, 10),
+        };
+        word.map(Token::Number)
+            .ok_or_else(|| self.error(start, &format!("invalid number `{literal}`")))
+    }
+
+    /// Lexes a string literal and returns its unescaped bytes.
+    fn string(&mut self) -> Result<Vec<u8>, AsmError> {
+        let start = self.pos;
+        let mut chars = self.rest().char_indices();
+        let (_, quote) = chars.next().unwrap();
+        let mut bytes = vec![];
+        let mut buf = [0; 4];
+
+        while let Some((idx, c)) = chars.next() {
+            if c == quote {
+                self.pos += idx + 1;
+                return Ok(bytes);
+            }
+            if c != '\\' {
+                bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                continue;
+            }
+
+            let escaped = match chars.next() {
+                Some((_, c)) => c,
+                None => break,
+            };
+            match escaped {
+                'n' => bytes.push(b'\n'),
+                'r' => bytes.push(b'\r'),
+                't' => bytes.push(b'\t'),
+                'b' => bytes.push(0x08),
+                'f' => bytes.push(0x0c),
+                'v' => bytes.push(0x0b),
+                '0' => bytes.push(0),
+                '\n' => {}
+                'x' => {
+                    let digits: String = chars.by_ref().take(2).map(|(_, c)| c).collect();
+                    let byte = u8::from_str_radix(&digits, 16)
+                        .map_err(|_| self.error(start, "invalid escape sequence in string"))?;
+                    bytes.push(byte);
+                }
+                'u' => {
+                    let digits: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                    let c = u32::from_str_radix(&digits, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| self.error(start, "invalid escape sequence in string"))?;
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                c => bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes()),
+            }
+        }
+
+        Err(self.error(start, "unterminated string"))
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_ident_char(c: char) -> bool {
+    is_ident_start(c) || c.is_ascii_digit() || c == '.'
+}
+
+/// Parses a number in `radix` to a big-endian word, or returns `None` if it
+/// is invalid or doesn't fit in 256 bits.
+fn parse_word(digits: &str, radix: u32) -> Option<[u8; 32]> {
+    if digits.is_empty() {
+        return None;
+    }
+
+    let mut word = [0u8; 32];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(radix)?;
+        for byte in word.iter_mut().rev() {
+            let value = *byte as u32 * radix + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(word)
+}
+
+fn line_col(src: &str, pos: usize) -> (usize, usize) {
+    let before = &src[..pos];
+    let line = before.matches('\n').count() + 1;
+    let col = before.len() - before.rfind('\n').map_or(0, |idx| idx + 1) + 1;
+    (line, col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(value: u8) -> Expression {
+        let mut word = [0; 32];
+        word[31] = value;
+        Expression::Literal(Literal::Number(word))
+    }
+
+    #[test]
+    fn parse_object_with_data() {
+        let src = r#"
+            /// @use-src 0:"foo.fe"
+            object "a" {
+                code { let x, y := f(1, 0x2) // comment
+                    function f(a, b) -> r, s { r := b s := a }
+                    /* @src 0:1:2 */ sstore(x, y)
+                }
+                object "b" { code { } }
+                data "c" hex"01_ff"
+                data "d" "\"\x41\n"
+            }
+        "#;
+
+        let object = parse_object(src).unwrap();
+        assert_eq!(object.name, "a");
+        assert_eq!(
+            object.code.statements,
+            vec![
+                Statement::VariableDeclaration {
+                    names: vec!["x".into(), "y".into()],
+                    value: Some(Expression::Call {
+                        name: "f".into(),
+                        args: vec![number(1), number(2)],
+                    }),
+                },
+                Statement::FunctionDefinition(FunctionDefinition {
+                    name: "f".into(),
+                    params: vec!["a".into(), "b".into()],
+                    returns: vec!["r".into(), "s".into()],
+                    body: Block {
+                        statements: vec![
+                            Statement::Assignment {
+                                names: vec!["r".into()],
+                                value: Expression::Identifier("b".into()),
+                            },
+                            Statement::Assignment {
+                                names: vec!["s".into()],
+                                value: Expression::Identifier("a".into()),
+                            },
+                        ],
+                    },
+                }),
+                Statement::Expression(Expression::Call {
+                    name: "sstore".into(),
+                    args: vec![
+                        Expression::Identifier("x".into()),
+                        Expression::Identifier("y".into()),
+                    ],
+                }),
+            ]
+        );
+        assert_eq!(object.objects[0].name, "b");
+        assert_eq!(
+            object.data,
+            vec![
+                Data {
+                    name: "c".into(),
+                    value: vec![0x01, 0xff],
+                },
+                Data {
+                    name: "d".into(),
+                    value: b"\"A\n".to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_number() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(parse_word(max, 10), Some([0xff; 32]));
+        assert_eq!(parse_word(&format!("{max}0"), 10), None);
+        assert_eq!(parse_word("1234", 16).unwrap()[30..], [0x12, 0x34]);
+        assert_eq!(parse_word("12a", 10), None);
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!(
+            parse_object("{\n  let x := \n}").unwrap_err().0,
+            "3:1: expected literal, found `}`"
+        );
+        assert_eq!(
+            parse_object("{ if 1 { }").unwrap_err().0,
+            "1:11: expected statement, found end of input"
+        );
+    }
+}
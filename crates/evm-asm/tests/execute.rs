@@ -0,0 +1,415 @@
+use fe_common::EvmVersion;
+use fe_evm_asm::{assemble, compile_single_contract, function_sizes, parse_object, AsmError};
+use revm::primitives::{
+    AccountInfo, Address, Bytecode, Bytes, Env, ExecutionResult, SpecId, TransactTo, B256, U256,
+};
+
+const ARITHMETIC: &str = r#"
+{
+    let x := calldataload(0)
+    mstore(0, add(mul(x, 3), sub(x, 1)))
+    mstore(32, div(not(0), exp(2, 248)))
+    mstore(64, "abc")
+    return(0, 96)
+}
+"#;
+
+const FUNCTIONS: &str = r#"
+{
+    let n := calldataload(0)
+    let q, r := divmod(n, 7)
+    mstore(0, fib(n))
+    mstore(32, q)
+    mstore(64, r)
+    mstore(96, first_set(n))
+    {
+        function double(x) -> y { y := add(x, x) }
+        mstore(128, double(n))
+    }
+    return(0, 160)
+
+    function fib(x) -> y {
+        switch lt(x, 2)
+        case 1 { y := x }
+        default { y := add(fib(sub(x, 1)), fib(sub(x, 2))) }
+    }
+
+    function divmod(a, b) -> quotient, remainder {
+        quotient := div(a, b)
+        remainder := mod(a, b)
+    }
+
+    function first_set(x) -> bit {
+        bit := 256
+        for { let i := 0 } lt(i, 256) { i := add(i, 1) } {
+            if and(shr(i, x), 1) {
+                bit := i
+                leave
+            }
+        }
+    }
+}
+"#;
+
+const CONTROL_FLOW: &str = r#"
+{
+    let n := calldataload(0)
+    let sum := 0
+    for { let i := 0 } 1 { i := add(i, 1) } {
+        if gt(i, n) { break }
+        if mod(i, 2) { continue }
+        sum := add(sum, i)
+    }
+    mstore(0, sum)
+
+    let kind
+    switch n
+    case 0 { kind := "zero" }
+    case "a" { kind := 1 }
+    default {
+        let tmp := mul(n, 2)
+        kind := tmp
+    }
+    mstore(32, kind)
+    return(0, 64)
+}
+"#;
+
+/// Declares more variables than the stack can reach, but never more than 16
+/// at the same time.
+const MANY_VARIABLES: &str = r#"
+{
+    mstore(0, f(calldataload(0)))
+    return(0, 32)
+
+    function f(x) -> r {
+        let a := add(x, 1)  let b := add(a, 2)  let c := add(b, 3)  let d := add(c, 4)
+        let e := add(d, 5)  let f := add(e, 6)  let g := add(f, 7)  let h := add(g, 8)
+        let i := add(h, 9)  let j := add(i, 10) let k := add(j, 11) let l := add(k, 12)
+        let m := add(l, 13) let n := add(m, 14) let o := add(n, 15) let p := add(o, 16)
+        let q := add(p, 17) let s := add(q, 18) let t := add(s, 19) let u := add(t, 20)
+        r := add(u, x)
+    }
+}
+"#;
+
+const TOO_DEEP: &str = r#"
+{
+    function f(x) -> r {
+        let a := 1 let b := 2 let c := 3 let d := 4 let e := 5 let f := 6
+        let g := 7 let h := 8 let i := 9 let j := 10 let k := 11 let l := 12
+        let m := 13 let n := 14 let o := 15 let p := 16 let q := 17
+        r := add(x, add(q, add(p, add(o, add(n, add(m, add(l, add(k, add(j,
+            add(i, add(h, add(g, add(f, add(e, add(d, add(c, add(b, a)))))))))))))))))
+    }
+    mstore(0, f(1))
+}
+"#;
+
+/// Like `TOO_DEEP`, but the memory above `memoryguard` is reserved, so the
+/// variables of `f`, and of the object code, are moved to memory.
+const SPILLED: &str = r#"
+{
+    let ptr := memoryguard(0x80)
+    mstore(ptr, 0xff)
+    let r1, r2 := f(1)
+    let r3, r4 := f(r1)
+    let a := 1 let b := 2 let c := 3 let d := 4 let e := 5 let f := 6
+    let g := 7 let h := 8 let i := 9 let j := 10 let k := 11 let l := 12
+    let m := 13 let n := 14 let o := 15 let p := 16 let q := 17
+    mstore(0, add(r3, a))
+    mstore(32, r4)
+    mstore(64, mload(ptr))
+    return(0, 96)
+
+    function f(x) -> r, s {
+        let a := 1 let b := 2 let c := 3 let d := 4 let e := 5 let f := 6
+        let g := 7 let h := 8 let i := 9 let j := 10 let k := 11 let l := 12
+        let m := 13 let n := 14 let o := 15 let p := 16 let q := 17
+        for { let y := 0 } lt(y, 3) { y := add(y, 1) } {
+            if eq(y, 2) { break }
+            s := add(s, 1)
+        }
+        if gt(x, 1) { leave }
+        r := add(x, add(q, add(p, add(o, add(n, add(m, add(l, add(k, add(j,
+            add(i, add(h, add(g, add(f, add(e, add(d, add(c, add(b, a)))))))))))))))))
+    }
+}
+"#;
+
+/// Deploys a runtime object that returns a data item, and stores the
+/// constructor argument in an immutable.
+const DEPLOY: &str = r#"
+object "deploy" {
+    code {
+        let arg_size := sub(codesize(), datasize("deploy"))
+        codecopy(0x1000, datasize("deploy"), arg_size)
+        let size := datasize("runtime")
+        datacopy(0, dataoffset("runtime"), size)
+        setimmutable(0, "arg", mload(0x1000))
+        return(0, size)
+    }
+    object "runtime" {
+        code {
+            let size := datasize("message")
+            datacopy(0, dataoffset("message"), size)
+            mstore(size, loadimmutable("arg"))
+            mstore(add(size, 32), loadimmutable("arg"))
+            return(0, add(size, 64))
+        }
+        data "message" "Hello, \"world\"\n"
+    }
+    data "unused" hex"c0ffee"
+}
+"#;
+
+fn word(value: u64) -> Vec<u8> {
+    let mut word = vec![0; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn words(values: &[u64]) -> Vec<u8> {
+    values.iter().flat_map(|value| word(*value)).collect()
+}
+
+/// Runs `code` with `calldata` on Cancun, and returns whether it succeeded and
+/// its output.
+fn execute(code: &[u8], calldata: &[u8]) -> (bool, Vec<u8>) {
+    execute_on(SpecId::CANCUN, code, calldata)
+}
+
+fn execute_on(spec_id: SpecId, code: &[u8], calldata: &[u8]) -> (bool, Vec<u8>) {
+    let address = Address::repeat_byte(0x42);
+    let bytecode = Bytecode::new_raw(Bytes::copy_from_slice(code));
+    let mut database = revm::InMemoryDB::default();
+    database.insert_account_info(
+        address,
+        AccountInfo::new(U256::ZERO, 0, B256::default(), bytecode),
+    );
+
+    let mut env = Env::default();
+    env.tx.transact_to = TransactTo::Call(address);
+    env.tx.data = Bytes::copy_from_slice(calldata);
+
+    let mut evm = revm::EvmBuilder::default()
+        .with_db(database)
+        .with_env(Box::new(env))
+        .with_spec_id(spec_id)
+        .build();
+    match evm.transact().expect("evm failure").result {
+        ExecutionResult::Success { output, .. } => (true, output.into_data().to_vec()),
+        ExecutionResult::Revert { output, .. } => (false, output.to_vec()),
+        ExecutionResult::Halt { reason, .. } => panic!("execution halted: {reason:?}"),
+    }
+}
+
+fn run(src: &str, calldata: &[u8]) -> Vec<u8> {
+    let bytecode = assemble(&parse_object(src).unwrap(), EvmVersion::default()).unwrap();
+    let (success, output) = execute(&bytecode, calldata);
+    assert!(success, "execution reverted");
+    output
+}
+
+#[test]
+fn arithmetic() {
+    let mut expected = words(&[10 * 3 + 9, 0xff]);
+    expected.extend(b"abc");
+    expected.extend([0; 29]);
+    assert_eq!(run(ARITHMETIC, &word(10)), expected);
+}
+
+#[test]
+fn functions() {
+    assert_eq!(run(FUNCTIONS, &word(20)), words(&[6765, 2, 6, 2, 40]));
+    assert_eq!(run(FUNCTIONS, &word(0)), words(&[0, 0, 0, 256, 0]));
+}
+
+#[test]
+fn control_flow() {
+    assert_eq!(run(CONTROL_FLOW, &word(10)), words(&[30, 20]));
+    let mut expected = words(&[0]);
+    expected.extend(b"zero");
+    expected.extend([0; 28]);
+    assert_eq!(run(CONTROL_FLOW, &word(0)), expected);
+}
+
+#[test]
+fn many_variables() {
+    assert_eq!(run(MANY_VARIABLES, &word(1)), word(2 + 210));
+}
+
+#[test]
+fn stack_too_deep() {
+    let err = assemble(&parse_object(TOO_DEEP).unwrap(), EvmVersion::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "stack too deep: variable `a` is 17 slots below the top of the stack, but only 16 can be reached in function `f`"
+    );
+}
+
+#[test]
+fn spilled_variables() {
+    assert_eq!(run(SPILLED, &[]), words(&[1, 2, 0xff]));
+
+    let recursive = SPILLED.replace("if gt(x, 1) { leave }", "if gt(x, 1) { r, s := f(1) }");
+    let err = assemble(&parse_object(&recursive).unwrap(), EvmVersion::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "stack too deep in function `f`, whose variables can't be moved to memory since it's recursive"
+    );
+}
+
+#[test]
+fn function_sizes_of_object() {
+    let object = parse_object(FUNCTIONS).unwrap();
+    let sizes = function_sizes(&object, EvmVersion::default()).unwrap();
+    let names: Vec<_> = sizes.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["fib", "first_set", "divmod", "double"]);
+    assert!(sizes.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+    let total = assemble(&object, EvmVersion::default()).unwrap().len();
+    let functions_total: usize = sizes.iter().map(|(_, size)| size).sum();
+    assert!(functions_total < total);
+}
+
+#[test]
+fn deploy() {
+    let contract = compile_single_contract("deploy", DEPLOY, EvmVersion::default(), true).unwrap();
+    let mut init_code = hex::decode(&contract.bytecode).unwrap();
+    init_code.extend(word(0xabcd));
+
+    let (success, runtime) = execute(&init_code, &[]);
+    assert!(success);
+    assert_eq!(
+        runtime.len(),
+        hex::decode(&contract.runtime_bytecode).unwrap().len()
+    );
+
+    let (success, output) = execute(&runtime, &[]);
+    assert!(success);
+    let mut expected = b"Hello, \"world\"\n".to_vec();
+    expected.extend(words(&[0xabcd, 0xabcd]));
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn errors() {
+    let assemble_src =
+        |src: &str| assemble(&parse_object(src).unwrap(), EvmVersion::default()).unwrap_err();
+    assert_eq!(
+        assemble_src("{ pop(f()) }"),
+        AsmError("unknown function `f` in object `object`".into())
+    );
+    assert_eq!(
+        assemble_src("{ function f() { break } }"),
+        AsmError("`break` or `continue` outside of a loop in function `f`".into())
+    );
+    assert_eq!(
+        assemble_src("{ let a, b := add(1, 2) }"),
+        AsmError("expected 2 values, found 1 in object `object`".into())
+    );
+    assert_eq!(
+        assemble_src(r#"{ pop(datasize("x")) }"#),
+        AsmError("unknown object or data `x` in object `object`".into())
+    );
+    assert_eq!(
+        compile_single_contract(
+            "a",
+            r#"object "b" { code { } }"#,
+            EvmVersion::default(),
+            false
+        )
+        .err()
+        .unwrap(),
+        AsmError("expected an object named `a`, found `b`".into())
+    );
+}
+
+#[test]
+fn evm_versions() {
+    // `PUSH0` is only emitted from Shanghai on, so the London bytecode runs on
+    // London.
+    let src = "{ mstore(0, add(calldataload(0), 0)) return(0, 32) }";
+    for (evm_version, spec_id, push0) in [
+        (EvmVersion::London, SpecId::LONDON, false),
+        (EvmVersion::Shanghai, SpecId::SHANGHAI, true),
+    ] {
+        let bytecode = assemble(&parse_object(src).unwrap(), evm_version).unwrap();
+        assert_eq!(bytecode.contains(&0x5f), push0);
+        assert_eq!(execute_on(spec_id, &bytecode, &word(7)), (true, word(7)));
+    }
+
+    let assemble_src = |src: &str, evm_version| {
+        assemble(&parse_object(src).unwrap(), evm_version)
+            .err()
+            .map(|err| err.0)
+    };
+    assert_eq!(
+        assemble_src("{ tstore(0, 1) }", EvmVersion::Shanghai),
+        Some("`tstore` is not available in EVM version `shanghai` in object `object`".into())
+    );
+    assert_eq!(
+        assemble_src("{ pop(prevrandao()) }", EvmVersion::London),
+        Some("`prevrandao` is not available in EVM version `london` in object `object`".into())
+    );
+    assert_eq!(
+        assemble_src("{ pop(difficulty()) }", EvmVersion::Paris),
+        Some("`difficulty` is not available in EVM version `paris` in object `object`".into())
+    );
+    assert_eq!(
+        assemble_src("{ pop(difficulty()) }", EvmVersion::London),
+        None
+    );
+}
+
+/// Checks that the bytecode of the assembler behaves like the bytecode of
+/// solc.
+#[cfg(feature = "solc-backend")]
+#[test]
+fn differential() {
+    let escape = |src: &str| {
+        src.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    };
+
+    for (src, calldata) in [
+        (ARITHMETIC, word(10)),
+        (FUNCTIONS, word(20)),
+        (FUNCTIONS, word(0)),
+        (CONTROL_FLOW, word(10)),
+        (CONTROL_FLOW, word(0)),
+        (MANY_VARIABLES, word(1)),
+    ] {
+        let solc = fe_yulc::compile_single_contract(
+            "object",
+            &escape(src),
+            false,
+            EvmVersion::default(),
+            false,
+        )
+        .expect("solc failed");
+        let solc_output = execute(&hex::decode(solc.bytecode).unwrap(), &calldata);
+        let bytecode = assemble(&parse_object(src).unwrap(), EvmVersion::default()).unwrap();
+        assert_eq!(execute(&bytecode, &calldata), solc_output);
+    }
+
+    let solc = fe_yulc::compile_single_contract(
+        "deploy",
+        &escape(DEPLOY),
+        false,
+        EvmVersion::default(),
+        true,
+    )
+    .expect("solc failed");
+    let native = compile_single_contract("deploy", DEPLOY, EvmVersion::default(), true).unwrap();
+    for contract in [solc.bytecode, native.bytecode] {
+        let mut init_code = hex::decode(contract).unwrap();
+        init_code.extend(word(0xabcd));
+        let (_, runtime) = execute(&init_code, &[]);
+        let mut expected = b"Hello, \"world\"\n".to_vec();
+        expected.extend(words(&[0xabcd, 0xabcd]));
+        assert_eq!(execute(&runtime, &[]), (true, expected));
+    }
+}
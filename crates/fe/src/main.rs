@@ -17,12 +17,21 @@ fn main() {
     let cli = FelangCli::parse();
 
     match cli.command {
+        Commands::Abi(arg) => {
+            task::abi(arg);
+        }
         Commands::Build(arg) => {
             task::build(arg);
         }
         Commands::Check(arg) => {
             task::check(arg);
         }
+        Commands::Fmt(arg) => {
+            task::fmt(arg);
+        }
+        Commands::Lsp(arg) => {
+            task::lsp(arg);
+        }
         Commands::New(arg) => {
             task::create_new_project(arg);
         }
@@ -30,7 +39,6 @@ fn main() {
         Commands::Verify(arg) => {
             task::verify(arg);
         }
-        #[cfg(feature = "solc-backend")]
         Commands::Test(arg) => {
             task::test(arg);
         }
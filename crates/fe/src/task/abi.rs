@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use clap::{Args, Subcommand};
+use fe_abi::import::{import_interface, ImportOptions};
+
+#[derive(Args)]
+#[clap(about = "Work with contract ABIs")]
+pub struct AbiArgs {
+    #[clap(subcommand)]
+    command: AbiCommands,
+}
+
+#[derive(Subcommand)]
+enum AbiCommands {
+    Import(ImportArgs),
+}
+
+#[derive(Args)]
+#[clap(about = "Print a Fe interface for calling a contract with the given JSON ABI")]
+struct ImportArgs {
+    /// A JSON ABI, or a build artifact with an `abi` field.
+    input_path: String,
+    /// The name of the interface. Defaults to the name of the input file.
+    #[clap(long, takes_value(true))]
+    name: Option<String>,
+    /// The maximum length of `string` and `bytes` values, which are
+    /// represented by `String<N>` and `Array<u8, N>`.
+    #[clap(long, default_value_t = ImportOptions::default().max_dynamic_len)]
+    max_dynamic_len: usize,
+}
+
+pub fn abi(args: AbiArgs) {
+    match args.command {
+        AbiCommands::Import(args) => import(args),
+    }
+}
+
+fn import(args: ImportArgs) {
+    let path = &args.input_path;
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(err) => {
+            eprintln!("Failed to load file: `{path}`. Error: {err}");
+            std::process::exit(1)
+        }
+    };
+    let name = args.name.unwrap_or_else(|| {
+        Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+
+    let options = ImportOptions {
+        max_dynamic_len: args.max_dynamic_len,
+    };
+    let imported = match import_interface(&name, &json, &options) {
+        Ok(imported) => imported,
+        Err(err) => {
+            eprintln!("Failed to import ABI from `{path}`. Error: {err}");
+            std::process::exit(1)
+        }
+    };
+
+    for skipped in &imported.skipped {
+        eprintln!("Skipped {skipped}");
+    }
+    print!("{}", imported.source);
+}
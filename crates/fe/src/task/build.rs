@@ -6,7 +6,8 @@ use clap::{ArgEnum, Args};
 use fe_common::diagnostics::print_diagnostics;
 use fe_common::files::SourceFileId;
 use fe_common::utils::files::{get_project_root, BuildFiles, ProjectMode};
-use fe_driver::CompiledModule;
+use fe_common::EvmVersion;
+use fe_driver::{AnalyzerDb, CodegenDb, CompiledModule};
 
 const DEFAULT_OUTPUT_DIR_NAME: &str = "output";
 
@@ -17,6 +18,8 @@ enum Emit {
     LoweredAst,
     Bytecode,
     RuntimeBytecode,
+    SourceMap,
+    StorageLayout,
     Tokens,
     Yul,
 }
@@ -40,8 +43,23 @@ pub struct BuildArgs {
     mir: bool,
     #[clap(long)]
     overwrite: bool,
-    #[clap(long, takes_value(true))]
+    #[clap(
+        long,
+        takes_value(true),
+        help("Optimize the MIR before generating Yul and run the Yul optimizer [default: true]")
+    )]
     optimize: Option<bool>,
+    #[clap(
+        long,
+        takes_value(true),
+        help("The EVM version to compile for, overriding fe.toml [default: cancun]")
+    )]
+    evm_version: Option<EvmVersion>,
+    #[clap(
+        long,
+        help("Build contracts that exceed the code size limits of EIP-170 and EIP-3860")
+    )]
+    allow_oversize: bool,
 }
 
 fn build_single_file(compile_arg: &BuildArgs) -> (String, CompiledModule) {
@@ -52,6 +70,9 @@ fn build_single_file(compile_arg: &BuildArgs) -> (String, CompiledModule) {
     let optimize = compile_arg.optimize.unwrap_or(true);
 
     let mut db = fe_driver::Db::default();
+    db.set_evm_version(compile_arg.evm_version.unwrap_or_default());
+    db.set_codegen_source_map(emit.contains(&Emit::SourceMap));
+    db.set_codegen_allow_oversize(compile_arg.allow_oversize);
     let content = match std::fs::read_to_string(input_path) {
         Err(err) => {
             eprintln!("Failed to load file: `{input_path}`. Error: {err}");
@@ -105,6 +126,14 @@ fn build_ingot(compile_arg: &BuildArgs) -> (String, CompiledModule) {
     }
 
     let mut db = fe_driver::Db::default();
+    db.set_evm_version(
+        compile_arg
+            .evm_version
+            .or_else(|| build_files.root_project_evm_version())
+            .unwrap_or_default(),
+    );
+    db.set_codegen_source_map(emit.contains(&Emit::SourceMap));
+    db.set_codegen_allow_oversize(compile_arg.allow_oversize);
     let compiled_module = match fe_driver::compile_ingot(
         &mut db,
         &build_files,
@@ -133,10 +162,9 @@ pub fn build(compile_arg: BuildArgs) {
         return mir_dump(input_path);
     }
 
-    let _with_bytecode = emit.contains(&Emit::Bytecode);
     #[cfg(not(feature = "solc-backend"))]
-    if _with_bytecode {
-        eprintln!("Warning: bytecode output requires 'solc-backend' feature. Try `cargo build --release --features solc-backend`. Skipping.");
+    if emit.contains(&Emit::SourceMap) {
+        eprintln!("Warning: source map output requires 'solc-backend' feature. Try `cargo build --release --features solc-backend`. Skipping.");
     }
 
     let (content, compiled_module) = if Path::new(input_path).is_file() {
@@ -202,17 +230,23 @@ fn write_compiled_module(
             write_output(&contract_output_dir.join(file_name), &contract.json_abi)?;
         }
 
+        if targets.contains(&Emit::StorageLayout) {
+            let file_name = format!("{}_storage.json", &name);
+            write_output(
+                &contract_output_dir.join(file_name),
+                &contract.storage_layout,
+            )?;
+        }
+
         if targets.contains(&Emit::Yul) {
             let file_name = format!("{}_ir.yul", &name);
             write_output(&contract_output_dir.join(file_name), &contract.yul)?;
         }
 
-        #[cfg(feature = "solc-backend")]
         if targets.contains(&Emit::Bytecode) {
             let file_name = format!("{}.bin", &name);
             write_output(&contract_output_dir.join(file_name), &contract.bytecode)?;
         }
-        #[cfg(feature = "solc-backend")]
         if targets.contains(&Emit::RuntimeBytecode) {
             let file_name = format!("{}.runtime.bin", &name);
             write_output(
@@ -220,6 +254,11 @@ fn write_compiled_module(
                 &contract.runtime_bytecode,
             )?;
         }
+        #[cfg(feature = "solc-backend")]
+        if targets.contains(&Emit::SourceMap) {
+            let file_name = format!("{}_source_map.json", &name);
+            write_output(&contract_output_dir.join(file_name), &contract.source_map)?;
+        }
     }
 
     Ok(())
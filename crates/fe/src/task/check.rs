@@ -6,7 +6,7 @@ use fe_common::{
     utils::files::get_project_root,
     utils::files::BuildFiles,
 };
-use fe_driver::Db;
+use fe_driver::{AnalyzerDb, Db};
 
 #[derive(Args)]
 #[clap(about = "Analyze the current project and report errors, but don't build artifacts")]
@@ -36,6 +36,9 @@ fn check_ingot(db: &mut Db, input_path: &str) -> Vec<Diagnostic> {
         }
     };
 
+    if let Some(evm_version) = build_files.root_project_evm_version() {
+        db.set_evm_version(evm_version);
+    }
     fe_driver::check_ingot(db, &build_files)
 }
 
@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use clap::Args;
+use fe_common::{diagnostics::print_diagnostics, utils::files::get_project_root, SourceFileId};
+use fe_parser::formatter::format_source;
+use walkdir::WalkDir;
+
+#[derive(Args)]
+#[clap(
+    about = "Format the Fe source files of the current project",
+    long_about = "Format the Fe source files of the current project.\n\n\
+        Comments are kept in place. A statement with a comment deep inside an \
+        expression, e.g. between the arguments of a nested call, is left as it is \
+        written, so its layout isn't made canonical."
+)]
+pub struct FmtArgs {
+    #[clap(default_value_t = get_project_root().unwrap_or(".".to_string()))]
+    input_path: String,
+    #[clap(
+        long,
+        help = "Don't write the formatted files, but exit with an error if any file isn't formatted"
+    )]
+    check: bool,
+}
+
+fn source_files(input_path: &str) -> Vec<String> {
+    if Path::new(input_path).is_file() {
+        return vec![input_path.to_string()];
+    }
+
+    let mut files = vec![];
+    for entry in WalkDir::new(input_path) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                eprintln!("Error loading source files.\n{err}");
+                std::process::exit(1)
+            }
+        };
+        let path = entry.path();
+        if path.is_file() && path.extension().and_then(std::ffi::OsStr::to_str) == Some("fe") {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+    files
+}
+
+pub fn fmt(args: FmtArgs) {
+    let mut db = fe_driver::Db::default();
+    let mut has_errors = false;
+    let mut unformatted = vec![];
+
+    for path in source_files(&args.input_path) {
+        let content = match std::fs::read_to_string(&path) {
+            Err(err) => {
+                eprintln!("Failed to load file: `{}`. Error: {}", &path, err);
+                std::process::exit(1)
+            }
+            Ok(content) => content,
+        };
+
+        let file_id = SourceFileId::new_local(&mut db, &path, content.as_str().into());
+        let formatted = match format_source(file_id, &content) {
+            Ok(formatted) => formatted,
+            Err(diags) => {
+                print_diagnostics(&db, &diags);
+                has_errors = true;
+                continue;
+            }
+        };
+
+        if formatted == content {
+            continue;
+        }
+        if args.check {
+            unformatted.push(path);
+        } else if let Err(err) = std::fs::write(&path, formatted) {
+            eprintln!("Failed to write file: `{}`. Error: {}", &path, err);
+            std::process::exit(1)
+        }
+    }
+
+    if has_errors {
+        std::process::exit(1);
+    }
+    if !unformatted.is_empty() {
+        eprintln!("The following files are not formatted:");
+        for path in unformatted {
+            eprintln!("  {path}");
+        }
+        std::process::exit(1);
+    }
+
+    eprintln!("Finished");
+}
@@ -0,0 +1,12 @@
+use clap::Args;
+
+#[derive(Args)]
+#[clap(about = "Start the Fe language server, communicating over stdin and stdout")]
+pub struct LspArgs {}
+
+pub fn lsp(_args: LspArgs) {
+    if let Err(err) = fe_language_server::run_server() {
+        eprintln!("Language server error: {err}");
+        std::process::exit(1);
+    }
+}
@@ -1,26 +1,32 @@
+mod abi;
 mod build;
 mod check;
+mod fmt;
+mod lsp;
 mod new;
-#[cfg(feature = "solc-backend")]
 mod test;
 mod verify;
 
+pub use abi::{abi, AbiArgs};
 pub use build::{build, BuildArgs};
 pub use check::{check, CheckArgs};
 use clap::Subcommand;
+pub use fmt::{fmt, FmtArgs};
+pub use lsp::{lsp, LspArgs};
 pub use new::{create_new_project, NewProjectArgs};
-#[cfg(feature = "solc-backend")]
 pub use test::{test, TestArgs};
 #[cfg(feature = "solc-backend")]
 pub use verify::{verify, VerifyArgs};
 
 #[derive(Subcommand)]
 pub enum Commands {
+    Abi(AbiArgs),
     Build(BuildArgs),
     Check(CheckArgs),
+    Fmt(FmtArgs),
+    Lsp(LspArgs),
     New(NewProjectArgs),
     #[cfg(feature = "solc-backend")]
     Verify(VerifyArgs),
-    #[cfg(feature = "solc-backend")]
     Test(TestArgs),
 }
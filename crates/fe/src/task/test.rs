@@ -1,15 +1,21 @@
-#![cfg(feature = "solc-backend")]
 use std::path::Path;
 
 use clap::Args;
 use colored::Colorize;
 use fe_common::diagnostics::print_diagnostics;
 use fe_common::utils::files::{get_project_root, BuildFiles};
+use fe_common::EvmVersion;
+use fe_driver::AnalyzerDb;
+#[cfg(feature = "solc-backend")]
 use fe_driver::CompiledTest;
-use fe_test_runner::TestSink;
+use fe_driver::InterpretedTest;
+#[cfg(feature = "solc-backend")]
+use fe_test_runner::GasSnapshot;
+use fe_test_runner::{FuzzConfig, TestSink};
 
 #[derive(Args)]
 #[clap(about = "Execute tests in the current project")]
+#[cfg_attr(not(feature = "solc-backend"), allow(dead_code))]
 pub struct TestArgs {
     #[clap(default_value_t = get_project_root().unwrap_or(".".to_string()))]
     input_path: String,
@@ -19,25 +25,158 @@ pub struct TestArgs {
     optimize: Option<bool>,
     #[clap(long)]
     logs: bool,
+    /// Run the tests with the MIR interpreter instead of compiling them with
+    /// solc. Always enabled if fe is built without the solc backend.
+    #[clap(long)]
+    interpret: bool,
+    /// The EVM version to compile the tests for, overriding `evm-version` in
+    /// fe.toml [default: cancun]
+    #[clap(long, takes_value(true))]
+    evm_version: Option<EvmVersion>,
+    /// Print the gas used by each test and by the contract functions it
+    /// called
+    #[cfg(feature = "solc-backend")]
+    #[clap(long, conflicts_with = "interpret")]
+    gas_report: bool,
+    /// Write the gas used by each test to `.gas-snapshot`, and fail if a test
+    /// uses more gas than in the existing snapshot
+    #[cfg(feature = "solc-backend")]
+    #[clap(long, conflicts_with = "interpret")]
+    snapshot: bool,
+    /// The percentage by which the gas of a test may exceed the existing
+    /// snapshot
+    #[cfg(feature = "solc-backend")]
+    #[clap(long, takes_value(true), default_value_t = 0.)]
+    snapshot_tolerance: f64,
+    /// The number of random inputs that each test with parameters is run with
+    #[clap(long, takes_value(true), default_value_t = FuzzConfig::DEFAULT_RUNS)]
+    fuzz_runs: u32,
+    /// The seed of the random inputs of tests with parameters, to reproduce
+    /// a previous run [default: random]
+    #[clap(long, takes_value(true))]
+    fuzz_seed: Option<u64>,
 }
 
 pub fn test(args: TestArgs) {
     let path = &args.input_path;
+    let interpret = args.interpret || cfg!(not(feature = "solc-backend"));
 
-    let test_sink = if Path::new(path).is_file() {
-        test_single_file(&args)
-    } else {
-        test_ingot(&args)
+    let fuzz = FuzzConfig::new(args.fuzz_runs, args.fuzz_seed);
+    let test_sink = match (Path::new(path).is_file(), interpret) {
+        (true, true) => interpret_single_file(&args, fuzz),
+        (false, true) => interpret_ingot(&args, fuzz),
+        #[cfg(feature = "solc-backend")]
+        (true, false) => test_single_file(&args, fuzz),
+        #[cfg(feature = "solc-backend")]
+        (false, false) => test_ingot(&args, fuzz),
+        #[cfg(not(feature = "solc-backend"))]
+        (_, false) => unreachable!(),
     };
 
     println!("{test_sink}");
 
+    #[cfg(feature = "solc-backend")]
+    {
+        if args.gas_report {
+            println!();
+            print!("{}", test_sink.gas_report());
+        }
+        if args.snapshot && !check_gas_snapshot(&args, &test_sink) {
+            std::process::exit(1)
+        }
+    }
+
     if test_sink.failure_count() != 0 {
         std::process::exit(1)
     }
 }
 
-pub fn execute_tests(module_name: &str, tests: &[CompiledTest], sink: &mut TestSink) {
+/// Writes the gas used by the tests to the `.gas-snapshot` file of the
+/// project, and returns whether no test uses more gas than in the previous
+/// snapshot, beyond the tolerance. With `--filter`, the tests that didn't run
+/// keep their previous entries.
+#[cfg(feature = "solc-backend")]
+fn check_gas_snapshot(args: &TestArgs, test_sink: &TestSink) -> bool {
+    let input_path = Path::new(&args.input_path);
+    let snapshot_path = if input_path.is_file() {
+        input_path.with_file_name(".gas-snapshot")
+    } else {
+        input_path.join(".gas-snapshot")
+    };
+
+    let previous = match std::fs::read_to_string(&snapshot_path) {
+        Ok(previous) => match previous.parse::<GasSnapshot>() {
+            Ok(previous) => previous,
+            Err(err) => {
+                eprintln!("Failed to parse `{}`: {err}", snapshot_path.display());
+                std::process::exit(1)
+            }
+        },
+        Err(_) => GasSnapshot::default(),
+    };
+
+    let snapshot = test_sink.gas_report().snapshot();
+    let regressions = snapshot.regressions(&previous, args.snapshot_tolerance);
+    let snapshot = match &args.filter {
+        Some(filter) if !filter.is_empty() => {
+            let mut merged = previous;
+            merged.extend(snapshot);
+            merged
+        }
+        _ => snapshot,
+    };
+    if let Err(err) = std::fs::write(&snapshot_path, snapshot.to_string()) {
+        eprintln!("Failed to write `{}`: {err}", snapshot_path.display());
+        std::process::exit(1)
+    }
+
+    if regressions.is_empty() {
+        return true;
+    }
+    println!();
+    println!("The following tests use more gas than in the previous snapshot:");
+    for regression in &regressions {
+        println!("  {}", regression.to_string().red());
+    }
+    false
+}
+
+#[cfg(feature = "solc-backend")]
+pub fn execute_tests(
+    module_name: &str,
+    tests: &[CompiledTest],
+    fuzz: FuzzConfig,
+    sink: &mut TestSink,
+) {
+    execute_batch(
+        module_name,
+        tests,
+        |test| test.name.as_str(),
+        |test| test.execute(sink, fuzz),
+    )
+}
+
+pub fn interpret_tests(
+    db: &fe_driver::Db,
+    module_name: &str,
+    tests: &[InterpretedTest],
+    fuzz: FuzzConfig,
+    sink: &mut TestSink,
+) {
+    execute_batch(
+        module_name,
+        tests,
+        |test| test.name.as_str(),
+        |test| test.execute(db, sink, fuzz),
+    )
+}
+
+fn execute_batch<T>(
+    module_name: &str,
+    tests: &[T],
+    name: impl Fn(&T) -> &str,
+    mut execute: impl FnMut(&T) -> bool,
+) {
     if tests.len() == 1 {
         println!("executing 1 test in {module_name}:");
     } else {
@@ -45,8 +184,8 @@ pub fn execute_tests(module_name: &str, tests: &[CompiledTest], sink: &mut TestS
     }
 
     for test in tests {
-        print!("  {} ...", test.name);
-        let test_passed = test.execute(sink);
+        print!("  {} ...", name(test));
+        let test_passed = execute(test);
 
         if test_passed {
             println!(" {}", "passed".green())
@@ -57,24 +196,20 @@ pub fn execute_tests(module_name: &str, tests: &[CompiledTest], sink: &mut TestS
     println!();
 }
 
-fn test_single_file(args: &TestArgs) -> TestSink {
+#[cfg(feature = "solc-backend")]
+fn test_single_file(args: &TestArgs, fuzz: FuzzConfig) -> TestSink {
     let input_path = &args.input_path;
     let optimize = args.optimize.unwrap_or(true);
     let logs = args.logs;
 
-    let mut db = fe_driver::Db::default();
-    let content = match std::fs::read_to_string(input_path) {
-        Err(err) => {
-            eprintln!("Failed to load file: `{input_path}`. Error: {err}");
-            std::process::exit(1)
-        }
-        Ok(content) => content,
-    };
+    let content = read_file(input_path);
 
+    let mut db = fe_driver::Db::default();
+    db.set_evm_version(args.evm_version.unwrap_or_default());
     match fe_driver::compile_single_file_tests(&mut db, input_path, &content, optimize) {
         Ok((name, tests)) => {
             let mut sink = TestSink::new(logs);
-            execute_tests(&name, &tests, &mut sink);
+            execute_tests(&name, &tests, fuzz, &mut sink);
             sink
         }
         Err(error) => {
@@ -85,32 +220,72 @@ fn test_single_file(args: &TestArgs) -> TestSink {
     }
 }
 
-fn test_ingot(args: &TestArgs) -> TestSink {
+#[cfg(feature = "solc-backend")]
+fn test_ingot(args: &TestArgs, fuzz: FuzzConfig) -> TestSink {
     let input_path = &args.input_path;
     let optimize = args.optimize.unwrap_or(true);
     let logs = args.logs;
+    let build_files = load_build_files(input_path);
 
-    if !Path::new(input_path).exists() {
-        eprintln!("Input directory does not exist: `{input_path}`.");
-        std::process::exit(1)
+    let mut db = fe_driver::Db::default();
+    db.set_evm_version(
+        args.evm_version
+            .or_else(|| build_files.root_project_evm_version())
+            .unwrap_or_default(),
+    );
+    match fe_driver::compile_ingot_tests(&mut db, &build_files, optimize) {
+        Ok(test_batches) => {
+            let mut sink = TestSink::new(logs);
+            for (module_name, tests) in test_batches {
+                let tests = filter_tests(&tests, &args.filter, |test| test.name.as_str());
+                execute_tests(&module_name, &tests, fuzz, &mut sink);
+            }
+            sink
+        }
+        Err(error) => {
+            eprintln!("Unable to compile {input_path}.");
+            print_diagnostics(&db, &error.0);
+            std::process::exit(1)
+        }
     }
+}
 
-    let build_files = match BuildFiles::load_fs(input_path) {
-        Ok(files) => files,
-        Err(err) => {
-            eprintln!("Failed to load project files.\nError: {err}");
+fn interpret_single_file(args: &TestArgs, fuzz: FuzzConfig) -> TestSink {
+    let input_path = &args.input_path;
+    let content = read_file(input_path);
+
+    let mut db = fe_driver::Db::default();
+    db.set_evm_version(args.evm_version.unwrap_or_default());
+    match fe_driver::lower_single_file_tests(&mut db, input_path, &content) {
+        Ok((name, tests)) => {
+            let mut sink = TestSink::new(args.logs);
+            interpret_tests(&db, &name, &tests, fuzz, &mut sink);
+            sink
+        }
+        Err(error) => {
+            eprintln!("Unable to compile {input_path}.");
+            print_diagnostics(&db, &error.0);
             std::process::exit(1)
         }
-    };
+    }
+}
 
-    let mut db = fe_driver::Db::default();
+fn interpret_ingot(args: &TestArgs, fuzz: FuzzConfig) -> TestSink {
+    let input_path = &args.input_path;
+    let build_files = load_build_files(input_path);
 
-    match fe_driver::compile_ingot_tests(&mut db, &build_files, optimize) {
+    let mut db = fe_driver::Db::default();
+    db.set_evm_version(
+        args.evm_version
+            .or_else(|| build_files.root_project_evm_version())
+            .unwrap_or_default(),
+    );
+    match fe_driver::lower_ingot_tests(&mut db, &build_files) {
         Ok(test_batches) => {
-            let mut sink = TestSink::new(logs);
+            let mut sink = TestSink::new(args.logs);
             for (module_name, tests) in test_batches {
-                let tests = filter_tests(&tests, &args.filter);
-                execute_tests(&module_name, &tests, &mut sink);
+                let tests = filter_tests(&tests, &args.filter, |test| test.name.as_str());
+                interpret_tests(&db, &module_name, &tests, fuzz, &mut sink);
             }
             sink
         }
@@ -122,11 +297,40 @@ fn test_ingot(args: &TestArgs) -> TestSink {
     }
 }
 
-fn filter_tests(tests: &[CompiledTest], filter: &Option<String>) -> Vec<CompiledTest> {
+fn read_file(input_path: &str) -> String {
+    match std::fs::read_to_string(input_path) {
+        Err(err) => {
+            eprintln!("Failed to load file: `{input_path}`. Error: {err}");
+            std::process::exit(1)
+        }
+        Ok(content) => content,
+    }
+}
+
+fn load_build_files(input_path: &str) -> BuildFiles {
+    if !Path::new(input_path).exists() {
+        eprintln!("Input directory does not exist: `{input_path}`.");
+        std::process::exit(1)
+    }
+
+    match BuildFiles::load_fs(input_path) {
+        Ok(files) => files,
+        Err(err) => {
+            eprintln!("Failed to load project files.\nError: {err}");
+            std::process::exit(1)
+        }
+    }
+}
+
+fn filter_tests<T: Clone>(
+    tests: &[T],
+    filter: &Option<String>,
+    name: impl Fn(&T) -> &str,
+) -> Vec<T> {
     match filter {
         Some(word) if !word.is_empty() => tests
             .iter()
-            .filter(|test| test.name.contains(word))
+            .filter(|test| name(test).contains(word))
             .cloned()
             .collect(),
         Some(_) | None => tests.to_vec(),
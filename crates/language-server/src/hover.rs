@@ -0,0 +1,77 @@
+use crate::references::{item_at, module_definitions};
+use crate::state::ServerState;
+use crate::util::position_to_offset;
+use fe_analyzer::display::Displayable;
+use fe_analyzer::namespace::items::{Item, ModuleId, TypeDef};
+use fe_analyzer::AnalyzerDb;
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
+
+/// Shows the signature of the item under the cursor, or else the type of the
+/// innermost expression under the cursor.
+pub fn hover(state: &ServerState, params: HoverParams) -> Option<Hover> {
+    let params = params.text_document_position_params;
+    let doc = state.document(&params.text_document.uri)?;
+    let text = doc.file.content(&state.db);
+    let offset = position_to_offset(&text, params.position);
+
+    let info = match item_at(&state.db, doc.module, offset) {
+        Some(item) => item_info(&state.db, item)?,
+        None => expr_type(&state.db, doc.module, offset)?,
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("```fe\n{info}\n```"),
+        }),
+        range: None,
+    })
+}
+
+fn item_info(db: &dyn AnalyzerDb, item: Item) -> Option<String> {
+    let info = match item {
+        Item::Function(id) => {
+            let sig = &id.data(db).ast.kind.sig;
+            let src = sig.span.file_id.content(db.upcast());
+            src[sig.span.start..sig.span.end].to_string()
+        }
+        Item::Constant(id) => match id.typ(db) {
+            Ok(typ) => format!("const {}: {}", id.name(db), typ.display(db)),
+            Err(_) => format!("const {}", id.name(db)),
+        },
+        Item::Type(TypeDef::Alias(id)) => match id.type_id(db) {
+            Ok(typ) => format!("type {} = {}", id.name(db), typ.display(db)),
+            Err(_) => format!("type {}", id.name(db)),
+        },
+        Item::Type(TypeDef::Contract(id)) => format!("contract {}", id.name(db)),
+        Item::Type(TypeDef::Enum(id)) => format!("enum {}", id.name(db)),
+        Item::Type(TypeDef::Struct(id)) => format!("struct {}", id.name(db)),
+        Item::Trait(id) => format!("trait {}", id.name(db)),
+        _ => return None,
+    };
+    Some(info)
+}
+
+/// Returns the type of the smallest expression that contains the offset.
+fn expr_type(db: &dyn AnalyzerDb, module: ModuleId, offset: usize) -> Option<String> {
+    let function = module_definitions(db, module)
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Function(id) => Some(id),
+            _ => None,
+        })
+        .find(|id| {
+            let span = id.span(db);
+            span.start <= offset && offset < span.end
+        })?;
+
+    let body = function.body(db);
+    let (_, attributes) = body
+        .expressions
+        .iter()
+        .filter_map(|(node_id, attributes)| Some((body.spans.get(node_id)?, attributes)))
+        .filter(|(span, _)| span.start <= offset && offset < span.end)
+        .min_by_key(|(span, _)| span.end - span.start)?;
+
+    Some(attributes.typ.display(db).to_string())
+}
@@ -0,0 +1,206 @@
+//! Fe language server.
+//!
+//! Implements the Language Server Protocol over stdio, on top of the same
+//! incremental salsa databases that are used by the compiler. Every open
+//! document is analyzed as a standalone module, and re-analyzed when its
+//! content changes.
+
+mod hover;
+mod references;
+mod state;
+mod util;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as Notification_, PublishDiagnostics,
+};
+use lsp_types::request::{GotoDefinition, HoverRequest, References, Request as Request_};
+use lsp_types::{
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionResponse, HoverProviderCapability, OneOf, PublishDiagnosticsParams,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+
+pub use state::ServerState;
+
+pub type ServerResult<T> = Result<T, Box<dyn Error + Sync + Send>>;
+
+/// The capabilities advertised to the client on initialization.
+pub fn server_capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+/// Run the language server on stdin and stdout, until the client asks it to
+/// shut down.
+pub fn run_server() -> ServerResult<()> {
+    let (connection, io_threads) = Connection::stdio();
+    run(&connection)?;
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Initialize the server on the given connection, and handle messages until
+/// the client asks the server to shut down.
+pub fn run(connection: &Connection) -> ServerResult<()> {
+    let capabilities = serde_json::to_value(server_capabilities())?;
+    connection.initialize(capabilities)?;
+
+    let mut state = ServerState::default();
+    for msg in &connection.receiver {
+        match msg {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    return Ok(());
+                }
+                handle_request(connection, &mut state, req)?;
+            }
+            Message::Notification(not) => handle_notification(connection, &mut state, not)?,
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(
+    connection: &Connection,
+    state: &mut ServerState,
+    req: Request,
+) -> ServerResult<()> {
+    match req.method.as_str() {
+        HoverRequest::METHOD => {
+            if let Some((id, params)) = request_params(connection, req)? {
+                respond(connection, id, hover::hover(state, params))?;
+            }
+        }
+        GotoDefinition::METHOD => {
+            if let Some((id, params)) = request_params(connection, req)? {
+                let locations = references::goto_definition(state, params);
+                respond(connection, id, locations.map(GotoDefinitionResponse::Array))?;
+            }
+        }
+        References::METHOD => {
+            if let Some((id, params)) = request_params(connection, req)? {
+                respond(connection, id, references::find_references(state, params))?;
+            }
+        }
+        _ => {
+            let message = format!("unsupported request: {}", req.method);
+            respond_err(connection, req.id, ErrorCode::MethodNotFound, message)?;
+        }
+    }
+    Ok(())
+}
+
+fn handle_notification(
+    connection: &Connection,
+    state: &mut ServerState,
+    not: Notification,
+) -> ServerResult<()> {
+    match not.method.as_str() {
+        DidOpenTextDocument::METHOD => {
+            if let Some(params) = notification_params::<DidOpenTextDocumentParams>(not) {
+                let doc = params.text_document;
+                state.open_document(doc.uri.clone(), &doc.text);
+                publish_diagnostics(connection, state, doc.uri)?;
+            }
+        }
+        DidChangeTextDocument::METHOD => {
+            if let Some(params) = notification_params::<DidChangeTextDocumentParams>(not) {
+                // The server only supports full document sync, so the last
+                // change contains the whole document.
+                let uri = params.text_document.uri;
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    state.change_document(&uri, &change.text);
+                }
+                publish_diagnostics(connection, state, uri)?;
+            }
+        }
+        DidCloseTextDocument::METHOD => {
+            if let Some(params) = notification_params::<DidCloseTextDocumentParams>(not) {
+                let uri = params.text_document.uri;
+                state.close_document(&uri);
+                // Clear the diagnostics of the closed document.
+                let params = PublishDiagnosticsParams::new(uri, vec![], None);
+                send_notification::<PublishDiagnostics>(connection, params)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn publish_diagnostics(connection: &Connection, state: &ServerState, uri: Url) -> ServerResult<()> {
+    let diagnostics = state.diagnostics(&uri);
+    let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+    send_notification::<PublishDiagnostics>(connection, params)
+}
+
+/// Deserializes the request parameters. Responds with an error and returns
+/// `None` if they're invalid.
+fn request_params<P: DeserializeOwned>(
+    connection: &Connection,
+    req: Request,
+) -> ServerResult<Option<(RequestId, P)>> {
+    match serde_json::from_value(req.params) {
+        Ok(params) => Ok(Some((req.id, params))),
+        Err(err) => {
+            respond_err(
+                connection,
+                req.id,
+                ErrorCode::InvalidParams,
+                err.to_string(),
+            )?;
+            Ok(None)
+        }
+    }
+}
+
+/// Deserializes the notification parameters. Notifications can't be answered,
+/// so invalid notifications are reported on stderr and otherwise ignored.
+fn notification_params<P: DeserializeOwned>(not: Notification) -> Option<P> {
+    match serde_json::from_value(not.params) {
+        Ok(params) => Some(params),
+        Err(err) => {
+            eprintln!("invalid `{}` notification: {}", not.method, err);
+            None
+        }
+    }
+}
+
+fn respond(connection: &Connection, id: RequestId, result: impl Serialize) -> ServerResult<()> {
+    connection
+        .sender
+        .send(Response::new_ok(id, result).into())?;
+    Ok(())
+}
+
+fn respond_err(
+    connection: &Connection,
+    id: RequestId,
+    code: ErrorCode,
+    message: String,
+) -> ServerResult<()> {
+    connection
+        .sender
+        .send(Response::new_err(id, code as i32, message).into())?;
+    Ok(())
+}
+
+fn send_notification<N: Notification_>(
+    connection: &Connection,
+    params: N::Params,
+) -> ServerResult<()> {
+    let not = Notification::new(N::METHOD.to_string(), params);
+    connection.sender.send(not.into())?;
+    Ok(())
+}
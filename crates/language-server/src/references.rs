@@ -0,0 +1,443 @@
+//! Name resolution for goto-definition and find-references.
+//!
+//! The references of a module are collected by resolving the names and paths
+//! in its AST, and from the call targets recorded by the analyzer in the
+//! function bodies (which also covers method calls).
+
+use crate::state::ServerState;
+use crate::util::{position_to_offset, span_to_range};
+use fe_analyzer::context::{CallType, NamedThing};
+use fe_analyzer::namespace::items::{Item, ModuleId, TypeDef};
+use fe_analyzer::namespace::types::Type;
+use fe_analyzer::AnalyzerDb;
+use fe_common::Span;
+use fe_parser::ast::{self, Expr, FuncStmt, GenericArg, Pattern, TypeDesc};
+use fe_parser::node::Node;
+use indexmap::IndexMap;
+use lsp_types::{GotoDefinitionParams, Location, ReferenceParams};
+
+/// An occurrence of the name of an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reference {
+    pub span: Span,
+    pub item: Item,
+}
+
+pub fn goto_definition(state: &ServerState, params: GotoDefinitionParams) -> Option<Vec<Location>> {
+    let params = params.text_document_position_params;
+    let doc = state.document(&params.text_document.uri)?;
+    let text = doc.file.content(&state.db);
+    let offset = position_to_offset(&text, params.position);
+
+    let item = item_at(&state.db, doc.module, offset)?;
+    let location = item_location(state, item)?;
+    Some(vec![location])
+}
+
+pub fn find_references(state: &ServerState, params: ReferenceParams) -> Option<Vec<Location>> {
+    let position = params.text_document_position;
+    let doc = state.document(&position.text_document.uri)?;
+    let text = doc.file.content(&state.db);
+    let offset = position_to_offset(&text, position.position);
+    let item = item_at(&state.db, doc.module, offset)?;
+
+    let mut locations = vec![];
+    if params.context.include_declaration {
+        locations.extend(item_location(state, item));
+    }
+    for (uri, doc) in state.documents() {
+        for reference in module_references(&state.db, doc.module) {
+            if reference.item == item {
+                locations.push(Location::new(
+                    uri.clone(),
+                    span_to_range(&state.db, reference.span),
+                ));
+            }
+        }
+    }
+    Some(locations)
+}
+
+/// Returns the location of the item's name, if it's defined in an open
+/// document.
+fn item_location(state: &ServerState, item: Item) -> Option<Location> {
+    let span = item.name_span(&state.db)?;
+    let uri = state.document_uri(span.file_id)?;
+    Some(Location::new(uri.clone(), span_to_range(&state.db, span)))
+}
+
+/// Returns the item that is referenced or defined at the given offset.
+pub fn item_at(db: &dyn AnalyzerDb, module: ModuleId, offset: usize) -> Option<Item> {
+    let contains = |span: Span| span.start <= offset && offset <= span.end;
+
+    if let Some(reference) = module_references(db, module)
+        .into_iter()
+        .find(|reference| contains(reference.span))
+    {
+        return Some(reference.item);
+    }
+
+    module_definitions(db, module)
+        .into_iter()
+        .find(|item| item.name_span(db).is_some_and(contains))
+}
+
+/// All items defined in the module, including the functions of contracts,
+/// structs, enums and impls.
+pub fn module_definitions(db: &dyn AnalyzerDb, module: ModuleId) -> Vec<Item> {
+    let mut items = vec![];
+    for item in module.all_items(db).iter() {
+        items.push(*item);
+        let functions = match item {
+            Item::Type(TypeDef::Contract(id)) => id.all_functions(db),
+            Item::Type(TypeDef::Struct(id)) => id.all_functions(db),
+            Item::Type(TypeDef::Enum(id)) => id.all_functions(db),
+            _ => continue,
+        };
+        items.extend(functions.iter().map(|id| Item::Function(*id)));
+    }
+    for impl_ in module.all_impls(db).iter() {
+        items.extend(impl_.all_functions(db).iter().map(|id| Item::Function(*id)));
+    }
+    items
+}
+
+/// All references to items in the module, in source order.
+pub fn module_references(db: &dyn AnalyzerDb, module: ModuleId) -> Vec<Reference> {
+    let mut collector = Collector {
+        db,
+        module,
+        refs: IndexMap::new(),
+    };
+    collector.module(&module.ast(db));
+
+    // The call targets resolved by the analyzer take precedence, since they
+    // also take the function's scope into account.
+    for item in module_definitions(db, module) {
+        let function = match item {
+            Item::Function(id) => id,
+            _ => continue,
+        };
+        let body = function.body(db);
+        for (node_id, call) in body.calls.iter() {
+            let (span, item) = match (body.spans.get(node_id), call_target(db, call)) {
+                (Some(span), Some(item)) => (span, item),
+                _ => continue,
+            };
+            // The callee may be a path or method access (eg. `self.foo`);
+            // only the function name refers to the function.
+            let src = span.file_id.content(db.upcast());
+            let name_len = src[span.start..span.end]
+                .chars()
+                .rev()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .map(char::len_utf8)
+                .sum::<usize>();
+            let span = Span::new(span.file_id, span.end - name_len, span.end);
+            collector
+                .refs
+                .insert((span.start, span.end), Reference { span, item });
+        }
+    }
+
+    let mut refs = collector.refs.into_values().collect::<Vec<_>>();
+    refs.sort_by_key(|reference| reference.span.start);
+    refs
+}
+
+fn call_target(db: &dyn AnalyzerDb, call: &CallType) -> Option<Item> {
+    match call {
+        CallType::AssociatedFunction { function, .. }
+        | CallType::ValueMethod {
+            method: function, ..
+        }
+        | CallType::External { function, .. }
+        | CallType::Pure(function) => Some(Item::Function(*function)),
+        CallType::TraitValueMethod { method, .. } => method.function(db).map(Item::Function),
+        CallType::TypeConstructor(typ) => match typ.typ(db) {
+            Type::Struct(id) => Some(Item::Type(TypeDef::Struct(id))),
+            Type::Contract(id) => Some(Item::Type(TypeDef::Contract(id))),
+            _ => None,
+        },
+        CallType::BuiltinFunction(_)
+        | CallType::Intrinsic(_)
+        | CallType::BuiltinValueMethod { .. }
+        | CallType::BuiltinAssociatedFunction { .. }
+        | CallType::EnumConstructor(_) => None,
+    }
+}
+
+/// Walks the AST of a module, and resolves the names and paths that refer to
+/// items.
+struct Collector<'a> {
+    db: &'a dyn AnalyzerDb,
+    module: ModuleId,
+    refs: IndexMap<(usize, usize), Reference>,
+}
+
+impl<'a> Collector<'a> {
+    fn module(&mut self, module: &ast::Module) {
+        for stmt in &module.body {
+            match stmt {
+                ast::ModuleStmt::TypeAlias(node) => self.type_desc(&node.kind.typ),
+                ast::ModuleStmt::Constant(node) => {
+                    self.type_desc(&node.kind.typ);
+                    self.expr(&node.kind.value);
+                }
+                ast::ModuleStmt::Contract(node) => {
+                    self.fields(&node.kind.fields);
+                    for stmt in &node.kind.body {
+                        if let ast::ContractStmt::Function(func) = stmt {
+                            self.function(func);
+                        }
+                    }
+                }
+                ast::ModuleStmt::Struct(node) | ast::ModuleStmt::Error(node) => {
+                    self.fields(&node.kind.fields);
+                    node.kind
+                        .functions
+                        .iter()
+                        .for_each(|func| self.function(func));
+                }
+                ast::ModuleStmt::Enum(node) => {
+                    for variant in &node.kind.variants {
+                        if let ast::VariantKind::Tuple(elts) = &variant.kind.kind {
+                            elts.iter().for_each(|elt| self.type_desc(elt));
+                        }
+                    }
+                    node.kind
+                        .functions
+                        .iter()
+                        .for_each(|func| self.function(func));
+                }
+                ast::ModuleStmt::Trait(node) => {
+                    node.kind
+                        .functions
+                        .iter()
+                        .for_each(|sig| self.signature(sig));
+                }
+                ast::ModuleStmt::Interface(node) => {
+                    node.kind
+                        .functions
+                        .iter()
+                        .for_each(|sig| self.signature(sig));
+                }
+                ast::ModuleStmt::Impl(node) => {
+                    self.name(&node.kind.impl_trait);
+                    self.type_desc(&node.kind.receiver);
+                    node.kind
+                        .functions
+                        .iter()
+                        .for_each(|func| self.function(func));
+                }
+                ast::ModuleStmt::Function(func) => self.function(func),
+                ast::ModuleStmt::Pragma(_)
+                | ast::ModuleStmt::Use(_)
+                | ast::ModuleStmt::Attribute(_)
+                | ast::ModuleStmt::ParseError(_) => {}
+            }
+        }
+    }
+
+    fn fields(&mut self, fields: &[Node<ast::Field>]) {
+        for field in fields {
+            self.type_desc(&field.kind.typ);
+            if let Some(value) = &field.kind.value {
+                self.expr(value);
+            }
+        }
+    }
+
+    fn function(&mut self, func: &Node<ast::Function>) {
+        self.signature(&func.kind.sig);
+        self.stmts(&func.kind.body);
+    }
+
+    fn signature(&mut self, sig: &Node<ast::FunctionSignature>) {
+        for param in &sig.kind.generic_params.kind {
+            if let ast::GenericParameter::Bounded { bound, .. } = param {
+                self.type_desc(bound);
+            }
+        }
+        for arg in &sig.kind.args {
+            if let ast::FunctionArg::Regular { typ, .. } = &arg.kind {
+                self.type_desc(typ);
+            }
+        }
+        if let Some(typ) = &sig.kind.return_type {
+            self.type_desc(typ);
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[Node<FuncStmt>]) {
+        stmts.iter().for_each(|stmt| self.stmt(stmt))
+    }
+
+    fn stmt(&mut self, stmt: &Node<FuncStmt>) {
+        match &stmt.kind {
+            FuncStmt::Return { value } => value.iter().for_each(|value| self.expr(value)),
+            FuncStmt::VarDecl { typ, value, .. } => {
+                self.type_desc(typ);
+                value.iter().for_each(|value| self.expr(value));
+            }
+            FuncStmt::ConstantDecl { typ, value, .. } => {
+                self.type_desc(typ);
+                self.expr(value);
+            }
+            FuncStmt::Assign { target, value } | FuncStmt::AugAssign { target, value, .. } => {
+                self.expr(target);
+                self.expr(value);
+            }
+            FuncStmt::For { iter, body, .. } => {
+                self.expr(iter);
+                self.stmts(body);
+            }
+            FuncStmt::While { test, body } => {
+                self.expr(test);
+                self.stmts(body);
+            }
+            FuncStmt::If {
+                test,
+                body,
+                or_else,
+            } => {
+                self.expr(test);
+                self.stmts(body);
+                self.stmts(or_else);
+            }
+            FuncStmt::Match { expr, arms } => {
+                self.expr(expr);
+                for arm in arms {
+                    self.pattern(&arm.kind.pat);
+                    self.stmts(&arm.kind.body);
+                }
+            }
+            FuncStmt::Assert { test, msg } => {
+                self.expr(test);
+                msg.iter().for_each(|msg| self.expr(msg));
+            }
+            FuncStmt::Expr { value } => self.expr(value),
+            FuncStmt::Revert { error } => error.iter().for_each(|error| self.expr(error)),
+            FuncStmt::Unsafe(body) => self.stmts(body),
+            FuncStmt::Break | FuncStmt::Continue => {}
+        }
+    }
+
+    fn pattern(&mut self, pat: &Node<Pattern>) {
+        match &pat.kind {
+            Pattern::Path(path) => self.path(&path.kind.segments),
+            Pattern::PathTuple(path, elts) => {
+                self.path(&path.kind.segments);
+                elts.iter().for_each(|elt| self.pattern(elt));
+            }
+            Pattern::PathStruct { path, fields, .. } => {
+                self.path(&path.kind.segments);
+                fields.iter().for_each(|(_, pat)| self.pattern(pat));
+            }
+            Pattern::Tuple(elts) | Pattern::Or(elts) => {
+                elts.iter().for_each(|elt| self.pattern(elt))
+            }
+            Pattern::WildCard | Pattern::Rest | Pattern::Literal(_) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Node<Expr>) {
+        match &expr.kind {
+            Expr::Ternary {
+                if_expr,
+                test,
+                else_expr,
+            } => {
+                self.expr(if_expr);
+                self.expr(test);
+                self.expr(else_expr);
+            }
+            Expr::BoolOperation { left, right, .. }
+            | Expr::BinOperation { left, right, .. }
+            | Expr::CompOperation { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::UnaryOperation { operand, .. } => self.expr(operand),
+            Expr::Attribute { value, .. } | Expr::Try { value } => self.expr(value),
+            Expr::Subscript { value, index } => {
+                self.expr(value);
+                self.expr(index);
+            }
+            Expr::Call {
+                func,
+                generic_args,
+                args,
+            } => {
+                self.expr(func);
+                if let Some(generic_args) = generic_args {
+                    generic_args
+                        .kind
+                        .iter()
+                        .for_each(|arg| self.generic_arg(arg));
+                }
+                args.kind.iter().for_each(|arg| self.expr(&arg.kind.value));
+            }
+            Expr::List { elts } | Expr::Tuple { elts } => {
+                elts.iter().for_each(|elt| self.expr(elt))
+            }
+            Expr::Repeat { value, len } => {
+                self.expr(value);
+                self.generic_arg(&len.kind);
+            }
+            Expr::Name(name) => self.resolve(&[Node::new(name.clone(), expr.span)]),
+            Expr::Path(path) => self.path(&path.segments),
+            Expr::Bool(_) | Expr::Num(_) | Expr::Str(_) | Expr::Unit => {}
+        }
+    }
+
+    fn generic_arg(&mut self, arg: &GenericArg) {
+        match arg {
+            GenericArg::TypeDesc(typ) => self.type_desc(typ),
+            GenericArg::ConstExpr(expr) => self.expr(expr),
+            GenericArg::Int(_) => {}
+        }
+    }
+
+    fn type_desc(&mut self, typ: &Node<TypeDesc>) {
+        match &typ.kind {
+            TypeDesc::Base { base } => self.resolve(&[Node::new(base.clone(), typ.span)]),
+            TypeDesc::Path(path) => self.path(&path.segments),
+            TypeDesc::Tuple { items } => items.iter().for_each(|item| self.type_desc(item)),
+            TypeDesc::Generic { base, args } => {
+                self.path(&base.kind.segments);
+                args.kind.iter().for_each(|arg| self.generic_arg(arg));
+            }
+            TypeDesc::Unit | TypeDesc::SelfType => {}
+        }
+    }
+
+    fn name(&mut self, name: &Node<ast::SmolStr>) {
+        self.resolve(std::slice::from_ref(name))
+    }
+
+    /// Resolves every prefix of the path, so that each segment refers to its
+    /// own item.
+    fn path(&mut self, segments: &[Node<ast::SmolStr>]) {
+        for idx in 0..segments.len() {
+            self.resolve(&segments[..=idx]);
+        }
+    }
+
+    fn resolve(&mut self, segments: &[Node<ast::SmolStr>]) {
+        let path = ast::Path {
+            segments: segments.to_vec(),
+        };
+        let item = match self.module.resolve_path_internal(self.db, &path).value {
+            Some(NamedThing::Item(item)) => item,
+            _ => return,
+        };
+        if item.name_span(self.db).is_none() {
+            return;
+        }
+
+        let span = segments.last().expect("empty path").span;
+        self.refs
+            .insert((span.start, span.end), Reference { span, item });
+    }
+}
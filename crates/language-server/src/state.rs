@@ -0,0 +1,119 @@
+use crate::util::diagnostic_to_lsp;
+use fe_analyzer::namespace::items::{IngotId, IngotMode, ModuleId, ModuleSource};
+use fe_analyzer::AnalyzerDb;
+use fe_common::db::SourceDb;
+use fe_common::files::FileKind;
+use fe_common::SourceFileId;
+use fe_driver::Db;
+use indexmap::{indexmap, IndexMap};
+use lsp_types::{Diagnostic, Url};
+use std::rc::Rc;
+
+/// An open document, analyzed as a standalone module.
+#[derive(Debug, Clone, Copy)]
+pub struct Document {
+    pub module: ModuleId,
+    pub file: SourceFileId,
+}
+
+/// The state of the language server: the salsa database, and the documents
+/// that are open in the client.
+pub struct ServerState {
+    pub db: Db,
+    std: IngotId,
+    documents: IndexMap<Url, Document>,
+}
+
+impl Default for ServerState {
+    fn default() -> Self {
+        let mut db = Db::default();
+        let std = IngotId::std_lib(&mut db);
+        Self {
+            db,
+            std,
+            documents: IndexMap::new(),
+        }
+    }
+}
+
+impl ServerState {
+    /// Add a document to the database, or replace its content if it's
+    /// already open.
+    pub fn open_document(&mut self, uri: Url, text: &str) {
+        if self.documents.contains_key(&uri) {
+            return self.change_document(&uri, text);
+        }
+
+        // Each document gets its own ingot, named after the document path, so
+        // that documents in the same directory don't share an ingot.
+        let path = document_path(&uri);
+        let ingot = IngotId::from_files(
+            &mut self.db,
+            &path,
+            IngotMode::StandaloneModule,
+            FileKind::Local,
+            &[(path.as_str(), text)],
+        );
+        self.db
+            .set_ingot_external_ingots(ingot, Rc::new(indexmap! { "std".into() => self.std }));
+
+        let module = ingot
+            .root_module(&self.db)
+            .expect("standalone ingot has no root module");
+        let file = match module.data(&self.db).source {
+            ModuleSource::File(file) => file,
+            ModuleSource::Dir(_) => unreachable!("standalone module must have a source file"),
+        };
+        self.documents.insert(uri, Document { module, file });
+    }
+
+    /// Replace the content of an open document. Only the queries that depend
+    /// on the content of the document are recomputed.
+    pub fn change_document(&mut self, uri: &Url, text: &str) {
+        match self.documents.get(uri) {
+            Some(doc) => self.db.set_file_content(doc.file, text.into()),
+            None => self.open_document(uri.clone(), text),
+        }
+    }
+
+    pub fn close_document(&mut self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+
+    pub fn document(&self, uri: &Url) -> Option<Document> {
+        self.documents.get(uri).copied()
+    }
+
+    pub fn documents(&self) -> impl Iterator<Item = (&Url, &Document)> {
+        self.documents.iter()
+    }
+
+    /// Returns the uri of the open document with the given source file.
+    pub fn document_uri(&self, file: SourceFileId) -> Option<&Url> {
+        self.documents
+            .iter()
+            .find(|(_, doc)| doc.file == file)
+            .map(|(uri, _)| uri)
+    }
+
+    /// Analyzes the document, and returns its diagnostics.
+    pub fn diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let doc = match self.document(uri) {
+            Some(doc) => doc,
+            None => return vec![],
+        };
+
+        doc.module
+            .diagnostics(&self.db)
+            .into_iter()
+            .map(|diag| diagnostic_to_lsp(&self.db, doc.file, diag))
+            .collect()
+    }
+}
+
+fn document_path(uri: &Url) -> String {
+    match uri.to_file_path() {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(()) => uri.path().to_string(),
+    }
+}
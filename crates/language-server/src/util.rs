@@ -0,0 +1,106 @@
+use fe_common::db::SourceDb;
+use fe_common::diagnostics::{Diagnostic, LabelStyle, Severity};
+use fe_common::{SourceFileId, Span};
+use lsp_types::{DiagnosticSeverity, Position, Range};
+
+/// Converts a byte offset into an LSP position. LSP positions count UTF-16
+/// code units from the start of the line.
+pub fn offset_to_position(text: &str, offset: usize) -> Position {
+    let offset = offset.min(text.len());
+    let line_start = text[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let line = text[..line_start].matches('\n').count();
+    let character = text[line_start..offset].encode_utf16().count();
+    Position::new(line as u32, character as u32)
+}
+
+/// Converts an LSP position into a byte offset. Positions past the end of a
+/// line are clamped to the end of the line.
+pub fn position_to_offset(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(idx) => line_start += idx + 1,
+            None => return text.len(),
+        }
+    }
+
+    let mut utf16_count = 0;
+    for (idx, c) in text[line_start..].char_indices() {
+        if c == '\n' || utf16_count >= position.character as usize {
+            return line_start + idx;
+        }
+        utf16_count += c.len_utf16();
+    }
+    text.len()
+}
+
+pub fn span_to_range(db: &dyn SourceDb, span: Span) -> Range {
+    let text = span.file_id.content(db);
+    Range::new(
+        offset_to_position(&text, span.start),
+        offset_to_position(&text, span.end),
+    )
+}
+
+/// Converts a compiler diagnostic into an LSP diagnostic, located at its
+/// primary label in the given file. Secondary labels and notes are appended to
+/// the message.
+pub fn diagnostic_to_lsp(
+    db: &dyn SourceDb,
+    file: SourceFileId,
+    diag: Diagnostic,
+) -> lsp_types::Diagnostic {
+    let mut labels = diag
+        .labels
+        .iter()
+        .filter(|label| label.span.file_id == file)
+        .collect::<Vec<_>>();
+    labels.sort_by_key(|label| label.style != LabelStyle::Primary);
+
+    let range = labels
+        .first()
+        .map_or_else(Range::default, |label| span_to_range(db, label.span));
+
+    let mut message = diag.message;
+    for label in &labels {
+        if !label.message.is_empty() {
+            message.push_str(&format!("\n{}", label.message));
+        }
+    }
+    for note in &diag.notes {
+        message.push_str(&format!("\n{note}"));
+    }
+
+    let severity = match diag.severity {
+        Severity::Bug | Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Note => DiagnosticSeverity::INFORMATION,
+        Severity::Help => DiagnosticSeverity::HINT,
+    };
+
+    lsp_types::Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some("fe".into()),
+        message,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        let text = "fn f() {\n    let x: u8 = \"é\" 1\n}";
+        for offset in [0, 3, 9, 13, 26, 29, text.len()] {
+            let pos = offset_to_position(text, offset);
+            assert_eq!(position_to_offset(text, pos), offset);
+        }
+        assert_eq!(offset_to_position(text, 13), Position::new(1, 4));
+        // `é` is 2 bytes, but a single UTF-16 code unit.
+        assert_eq!(offset_to_position(text, 29), Position::new(1, 19));
+        assert_eq!(position_to_offset(text, Position::new(1, 100)), 31);
+    }
+}
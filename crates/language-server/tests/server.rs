@@ -0,0 +1,281 @@
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use serde_json::{json, Value};
+use std::thread::{self, JoinHandle};
+
+const SRC: &str = "\
+struct Point {
+    pub x: u256
+    pub y: u256
+
+    pub fn sum(self) -> u256 {
+        return self.x + self.y
+    }
+}
+
+const SCALE: u256 = 2
+
+pub fn scaled(p: Point) -> u256 {
+    return p.sum() * SCALE
+}
+";
+
+const URI: &str = "file:///fe/src/point.fe";
+
+/// An LSP client talking to a server running on another thread.
+struct Client {
+    conn: Connection,
+    server: Option<JoinHandle<()>>,
+    next_id: i32,
+}
+
+impl Client {
+    fn start() -> Self {
+        let (server_conn, conn) = Connection::memory();
+        let server = thread::spawn(move || fe_language_server::run(&server_conn).unwrap());
+        let mut client = Self {
+            conn,
+            server: Some(server),
+            next_id: 0,
+        };
+
+        let result = client.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!(result["capabilities"]["hoverProvider"], json!(true));
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        let req = Request::new(id.clone(), method.to_string(), params);
+        self.conn.sender.send(req.into()).unwrap();
+
+        for msg in &self.conn.receiver {
+            if let Message::Response(resp) = msg {
+                assert_eq!(resp.id, id);
+                assert!(resp.error.is_none(), "{:?}", resp.error);
+                return resp.result.unwrap_or(Value::Null);
+            }
+        }
+        panic!("server disconnected")
+    }
+
+    fn notify(&self, method: &str, params: Value) {
+        let not = Notification::new(method.to_string(), params);
+        self.conn.sender.send(not.into()).unwrap();
+    }
+
+    fn diagnostics(&self, uri: &str) -> Vec<Value> {
+        for msg in &self.conn.receiver {
+            if let Message::Notification(not) = msg {
+                assert_eq!(not.method, "textDocument/publishDiagnostics");
+                assert_eq!(not.params["uri"], uri);
+                return not.params["diagnostics"].as_array().unwrap().clone();
+            }
+        }
+        panic!("server disconnected")
+    }
+
+    fn open(&self, text: &str) -> Vec<Value> {
+        self.open_document(URI, text)
+    }
+
+    fn change(&self, text: &str) -> Vec<Value> {
+        self.change_document(URI, text)
+    }
+
+    fn open_document(&self, uri: &str, text: &str) -> Vec<Value> {
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": { "uri": uri, "languageId": "fe", "version": 1, "text": text }
+            }),
+        );
+        self.diagnostics(uri)
+    }
+
+    fn change_document(&self, uri: &str, text: &str) -> Vec<Value> {
+        self.notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{ "text": text }]
+            }),
+        );
+        self.diagnostics(uri)
+    }
+
+    fn position_request(&mut self, method: &str, position: Value, extra: Value) -> Value {
+        let mut params = json!({ "textDocument": { "uri": URI }, "position": position });
+        params
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        self.request(method, params)
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            return;
+        }
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        self.server.take().unwrap().join().unwrap();
+    }
+}
+
+/// Returns the LSP position of the `nth` occurrence of `needle` in `SRC`,
+/// offset by `delta` characters.
+fn position(needle: &str, nth: usize, delta: usize) -> Value {
+    let offset = SRC.match_indices(needle).nth(nth).unwrap().0 + delta;
+    let line = SRC[..offset].matches('\n').count();
+    let character = offset - SRC[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    json!({ "line": line, "character": character })
+}
+
+fn range(needle: &str, nth: usize) -> Value {
+    json!({ "start": position(needle, nth, 0), "end": position(needle, nth, needle.len()) })
+}
+
+fn hover_text(result: &Value) -> &str {
+    result["contents"]["value"].as_str().unwrap()
+}
+
+#[test]
+fn diagnostics() {
+    let mut client = Client::start();
+    assert_eq!(client.open(SRC), Vec::<Value>::new());
+
+    let src = SRC.replace("* SCALE", "* true");
+    let diags = client.change(&src);
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0]["severity"], 1);
+    assert_eq!(
+        diags[0]["message"],
+        "`*` operands must be numeric\nthis has type `u256`\nthis has type `bool`"
+    );
+    assert_eq!(diags[0]["range"], range("p.sum()", 0));
+
+    assert_eq!(client.change(SRC), Vec::<Value>::new());
+
+    client.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": URI } }),
+    );
+    assert_eq!(client.diagnostics(URI), Vec::<Value>::new());
+
+    // Requests for unknown documents have no result.
+    let hover = client.position_request("textDocument/hover", position("SCALE", 1, 0), json!({}));
+    assert_eq!(hover, Value::Null);
+}
+
+#[test]
+fn diagnostics_of_several_documents() {
+    let client = Client::start();
+    let src = "\
+trait Double {
+    fn double(self) -> u256;
+}
+
+impl Double for u256 {
+    fn double(self) -> u256 {
+        return self * 2
+    }
+}
+
+pub fn quadruple(x: u256) -> u256 {
+    return x.double().double()
+}
+";
+    assert_eq!(client.open(src), Vec::<Value>::new());
+
+    // Opening another document doesn't hide the impls of the first one.
+    let other_uri = "file:///fe/src/other.fe";
+    assert_eq!(
+        client.open_document(other_uri, "pub fn one() -> u256 {\n    return 1\n}\n"),
+        Vec::<Value>::new()
+    );
+    assert_eq!(client.change(src), Vec::<Value>::new());
+
+    // The documents are analyzed separately, so the impl isn't visible from
+    // the other document.
+    let diags = client.change_document(
+        other_uri,
+        "pub fn two() -> u256 {\n    return 1.double()\n}\n",
+    );
+    assert_eq!(diags.len(), 1);
+    assert_eq!(
+        diags[0]["message"],
+        "No function `double` exists on type `u256`\nundefined function"
+    );
+}
+
+#[test]
+fn hover() {
+    let mut client = Client::start();
+    client.open(SRC);
+
+    let hover = client.position_request("textDocument/hover", position("SCALE", 1, 2), json!({}));
+    assert_eq!(hover_text(&hover), "```fe\nconst SCALE: u256\n```");
+
+    let hover = client.position_request("textDocument/hover", position("sum", 1, 0), json!({}));
+    assert_eq!(hover_text(&hover), "```fe\npub fn sum(self) -> u256\n```");
+
+    // Expressions that don't refer to an item show their type.
+    let hover = client.position_request("textDocument/hover", position("p.sum", 0, 0), json!({}));
+    assert_eq!(hover_text(&hover), "```fe\nPoint\n```");
+    let hover = client.position_request("textDocument/hover", position("self.x", 0, 5), json!({}));
+    assert_eq!(hover_text(&hover), "```fe\nu256\n```");
+}
+
+#[test]
+fn goto_definition() {
+    let mut client = Client::start();
+    client.open(SRC);
+
+    // A method call
+    let result =
+        client.position_request("textDocument/definition", position("sum", 1, 1), json!({}));
+    assert_eq!(result, json!([{ "uri": URI, "range": range("sum", 0) }]));
+
+    // A type in a function signature
+    let result = client.position_request(
+        "textDocument/definition",
+        position("Point", 1, 0),
+        json!({}),
+    );
+    assert_eq!(result, json!([{ "uri": URI, "range": range("Point", 0) }]));
+
+    // Builtin types don't have a definition
+    let result =
+        client.position_request("textDocument/definition", position("u256", 0, 0), json!({}));
+    assert_eq!(result, Value::Null);
+}
+
+#[test]
+fn find_references() {
+    let mut client = Client::start();
+    client.open(SRC);
+
+    let result = client.position_request(
+        "textDocument/references",
+        position("SCALE", 0, 0),
+        json!({ "context": { "includeDeclaration": true } }),
+    );
+    assert_eq!(
+        result,
+        json!([
+            { "uri": URI, "range": range("SCALE", 0) },
+            { "uri": URI, "range": range("SCALE", 1) },
+        ])
+    );
+
+    let result = client.position_request(
+        "textDocument/references",
+        position("sum", 0, 0),
+        json!({ "context": { "includeDeclaration": false } }),
+    );
+    assert_eq!(result, json!([{ "uri": URI, "range": range("sum", 1) }]));
+}
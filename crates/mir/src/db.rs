@@ -7,6 +7,7 @@ use fe_analyzer::{
     AnalyzerDb,
 };
 use fe_common::db::{SourceDb, SourceDbStorage, Upcast, UpcastMut};
+use fe_common::EvmVersion;
 use smol_str::SmolStr;
 
 use crate::ir::{self, ConstantId, TypeId};
@@ -70,15 +71,26 @@ pub trait MirDb: AnalyzerDb + Upcast<dyn AnalyzerDb> + UpcastMut<dyn AnalyzerDb>
     ) -> ir::FunctionId;
     #[salsa::invoke(queries::function::mir_lowered_func_body)]
     fn mir_lowered_func_body(&self, func: ir::FunctionId) -> Rc<ir::FunctionBody>;
+    #[salsa::invoke(queries::function::mir_optimized_func_body)]
+    fn mir_optimized_func_body(&self, func: ir::FunctionId) -> Rc<ir::FunctionBody>;
 }
 
 #[salsa::database(SourceDbStorage, AnalyzerDbStorage, MirDbStorage)]
-#[derive(Default)]
 pub struct NewDb {
     storage: salsa::Storage<NewDb>,
 }
 impl salsa::Database for NewDb {}
 
+impl Default for NewDb {
+    fn default() -> Self {
+        let mut db = Self {
+            storage: Default::default(),
+        };
+        db.set_evm_version(EvmVersion::default());
+        db
+    }
+}
+
 impl Upcast<dyn SourceDb> for NewDb {
     fn upcast(&self) -> &(dyn SourceDb + 'static) {
         self
@@ -11,6 +11,7 @@ use crate::{
     db::MirDb,
     ir::{self, function::Linkage, FunctionSignature, TypeId},
     lower::function::{lower_func_body, lower_func_signature, lower_monomorphized_func_signature},
+    optim,
 };
 
 pub fn mir_lowered_func_signature(
@@ -47,6 +48,12 @@ pub fn mir_lowered_func_body(db: &dyn MirDb, func: ir::FunctionId) -> Rc<ir::Fun
     lower_func_body(db, func)
 }
 
+pub fn mir_optimized_func_body(db: &dyn MirDb, func: ir::FunctionId) -> Rc<ir::FunctionBody> {
+    let mut body = (*func.body(db)).clone();
+    optim::optimize(db, &mut body);
+    Rc::new(body)
+}
+
 impl ir::FunctionId {
     pub fn signature(self, db: &dyn MirDb) -> Rc<FunctionSignature> {
         db.lookup_mir_intern_function(self)
@@ -68,6 +75,11 @@ impl ir::FunctionId {
         db.mir_lowered_func_body(self)
     }
 
+    /// Returns the body after running the optimization pipeline over it.
+    pub fn optimized_body(self, db: &dyn MirDb) -> Rc<ir::FunctionBody> {
+        db.mir_optimized_func_body(self)
+    }
+
     pub fn module(self, db: &dyn MirDb) -> analyzer_items::ModuleId {
         let analyzer_func = self.analyzer_func(db);
         analyzer_func.module(db.upcast())
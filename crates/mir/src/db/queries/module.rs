@@ -16,15 +16,22 @@ pub fn mir_lower_module_all_functions(
             functions.push(db.mir_lowered_func_signature(*func))
         }
 
-        analyzer_items::Item::Type(TypeDef::Contract(contract)) => {
+        // Interface functions have no body, they're only called externally.
+        analyzer_items::Item::Type(TypeDef::Contract(contract))
+            if !contract.is_interface(db.upcast()) =>
+        {
             functions.extend_from_slice(&db.mir_lower_contract_all_functions(*contract))
         }
 
-        analyzer_items::Item::Type(TypeDef::Struct(struct_)) => {
+        // Functions of generic structs and enums are lowered for each instance
+        // when they are called.
+        analyzer_items::Item::Type(TypeDef::Struct(struct_))
+            if !struct_.is_generic(db.upcast()) =>
+        {
             functions.extend_from_slice(&db.mir_lower_struct_all_functions(*struct_))
         }
 
-        analyzer_items::Item::Type(TypeDef::Enum(enum_)) => {
+        analyzer_items::Item::Type(TypeDef::Enum(enum_)) if !enum_.is_generic(db.upcast()) => {
             functions.extend_from_slice(&db.mir_lower_enum_all_functions(*enum_))
         }
 
@@ -289,6 +289,17 @@ impl TypeId {
     }
 
     /// Returns an offset of the element of aggregate type.
+    ///
+    /// The layout follows the storage layout rules of Solidity so that a
+    /// contract can share its storage with a Solidity one:
+    /// * Primitive elements are packed into the same slot as long as they fit
+    ///   in it, otherwise they start a new slot.
+    /// * Aggregate elements start a new slot, and so does the element that
+    ///   follows an aggregate element.
+    ///
+    /// Memory uses the same layout, but the offset of a primitive in a storage
+    /// slot is counted from the lower-order end of the slot as in Solidity,
+    /// see [`TypeId::has_packed_fields`].
     pub fn aggregate_elem_offset<T>(self, db: &dyn MirDb, elem_idx: T, slot_size: usize) -> usize
     where
         T: num_traits::ToPrimitive,
@@ -305,13 +316,14 @@ impl TypeId {
             TypeKind::Array(def) => array_elem_size_imp(db, def, slot_size) * elem_idx,
             TypeKind::Enum(_) => self.enum_data_offset(db, slot_size),
             _ => {
+                let prev_ty = self.projection_ty_imm(db, elem_idx - 1);
                 let mut offset = self.aggregate_elem_offset(db, elem_idx - 1, slot_size)
-                    + self
-                        .projection_ty_imm(db, elem_idx - 1)
-                        .size_of(db, slot_size);
+                    + prev_ty.size_of(db, slot_size);
 
                 let elem_ty = self.projection_ty_imm(db, elem_idx);
-                if (offset % slot_size + elem_ty.size_of(db, slot_size)) > slot_size {
+                if !prev_ty.is_primitive(db)
+                    || (offset % slot_size + elem_ty.size_of(db, slot_size)) > slot_size
+                {
                     offset = round_up(offset, slot_size);
                 }
 
@@ -320,6 +332,30 @@ impl TypeId {
         }
     }
 
+    /// Returns `true` if the type contains a primitive that doesn't fill a
+    /// whole slot. The bytes of such a primitive are at different positions
+    /// in memory and in storage, so a value of the type can't be copied
+    /// between them slot by slot.
+    pub fn has_packed_fields(self, db: &dyn MirDb, slot_size: usize) -> bool {
+        match &self.data(db).kind {
+            TypeKind::Array(def) => def.elem_ty.has_packed_fields(db, slot_size),
+            TypeKind::Tuple(def) => def
+                .items
+                .iter()
+                .any(|item| item.has_packed_fields(db, slot_size)),
+            TypeKind::Struct(def) | TypeKind::Contract(def) => def
+                .fields
+                .iter()
+                .any(|(_, ty)| ty.has_packed_fields(db, slot_size)),
+            TypeKind::Enum(_) => true,
+            TypeKind::String(_) | TypeKind::Map(_) | TypeKind::MPtr(_) | TypeKind::SPtr(_) => false,
+            _ => {
+                let size = self.size_of(db, slot_size);
+                size != 0 && size < slot_size
+            }
+        }
+    }
+
     pub fn is_aggregate(self, db: &dyn MirDb) -> bool {
         matches!(
             &self.data(db).kind,
@@ -335,6 +371,14 @@ impl TypeId {
         matches!(&self.data(db).as_ref().kind, TypeKind::Struct(_))
     }
 
+    /// Returns `true` if the type is a struct defined with `error`.
+    pub fn is_error(self, db: &dyn MirDb) -> bool {
+        matches!(
+            self.analyzer_ty(db).map(|ty| ty.typ(db.upcast())),
+            Some(analyzer_types::Type::Struct(id)) if id.is_error(db.upcast())
+        )
+    }
+
     pub fn is_array(self, db: &dyn MirDb) -> bool {
         matches!(&self.data(db).kind, TypeKind::Array(_))
     }
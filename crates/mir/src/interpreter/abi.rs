@@ -0,0 +1,432 @@
+use fe_abi::{
+    function::{AbiFunction, AbiFunctionType, StateMutability},
+    types::{AbiTupleField, AbiType},
+};
+use fe_analyzer::constants::INDEXED;
+use fe_common::utils::keccak;
+
+use crate::ir::{FunctionId, TypeId, TypeKind};
+
+use super::{
+    machine::{Exec, Machine, Space, SLOT_SIZE},
+    state::Log,
+    word::{self, Word},
+    InterpreterError,
+};
+
+const ERROR_INVALID_ABI_DATA: u64 = 0x103;
+
+/// A value to be encoded, either the value itself or a pointer to it.
+pub(super) enum AbiSrc {
+    Word(Word),
+    Ptr(Word, Space),
+}
+
+impl<'db> Machine<'db> {
+    /// Returns the ABI type of `ty`, zero sized fields are omitted as in the
+    /// legalized types of the Yul backend.
+    pub(super) fn abi_type(&self, ty: TypeId) -> Exec<AbiType> {
+        let db = self.db;
+        let abi_ty = match &ty.data(db).kind {
+            TypeKind::I8 => AbiType::Int(8),
+            TypeKind::I16 => AbiType::Int(16),
+            TypeKind::I32 => AbiType::Int(32),
+            TypeKind::I64 => AbiType::Int(64),
+            TypeKind::I128 => AbiType::Int(128),
+            TypeKind::I256 => AbiType::Int(256),
+            TypeKind::U8 => AbiType::UInt(8),
+            TypeKind::U16 => AbiType::UInt(16),
+            TypeKind::U32 => AbiType::UInt(32),
+            TypeKind::U64 => AbiType::UInt(64),
+            TypeKind::U128 => AbiType::UInt(128),
+            TypeKind::U256 => AbiType::UInt(256),
+            TypeKind::Bool => AbiType::Bool,
+            TypeKind::Address => AbiType::Address,
+            TypeKind::String(_) => AbiType::String,
+            TypeKind::Array(def) => {
+                if def.elem_ty.data(db).kind == TypeKind::U8 {
+                    AbiType::Bytes
+                } else {
+                    AbiType::Array {
+                        elem_ty: self.abi_type(def.elem_ty)?.into(),
+                        len: def.len,
+                    }
+                }
+            }
+            TypeKind::Tuple(def) => {
+                let mut fields = vec![];
+                for (idx, item) in def.items.iter().enumerate() {
+                    if !item.is_zero_sized(db) {
+                        fields.push(AbiTupleField::new(format!("{idx}"), self.abi_type(*item)?));
+                    }
+                }
+                AbiType::Tuple(fields)
+            }
+            TypeKind::Struct(def) => {
+                let mut fields = vec![];
+                for (name, ty) in def.fields.iter() {
+                    if !ty.is_zero_sized(db) {
+                        fields.push(AbiTupleField::new(name.to_string(), self.abi_type(*ty)?));
+                    }
+                }
+                AbiType::Tuple(fields)
+            }
+            TypeKind::MPtr(inner) | TypeKind::SPtr(inner) => self.abi_type(*inner)?,
+            TypeKind::Unit | TypeKind::Enum(_) | TypeKind::Map(_) | TypeKind::Contract(_) => {
+                let mut name = String::new();
+                ty.print(db, &mut name).unwrap();
+                return Err(
+                    InterpreterError::Unsupported(format!("ABI encoding of `{name}`")).into(),
+                );
+            }
+        };
+        Ok(abi_ty)
+    }
+
+    /// Returns the selector of an exported or external function.
+    pub(super) fn function_selector(&self, func: FunctionId) -> Exec<[u8; 4]> {
+        let db = self.db;
+        let mut args = vec![];
+        for param in func.signature(db).params.iter() {
+            let ty = param.ty.deref(db);
+            if param.ty.is_contract(db) || ty.is_zero_sized(db) {
+                continue;
+            }
+            args.push((param.name.to_string(), self.abi_type(ty)?));
+        }
+
+        let abi = AbiFunction::new(
+            AbiFunctionType::Function,
+            func.name(db).to_string(),
+            args,
+            None,
+            StateMutability::Pure,
+        );
+        Ok(abi.selector().selector_raw())
+    }
+
+    /// Returns the data of a revert with a `name(arg)` signature as emitted
+    /// by the Yul backend.
+    pub(super) fn revert_data(&mut self, name: &str, arg: AbiSrc, ty: TypeId) -> Exec<Vec<u8>> {
+        let db = self.db;
+        let args = match &ty.data(db).kind {
+            TypeKind::Struct(def) => {
+                let mut args = vec![];
+                for (_, field_ty) in def.fields.iter() {
+                    if !field_ty.is_zero_sized(db) {
+                        args.push(("".to_string(), self.abi_type(*field_ty)?));
+                    }
+                }
+                args
+            }
+            _ if ty.is_zero_sized(db) => vec![],
+            _ => vec![("_".to_string(), self.abi_type(ty)?)],
+        };
+
+        let abi = AbiFunction::new(
+            AbiFunctionType::Function,
+            name.to_string(),
+            args,
+            None,
+            StateMutability::Pure,
+        );
+        let mut data = abi.selector().selector_raw().to_vec();
+        if ty.is_zero_sized(db) {
+            return Ok(data);
+        }
+        if ty.is_error(db) {
+            // The fields of an error are the arguments of the revert, rather
+            // than a single tuple argument.
+            data.extend(self.abi_encode(&arg, ty)?);
+        } else {
+            data.extend(self.abi_encode_seq(&[(arg, ty)])?);
+        }
+        Ok(data)
+    }
+
+    /// Emits the event struct at `ptr`, indexed fields are logged as topics.
+    pub(super) fn emit(&mut self, ptr: &Word, space: Space, ty: TypeId) -> Exec<()> {
+        let db = self.db;
+        let def = match &ty.data(db).kind {
+            TypeKind::Struct(def) => def.clone(),
+            _ => unreachable!(),
+        };
+        let analyzer_struct = ty
+            .analyzer_ty(db)
+            .and_then(|ty| ty.as_struct(db.upcast()))
+            .unwrap();
+
+        let mut signature_tys = vec![];
+        let mut topics = vec![];
+        let mut data_args = vec![];
+        for (idx, (name, field_ty)) in def.fields.iter().enumerate() {
+            if field_ty.is_zero_sized(db) {
+                continue;
+            }
+            signature_tys.push(self.abi_type(*field_ty)?.selector_type_name());
+
+            let field_ptr = ptr + ty.aggregate_elem_offset(db, idx, SLOT_SIZE);
+            let indexed = analyzer_struct
+                .field(db.upcast(), name)
+                .is_some_and(|field| {
+                    field
+                        .attributes(db.upcast())
+                        .iter()
+                        .any(|attr| attr == INDEXED)
+                });
+            if indexed {
+                if !field_ty.is_primitive(db) {
+                    return Err(InterpreterError::Unsupported(
+                        "indexed event fields of aggregate types".into(),
+                    )
+                    .into());
+                }
+                let size = field_ty.size_of(db, SLOT_SIZE);
+                let topic = self.load_sized(space, &field_ptr, size)?;
+                topics.push(word::to_bytes(&self.primitive_cast(&topic, *field_ty)));
+            } else {
+                data_args.push((AbiSrc::Ptr(field_ptr, space), *field_ty));
+            }
+        }
+
+        let signature = format!("{}({})", def.name, signature_tys.join(","));
+        topics.insert(0, keccak::full_as_bytes(signature.as_bytes()));
+        let data = self.abi_encode_seq(&data_args)?;
+        self.log(Log {
+            address: self.address(),
+            topics,
+            data,
+        });
+        Ok(())
+    }
+
+    /// Encodes a sequence of values, i.e. a tuple of the values without the
+    /// tuple header.
+    pub(super) fn abi_encode_seq(&mut self, args: &[(AbiSrc, TypeId)]) -> Exec<Vec<u8>> {
+        let mut parts = Vec::with_capacity(args.len());
+        for (src, ty) in args {
+            let is_static = self.abi_type(*ty)?.is_static();
+            parts.push((self.abi_encode(src, *ty)?, is_static));
+        }
+        Ok(encode_head_tail(parts))
+    }
+
+    /// Encodes a single value, dynamic values are encoded without an offset.
+    pub(super) fn abi_encode(&mut self, src: &AbiSrc, ty: TypeId) -> Exec<Vec<u8>> {
+        let db = self.db;
+        if ty.is_primitive(db) {
+            let value = match src {
+                AbiSrc::Word(value) => value.clone(),
+                AbiSrc::Ptr(ptr, space) => {
+                    self.load_sized(*space, ptr, ty.size_of(db, SLOT_SIZE))?
+                }
+            };
+            return Ok(word::to_bytes(&self.primitive_cast(&value, ty)).to_vec());
+        }
+
+        let (ptr, space) = match src {
+            AbiSrc::Ptr(ptr, space) => (ptr.clone(), *space),
+            AbiSrc::Word(_) => unreachable!(),
+        };
+
+        let abi_ty = self.abi_type(ty)?;
+        match &ty.data(db).kind {
+            TypeKind::String(_) => {
+                let len = self.load_sized(space, &ptr, 32)?;
+                let len = word::to_usize(&len)
+                    .ok_or_else(|| InterpreterError::Halt("string length out of bounds".into()))?;
+                let data = self.read(space, &(&ptr + 32u8), len)?;
+                Ok(encode_bytes(&data))
+            }
+
+            TypeKind::Array(def) if abi_ty.is_bytes() => {
+                let data = self.read(space, &ptr, def.len)?;
+                Ok(encode_bytes(&data))
+            }
+
+            TypeKind::Array(def) => {
+                let elem_size = ty.array_elem_size(db, SLOT_SIZE);
+                let elem_is_static = self.abi_type(def.elem_ty)?.is_static();
+                let mut parts = vec![];
+                for idx in 0..def.len {
+                    let elem = AbiSrc::Ptr(&ptr + idx * elem_size, space);
+                    parts.push((self.abi_encode(&elem, def.elem_ty)?, elem_is_static));
+                }
+                Ok(encode_head_tail(parts))
+            }
+
+            _ => {
+                let mut parts = vec![];
+                for idx in 0..ty.aggregate_field_num(db) {
+                    let field_ty = ty.projection_ty_imm(db, idx);
+                    if field_ty.is_zero_sized(db) {
+                        continue;
+                    }
+                    let field =
+                        AbiSrc::Ptr(&ptr + ty.aggregate_elem_offset(db, idx, SLOT_SIZE), space);
+                    let is_static = self.abi_type(field_ty)?.is_static();
+                    parts.push((self.abi_encode(&field, field_ty)?, is_static));
+                }
+                Ok(encode_head_tail(parts))
+            }
+        }
+    }
+
+    /// Decodes a sequence of values, aggregate values are allocated in memory
+    /// and returned as pointers.
+    ///
+    /// Reverts with the `Error(0x103)` of the Yul backend if the data is
+    /// malformed.
+    pub(super) fn abi_decode_seq(&mut self, data: &[u8], tys: &[TypeId]) -> Exec<Vec<Word>> {
+        let mut abi_tys = vec![];
+        for ty in tys {
+            abi_tys.push(self.abi_type(*ty)?);
+        }
+        if abi_tys.iter().all(|ty| ty.is_static()) {
+            let size: usize = abi_tys.iter().map(|ty| ty.header_size()).sum();
+            if data.len() != size {
+                return Err(self.numeric_revert("Error", ERROR_INVALID_ABI_DATA));
+            }
+        }
+
+        let mut values = vec![];
+        let mut head = 0;
+        for (ty, abi_ty) in tys.iter().zip(&abi_tys) {
+            let value = if abi_ty.is_static() {
+                self.abi_decode(data, head, *ty)?
+            } else {
+                let offset = self.decode_offset(data, head)?;
+                self.abi_decode(data, offset, *ty)?
+            };
+            values.push(value);
+            head += abi_ty.header_size();
+        }
+        Ok(values)
+    }
+
+    fn abi_decode(&mut self, data: &[u8], offset: usize, ty: TypeId) -> Exec<Word> {
+        let db = self.db;
+        if ty.is_primitive(db) {
+            let value = word::from_bytes(self.decode_slice(data, offset, 32)?);
+            if self.primitive_cast(&value, ty) != value {
+                return Err(self.numeric_revert("Error", ERROR_INVALID_ABI_DATA));
+            }
+            return Ok(value);
+        }
+
+        let ptr = self.alloc(ty.size_of(db, SLOT_SIZE))?;
+        self.abi_decode_into(data, offset, &ptr, ty)?;
+        Ok(ptr)
+    }
+
+    fn abi_decode_into(&mut self, data: &[u8], offset: usize, ptr: &Word, ty: TypeId) -> Exec<()> {
+        let db = self.db;
+        if ty.is_primitive(db) {
+            let value = self.abi_decode(data, offset, ty)?;
+            return self.store_sized(Space::Memory, ptr, &value, ty.size_of(db, SLOT_SIZE));
+        }
+
+        let abi_ty = self.abi_type(ty)?;
+        match &ty.data(db).kind {
+            TypeKind::String(max_len) => {
+                let len = self.decode_offset(data, offset)?;
+                if len > *max_len {
+                    return Err(self.numeric_revert("Error", ERROR_INVALID_ABI_DATA));
+                }
+                let bytes = self.decode_slice(data, offset + 32, len)?.to_vec();
+                self.write(Space::Memory, ptr, &word::to_bytes(&Word::from(len)))?;
+                self.write(Space::Memory, &(ptr + 32u8), &bytes)
+            }
+
+            TypeKind::Array(def) if abi_ty.is_bytes() => {
+                let len = self.decode_offset(data, offset)?;
+                if len != def.len {
+                    return Err(self.numeric_revert("Error", ERROR_INVALID_ABI_DATA));
+                }
+                let bytes = self.decode_slice(data, offset + 32, len)?.to_vec();
+                self.write(Space::Memory, ptr, &bytes)
+            }
+
+            TypeKind::Array(def) => {
+                let elem_size = ty.array_elem_size(db, SLOT_SIZE);
+                let elem_abi_ty = self.abi_type(def.elem_ty)?;
+                for idx in 0..def.len {
+                    let elem_ptr = ptr + idx * elem_size;
+                    let head = offset + idx * elem_abi_ty.header_size();
+                    let elem_offset = if elem_abi_ty.is_static() {
+                        head
+                    } else {
+                        offset + self.decode_offset(data, head)?
+                    };
+                    self.abi_decode_into(data, elem_offset, &elem_ptr, def.elem_ty)?;
+                }
+                Ok(())
+            }
+
+            _ => {
+                let mut head = offset;
+                for idx in 0..ty.aggregate_field_num(db) {
+                    let field_ty = ty.projection_ty_imm(db, idx);
+                    if field_ty.is_zero_sized(db) {
+                        continue;
+                    }
+                    let field_ptr = ptr + ty.aggregate_elem_offset(db, idx, SLOT_SIZE);
+                    let field_abi_ty = self.abi_type(field_ty)?;
+                    let field_offset = if field_abi_ty.is_static() {
+                        head
+                    } else {
+                        offset + self.decode_offset(data, head)?
+                    };
+                    self.abi_decode_into(data, field_offset, &field_ptr, field_ty)?;
+                    head += field_abi_ty.header_size();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn decode_offset(&mut self, data: &[u8], offset: usize) -> Exec<usize> {
+        let value = word::from_bytes(self.decode_slice(data, offset, 32)?);
+        match word::to_usize(&value).filter(|value| *value <= data.len()) {
+            Some(value) => Ok(value),
+            None => Err(self.numeric_revert("Error", ERROR_INVALID_ABI_DATA)),
+        }
+    }
+
+    fn decode_slice<'a>(&mut self, data: &'a [u8], offset: usize, len: usize) -> Exec<&'a [u8]> {
+        match data.get(offset..offset + len) {
+            Some(slice) => Ok(slice),
+            None => Err(self.numeric_revert("Error", ERROR_INVALID_ABI_DATA)),
+        }
+    }
+}
+
+/// Encodes `len ++ data` with the data right padded to a multiple of 32.
+fn encode_bytes(data: &[u8]) -> Vec<u8> {
+    let mut enc = word::to_bytes(&Word::from(data.len())).to_vec();
+    enc.extend_from_slice(data);
+    enc.resize(32 + data.len().div_ceil(32) * 32, 0);
+    enc
+}
+
+/// Lays out encoded values as a tuple, static values are placed in the head
+/// and dynamic values in the tail with their offsets in the head.
+fn encode_head_tail(parts: Vec<(Vec<u8>, bool)>) -> Vec<u8> {
+    let head_size: usize = parts
+        .iter()
+        .map(|(enc, is_static)| if *is_static { enc.len() } else { 32 })
+        .sum();
+
+    let mut head = Vec::with_capacity(head_size);
+    let mut tail = vec![];
+    for (enc, is_static) in parts {
+        if is_static {
+            head.extend(enc);
+        } else {
+            head.extend_from_slice(&word::to_bytes(&Word::from(head_size + tail.len())));
+            tail.extend(enc);
+        }
+    }
+    head.extend(tail);
+    head
+}
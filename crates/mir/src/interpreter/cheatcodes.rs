@@ -0,0 +1,234 @@
+//! The cheatcodes of `std::testing`, which change the environment that a test
+//! runs in. Cheatcodes are calls to [`CHEATCODE_ADDRESS`], which the machine
+//! answers itself with the same effects as the test runner.
+//!
+//! [`CHEATCODE_ADDRESS`]: super::CHEATCODE_ADDRESS
+
+use fe_common::utils::keccak;
+
+use super::{
+    machine::{CallResult, Machine},
+    state::{self, Address, Block, Log},
+    word::{self, Word},
+    TEST_ADDRESS,
+};
+
+/// `warp(uint256)`
+const WARP: [u8; 4] = [0xe5, 0xd6, 0xbf, 0x02];
+/// `roll(uint256)`
+const ROLL: [u8; 4] = [0x1f, 0x7b, 0x4f, 0x30];
+/// `deal(address,uint256)`
+const DEAL: [u8; 4] = [0xc8, 0x8a, 0x5e, 0x6d];
+/// `prank(address)`
+const PRANK: [u8; 4] = [0xca, 0x66, 0x9f, 0xa7];
+/// `startPrank(address)`
+const START_PRANK: [u8; 4] = [0x06, 0x44, 0x7d, 0x56];
+/// `stopPrank()`
+const STOP_PRANK: [u8; 4] = [0x90, 0xc5, 0x01, 0x3b];
+/// `nextCallValue(uint256)`
+const NEXT_CALL_VALUE: [u8; 4] = [0xe3, 0xf2, 0x61, 0xb8];
+/// `snapshot()`
+const SNAPSHOT: [u8; 4] = [0x97, 0x11, 0x71, 0x5a];
+/// `revertTo(uint256)`
+const REVERT_TO: [u8; 4] = [0x44, 0xd7, 0xf0, 0xa4];
+/// `expectEmit()`
+const EXPECT_EMIT: [u8; 4] = [0x44, 0x0e, 0xd1, 0x0d];
+
+/// The effects of the cheatcodes called by a test on its calls and contract
+/// creations.
+#[derive(Default)]
+pub(super) struct Cheatcodes {
+    prank: Option<Prank>,
+    next_call_value: Option<Word>,
+    snapshots: Vec<Snapshot>,
+    expected_emit: Option<ExpectedEmit>,
+}
+
+/// The `msg.sender` of calls made by the test.
+struct Prank {
+    sender: Address,
+    /// Whether the prank ends after the next call, as opposed to when
+    /// `stop_prank` is called.
+    single_call: bool,
+}
+
+/// An event that the next call or contract creation of the test must emit.
+#[derive(Default)]
+struct ExpectedEmit {
+    /// The event, which is the next one that the test emits itself after
+    /// calling `expect_emit`.
+    log: Option<Log>,
+    /// The call depth of the test while it makes the call that must emit the
+    /// event.
+    depth: Option<usize>,
+    found: bool,
+}
+
+struct Snapshot {
+    /// The call depth of the frame that took the snapshot.
+    depth: usize,
+    state: state::Snapshot,
+    block: Block,
+}
+
+impl<'db> Machine<'db> {
+    /// Executes the cheatcode encoded in `input`. A cheatcode that fails
+    /// reverts with its reason, like the test runner.
+    pub(super) fn cheatcode(&mut self, input: &[u8]) -> CallResult {
+        match self.exec_cheatcode(input) {
+            Ok(output) => CallResult::success(output),
+            Err(reason) => CallResult::failure(revert_reason(reason)),
+        }
+    }
+
+    fn exec_cheatcode(&mut self, input: &[u8]) -> Result<Vec<u8>, String> {
+        if input.len() < 4 {
+            return Err("invalid cheatcode call".into());
+        }
+        let (selector, args) = input.split_at(4);
+        let selector = <[u8; 4]>::try_from(selector).unwrap();
+        let words = |count: usize| -> Result<Vec<Word>, String> {
+            if args.len() != count * 32 {
+                return Err("invalid cheatcode arguments".into());
+            }
+            Ok(args.chunks(32).map(word::from_bytes).collect())
+        };
+
+        let depth = self.context_depth();
+        let cheatcodes = &mut self.cheatcodes;
+        match selector {
+            WARP => self.block.timestamp = words(1)?.remove(0),
+            ROLL => self.block.number = words(1)?.remove(0),
+            DEAL => {
+                let words = words(2)?;
+                let address = word::to_address(&words[0]);
+                self.world.account_mut(address).balance = words[1].clone();
+            }
+            PRANK | START_PRANK => {
+                cheatcodes.prank = Some(Prank {
+                    sender: word::to_address(&words(1)?[0]),
+                    single_call: selector == PRANK,
+                })
+            }
+            STOP_PRANK => {
+                words(0)?;
+                cheatcodes.prank = None
+            }
+            NEXT_CALL_VALUE => cheatcodes.next_call_value = Some(words(1)?.remove(0)),
+            SNAPSHOT => {
+                words(0)?;
+                cheatcodes.snapshots.push(Snapshot {
+                    depth,
+                    state: self.world.snapshot(),
+                    block: self.block.clone(),
+                });
+                let id = Word::from(cheatcodes.snapshots.len() - 1);
+                return Ok(word::to_bytes(&id).to_vec());
+            }
+            REVERT_TO => {
+                let id = words(1)?.remove(0);
+                let snapshot = word::to_usize(&id)
+                    .and_then(|id| cheatcodes.snapshots.get(id))
+                    .ok_or_else(|| format!("unknown snapshot {id}"))?;
+                if snapshot.depth != depth {
+                    return Err(format!(
+                        "snapshot {id} must be reverted to by the function that took it"
+                    ));
+                }
+                self.world.restore(snapshot.state.clone());
+                self.block = snapshot.block.clone();
+            }
+            EXPECT_EMIT => {
+                words(0)?;
+                cheatcodes.expected_emit = Some(ExpectedEmit::default())
+            }
+            _ => {
+                let selector: String = selector.iter().map(|byte| format!("{byte:02x}")).collect();
+                return Err(format!("unknown cheatcode 0x{selector}"));
+            }
+        }
+        Ok(vec![])
+    }
+
+    /// Returns the caller and the value of the call or contract creation that
+    /// the current context is about to make with `value`. The cheatcodes
+    /// change them if the caller is the test.
+    pub(super) fn outgoing_call(&mut self, value: Word) -> (Address, Word) {
+        let caller = self.address();
+        if caller != TEST_ADDRESS {
+            return (caller, value);
+        }
+
+        let depth = self.context_depth();
+        let cheatcodes = &mut self.cheatcodes;
+        if let Some(expected) = &mut cheatcodes.expected_emit {
+            if expected.log.is_some() && expected.depth.is_none() {
+                expected.depth = Some(depth)
+            }
+        }
+        let sender = match &cheatcodes.prank {
+            Some(prank) => {
+                let sender = prank.sender;
+                if prank.single_call {
+                    cheatcodes.prank = None
+                }
+                sender
+            }
+            None => caller,
+        };
+        let value = cheatcodes.next_call_value.take().unwrap_or(value);
+        (sender, value)
+    }
+
+    /// Checks that the expected event was emitted by the call or contract
+    /// creation that ended at call depth `depth`. At the end of the test,
+    /// i.e. at depth 0, every `expect_emit` must have been checked. Returns
+    /// the revert data of the failed check.
+    pub(super) fn check_expected_emit(&mut self, depth: usize) -> Result<(), Vec<u8>> {
+        let expected = match &self.cheatcodes.expected_emit {
+            Some(expected) if expected.depth == Some(depth) || depth == 0 => {
+                self.cheatcodes.expected_emit.take().unwrap()
+            }
+            _ => return Ok(()),
+        };
+        let reason = if expected.found {
+            return Ok(());
+        } else if expected.log.is_none() {
+            "`expect_emit` must be followed by the expected event"
+        } else if expected.depth.is_none() {
+            "`expect_emit` must be followed by a call"
+        } else {
+            "expected event was not emitted"
+        };
+        Err(revert_reason(reason.into()))
+    }
+
+    /// Adds `log` to the logs of the execution, and compares it with the
+    /// expected event.
+    pub(super) fn log(&mut self, log: Log) {
+        if let Some(expected) = &mut self.cheatcodes.expected_emit {
+            match &expected.log {
+                None if log.address == TEST_ADDRESS => expected.log = Some(log.clone()),
+                Some(event)
+                    if expected.depth.is_some()
+                        && log.topics == event.topics
+                        && log.data == event.data =>
+                {
+                    expected.found = true
+                }
+                _ => {}
+            }
+        }
+        self.world.logs.push(log);
+    }
+}
+
+/// Encodes `reason` as the output of `revert Error(string)`, as solc does.
+fn revert_reason(reason: String) -> Vec<u8> {
+    let mut output = keccak::full_as_bytes(b"Error(string)")[..4].to_vec();
+    output.extend(word::to_bytes(&Word::from(32u8)));
+    output.extend(word::to_bytes(&Word::from(reason.len())));
+    output.extend(reason.as_bytes());
+    output.resize(4 + (output.len() - 4).div_ceil(32) * 32, 0);
+    output
+}
@@ -0,0 +1,249 @@
+use fe_common::utils::keccak;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::ir::inst::YulIntrinsicOp;
+
+use super::{
+    machine::{CallResult, Exec, Halt, Machine, Space},
+    state::Log,
+    word::{self, Word},
+    InterpreterError, CHEATCODE_ADDRESS,
+};
+
+/// The highest address reserved for precompiled contracts.
+const MAX_PRECOMPILE: u8 = 0x0a;
+
+impl<'db> Machine<'db> {
+    /// Executes a Yul intrinsic, returns the pushed value if the intrinsic
+    /// has one.
+    pub(super) fn exec_intrinsic(
+        &mut self,
+        op: YulIntrinsicOp,
+        args: &[Word],
+    ) -> Exec<Option<Word>> {
+        use YulIntrinsicOp::*;
+
+        if let Some(value) = word::intrinsic(op, args) {
+            return Ok(Some(value));
+        }
+
+        let arg = |idx: usize| &args[idx];
+        let value = match op {
+            Stop => return Err(Halt::Stop),
+            Keccak256 => {
+                let data = self.read_memory(arg(0), arg(1))?;
+                word::from_bytes(&keccak::full_as_bytes(&data))
+            }
+            Pop => return Ok(None),
+            Mload => self.memory().load(arg(0))?,
+            Mstore => {
+                self.memory().store(arg(0), arg(1))?;
+                return Ok(None);
+            }
+            Mstore8 => {
+                let byte = word::to_bytes(arg(1))[31];
+                self.memory().write(arg(0), &[byte])?;
+                return Ok(None);
+            }
+            Sload => self.sload(arg(0)),
+            Sstore => {
+                self.sstore(arg(0).clone(), arg(1).clone());
+                return Ok(None);
+            }
+            Tload => self.tload(arg(0)),
+            Tstore => {
+                self.tstore(arg(0).clone(), arg(1).clone());
+                return Ok(None);
+            }
+            Mcopy => {
+                let data = self.read_memory(arg(1), arg(2))?;
+                self.memory().write(arg(0), &data)?;
+                return Ok(None);
+            }
+            Msize => Word::from(self.memory().size()),
+            Gas => Word::from(u64::MAX),
+            Address => word::from_address(&self.address()),
+            Balance => self.world.balance(&word::to_address(arg(0))),
+            Selfbalance => self.world.balance(&self.address()),
+            Caller => word::from_address(&self.caller()),
+            Callvalue => self.callvalue(),
+            Calldataload => {
+                let data = copy_padded(self.calldata(), arg(0), 32);
+                word::from_bytes(&data)
+            }
+            Calldatasize => Word::from(self.calldata().len()),
+            Calldatacopy => {
+                let len = to_len(arg(2))?;
+                let data = copy_padded(self.calldata(), arg(1), len);
+                self.memory().write(arg(0), &data)?;
+                return Ok(None);
+            }
+            Returndatasize => Word::from(self.return_data().len()),
+            Returndatacopy => {
+                let len = to_len(arg(2))?;
+                let data = word::to_usize(arg(1))
+                    .and_then(|offset| self.return_data().get(offset..offset.checked_add(len)?))
+                    .ok_or_else(|| {
+                        InterpreterError::Halt("return data access out of bounds".into())
+                    })?
+                    .to_vec();
+                self.memory().write(arg(0), &data)?;
+                return Ok(None);
+            }
+            // Only contracts deployed by the interpreter have code, which is
+            // never inspected byte by byte.
+            Extcodesize => {
+                let address = word::to_address(arg(0));
+                let has_code = self
+                    .world
+                    .account(&address)
+                    .is_some_and(|account| account.code.is_some());
+                word::from_bool(has_code)
+            }
+            Extcodehash => {
+                let address = word::to_address(arg(0));
+                match self.world.account(&address) {
+                    None => Word::zero(),
+                    Some(account) if account.code.is_none() => {
+                        word::from_bytes(&keccak::full_as_bytes(&[]))
+                    }
+                    Some(_) => return Err(unsupported(op)),
+                }
+            }
+            Call => {
+                let success =
+                    self.raw_call(arg(1), arg(2).clone(), (arg(3), arg(4)), (arg(5), arg(6)))?;
+                word::from_bool(success)
+            }
+            Staticcall => {
+                let success =
+                    self.raw_call(arg(1), Word::zero(), (arg(2), arg(3)), (arg(4), arg(5)))?;
+                word::from_bool(success)
+            }
+            Return => {
+                let data = self.read_memory(arg(0), arg(1))?;
+                return Err(Halt::Return(data));
+            }
+            Revert => {
+                let data = self.read_memory(arg(0), arg(1))?;
+                return Err(Halt::Revert(data));
+            }
+            Invalid => return Err(InterpreterError::Halt("invalid instruction".into()).into()),
+            Log0 | Log1 | Log2 | Log3 | Log4 => {
+                let data = self.read_memory(arg(0), arg(1))?;
+                let topics = args[2..].iter().map(word::to_bytes).collect();
+                self.log(Log {
+                    address: self.address(),
+                    topics,
+                    data,
+                });
+                return Ok(None);
+            }
+            Chainid => Word::one(),
+            Timestamp => self.block.timestamp.clone(),
+            Number => self.block.number.clone(),
+            Gaslimit => word::max(),
+            Basefee | Origin | Gasprice | Blockhash | Coinbase | Prevrandao => Word::zero(),
+            Pc | Codesize | Codecopy | Extcodecopy | Create | Create2 | Callcode | Delegatecall
+            | Selfdestruct => return Err(unsupported(op)),
+            _ => unreachable!("`{op}` is evaluated by `word::intrinsic`"),
+        };
+
+        Ok(Some(value))
+    }
+
+    fn read_memory(&mut self, offset: &Word, len: &Word) -> Exec<Vec<u8>> {
+        let len = to_len(len)?;
+        self.read(Space::Memory, offset, len)
+    }
+
+    /// Executes `call` or `staticcall` and copies the output to memory.
+    fn raw_call(
+        &mut self,
+        to: &Word,
+        value: Word,
+        (in_offset, in_len): (&Word, &Word),
+        (out_offset, out_len): (&Word, &Word),
+    ) -> Exec<bool> {
+        let input = self.read_memory(in_offset, in_len)?;
+        let to = word::to_address(to);
+        let result = if to[..19].iter().all(|b| *b == 0) && (1..=MAX_PRECOMPILE).contains(&to[19]) {
+            self.precompile(to[19], &input)?
+        } else if to == CHEATCODE_ADDRESS {
+            self.cheatcode(&input)
+        } else {
+            self.message_call(to, value, input)?
+        };
+
+        let out_len = to_len(out_len)?.min(result.output.len());
+        self.memory().write(out_offset, &result.output[..out_len])?;
+        let success = result.success;
+        self.set_return_data(result.output);
+        Ok(success)
+    }
+
+    fn precompile(&mut self, address: u8, input: &[u8]) -> Exec<CallResult> {
+        let output = match address {
+            // identity
+            0x04 => input.to_vec(),
+            // modexp
+            0x05 => modexp(input),
+            _ => {
+                return Err(InterpreterError::Unsupported(format!(
+                    "precompiled contract at address {address:#x}"
+                ))
+                .into())
+            }
+        };
+        Ok(CallResult::success(output))
+    }
+}
+
+fn unsupported(op: YulIntrinsicOp) -> Halt {
+    InterpreterError::Unsupported(format!("`{op}`")).into()
+}
+
+fn to_len(len: &Word) -> Result<usize, InterpreterError> {
+    word::to_usize(len).ok_or_else(|| InterpreterError::Halt("length out of bounds".into()))
+}
+
+/// Reads `len` bytes of `data` at `offset`, bytes out of bounds are zero.
+fn copy_padded(data: &[u8], offset: &Word, len: usize) -> Vec<u8> {
+    let mut result = vec![0; len];
+    if let Some(offset) = word::to_usize(offset).filter(|offset| *offset < data.len()) {
+        let n = (data.len() - offset).min(len);
+        result[..n].copy_from_slice(&data[offset..offset + n]);
+    }
+    result
+}
+
+fn modexp(input: &[u8]) -> Vec<u8> {
+    let word_at = |offset: usize| word::from_bytes(&copy_padded(input, &Word::from(offset), 32));
+    let lens: Option<Vec<usize>> = (0..3).map(|idx| word_at(idx * 32).to_usize()).collect();
+    let (base_len, exp_len, mod_len) = match lens.as_deref() {
+        Some(&[base_len, exp_len, mod_len]) => (base_len, exp_len, mod_len),
+        _ => return vec![],
+    };
+
+    let mut offset = 96;
+    let mut read = |len: usize| {
+        let bytes = copy_padded(input, &Word::from(offset), len);
+        offset += len;
+        word::from_bytes(&bytes)
+    };
+    let base = read(base_len);
+    let exp = read(exp_len);
+    let modulus = read(mod_len);
+
+    let result = if modulus.is_zero() {
+        Word::zero()
+    } else {
+        base.modpow(&exp, &modulus)
+    };
+    let bytes = result.to_bytes_be();
+    let mut output = vec![0; mod_len];
+    if bytes.len() <= mod_len && !result.is_zero() {
+        output[mod_len - bytes.len()..].copy_from_slice(&bytes);
+    }
+    output
+}
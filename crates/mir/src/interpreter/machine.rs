@@ -0,0 +1,1412 @@
+use std::rc::Rc;
+
+use fe_analyzer::namespace::items::{self as analyzer_items, ContractId};
+use fe_common::utils::keccak;
+use fxhash::FxHashMap;
+use num_bigint::BigInt;
+use num_traits::Zero;
+
+use crate::{
+    db::MirDb,
+    ir::{
+        constant::ConstantValue,
+        function::Linkage,
+        inst::{BinOp, CallType, CastKind, InstKind, UnOp},
+        types::TupleDef,
+        value::AssignableValue,
+        BasicBlockId, FunctionBody, FunctionId, InstId, Type, TypeId, TypeKind, Value, ValueId,
+    },
+};
+
+use super::{
+    abi::AbiSrc,
+    cheatcodes::Cheatcodes,
+    state::{Address, Block, Memory, World},
+    word::{self, Word},
+    InterpreterError, Outcome, TEST_ADDRESS,
+};
+
+/// The slot size the Yul backend uses to lay out aggregate types.
+pub(super) const SLOT_SIZE: usize = 32;
+
+const MAX_CALL_DEPTH: usize = 1024;
+
+const FREE_MEMORY_ADDRESS_STORE: u8 = 0x40;
+const FREE_MEMORY_START: u8 = 0x60;
+
+const PANIC_OVERFLOW: u64 = 0x11;
+const PANIC_ZERO_DIVISION: u64 = 0x12;
+const PANIC_OUT_OF_BOUNDS: u64 = 0x32;
+
+/// The reason an execution stopped before the current function returned.
+pub(super) enum Halt {
+    Stop,
+    Return(Vec<u8>),
+    Revert(Vec<u8>),
+    Error(InterpreterError),
+}
+
+impl From<InterpreterError> for Halt {
+    fn from(err: InterpreterError) -> Self {
+        Self::Error(err)
+    }
+}
+
+pub(super) type Exec<T> = Result<T, Halt>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Space {
+    Memory,
+    Storage,
+}
+
+/// The environment of a message call.
+struct CallContext {
+    address: Address,
+    caller: Address,
+    value: Word,
+    calldata: Vec<u8>,
+    memory: Memory,
+    return_data: Vec<u8>,
+}
+
+impl CallContext {
+    fn new(address: Address, caller: Address, value: Word, calldata: Vec<u8>) -> Self {
+        Self {
+            address,
+            caller,
+            value,
+            calldata,
+            memory: Memory::default(),
+            return_data: vec![],
+        }
+    }
+}
+
+pub(super) struct CallResult {
+    pub(super) success: bool,
+    pub(super) output: Vec<u8>,
+}
+
+impl CallResult {
+    pub(super) fn success(output: Vec<u8>) -> Self {
+        Self {
+            success: true,
+            output,
+        }
+    }
+
+    pub(super) fn failure(output: Vec<u8>) -> Self {
+        Self {
+            success: false,
+            output,
+        }
+    }
+}
+
+struct Frame<'a> {
+    body: &'a FunctionBody,
+    values: FxHashMap<ValueId, Word>,
+}
+
+enum Flow {
+    Continue,
+    Jump(BasicBlockId),
+    Return(Option<Word>),
+}
+
+pub(super) struct Machine<'db> {
+    pub(super) db: &'db dyn MirDb,
+    pub(super) world: World,
+    pub(super) block: Block,
+    pub(super) cheatcodes: Cheatcodes,
+    contexts: Vec<CallContext>,
+    depth: usize,
+    /// Runs the optimized function bodies instead of the lowered ones.
+    optimize: bool,
+}
+
+impl<'db> Machine<'db> {
+    pub(super) fn new(db: &'db dyn MirDb, optimize: bool) -> Self {
+        Self {
+            db,
+            world: World::default(),
+            block: Block::default(),
+            cheatcodes: Cheatcodes::default(),
+            contexts: vec![],
+            depth: 0,
+            optimize,
+        }
+    }
+
+    pub(super) fn run_test(
+        mut self,
+        test: FunctionId,
+        calldata: Vec<u8>,
+    ) -> Result<Outcome, InterpreterError> {
+        self.world.account_mut(TEST_ADDRESS);
+        self.contexts.push(CallContext::new(
+            TEST_ADDRESS,
+            Address::default(),
+            Word::zero(),
+            calldata.clone(),
+        ));
+
+        let result = self
+            .decode_args(test, &calldata)
+            .and_then(|args| self.call_function(test, args));
+        let checked = self.check_expected_emit(0);
+        match result {
+            Ok(_) | Err(Halt::Stop) | Err(Halt::Return(_)) => match checked {
+                Ok(()) => Ok(Outcome::Success {
+                    logs: self.world.logs,
+                }),
+                Err(data) => Ok(Outcome::Revert { data }),
+            },
+            Err(Halt::Revert(data)) => Ok(Outcome::Revert { data }),
+            Err(Halt::Error(err)) => Err(err),
+        }
+    }
+
+    fn cx(&self) -> &CallContext {
+        self.contexts.last().unwrap()
+    }
+
+    fn cx_mut(&mut self) -> &mut CallContext {
+        self.contexts.last_mut().unwrap()
+    }
+
+    pub(super) fn address(&self) -> Address {
+        self.cx().address
+    }
+
+    /// The number of message calls and contract creations that are being
+    /// executed, including the test.
+    pub(super) fn context_depth(&self) -> usize {
+        self.contexts.len()
+    }
+
+    pub(super) fn caller(&self) -> Address {
+        self.cx().caller
+    }
+
+    pub(super) fn callvalue(&self) -> Word {
+        self.cx().value.clone()
+    }
+
+    pub(super) fn calldata(&self) -> &[u8] {
+        &self.cx().calldata
+    }
+
+    pub(super) fn return_data(&self) -> &[u8] {
+        &self.cx().return_data
+    }
+
+    pub(super) fn set_return_data(&mut self, data: Vec<u8>) {
+        self.cx_mut().return_data = data;
+    }
+
+    pub(super) fn memory(&mut self) -> &mut Memory {
+        &mut self.cx_mut().memory
+    }
+
+    pub(super) fn read(&mut self, space: Space, ptr: &Word, len: usize) -> Exec<Vec<u8>> {
+        match space {
+            Space::Memory => Ok(self.memory().read(ptr, len)?),
+            Space::Storage => {
+                let address = self.address();
+                Ok(self.world.account_mut(address).storage.read(ptr, len))
+            }
+        }
+    }
+
+    pub(super) fn write(&mut self, space: Space, ptr: &Word, data: &[u8]) -> Exec<()> {
+        match space {
+            Space::Memory => Ok(self.memory().write(ptr, data)?),
+            Space::Storage => {
+                let address = self.address();
+                self.world.account_mut(address).storage.write(ptr, data);
+                Ok(())
+            }
+        }
+    }
+
+    pub(super) fn sload(&mut self, slot: &Word) -> Word {
+        let address = self.address();
+        self.world.account_mut(address).storage.load(slot)
+    }
+
+    pub(super) fn sstore(&mut self, slot: Word, value: Word) {
+        let address = self.address();
+        self.world.account_mut(address).storage.store(slot, value)
+    }
+
+    pub(super) fn tload(&mut self, slot: &Word) -> Word {
+        let address = self.address();
+        self.world.account_mut(address).transient.load(slot)
+    }
+
+    pub(super) fn tstore(&mut self, slot: Word, value: Word) {
+        let address = self.address();
+        self.world.account_mut(address).transient.store(slot, value)
+    }
+
+    /// Loads a value of `size` bytes from `ptr`.
+    pub(super) fn load_sized(&mut self, space: Space, ptr: &Word, size: usize) -> Exec<Word> {
+        match space {
+            Space::Memory => Ok(word::from_bytes(&self.memory().read(ptr, size)?)),
+            Space::Storage => {
+                let address = self.address();
+                Ok(self
+                    .world
+                    .account_mut(address)
+                    .storage
+                    .load_value(ptr, size))
+            }
+        }
+    }
+
+    /// Stores the lowest `size` bytes of `value` at `ptr`.
+    pub(super) fn store_sized(
+        &mut self,
+        space: Space,
+        ptr: &Word,
+        value: &Word,
+        size: usize,
+    ) -> Exec<()> {
+        match space {
+            Space::Memory => {
+                let bytes = word::to_bytes(value);
+                Ok(self.memory().write(ptr, &bytes[32 - size.min(32)..])?)
+            }
+            Space::Storage => {
+                let address = self.address();
+                self.world
+                    .account_mut(address)
+                    .storage
+                    .store_value(ptr, value, size);
+                Ok(())
+            }
+        }
+    }
+
+    fn ptr_load(&mut self, ptr: &Word, ptr_ty: TypeId) -> Exec<Word> {
+        let size = ptr_ty.deref(self.db).size_of(self.db, SLOT_SIZE);
+        self.load_sized(self.space(ptr_ty), ptr, size)
+    }
+
+    fn ptr_store(&mut self, ptr: &Word, value: &Word, ptr_ty: TypeId) -> Exec<()> {
+        let size = ptr_ty.deref(self.db).size_of(self.db, SLOT_SIZE);
+        self.store_sized(self.space(ptr_ty), ptr, value, size)
+    }
+
+    pub(super) fn ptr_copy(
+        &mut self,
+        src: &Word,
+        src_space: Space,
+        dst: &Word,
+        dst_space: Space,
+        size: usize,
+    ) -> Exec<()> {
+        // Storage to storage copies are done slot by slot.
+        let size = if src_space == Space::Storage && dst_space == Space::Storage {
+            size.div_ceil(32) * 32
+        } else {
+            size
+        };
+        let data = self.read(src_space, src, size)?;
+        self.write(dst_space, dst, &data)
+    }
+
+    /// Copies a value of `ty` in the same manner as the Yul backend: a value
+    /// with packed primitives is copied primitive by primitive between memory
+    /// and storage, since the primitives are at different positions in a
+    /// memory word and in a storage slot.
+    fn value_copy(
+        &mut self,
+        src: &Word,
+        src_space: Space,
+        dst: &Word,
+        dst_space: Space,
+        ty: TypeId,
+    ) -> Exec<()> {
+        let db = self.db;
+        if src_space == dst_space || !ty.has_packed_fields(db, SLOT_SIZE) {
+            let size = ty.size_of(db, SLOT_SIZE);
+            return self.ptr_copy(src, src_space, dst, dst_space, size);
+        }
+
+        let offset = |ptr: &Word, offset: usize| (ptr + offset) % word::modulus();
+        match &ty.data(db).kind {
+            TypeKind::Array(def) => {
+                let elem_size = ty.array_elem_size(db, SLOT_SIZE);
+                for idx in 0..def.len {
+                    let (src, dst) = (offset(src, idx * elem_size), offset(dst, idx * elem_size));
+                    self.value_copy(&src, src_space, &dst, dst_space, def.elem_ty)?;
+                }
+            }
+
+            TypeKind::Enum(def) => {
+                let disc_size = ty.enum_disc_type(db).size_of(db, SLOT_SIZE);
+                let disc = self.load_sized(src_space, src, disc_size)?;
+                self.store_sized(dst_space, dst, &disc, disc_size)?;
+
+                let variant = word::to_usize(&disc).and_then(|tag| def.variants.get(tag));
+                if let Some(variant) = variant {
+                    let data_offset = ty.enum_data_offset(db, SLOT_SIZE);
+                    let (src, dst) = (offset(src, data_offset), offset(dst, data_offset));
+                    self.value_copy(&src, src_space, &dst, dst_space, variant.ty)?;
+                }
+            }
+
+            _ if ty.is_aggregate(db) => {
+                for idx in 0..ty.aggregate_field_num(db) {
+                    let field_ty = ty.projection_ty_imm(db, idx);
+                    let field_offset = ty.aggregate_elem_offset(db, idx, SLOT_SIZE);
+                    let (src, dst) = (offset(src, field_offset), offset(dst, field_offset));
+                    self.value_copy(&src, src_space, &dst, dst_space, field_ty)?;
+                }
+            }
+
+            _ => {
+                let size = ty.size_of(db, SLOT_SIZE);
+                let value = self.load_sized(src_space, src, size)?;
+                self.store_sized(dst_space, dst, &value, size)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn alloc(&mut self, size: usize) -> Exec<Word> {
+        let ptr = self.avail()?;
+        self.memory()
+            .store(&Word::from(FREE_MEMORY_ADDRESS_STORE), &(&ptr + size))?;
+        Ok(ptr)
+    }
+
+    pub(super) fn avail(&mut self) -> Exec<Word> {
+        let ptr = self.memory().load(&Word::from(FREE_MEMORY_ADDRESS_STORE))?;
+        if ptr.is_zero() {
+            Ok(Word::from(FREE_MEMORY_START))
+        } else {
+            Ok(ptr)
+        }
+    }
+
+    pub(super) fn space(&self, ptr_ty: TypeId) -> Space {
+        if ptr_ty.is_sptr(self.db) {
+            Space::Storage
+        } else {
+            Space::Memory
+        }
+    }
+
+    pub(super) fn u256_ty(&self) -> TypeId {
+        self.db
+            .mir_intern_type(Type::new(TypeKind::U256, None).into())
+    }
+
+    /// Returns the type a value has after the legalization of the Yul
+    /// backend, i.e. aggregate values are represented by pointers.
+    pub(super) fn legalized_ty(&self, ty: TypeId) -> TypeId {
+        if ty.is_contract(self.db) {
+            ty.make_sptr(self.db)
+        } else if ty.is_aggregate(self.db) || ty.is_string(self.db) {
+            ty.make_mptr(self.db)
+        } else {
+            ty
+        }
+    }
+
+    /// Returns the legalized return type of a function, `None` if the
+    /// function doesn't return a value at runtime.
+    pub(super) fn legalized_return_ty(&self, func: FunctionId) -> Option<TypeId> {
+        let ret_ty = func.signature(self.db).return_type?;
+        if ret_ty.is_contract(self.db) || ret_ty.deref(self.db).is_zero_sized(self.db) {
+            None
+        } else {
+            Some(self.legalized_ty(ret_ty))
+        }
+    }
+
+    pub(super) fn primitive_cast(&self, value: &Word, ty: TypeId) -> Word {
+        let size = ty.size_of(self.db, SLOT_SIZE);
+        if ty.is_signed(self.db) {
+            word::sign_extend(value, size)
+        } else {
+            word::mask(value, size)
+        }
+    }
+
+    fn extend(&self, value: Word, ty: TypeId) -> Word {
+        if ty.is_primitive(self.db) {
+            self.primitive_cast(&value, ty)
+        } else {
+            value
+        }
+    }
+
+    pub(super) fn call_function(
+        &mut self,
+        func: FunctionId,
+        args: Vec<Word>,
+    ) -> Exec<Option<Word>> {
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(InterpreterError::Halt("call depth limit exceeded".into()).into());
+        }
+
+        self.depth += 1;
+        let body = if self.optimize {
+            func.optimized_body(self.db)
+        } else {
+            func.body(self.db)
+        };
+        let result = self.exec_body(&body, args);
+        self.depth -= 1;
+        result
+    }
+
+    fn exec_body(&mut self, body: &Rc<FunctionBody>, args: Vec<Word>) -> Exec<Option<Word>> {
+        let mut frame = Frame {
+            body,
+            values: body.store.func_args().zip(args).collect(),
+        };
+
+        let mut block = body.order.entry();
+        loop {
+            let mut dest = None;
+            for inst in body.order.iter_inst(block) {
+                match self.exec_inst(&mut frame, inst)? {
+                    Flow::Continue => {}
+                    Flow::Jump(block) => {
+                        dest = Some(block);
+                        break;
+                    }
+                    Flow::Return(value) => return Ok(value),
+                }
+            }
+
+            block = dest
+                .ok_or_else(|| InterpreterError::Halt("basic block without a terminator".into()))?;
+        }
+    }
+
+    fn exec_inst(&mut self, frame: &mut Frame, inst: InstId) -> Exec<Flow> {
+        let db = self.db;
+        let body = frame.body;
+        let result = body.store.inst_result(inst);
+
+        // Temporaries are redeclared each time their defining instruction is
+        // executed, which gives aggregates a fresh memory allocation.
+        if let Some(AssignableValue::Value(value)) = result {
+            if !frame.values.contains_key(value)
+                || matches!(body.store.value_data(*value), Value::Temporary { .. })
+            {
+                self.declare(frame, *value)?;
+            }
+        }
+
+        match &body.store.inst_data(inst).kind {
+            InstKind::Declare { local } => self.declare(frame, *local)?,
+
+            InstKind::Unary { op, value } => {
+                let result = result.unwrap();
+                let operand = self.value(frame, *value)?;
+                let value = match op {
+                    UnOp::Not => word::from_bool(operand.is_zero()),
+                    UnOp::Inv => word::max() ^ operand,
+                    UnOp::Neg => {
+                        if body.store.value_data(*value).is_imm() {
+                            word::from_bigint(&-BigInt::from(operand))
+                        } else {
+                            let ty = body.store.value_ty(*value);
+                            self.checked_arith(BinOp::Sub, &Word::zero(), &operand, ty)?
+                        }
+                    }
+                };
+                let result_ty = self.assignable_ty(frame, result).deref(db);
+                self.assign(frame, result, value, result_ty)?;
+            }
+
+            InstKind::Binary { op, lhs, rhs } => {
+                let result = result.unwrap();
+                let result_ty = self.assignable_ty(frame, result).deref(db);
+                let is_lhs_signed = body.store.value_ty(*lhs).is_signed(db);
+                let lhs = self.value(frame, *lhs)?;
+                let rhs = self.value(frame, *rhs)?;
+                let value = match op {
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod | BinOp::Pow => {
+                        self.checked_arith(*op, &lhs, &rhs, result_ty)?
+                    }
+                    BinOp::Shl => word::shl(&rhs, &lhs),
+                    BinOp::Shr if result_ty.is_signed(db) => word::sar(&rhs, &lhs),
+                    BinOp::Shr => word::shr(&rhs, &lhs),
+                    BinOp::BitOr | BinOp::LogicalOr => lhs | rhs,
+                    BinOp::BitXor => lhs ^ rhs,
+                    BinOp::BitAnd | BinOp::LogicalAnd => lhs & rhs,
+                    BinOp::Eq => word::from_bool(lhs == rhs),
+                    BinOp::Ne => word::from_bool(lhs != rhs),
+                    BinOp::Ge | BinOp::Gt | BinOp::Le | BinOp::Lt => {
+                        let ordering = if is_lhs_signed {
+                            word::to_signed(&lhs).cmp(&word::to_signed(&rhs))
+                        } else {
+                            lhs.cmp(&rhs)
+                        };
+                        word::from_bool(match op {
+                            BinOp::Ge => ordering.is_ge(),
+                            BinOp::Gt => ordering.is_gt(),
+                            BinOp::Le => ordering.is_le(),
+                            _ => ordering.is_lt(),
+                        })
+                    }
+                };
+                self.assign(frame, result, value, result_ty)?;
+            }
+
+            InstKind::Cast { kind, value, to } => {
+                let result = result.unwrap();
+                let from_ty = self.value_ty(frame, *value);
+                let operand = self.value(frame, *value)?;
+                match kind {
+                    CastKind::Primitive => {
+                        let value = self.primitive_cast(&operand, from_ty);
+                        self.assign(frame, result, value, *to)?;
+                    }
+                    CastKind::Untag => {
+                        let offset = from_ty.deref(db).enum_data_offset(db, SLOT_SIZE);
+                        let to_ty = if to.is_zero_sized(db) || to.is_primitive(db) {
+                            *to
+                        } else if from_ty.is_sptr(db) {
+                            to.make_sptr(db)
+                        } else {
+                            to.make_mptr(db)
+                        };
+                        self.assign(frame, result, operand + offset, to_ty)?;
+                    }
+                }
+            }
+
+            InstKind::AggregateConstruct { ty, args } => {
+                let result = result.unwrap();
+                let ptr = self.assignable_ptr(frame, result)?;
+                let ptr_ty = self.assignable_ty(frame, result);
+                self.aggregate_init(frame, &ptr, self.space(ptr_ty), *ty, args)?;
+            }
+
+            InstKind::Bind { src } => {
+                let result = result.unwrap();
+                let str_constant = match body.store.value_data(*src) {
+                    Value::Constant { constant, .. } => match &constant.data(db).value {
+                        ConstantValue::Str(s) => Some(s.clone()),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+
+                if let Some(s) = str_constant {
+                    let ptr = self.assignable_ptr(frame, result)?;
+                    let ptr_ty = self.assignable_ty(frame, result);
+                    let mut data = word::to_bytes(&Word::from(s.len())).to_vec();
+                    data.extend_from_slice(s.as_bytes());
+                    self.write(self.space(ptr_ty), &ptr, &data)?;
+                } else {
+                    let src_ty = self.value_ty(frame, *src);
+                    let value = self.value(frame, *src)?;
+                    self.assign(frame, result, value, src_ty)?;
+                }
+            }
+
+            InstKind::MemCopy { src } => {
+                let result = result.unwrap();
+                let dst = self.assignable_ptr(frame, result)?;
+                let dst_ty = self.assignable_ty(frame, result);
+                let src_ty = self.value_ty(frame, *src);
+                let src = self.value(frame, *src)?;
+                let (src_space, dst_space) = (self.space(src_ty), self.space(dst_ty));
+                self.value_copy(&src, src_space, &dst, dst_space, src_ty.deref(db))?;
+            }
+
+            InstKind::Load { src } => {
+                let result = result.unwrap();
+                let src_ty = self.value_ty(frame, *src);
+                let src = self.value(frame, *src)?;
+                self.assign(frame, result, src, src_ty)?;
+            }
+
+            InstKind::AggregateAccess { value, indices } => {
+                let result = result.unwrap();
+                let mut ptr = self.value(frame, *value)?;
+                let mut inner_ty = self.value_ty(frame, *value);
+                for &idx in indices {
+                    ptr = self.elem_ptr(frame, ptr, inner_ty.deref(db), idx)?;
+                    inner_ty = inner_ty.projection_ty(db, body.store.value_data(idx));
+                }
+                self.assign(frame, result, ptr, inner_ty)?;
+            }
+
+            InstKind::MapAccess { key, value } => {
+                let result = result.unwrap();
+                let map_ty = self.value_ty(frame, *value).deref(db);
+                let map_ptr = self.value(frame, *value)?;
+                let ptr = self.map_value_ptr(frame, &map_ptr, *key)?;
+                let value_ty = match &map_ty.data(db).kind {
+                    TypeKind::Map(def) => def.value_ty,
+                    _ => unreachable!(),
+                };
+                self.assign(frame, result, ptr, value_ty.make_sptr(db))?;
+            }
+
+            InstKind::Call {
+                func,
+                args,
+                call_type,
+            } => {
+                let (ret, ret_ty) = match call_type {
+                    CallType::Internal => {
+                        let args = args
+                            .iter()
+                            .map(|arg| self.value(frame, *arg))
+                            .collect::<Result<_, _>>()?;
+                        let ret = self.call_function(*func, args)?;
+                        (ret, self.legalized_return_ty(*func))
+                    }
+                    CallType::External => self.external_call(frame, *func, args)?,
+                };
+
+                if let (Some(result), Some(ret_ty)) = (result, ret_ty) {
+                    self.assign(frame, result, ret.unwrap_or_default(), ret_ty)?;
+                }
+            }
+
+            InstKind::Jump { dest } => return Ok(Flow::Jump(*dest)),
+
+            InstKind::Branch { cond, then, else_ } => {
+                let cond = self.value(frame, *cond)?;
+                return Ok(Flow::Jump(if cond.is_zero() { *else_ } else { *then }));
+            }
+
+            InstKind::Switch {
+                disc,
+                table,
+                default,
+            } => {
+                let disc = self.value(frame, *disc)?;
+                for (value, dest) in table.iter() {
+                    if self.value(frame, value)? == disc {
+                        return Ok(Flow::Jump(dest));
+                    }
+                }
+                return match default {
+                    Some(dest) => Ok(Flow::Jump(*dest)),
+                    None => Err(InterpreterError::Halt("no matching switch arm".into()).into()),
+                };
+            }
+
+            InstKind::Revert { arg } => {
+                let data = match arg {
+                    Some(arg) => {
+                        let arg_ty = self.value_ty(frame, *arg);
+                        let deref_ty = arg_ty.deref(db);
+                        let name = match &deref_ty.data(db).kind {
+                            TypeKind::Struct(def) => def.name.to_string(),
+                            TypeKind::String(_) => "Error".to_string(),
+                            _ => "Panic".to_string(),
+                        };
+                        let value = self.value(frame, *arg)?;
+                        let src = self.abi_src(value, arg_ty);
+                        self.revert_data(&name, src, deref_ty)?
+                    }
+                    None => vec![],
+                };
+                return Err(Halt::Revert(data));
+            }
+
+            InstKind::Emit { arg } => {
+                let event_ty = self.value_ty(frame, *arg);
+                let event = self.value(frame, *arg)?;
+                self.emit(&event, self.space(event_ty), event_ty.deref(db))?;
+            }
+
+            InstKind::Return { arg } => {
+                let value = match arg {
+                    Some(arg) => Some(self.value(frame, *arg)?),
+                    None => None,
+                };
+                return Ok(Flow::Return(value));
+            }
+
+            InstKind::Keccak256 { arg } => {
+                let result = result.unwrap();
+                let arg_ty = self.value_ty(frame, *arg);
+                let ptr = self.value(frame, *arg)?;
+                let size = arg_ty.deref(db).size_of(db, SLOT_SIZE);
+                let data = self.read(Space::Memory, &ptr, size)?;
+                let hash = word::from_bytes(&keccak::full_as_bytes(&data));
+                self.assign(frame, result, hash, self.u256_ty())?;
+            }
+
+            InstKind::AbiEncode { arg } => {
+                let result = result.unwrap();
+                let dst = self.assignable_ptr(frame, result)?;
+                let dst_ty = self.assignable_ty(frame, result);
+                let arg_ty = self.value_ty(frame, *arg);
+                let value = self.value(frame, *arg)?;
+                let src = self.abi_src(value, arg_ty);
+                let data = self.abi_encode(&src, arg_ty.deref(db))?;
+                self.write(self.space(dst_ty), &dst, &data)?;
+            }
+
+            InstKind::Nop => {}
+
+            InstKind::Create {
+                value,
+                contract,
+                args,
+            } => {
+                let value = self.value(frame, *value)?;
+                let init_data = self.init_data(frame, *contract, args)?;
+                let address = self.create(*contract, value, None, init_data)?;
+                if let Some(result) = result {
+                    self.assign(frame, result, address, self.u256_ty())?;
+                }
+            }
+
+            InstKind::Create2 {
+                value,
+                salt,
+                contract,
+                args,
+            } => {
+                let value = self.value(frame, *value)?;
+                let salt = self.value(frame, *salt)?;
+                let init_data = self.init_data(frame, *contract, args)?;
+                let address = self.create(*contract, value, Some(salt), init_data)?;
+                if let Some(result) = result {
+                    self.assign(frame, result, address, self.u256_ty())?;
+                }
+            }
+
+            InstKind::YulIntrinsic { op, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.value(frame, *arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let value = self.exec_intrinsic(*op, &args)?;
+                if let (Some(result), Some(value)) = (result, value) {
+                    self.assign(frame, result, value, self.u256_ty())?;
+                }
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    fn declare(&mut self, frame: &mut Frame, value: ValueId) -> Exec<()> {
+        let ty = self.value_ty(frame, value);
+        let deref_ty = ty.deref(self.db);
+        let init = if ty.is_mptr(self.db) && !deref_ty.is_zero_sized(self.db) {
+            self.alloc(deref_ty.size_of(self.db, SLOT_SIZE))?
+        } else {
+            Word::zero()
+        };
+        frame.values.insert(value, init);
+        Ok(())
+    }
+
+    fn value(&mut self, frame: &Frame, value: ValueId) -> Exec<Word> {
+        match frame.body.store.value_data(value) {
+            Value::Temporary { .. } | Value::Local(_) => {
+                Ok(frame.values.get(&value).cloned().unwrap_or_default())
+            }
+            Value::Immediate { imm, .. } => Ok(word::from_bigint(imm)),
+            Value::Constant { constant, .. } => match &constant.data(self.db).value {
+                ConstantValue::Immediate(imm) => Ok(word::from_bigint(imm)),
+                ConstantValue::Bool(value) => Ok(word::from_bool(*value)),
+                ConstantValue::Str(s) => {
+                    let ptr = self.alloc(32 + s.len())?;
+                    let mut data = word::to_bytes(&Word::from(s.len())).to_vec();
+                    data.extend_from_slice(s.as_bytes());
+                    self.write(Space::Memory, &ptr, &data)?;
+                    Ok(ptr)
+                }
+            },
+            Value::Unit { .. } => Ok(Word::zero()),
+        }
+    }
+
+    fn value_ty(&self, frame: &Frame, value: ValueId) -> TypeId {
+        self.legalized_ty(frame.body.store.value_ty(value))
+    }
+
+    pub(super) fn abi_src(&self, value: Word, ty: TypeId) -> AbiSrc {
+        if ty.is_ptr(self.db) {
+            AbiSrc::Ptr(value, self.space(ty))
+        } else {
+            AbiSrc::Word(value)
+        }
+    }
+
+    fn assignable_ty(&self, frame: &Frame, lhs: &AssignableValue) -> TypeId {
+        match lhs {
+            AssignableValue::Value(value) => self.value_ty(frame, *value),
+            AssignableValue::Aggregate { lhs, idx } => self
+                .assignable_ty(frame, lhs)
+                .projection_ty(self.db, frame.body.store.value_data(*idx)),
+            AssignableValue::Map { lhs, .. } => {
+                match &self
+                    .assignable_ty(frame, lhs)
+                    .deref(self.db)
+                    .data(self.db)
+                    .kind
+                {
+                    TypeKind::Map(def) => def.value_ty.make_sptr(self.db),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
+
+    fn assignable_ptr(&mut self, frame: &Frame, lhs: &AssignableValue) -> Exec<Word> {
+        match lhs {
+            AssignableValue::Value(value) => self.value(frame, *value),
+            AssignableValue::Aggregate { lhs, idx } => {
+                let base = self.assignable_ptr(frame, lhs)?;
+                let base_ty = self.assignable_ty(frame, lhs).deref(self.db);
+                self.elem_ptr(frame, base, base_ty, *idx)
+            }
+            AssignableValue::Map { lhs, key } => {
+                let map_ptr = self.assignable_ptr(frame, lhs)?;
+                self.map_value_ptr(frame, &map_ptr, *key)
+            }
+        }
+    }
+
+    /// Assigns `rhs` to `lhs` in the same manner as the Yul backend: values
+    /// are loaded from or stored to pointers, and pointees are copied when
+    /// the pointers live in different spaces.
+    fn assign(
+        &mut self,
+        frame: &mut Frame,
+        lhs: &AssignableValue,
+        rhs: Word,
+        rhs_ty: TypeId,
+    ) -> Exec<()> {
+        let db = self.db;
+        let lhs_ty = self.assignable_ty(frame, lhs);
+
+        match lhs {
+            AssignableValue::Value(value) => {
+                let value = *value;
+                match (lhs_ty.is_ptr(db), rhs_ty.is_ptr(db)) {
+                    (true, true) => {
+                        if lhs_ty.is_mptr(db) == rhs_ty.is_mptr(db) {
+                            frame.values.insert(value, rhs);
+                        } else {
+                            let dst = self.value(frame, value)?;
+                            let (src_space, dst_space) = (self.space(rhs_ty), self.space(lhs_ty));
+                            self.value_copy(&rhs, src_space, &dst, dst_space, rhs_ty.deref(db))?;
+                        }
+                    }
+                    (true, false) => {
+                        let dst = self.value(frame, value)?;
+                        self.ptr_store(&dst, &rhs, lhs_ty)?;
+                    }
+                    (false, true) => {
+                        let loaded = self.ptr_load(&rhs, rhs_ty)?;
+                        frame.values.insert(value, self.extend(loaded, lhs_ty));
+                    }
+                    (false, false) => {
+                        frame.values.insert(value, self.extend(rhs, lhs_ty));
+                    }
+                }
+            }
+
+            AssignableValue::Aggregate { .. } | AssignableValue::Map { .. } => {
+                let dst = self.assignable_ptr(frame, lhs)?;
+                if rhs_ty.is_ptr(db) {
+                    let (src_space, dst_space) = (self.space(rhs_ty), self.space(lhs_ty));
+                    self.value_copy(&rhs, src_space, &dst, dst_space, rhs_ty.deref(db))?;
+                } else {
+                    self.ptr_store(&dst, &rhs, lhs_ty)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn elem_ptr(&mut self, frame: &Frame, base: Word, ty: TypeId, idx: ValueId) -> Exec<Word> {
+        let db = self.db;
+        let idx_data = frame.body.store.value_data(idx);
+        let offset = match &ty.data(db).kind {
+            TypeKind::Array(def) => {
+                let index = self.value(frame, idx)?;
+                if !idx_data.is_imm() && def.len > 0 && index >= Word::from(def.len) {
+                    return Err(self.panic(PANIC_OUT_OF_BOUNDS));
+                }
+                index * ty.array_elem_size(db, SLOT_SIZE)
+            }
+            _ => match idx_data {
+                Value::Immediate { imm, .. } => {
+                    Word::from(ty.aggregate_elem_offset(db, imm.clone(), SLOT_SIZE))
+                }
+                _ => {
+                    return Err(InterpreterError::Unsupported(
+                        "dynamic indexing of a non-array aggregate".into(),
+                    )
+                    .into())
+                }
+            },
+        };
+        Ok((base + offset) % word::modulus())
+    }
+
+    fn map_value_ptr(&mut self, frame: &Frame, map_ptr: &Word, key: ValueId) -> Exec<Word> {
+        let db = self.db;
+        let key_ty = self.value_ty(frame, key);
+        let key_word = if key_ty.deref(db).is_zero_sized(db) {
+            word::from_bytes(&keccak::full_as_bytes(&[]))
+        } else if key_ty.is_ptr(db) {
+            let ptr = self.value(frame, key)?;
+            let size = key_ty.deref(db).size_of(db, SLOT_SIZE);
+            let data = self.read(self.space(key_ty), &ptr, size)?;
+            word::from_bytes(&keccak::full_as_bytes(&data))
+        } else {
+            self.value(frame, key)?
+        };
+
+        let mut data = word::to_bytes(&key_word).to_vec();
+        data.extend_from_slice(&word::to_bytes(map_ptr));
+        let hash = word::from_bytes(&keccak::full_as_bytes(&data));
+        Ok(hash & (word::max() ^ Word::from(0xffu8)))
+    }
+
+    fn aggregate_init(
+        &mut self,
+        frame: &Frame,
+        ptr: &Word,
+        space: Space,
+        ty: TypeId,
+        args: &[ValueId],
+    ) -> Exec<()> {
+        let db = self.db;
+        if ty.is_enum(db) {
+            let tag = self.value(frame, args[0])?;
+            let disc_size = ty.enum_disc_type(db).size_of(db, SLOT_SIZE);
+            self.store_sized(space, ptr, &tag, disc_size)?;
+
+            let data_args = &args[1..];
+            if data_args
+                .iter()
+                .all(|arg| frame.body.store.value_ty(*arg).deref(db).is_zero_sized(db))
+            {
+                return Ok(());
+            }
+
+            let items = data_args
+                .iter()
+                .map(|arg| frame.body.store.value_ty(*arg).deref(db))
+                .collect();
+            let data_ty =
+                db.mir_intern_type(Type::new(TypeKind::Tuple(TupleDef { items }), None).into());
+            let data_ptr = ptr + ty.enum_data_offset(db, SLOT_SIZE);
+            return self.aggregate_init(frame, &data_ptr, space, data_ty, data_args);
+        }
+
+        for (idx, arg) in args.iter().enumerate() {
+            let arg_ty = self.value_ty(frame, *arg);
+            let field_ty = ty.projection_ty_imm(db, idx);
+            if field_ty.is_zero_sized(db) {
+                continue;
+            }
+
+            let field_ptr = ptr + ty.aggregate_elem_offset(db, idx, SLOT_SIZE);
+            let value = self.value(frame, *arg)?;
+            if arg_ty.is_ptr(db) {
+                self.value_copy(&value, self.space(arg_ty), &field_ptr, space, field_ty)?;
+            } else {
+                let size = field_ty.size_of(db, SLOT_SIZE);
+                self.store_sized(space, &field_ptr, &value, size)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes `lhs op rhs` with the overflow and division checks of the
+    /// Yul backend, returning a `Panic` revert if a check fails.
+    fn checked_arith(&mut self, op: BinOp, lhs: &Word, rhs: &Word, ty: TypeId) -> Exec<Word> {
+        let signed = ty.is_signed(self.db);
+        let bits = ty.size_of(self.db, SLOT_SIZE) * 8;
+        match word::checked_arith(op, lhs, rhs, signed, bits) {
+            Some(value) => Ok(value),
+            None if matches!(op, BinOp::Div | BinOp::Mod) && rhs.is_zero() => {
+                Err(self.panic(PANIC_ZERO_DIVISION))
+            }
+            None => Err(self.panic(PANIC_OVERFLOW)),
+        }
+    }
+
+    /// Returns the `Panic(uint256)` revert the Yul backend emits on a failed
+    /// runtime check.
+    pub(super) fn panic(&mut self, code: u64) -> Halt {
+        self.numeric_revert("Panic", code)
+    }
+
+    pub(super) fn numeric_revert(&mut self, name: &str, code: u64) -> Halt {
+        let u256_ty = self.u256_ty();
+        match self.revert_data(name, AbiSrc::Word(Word::from(code)), u256_ty) {
+            Ok(data) => Halt::Revert(data),
+            Err(halt) => halt,
+        }
+    }
+
+    fn external_call(
+        &mut self,
+        frame: &Frame,
+        func: FunctionId,
+        args: &[ValueId],
+    ) -> Exec<(Option<Word>, Option<TypeId>)> {
+        let db = self.db;
+        let address = word::to_address(&self.value(frame, args[0])?);
+
+        let sig = func.signature(db);
+        let params = sig.params.iter().filter(|param| !param.ty.is_contract(db));
+        let mut abi_args = vec![];
+        for (param, arg) in params.zip(&args[1..]) {
+            let param_ty = param.ty.deref(db);
+            if param_ty.is_zero_sized(db) {
+                continue;
+            }
+            let arg_ty = self.value_ty(frame, *arg);
+            let value = self.value(frame, *arg)?;
+            abi_args.push((self.abi_src(value, arg_ty), param_ty));
+        }
+
+        let mut calldata = self.function_selector(func)?.to_vec();
+        calldata.extend(self.abi_encode_seq(&abi_args)?);
+
+        let result = self.message_call(address, Word::zero(), calldata)?;
+        self.set_return_data(result.output.clone());
+        if !result.success {
+            return Err(Halt::Revert(result.output));
+        }
+
+        match self.legalized_return_ty(func) {
+            Some(ret_ty) => {
+                let ret = self.abi_decode_seq(&result.output, &[ret_ty.deref(db)])?;
+                Ok((ret.into_iter().next(), Some(ret_ty)))
+            }
+            None => Ok((None, None)),
+        }
+    }
+
+    /// Executes a message call to `to`, reverting all state changes made
+    /// during the call if it fails.
+    pub(super) fn message_call(
+        &mut self,
+        to: Address,
+        value: Word,
+        calldata: Vec<u8>,
+    ) -> Result<CallResult, InterpreterError> {
+        let (caller, value) = self.outgoing_call(value);
+        let snapshot = self.world.snapshot();
+        let mut result = self.call_from(caller, to, value, calldata)?;
+        if let (Err(data), true) = (
+            self.check_expected_emit(self.contexts.len()),
+            result.success,
+        ) {
+            result = CallResult::failure(data);
+        }
+        if !result.success {
+            self.world.restore(snapshot);
+        }
+        Ok(result)
+    }
+
+    fn call_from(
+        &mut self,
+        caller: Address,
+        to: Address,
+        value: Word,
+        calldata: Vec<u8>,
+    ) -> Result<CallResult, InterpreterError> {
+        if self.contexts.len() > MAX_CALL_DEPTH || !self.world.transfer(caller, to, &value) {
+            return Ok(CallResult::failure(vec![]));
+        }
+
+        let contract = match self.world.account(&to).and_then(|account| account.code) {
+            Some(contract) => contract,
+            None => return Ok(CallResult::success(vec![])),
+        };
+
+        self.contexts
+            .push(CallContext::new(to, caller, value, calldata));
+        let result = self.dispatch(contract);
+        self.contexts.pop();
+
+        match result {
+            Ok(()) | Err(Halt::Stop) => Ok(CallResult::success(vec![])),
+            Err(Halt::Return(data)) => Ok(CallResult::success(data)),
+            Err(Halt::Revert(data)) => Ok(CallResult::failure(data)),
+            Err(Halt::Error(InterpreterError::Halt(_))) => Ok(CallResult::failure(vec![])),
+            Err(Halt::Error(err)) => Err(err),
+        }
+    }
+
+    /// Runs the entry point of `contract` in the current context, either
+    /// `__call__` or the ABI dispatcher over the exported functions.
+    fn dispatch(&mut self, contract: ContractId) -> Exec<()> {
+        let db = self.db;
+        if let Some(call_fn) = contract.call_function(db.upcast()) {
+            let call_fn = db.mir_lowered_func_signature(call_fn);
+            let args = vec![Word::zero(); call_fn.signature(db).params.len()];
+            self.call_function(call_fn, args)?;
+            return Ok(());
+        }
+
+        if self.calldata().is_empty() {
+            if let Some(receive) = contract.receive_function(db.upcast()) {
+                return self.run_entry_point(receive);
+            }
+        }
+
+        let mut selector = [0; 4];
+        let calldata = self.calldata();
+        let len = calldata.len().min(4);
+        selector[..len].copy_from_slice(&calldata[..len]);
+
+        let mut target = None;
+        for &func in db.mir_lower_contract_all_functions(contract).iter() {
+            if func.linkage(db) == Linkage::Export && self.function_selector(func)? == selector {
+                target = Some(func);
+                break;
+            }
+        }
+        let func = match target {
+            Some(func) => func,
+            None => {
+                return match contract.fallback_function(db.upcast()) {
+                    Some(fallback) => self.run_entry_point(fallback),
+                    None => Err(Halt::Return(vec![])),
+                }
+            }
+        };
+
+        let sig = func.signature(db);
+        if !self.callvalue().is_zero() && !sig.analyzer_func_id.is_payable(db.upcast()) {
+            return Err(Halt::Revert(vec![]));
+        }
+        let data = self.calldata().get(4..).unwrap_or_default().to_vec();
+        let args = self.decode_args(func, &data)?;
+        let ret = self.call_function(func, args)?;
+        let output = match self.legalized_return_ty(func) {
+            Some(ret_ty) => {
+                let src = self.abi_src(ret.unwrap_or_default(), ret_ty);
+                self.abi_encode_seq(&[(src, ret_ty.deref(db))])?
+            }
+            None => vec![],
+        };
+        Err(Halt::Return(output))
+    }
+
+    /// Decodes the arguments of `func` from the ABI encoded `data`. The
+    /// contract and zero-sized parameters aren't encoded and are passed zero.
+    fn decode_args(&mut self, func: FunctionId, data: &[u8]) -> Exec<Vec<Word>> {
+        let db = self.db;
+        let sig = func.signature(db);
+        let is_abi_param = |ty: TypeId| !ty.is_contract(db) && !ty.deref(db).is_zero_sized(db);
+        let abi_params: Vec<_> = sig
+            .params
+            .iter()
+            .filter(|param| is_abi_param(param.ty))
+            .map(|param| param.ty.deref(db))
+            .collect();
+        let mut decoded = if abi_params.is_empty() {
+            vec![]
+        } else {
+            self.abi_decode_seq(data, &abi_params)?
+        }
+        .into_iter();
+
+        Ok(sig
+            .params
+            .iter()
+            .map(|param| {
+                if is_abi_param(param.ty) {
+                    decoded.next().unwrap()
+                } else {
+                    Word::zero()
+                }
+            })
+            .collect())
+    }
+
+    /// ABI encodes `args`, the arguments passed to the `__init__` function of
+    /// `contract` when it's created.
+    fn init_data(
+        &mut self,
+        frame: &Frame,
+        contract: ContractId,
+        args: &[ValueId],
+    ) -> Exec<Vec<u8>> {
+        let db = self.db;
+        let init = match contract.init_function(db.upcast()) {
+            Some(init) => db.mir_lowered_func_signature(init),
+            None => return Ok(vec![]),
+        };
+        let sig = init.signature(db);
+        let params = sig
+            .params
+            .iter()
+            .filter(|param| !param.ty.is_contract(db) && !param.ty.deref(db).is_zero_sized(db));
+        let args: Vec<_> = args
+            .iter()
+            .copied()
+            .filter(|arg| !self.value_ty(frame, *arg).is_zero_sized(db))
+            .collect();
+        let mut abi_args = vec![];
+        for (param, arg) in params.zip(args) {
+            let arg_ty = self.value_ty(frame, arg);
+            let value = self.value(frame, arg)?;
+            abi_args.push((self.abi_src(value, arg_ty), param.ty.deref(db)));
+        }
+        self.abi_encode_seq(&abi_args)
+    }
+
+    /// Runs `__receive__` or `__fallback__`, which return no data.
+    fn run_entry_point(&mut self, func: analyzer_items::FunctionId) -> Exec<()> {
+        let db = self.db;
+        if !self.callvalue().is_zero() && !func.is_payable(db.upcast()) {
+            return Err(Halt::Revert(vec![]));
+        }
+
+        let func = db.mir_lowered_func_signature(func);
+        let args = vec![Word::zero(); func.signature(db).params.len()];
+        self.call_function(func, args)?;
+        Err(Halt::Return(vec![]))
+    }
+
+    /// Deploys `contract` by running its `__init__` function with the ABI
+    /// encoded `init_data` as its arguments.
+    ///
+    /// `create2` addresses can't be derived as on the EVM since there's no
+    /// init code to hash, the contract name is hashed instead.
+    fn create(
+        &mut self,
+        contract: ContractId,
+        value: Word,
+        salt: Option<Word>,
+        init_data: Vec<u8>,
+    ) -> Exec<Word> {
+        let (creator, value) = self.outgoing_call(value);
+        let snapshot = self.world.snapshot();
+        let address = self.create_from(creator, contract, value, salt, init_data)?;
+        if let (Err(data), false) = (
+            self.check_expected_emit(self.contexts.len()),
+            address.is_zero(),
+        ) {
+            self.world.restore(snapshot);
+            self.set_return_data(data);
+            return Ok(Word::zero());
+        }
+        Ok(address)
+    }
+
+    fn create_from(
+        &mut self,
+        creator: Address,
+        contract: ContractId,
+        value: Word,
+        salt: Option<Word>,
+        init_data: Vec<u8>,
+    ) -> Exec<Word> {
+        let db = self.db;
+        if self.contexts.len() > MAX_CALL_DEPTH || self.world.balance(&creator) < value {
+            return Ok(Word::zero());
+        }
+
+        let nonce = self
+            .world
+            .account(&creator)
+            .map_or(0, |account| account.nonce);
+        self.world.account_mut(creator).nonce += 1;
+        let address = match salt {
+            Some(salt) => {
+                let name = contract.name(db.upcast());
+                let mut data = vec![0xff];
+                data.extend_from_slice(&creator);
+                data.extend_from_slice(&word::to_bytes(&salt));
+                data.extend_from_slice(&keccak::full_as_bytes(name.as_bytes()));
+                word::to_address(&word::from_bytes(&keccak::full_as_bytes(&data)))
+            }
+            None => create_address(&creator, nonce),
+        };
+
+        if self
+            .world
+            .account(&address)
+            .is_some_and(|account| account.code.is_some() || account.nonce != 0)
+        {
+            return Ok(Word::zero());
+        }
+
+        let snapshot = self.world.snapshot();
+        self.world.transfer(creator, address, &value);
+        self.world.account_mut(address).nonce = 1;
+
+        // `__init__` can only be sent a value if it's `#payable`.
+        let sends_value = !value.is_zero();
+        self.contexts
+            .push(CallContext::new(address, creator, value, vec![]));
+        let result = match contract.init_function(db.upcast()) {
+            Some(init) if sends_value && !init.is_payable(db.upcast()) => Err(Halt::Revert(vec![])),
+            Some(init) => {
+                let init = db.mir_lowered_func_signature(init);
+                self.decode_args(init, &init_data)
+                    .and_then(|args| self.call_function(init, args))
+                    .map(|_| ())
+            }
+            None if sends_value => Err(Halt::Revert(vec![])),
+            None => Ok(()),
+        };
+        self.contexts.pop();
+
+        match result {
+            Ok(()) | Err(Halt::Stop) | Err(Halt::Return(_)) => {
+                self.world.account_mut(address).code = Some(contract);
+                Ok(word::from_address(&address))
+            }
+            Err(Halt::Revert(data)) => {
+                self.world.restore(snapshot);
+                self.set_return_data(data);
+                Ok(Word::zero())
+            }
+            Err(Halt::Error(InterpreterError::Halt(_))) => {
+                self.world.restore(snapshot);
+                Ok(Word::zero())
+            }
+            Err(halt) => Err(halt),
+        }
+    }
+}
+
+/// Computes the address of a contract created by `creator` with `nonce`,
+/// i.e. `keccak256(rlp([creator, nonce]))[12..]`.
+fn create_address(creator: &Address, nonce: u64) -> Address {
+    let mut payload = vec![0x80 + 20];
+    payload.extend_from_slice(creator);
+    if nonce == 0 {
+        payload.push(0x80);
+    } else if nonce < 0x80 {
+        payload.push(nonce as u8);
+    } else {
+        let bytes = nonce.to_be_bytes();
+        let bytes = &bytes[bytes.iter().position(|b| *b != 0).unwrap()..];
+        payload.push(0x80 + bytes.len() as u8);
+        payload.extend_from_slice(bytes);
+    }
+
+    let mut rlp = vec![0xc0 + payload.len() as u8];
+    rlp.extend(payload);
+    word::to_address(&word::from_bytes(&keccak::full_as_bytes(&rlp)))
+}
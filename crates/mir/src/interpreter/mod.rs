@@ -0,0 +1,86 @@
+//! A direct interpreter of MIR function bodies.
+//!
+//! The interpreter executes the lowered, unlegalized MIR of a function and
+//! models storage, memory, maps, events, reverts, message calls between
+//! contracts and the `YulIntrinsicOp` set with the same data layout and
+//! runtime checks as the Yul backend. This makes it possible to run `#[test]`
+//! functions without compiling them with `solc`.
+//!
+//! The cheatcodes of `std::testing` have the same effects as in the test
+//! runner. Gas isn't metered, and the code of a contract is never
+//! materialized, so intrinsics that inspect code, `delegatecall` and most
+//! precompiles are reported as [`InterpreterError::Unsupported`].
+
+mod abi;
+mod cheatcodes;
+mod intrinsics;
+mod machine;
+mod state;
+pub(crate) mod word;
+
+use std::fmt;
+
+use crate::{db::MirDb, ir::FunctionId};
+
+pub use state::{Address, Log};
+
+/// The address of the account a test function is executed on.
+pub const TEST_ADDRESS: Address = {
+    let mut address = [0; 20];
+    address[19] = 0x42;
+    address
+};
+
+/// The address of the cheatcodes of `std::testing`.
+pub const CHEATCODE_ADDRESS: Address = [
+    0x71, 0x09, 0x70, 0x9e, 0xcf, 0xa9, 0x1a, 0x80, 0x62, 0x6f, 0xf3, 0x98, 0x9d, 0x68, 0xf6, 0x7f,
+    0x5b, 0x1d, 0xd1, 0x2d,
+];
+
+/// The result of running a test function to completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Success { logs: Vec<Log> },
+    Revert { data: Vec<u8> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterpreterError {
+    /// The function uses a feature the interpreter doesn't model.
+    Unsupported(String),
+    /// The execution halted exceptionally, e.g. by `invalid` or an out of
+    /// bounds memory access.
+    Halt(String),
+}
+
+impl fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(feature) => write!(f, "unsupported by the interpreter: {feature}"),
+            Self::Halt(reason) => write!(f, "exceptional halt: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for InterpreterError {}
+
+/// Runs `test`, which takes no arguments other than `ctx`, in a fresh world.
+pub fn run_test(db: &dyn MirDb, test: FunctionId) -> Result<Outcome, InterpreterError> {
+    machine::Machine::new(db, false).run_test(test, vec![])
+}
+
+/// Runs `test` like [`run_test`], but executes the function bodies produced by
+/// the optimization pipeline (see [`crate::optim`]).
+pub fn run_optimized_test(db: &dyn MirDb, test: FunctionId) -> Result<Outcome, InterpreterError> {
+    machine::Machine::new(db, true).run_test(test, vec![])
+}
+
+/// Runs the fuzz test `test` in a fresh world with the arguments ABI encoded
+/// in `calldata`.
+pub fn run_fuzz_test(
+    db: &dyn MirDb,
+    test: FunctionId,
+    calldata: Vec<u8>,
+) -> Result<Outcome, InterpreterError> {
+    machine::Machine::new(db, false).run_test(test, calldata)
+}
@@ -0,0 +1,237 @@
+use fe_analyzer::namespace::items::ContractId;
+use fxhash::FxHashMap;
+use num_traits::{One, Zero};
+
+use super::{
+    word::{self, Word},
+    InterpreterError,
+};
+
+pub type Address = [u8; 20];
+
+/// Memory and storage accesses beyond this bound are treated as an
+/// exceptional halt, the EVM would run out of gas long before.
+const MAX_ADDRESSABLE: usize = 1 << 26;
+
+/// A log emitted by `log0` .. `log4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+}
+
+/// Byte addressed memory of a call frame.
+#[derive(Debug, Default)]
+pub(super) struct Memory {
+    bytes: Vec<u8>,
+}
+
+impl Memory {
+    pub(super) fn size(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub(super) fn read(&mut self, offset: &Word, len: usize) -> Result<Vec<u8>, InterpreterError> {
+        if len == 0 {
+            return Ok(vec![]);
+        }
+        let offset = self.expand(offset, len)?;
+        Ok(self.bytes[offset..offset + len].to_vec())
+    }
+
+    pub(super) fn write(&mut self, offset: &Word, data: &[u8]) -> Result<(), InterpreterError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let offset = self.expand(offset, data.len())?;
+        self.bytes[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    pub(super) fn load(&mut self, offset: &Word) -> Result<Word, InterpreterError> {
+        Ok(word::from_bytes(&self.read(offset, 32)?))
+    }
+
+    pub(super) fn store(&mut self, offset: &Word, value: &Word) -> Result<(), InterpreterError> {
+        self.write(offset, &word::to_bytes(value))
+    }
+
+    /// Expands memory in 32 byte steps so that `msize` matches the EVM.
+    fn expand(&mut self, offset: &Word, len: usize) -> Result<usize, InterpreterError> {
+        let offset = word::to_usize(offset)
+            .filter(|offset| offset + len <= MAX_ADDRESSABLE)
+            .ok_or_else(|| InterpreterError::Halt("memory access out of bounds".into()))?;
+        let end = (offset + len).div_ceil(32) * 32;
+        if self.bytes.len() < end {
+            self.bytes.resize(end, 0);
+        }
+        Ok(offset)
+    }
+}
+
+/// Storage of an account.
+///
+/// Storage pointers in MIR are byte addresses, a value at `ptr` lives in
+/// the slot `ptr / 32` at the byte offset `ptr % 32`. As in Solidity, the
+/// offset of a primitive value is counted from the lower-order end of the
+/// slot, while raw bytes are laid out from the higher-order end like in
+/// memory.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Storage {
+    slots: FxHashMap<Word, Word>,
+}
+
+impl Storage {
+    pub(super) fn load(&self, slot: &Word) -> Word {
+        self.slots.get(slot).cloned().unwrap_or_default()
+    }
+
+    pub(super) fn store(&mut self, slot: Word, value: Word) {
+        if value.is_zero() {
+            self.slots.remove(&slot);
+        } else {
+            self.slots.insert(slot, value);
+        }
+    }
+
+    /// Loads a primitive value of `size` bytes from `ptr`.
+    pub(super) fn load_value(&self, ptr: &Word, size: usize) -> Word {
+        let (slot, offset) = split_storage_ptr(ptr);
+        word::mask(&(self.load(&slot) >> (offset * 8)), size)
+    }
+
+    /// Stores the lowest `size` bytes of `value` at `ptr`.
+    pub(super) fn store_value(&mut self, ptr: &Word, value: &Word, size: usize) {
+        let (slot, offset) = split_storage_ptr(ptr);
+        let mask = (word::mask(&word::max(), size) << (offset * 8)) & word::max();
+        let value = (word::mask(value, size) << (offset * 8)) & &mask;
+        let old_value = self.load(&slot);
+        let new_value = (old_value & (word::max() ^ mask)) | value;
+        self.store(slot, new_value);
+    }
+
+    pub(super) fn read(&self, ptr: &Word, len: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(len);
+        let mut addr = ptr.clone();
+        while bytes.len() < len {
+            let (slot, offset) = split_storage_ptr(&addr);
+            let slot_bytes = word::to_bytes(&self.load(&slot));
+            let n = (32 - offset).min(len - bytes.len());
+            bytes.extend_from_slice(&slot_bytes[offset..offset + n]);
+            addr = wrapping_add(&addr, n);
+        }
+        bytes
+    }
+
+    pub(super) fn write(&mut self, ptr: &Word, data: &[u8]) {
+        let mut addr = ptr.clone();
+        let mut written = 0;
+        while written < data.len() {
+            let (slot, offset) = split_storage_ptr(&addr);
+            let mut slot_bytes = word::to_bytes(&self.load(&slot));
+            let n = (32 - offset).min(data.len() - written);
+            slot_bytes[offset..offset + n].copy_from_slice(&data[written..written + n]);
+            self.store(slot, word::from_bytes(&slot_bytes));
+            written += n;
+            addr = wrapping_add(&addr, n);
+        }
+    }
+}
+
+fn split_storage_ptr(ptr: &Word) -> (Word, usize) {
+    let slot = ptr >> 5u8;
+    let offset = word::to_usize(&(ptr & Word::from(31u8))).unwrap();
+    (slot, offset)
+}
+
+fn wrapping_add(ptr: &Word, n: usize) -> Word {
+    (ptr + n) % word::modulus()
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Account {
+    pub(super) balance: Word,
+    pub(super) nonce: u64,
+    pub(super) storage: Storage,
+    /// The transient storage of EIP-1153. It lives as long as the execution,
+    /// which is a single transaction.
+    pub(super) transient: Storage,
+    /// The contract deployed at the account, `None` for externally owned
+    /// accounts.
+    pub(super) code: Option<ContractId>,
+}
+
+/// The state shared by all call frames of an execution.
+#[derive(Debug, Default)]
+pub(super) struct World {
+    pub(super) accounts: FxHashMap<Address, Account>,
+    pub(super) logs: Vec<Log>,
+}
+
+/// The block that the execution is part of.
+#[derive(Debug, Clone)]
+pub(super) struct Block {
+    pub(super) number: Word,
+    pub(super) timestamp: Word,
+}
+
+impl Default for Block {
+    /// The block that the test runner executes tests in.
+    fn default() -> Self {
+        Self {
+            number: Word::zero(),
+            timestamp: Word::one(),
+        }
+    }
+}
+
+/// A copy of the world state that is restored when a call reverts.
+#[derive(Clone)]
+pub(super) struct Snapshot {
+    accounts: FxHashMap<Address, Account>,
+    log_len: usize,
+}
+
+impl World {
+    pub(super) fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    pub(super) fn account_mut(&mut self, address: Address) -> &mut Account {
+        self.accounts.entry(address).or_default()
+    }
+
+    pub(super) fn balance(&self, address: &Address) -> Word {
+        self.account(address)
+            .map(|account| account.balance.clone())
+            .unwrap_or_default()
+    }
+
+    /// Moves `value` from `from` to `to`, returns `false` if the balance of
+    /// `from` is insufficient.
+    pub(super) fn transfer(&mut self, from: Address, to: Address, value: &Word) -> bool {
+        if value.is_zero() {
+            self.account_mut(to);
+            return true;
+        }
+        if &self.balance(&from) < value {
+            return false;
+        }
+        self.account_mut(from).balance -= value;
+        self.account_mut(to).balance += value;
+        true
+    }
+
+    pub(super) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            accounts: self.accounts.clone(),
+            log_len: self.logs.len(),
+        }
+    }
+
+    pub(super) fn restore(&mut self, snapshot: Snapshot) {
+        self.accounts = snapshot.accounts;
+        self.logs.truncate(snapshot.log_len);
+    }
+}
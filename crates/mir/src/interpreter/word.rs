@@ -0,0 +1,235 @@
+//! Helpers for 256-bit EVM words represented as [`BigUint`].
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::ir::inst::{BinOp, YulIntrinsicOp};
+
+pub type Word = BigUint;
+
+pub(crate) fn modulus() -> BigUint {
+    BigUint::one() << 256
+}
+
+pub(crate) fn max() -> Word {
+    modulus() - 1u8
+}
+
+pub(crate) fn from_bool(value: bool) -> Word {
+    if value {
+        Word::one()
+    } else {
+        Word::zero()
+    }
+}
+
+/// Wraps an arbitrary precision integer into a word, i.e. computes
+/// `value mod 2^256`.
+pub(crate) fn from_bigint(value: &BigInt) -> Word {
+    let modulus = BigInt::from(modulus());
+    let value = ((value % &modulus) + &modulus) % modulus;
+    value.to_biguint().unwrap()
+}
+
+/// Interprets a word as a two's complement signed integer.
+pub(crate) fn to_signed(word: &Word) -> BigInt {
+    if word.bit(255) {
+        BigInt::from(word.clone()) - BigInt::from(modulus())
+    } else {
+        BigInt::from(word.clone())
+    }
+}
+
+pub(crate) fn from_bytes(bytes: &[u8]) -> Word {
+    BigUint::from_bytes_be(bytes)
+}
+
+pub(crate) fn to_bytes(word: &Word) -> [u8; 32] {
+    let bytes = word.to_bytes_be();
+    let mut result = [0; 32];
+    result[32 - bytes.len()..].copy_from_slice(&bytes);
+    result
+}
+
+pub(crate) fn to_usize(word: &Word) -> Option<usize> {
+    word.to_usize()
+}
+
+/// Keeps only the lowest `size` bytes of the word.
+pub(crate) fn mask(word: &Word, size: usize) -> Word {
+    if size >= 32 {
+        word.clone()
+    } else {
+        word & ((Word::one() << (size * 8)) - 1u8)
+    }
+}
+
+/// Extends the sign bit of the lowest `size` bytes to the whole word.
+pub(crate) fn sign_extend(word: &Word, size: usize) -> Word {
+    if size == 0 || size >= 32 {
+        return word.clone();
+    }
+
+    let masked = mask(word, size);
+    if masked.bit((size * 8 - 1) as u64) {
+        masked | (max() ^ ((Word::one() << (size * 8)) - 1u8))
+    } else {
+        masked
+    }
+}
+
+pub(crate) fn to_address(word: &Word) -> [u8; 20] {
+    let mut address = [0; 20];
+    address.copy_from_slice(&to_bytes(word)[12..]);
+    address
+}
+
+pub(crate) fn from_address(address: &[u8; 20]) -> Word {
+    from_bytes(address)
+}
+
+/// `shl(shift, value)`.
+pub(crate) fn shl(shift: &Word, value: &Word) -> Word {
+    match shift.to_usize().filter(|shift| *shift < 256) {
+        Some(shift) => (value << shift) % modulus(),
+        None => Word::zero(),
+    }
+}
+
+/// `shr(shift, value)`.
+pub(crate) fn shr(shift: &Word, value: &Word) -> Word {
+    match shift.to_usize().filter(|shift| *shift < 256) {
+        Some(shift) => value >> shift,
+        None => Word::zero(),
+    }
+}
+
+/// `sar(shift, value)`, the arithmetic right shift.
+pub(crate) fn sar(shift: &Word, value: &Word) -> Word {
+    let value = to_signed(value);
+    let shift = shift.to_usize().filter(|shift| *shift < 256).unwrap_or(256);
+    if shift >= 256 {
+        if value.sign() == Sign::Minus {
+            max()
+        } else {
+            Word::zero()
+        }
+    } else {
+        // `>>` on `BigInt` rounds towards negative infinity.
+        from_bigint(&(value >> shift))
+    }
+}
+
+/// Computes `lhs op rhs` for an integer of `bits` width with the overflow and
+/// division checks of the Yul backend. Returns `None` if a check fails.
+pub(crate) fn checked_arith(
+    op: BinOp,
+    lhs: &Word,
+    rhs: &Word,
+    signed: bool,
+    bits: usize,
+) -> Option<Word> {
+    let to_int = |word: &Word| {
+        if signed {
+            to_signed(word)
+        } else {
+            BigInt::from(word.clone())
+        }
+    };
+    let (lhs, rhs) = (to_int(lhs), to_int(rhs));
+
+    let result = match op {
+        BinOp::Add => lhs + rhs,
+        BinOp::Sub => lhs - rhs,
+        BinOp::Mul => lhs * rhs,
+        BinOp::Div | BinOp::Mod if rhs.is_zero() => return None,
+        BinOp::Div => lhs / rhs,
+        BinOp::Mod => lhs % rhs,
+        BinOp::Pow => {
+            // The exponent is always unsigned.
+            let exp = if rhs.is_negative() {
+                rhs + BigInt::from(modulus())
+            } else {
+                rhs
+            };
+            if exp.is_zero() {
+                BigInt::one()
+            } else if lhs.is_zero() || lhs.is_one() {
+                lhs
+            } else if lhs == -BigInt::one() {
+                if (&exp % 2u8).is_zero() {
+                    BigInt::one()
+                } else {
+                    lhs
+                }
+            } else {
+                lhs.pow(exp.to_u32().filter(|exp| *exp <= 256)?)
+            }
+        }
+        _ => unreachable!(),
+    };
+
+    let (min, max) = if signed {
+        (
+            -(BigInt::one() << (bits - 1)),
+            (BigInt::one() << (bits - 1)) - 1,
+        )
+    } else {
+        (BigInt::zero(), (BigInt::one() << bits) - 1)
+    };
+    if result < min || result > max {
+        None
+    } else {
+        Some(from_bigint(&result))
+    }
+}
+
+/// Evaluates a pure Yul intrinsic, returns `None` for intrinsics that access
+/// the machine state (see [`YulIntrinsicOp::is_pure`]).
+pub(crate) fn intrinsic(op: YulIntrinsicOp, args: &[Word]) -> Option<Word> {
+    use YulIntrinsicOp::*;
+
+    let arg = |idx: usize| &args[idx];
+    let value = match op {
+        Add => (arg(0) + arg(1)) % modulus(),
+        Sub => from_bigint(&(BigInt::from(arg(0).clone()) - BigInt::from(arg(1).clone()))),
+        Mul => (arg(0) * arg(1)) % modulus(),
+        Div if arg(1).is_zero() => Word::zero(),
+        Div => arg(0) / arg(1),
+        Sdiv if arg(1).is_zero() => Word::zero(),
+        Sdiv => from_bigint(&(to_signed(arg(0)) / to_signed(arg(1)))),
+        Mod if arg(1).is_zero() => Word::zero(),
+        Mod => arg(0) % arg(1),
+        Smod if arg(1).is_zero() => Word::zero(),
+        Smod => from_bigint(&(to_signed(arg(0)) % to_signed(arg(1)))),
+        Exp => arg(0).modpow(arg(1), &modulus()),
+        Not => max() ^ arg(0),
+        Lt => from_bool(arg(0) < arg(1)),
+        Gt => from_bool(arg(0) > arg(1)),
+        Slt => from_bool(to_signed(arg(0)) < to_signed(arg(1))),
+        Sgt => from_bool(to_signed(arg(0)) > to_signed(arg(1))),
+        Eq => from_bool(arg(0) == arg(1)),
+        Iszero => from_bool(arg(0).is_zero()),
+        And => arg(0) & arg(1),
+        Or => arg(0) | arg(1),
+        Xor => arg(0) ^ arg(1),
+        Byte => match arg(0).to_usize().filter(|idx| *idx < 32) {
+            Some(idx) => Word::from(to_bytes(arg(1))[idx]),
+            None => Word::zero(),
+        },
+        Shl => shl(arg(0), arg(1)),
+        Shr => shr(arg(0), arg(1)),
+        Sar => sar(arg(0), arg(1)),
+        Addmod if arg(2).is_zero() => Word::zero(),
+        Addmod => (arg(0) + arg(1)) % arg(2),
+        Mulmod if arg(2).is_zero() => Word::zero(),
+        Mulmod => (arg(0) * arg(1)) % arg(2),
+        Signextend => match arg(0).to_usize().filter(|size| *size < 31) {
+            Some(size) => sign_extend(arg(1), size + 1),
+            None => arg(1).clone(),
+        },
+        _ => return None,
+    };
+
+    Some(value)
+}
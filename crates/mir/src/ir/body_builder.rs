@@ -241,8 +241,18 @@ impl BodyBuilder {
         self.insert_inst(inst)
     }
 
-    pub fn create(&mut self, value: ValueId, contract: ContractId, source: SourceInfo) -> InstId {
-        let kind = InstKind::Create { value, contract };
+    pub fn create(
+        &mut self,
+        value: ValueId,
+        contract: ContractId,
+        args: Vec<ValueId>,
+        source: SourceInfo,
+    ) -> InstId {
+        let kind = InstKind::Create {
+            value,
+            contract,
+            args,
+        };
         let inst = Inst::new(kind, source);
         self.insert_inst(inst)
     }
@@ -252,12 +262,14 @@ impl BodyBuilder {
         value: ValueId,
         salt: ValueId,
         contract: ContractId,
+        args: Vec<ValueId>,
         source: SourceInfo,
     ) -> InstId {
         let kind = InstKind::Create2 {
             value,
             salt,
             contract,
+            args,
         };
         let inst = Inst::new(kind, source);
         self.insert_inst(inst)
@@ -192,6 +192,10 @@ impl BodyDataStore {
         self.inst_results.get(&inst)
     }
 
+    pub fn inst_result_mut(&mut self, inst: InstId) -> Option<&mut AssignableValue> {
+        self.inst_results.get_mut(&inst)
+    }
+
     pub fn map_result(&mut self, inst: InstId, result: AssignableValue) {
         self.inst_results.insert(inst, result);
     }
@@ -124,15 +124,18 @@ pub enum InstKind {
 
     Nop,
 
+    /// Deploys `contract`, `args` are passed to its `__init__` function.
     Create {
         value: ValueId,
         contract: ContractId,
+        args: Vec<ValueId>,
     },
 
     Create2 {
         value: ValueId,
         salt: ValueId,
         contract: ContractId,
+        args: Vec<ValueId>,
     },
 
     YulIntrinsic {
@@ -232,9 +235,12 @@ impl Inst {
             | Emit { arg }
             | Keccak256 { arg }
             | AbiEncode { arg }
-            | Create { value: arg, .. }
             | Branch { cond: arg, .. } => ValueIter::one(*arg),
 
+            Create { value, args, .. } => {
+                ValueIter::one(*value).chain(ValueIter::Slice(args.iter()))
+            }
+
             Switch { disc, table, .. } => {
                 ValueIter::one(*disc).chain(ValueIter::Slice(table.values.iter()))
             }
@@ -243,13 +249,14 @@ impl Inst {
             | MapAccess {
                 value: lhs,
                 key: rhs,
-            }
-            | Create2 {
-                value: lhs,
-                salt: rhs,
-                ..
             } => ValueIter::one(*lhs).chain(ValueIter::one(*rhs)),
 
+            Create2 {
+                value, salt, args, ..
+            } => ValueIter::one(*value)
+                .chain(ValueIter::one(*salt))
+                .chain(ValueIter::Slice(args.iter())),
+
             Revert { arg } | Return { arg } => ValueIter::One(*arg),
 
             Nop | Jump { .. } => ValueIter::Zero,
@@ -276,9 +283,12 @@ impl Inst {
             | Emit { arg }
             | Keccak256 { arg }
             | AbiEncode { arg }
-            | Create { value: arg, .. }
             | Branch { cond: arg, .. } => ValueIterMut::one(arg),
 
+            Create { value, args, .. } => {
+                ValueIterMut::one(value).chain(ValueIterMut::Slice(args.iter_mut()))
+            }
+
             Switch { disc, table, .. } => {
                 ValueIterMut::one(disc).chain(ValueIterMut::Slice(table.values.iter_mut()))
             }
@@ -287,13 +297,14 @@ impl Inst {
             | MapAccess {
                 value: lhs,
                 key: rhs,
-            }
-            | Create2 {
-                value: lhs,
-                salt: rhs,
-                ..
             } => ValueIterMut::one(lhs).chain(ValueIterMut::one(rhs)),
 
+            Create2 {
+                value, salt, args, ..
+            } => ValueIterMut::one(value)
+                .chain(ValueIterMut::one(salt))
+                .chain(ValueIterMut::Slice(args.iter_mut())),
+
             Revert { arg } | Return { arg } => ValueIterMut::One(arg.as_mut()),
 
             Nop | Jump { .. } => ValueIterMut::Zero,
@@ -439,6 +450,9 @@ pub enum YulIntrinsicOp {
     Mstore8,
     Sload,
     Sstore,
+    Tload,
+    Tstore,
+    Mcopy,
     Msize,
     Gas,
     Address,
@@ -489,6 +503,39 @@ impl YulIntrinsicOp {
             Self::Return | Self::Revert | Self::Selfdestruct | Self::Invalid
         )
     }
+
+    /// Returns `true` if the intrinsic computes its result from its arguments
+    /// alone, without reading or modifying the machine state.
+    pub fn is_pure(self) -> bool {
+        matches!(
+            self,
+            Self::Add
+                | Self::Sub
+                | Self::Mul
+                | Self::Div
+                | Self::Sdiv
+                | Self::Mod
+                | Self::Smod
+                | Self::Exp
+                | Self::Not
+                | Self::Lt
+                | Self::Gt
+                | Self::Slt
+                | Self::Sgt
+                | Self::Eq
+                | Self::Iszero
+                | Self::And
+                | Self::Or
+                | Self::Xor
+                | Self::Byte
+                | Self::Shl
+                | Self::Shr
+                | Self::Sar
+                | Self::Addmod
+                | Self::Mulmod
+                | Self::Signextend
+        )
+    }
 }
 
 impl fmt::Display for YulIntrinsicOp {
@@ -528,6 +575,9 @@ impl fmt::Display for YulIntrinsicOp {
             Self::Mstore8 => "__mstore8",
             Self::Sload => "__sload",
             Self::Sstore => "__sstore",
+            Self::Tload => "__tload",
+            Self::Tstore => "__tstore",
+            Self::Mcopy => "__mcopy",
             Self::Msize => "__msize",
             Self::Gas => "__gas",
             Self::Address => "__address",
@@ -614,6 +664,9 @@ impl From<fe_analyzer::builtins::Intrinsic> for YulIntrinsicOp {
             Intrinsic::__mstore8 => Self::Mstore8,
             Intrinsic::__sload => Self::Sload,
             Intrinsic::__sstore => Self::Sstore,
+            Intrinsic::__tload => Self::Tload,
+            Intrinsic::__tstore => Self::Tstore,
+            Intrinsic::__mcopy => Self::Mcopy,
             Intrinsic::__msize => Self::Msize,
             Intrinsic::__gas => Self::Gas,
             Intrinsic::__address => Self::Address,
@@ -654,7 +707,9 @@ impl From<fe_analyzer::builtins::Intrinsic> for YulIntrinsicOp {
             Intrinsic::__coinbase => Self::Coinbase,
             Intrinsic::__timestamp => Self::Timestamp,
             Intrinsic::__number => Self::Number,
-            Intrinsic::__prevrandao => Self::Prevrandao,
+            // `prevrandao` replaced `difficulty` in Paris, and reads the same
+            // opcode. The code generator picks the name of the target version.
+            Intrinsic::__difficulty | Intrinsic::__prevrandao => Self::Prevrandao,
             Intrinsic::__gaslimit => Self::Gaslimit,
         }
     }
@@ -1,7 +1,9 @@
 pub mod analysis;
 pub mod db;
 pub mod graphviz;
+pub mod interpreter;
 pub mod ir;
+pub mod optim;
 pub mod pretty_print;
 
 mod lower;
@@ -23,7 +23,7 @@ use crate::{
         body_builder::BodyBuilder,
         constant::ConstantValue,
         function::Linkage,
-        inst::{CallType, InstKind},
+        inst::{CallType, InstKind, SwitchTable, YulIntrinsicOp},
         value::{AssignableValue, Local},
         BasicBlockId, Constant, FunctionBody, FunctionId, FunctionParam, FunctionSignature, InstId,
         SourceInfo, TypeId, Value, ValueId,
@@ -66,7 +66,10 @@ pub fn lower_monomorphized_func_signature(
     let return_type = db.mir_lowered_type(analyzer_signature.return_type.clone().unwrap());
 
     let linkage = if func.is_public(db.upcast()) {
-        if func.is_contract_func(db.upcast()) && !func.is_constructor(db.upcast()) {
+        if func.is_contract_func(db.upcast())
+            && !func.is_constructor(db.upcast())
+            && !func.is_receive_or_fallback(db.upcast())
+        {
             Linkage::Export
         } else {
             Linkage::Public
@@ -139,18 +142,31 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
             }
 
             ast::FuncStmt::Assign { target, value } => {
-                let result = self.lower_assignable_value(target);
-                let (expr, _ty) = self.lower_expr(value);
-                self.builder.map_result(expr, result)
+                if let Some(slot) = self.transient_field_slot(target) {
+                    let value = self.lower_expr_to_value(value);
+                    self.lower_transient_store(slot, value, stmt.into());
+                } else {
+                    let result = self.lower_assignable_value(target);
+                    let (expr, _ty) = self.lower_expr(value);
+                    self.builder.map_result(expr, result)
+                }
             }
 
             ast::FuncStmt::AugAssign { target, op, value } => {
-                let result = self.lower_assignable_value(target);
-                let lhs = self.lower_expr_to_value(target);
-                let rhs = self.lower_expr_to_value(value);
+                if let Some(slot) = self.transient_field_slot(target) {
+                    let lhs = self.lower_expr_to_value(target);
+                    let rhs = self.lower_expr_to_value(value);
+                    let inst = self.lower_binop(op.kind, lhs, rhs, stmt.into());
+                    let value = self.map_to_tmp(inst, self.expr_ty(target));
+                    self.lower_transient_store(slot, value, stmt.into());
+                } else {
+                    let result = self.lower_assignable_value(target);
+                    let lhs = self.lower_expr_to_value(target);
+                    let rhs = self.lower_expr_to_value(value);
 
-                let inst = self.lower_binop(op.kind, lhs, rhs, stmt.into());
-                self.builder.map_result(inst, result)
+                    let inst = self.lower_binop(op.kind, lhs, rhs, stmt.into());
+                    self.builder.map_result(inst, result)
+                }
             }
 
             ast::FuncStmt::For { target, iter, body } => self.lower_for_loop(target, iter, body),
@@ -396,9 +412,15 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
             }
 
             ast::Expr::Attribute { .. } => {
-                let mut indices = vec![];
-                let value = self.lower_aggregate_access(expr, &mut indices);
-                self.builder.aggregate_access(value, indices, expr.into())
+                if let Some(slot) = self.transient_field_slot(expr) {
+                    let slot = self.make_u256_imm(slot);
+                    self.builder
+                        .yul_intrinsic(YulIntrinsicOp::Tload, vec![slot], expr.into())
+                } else {
+                    let mut indices = vec![];
+                    let value = self.lower_aggregate_access(expr, &mut indices);
+                    self.builder.aggregate_access(value, indices, expr.into())
+                }
             }
 
             ast::Expr::Subscript { value, index } => {
@@ -416,6 +438,8 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
                 }
             }
 
+            ast::Expr::Try { value } => self.lower_try(value, expr.into()),
+
             ast::Expr::Call {
                 func,
                 generic_args,
@@ -461,7 +485,7 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
             }
 
             ast::Expr::Path(path) => {
-                let value = self.resolve_path(path, expr.into());
+                let value = self.resolve_path(path, expr);
                 self.builder.bind(value, expr.into())
             }
 
@@ -805,7 +829,7 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
                 }
             }
             ast::Expr::Name(name) => self.resolve_name(name).into(),
-            ast::Expr::Path(path) => self.resolve_path(path, expr.into()).into(),
+            ast::Expr::Path(path) => self.resolve_path(path, expr).into(),
             _ => self.lower_expr_to_value(expr).into(),
         }
     }
@@ -870,6 +894,81 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
         self.builder.bind(tmp, SourceInfo::dummy())
     }
 
+    /// Lowers `value?` into a switch on the discriminant of `value`. The
+    /// payload of an `Ok` or `Some` value is the result of the expression, an
+    /// `Err` or `None` value is rebuilt as the return type and returned.
+    fn lower_try(&mut self, value: &Node<ast::Expr>, source: SourceInfo) -> InstId {
+        let enum_id = match self.analyzer_body.expressions[&value.id]
+            .typ
+            .deref(self.db.upcast())
+            .typ(self.db.upcast())
+        {
+            Type::Enum(id) => id,
+            _ => unreachable!("`?` operand must be an enum"),
+        };
+        let (ok_name, err_name) = if enum_id.is_std_enum(self.db.upcast(), "Option") {
+            ("Some", "None")
+        } else {
+            ("Ok", "Err")
+        };
+        let ok_variant = enum_id.variant(self.db.upcast(), ok_name).unwrap();
+        let err_variant = enum_id.variant(self.db.upcast(), err_name).unwrap();
+
+        let value = self.lower_expr_to_value(value);
+        let value_ty = self.builder.value_ty(value);
+        let disc_ty = value_ty.enum_disc_type(self.db);
+        let disc_index = self.make_u256_imm(0);
+        let disc = self
+            .builder
+            .aggregate_access(value, vec![disc_index], source.clone());
+        let disc = self.map_to_tmp(disc, disc_ty);
+
+        let ok_bb = self.builder.make_block();
+        let err_bb = self.builder.make_block();
+        let mut table = SwitchTable::default();
+        let ok_disc = self.make_imm(ok_variant.disc(self.db.upcast()), disc_ty);
+        table.add_arm(ok_disc, ok_bb);
+        self.builder
+            .switch(disc, table, Some(err_bb), source.clone());
+
+        self.builder.move_to_block(err_bb);
+        let return_ty = self.func.return_type(self.db).unwrap();
+        let tag_ty = return_ty.enum_disc_type(self.db);
+        let tag = self.make_imm(err_variant.disc(self.db.upcast()), tag_ty);
+        let err_data_ty = value_ty.enum_variant_type(self.db, err_variant);
+        let err_args = if err_data_ty.is_unit(self.db) {
+            vec![tag, self.make_unit()]
+        } else {
+            let err = self.lower_variant_payload(value, err_data_ty, source.clone());
+            vec![tag, err]
+        };
+        let err_value = self
+            .builder
+            .aggregate_construct(return_ty, err_args, source.clone());
+        let err_value = self.map_to_tmp(err_value, return_ty);
+        self.builder.ret(err_value, source.clone());
+
+        self.builder.move_to_block(ok_bb);
+        let ok_data_ty = value_ty.enum_variant_type(self.db, ok_variant);
+        let ok = self.lower_variant_payload(value, ok_data_ty, source.clone());
+        self.builder.bind(ok, source)
+    }
+
+    /// Returns the single field of the variant data of the enum `value`.
+    fn lower_variant_payload(
+        &mut self,
+        value: ValueId,
+        data_ty: TypeId,
+        source: SourceInfo,
+    ) -> ValueId {
+        let data = self.builder.untag_cast(value, data_ty, source.clone());
+        let data = self.map_to_tmp(data, data_ty);
+        let index = self.make_u256_imm(0);
+        let payload = self.builder.aggregate_access(data, vec![index], source);
+        let payload_ty = data_ty.projection_ty_imm(self.db, 0);
+        self.map_to_tmp(payload, payload_ty)
+    }
+
     fn lower_binop(
         &mut self,
         op: ast::BinOperator,
@@ -984,10 +1083,16 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
             }
 
             // We ignores `args[0]', which represents `context` and not used for now.
+            // The arguments after the value (and salt) are passed to `__init__`.
             AnalyzerCallType::BuiltinAssociatedFunction { contract, function } => match function {
-                ContractTypeMethod::Create => self.builder.create(args[1], *contract, source),
+                ContractTypeMethod::Create => {
+                    let init_args = args[2..].to_vec();
+                    self.builder.create(args[1], *contract, init_args, source)
+                }
                 ContractTypeMethod::Create2 => {
-                    self.builder.create2(args[1], args[2], *contract, source)
+                    let init_args = args[3..].to_vec();
+                    self.builder
+                        .create2(args[1], args[2], *contract, init_args, source)
                 }
             },
 
@@ -1031,8 +1136,11 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
                     .cloned()
                     .expect("unresolved generic type");
 
+                // The impl may be in any ingot of the program, not only in
+                // the ingot of the generic function.
+                let db = self.db.upcast();
                 let impl_ = concrete_type
-                    .get_impl_for(self.db.upcast(), *trait_id)
+                    .get_impl_for(db, *trait_id, db.root_ingot())
                     .expect("missing impl");
 
                 let function = impl_
@@ -1097,6 +1205,34 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
         }
     }
 
+    /// Returns the transient storage slot of the field if `expr` accesses a
+    /// `Transient` field of the contract. Every transient field takes a slot
+    /// of its own, numbered from `0` in declaration order.
+    fn transient_field_slot(&self, expr: &Node<ast::Expr>) -> Option<usize> {
+        let (value, attr) = match &expr.kind {
+            ast::Expr::Attribute { value, attr } => (value, attr),
+            _ => return None,
+        };
+
+        let value_ty = self.analyzer_body.expressions[&value.id].typ;
+        let contract = match value_ty.deref(self.db.upcast()).typ(self.db.upcast()) {
+            Type::SelfContract(contract) => contract,
+            _ => return None,
+        };
+
+        contract
+            .fields(self.db.upcast())
+            .values()
+            .filter(|field| field.is_transient(self.db.upcast()))
+            .position(|field| field.name(self.db.upcast()) == attr.kind)
+    }
+
+    fn lower_transient_store(&mut self, slot: usize, value: ValueId, source: SourceInfo) {
+        let slot = self.make_u256_imm(slot);
+        self.builder
+            .yul_intrinsic(YulIntrinsicOp::Tstore, vec![slot, value], source);
+    }
+
     fn lower_aggregate_access(
         &mut self,
         expr: &Node<ast::Expr>,
@@ -1190,7 +1326,8 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
 
     /// Resolve a path appeared in an expression.
     /// NOTE: Don't call this to resolve method receiver.
-    fn resolve_path(&mut self, path: &ast::Path, source: SourceInfo) -> ValueId {
+    fn resolve_path(&mut self, path: &ast::Path, expr: &Node<ast::Expr>) -> ValueId {
+        let source = SourceInfo::from(expr);
         let func_id = self.builder.func_id();
         let module = func_id.module(self.db);
         match module.resolve_path(self.db.upcast(), path).value.unwrap() {
@@ -1200,9 +1337,9 @@ impl<'db, 'a> BodyLowerHelper<'db, 'a> {
                 self.builder.make_constant(constant, ty)
             }
             NamedThing::EnumVariant(variant) => {
-                let enum_ty = self
-                    .db
-                    .mir_lowered_type(variant.parent(self.db.upcast()).as_type(self.db.upcast()));
+                // The variant is resolved to the generic definition if the enum is generic,
+                // so the type is taken from the expression.
+                let enum_ty = self.expr_ty(expr);
                 let tag_type = enum_ty.enum_disc_type(self.db);
                 let tag = self.make_imm(variant.disc(self.db.upcast()), tag_type);
                 let data = self.make_unit();
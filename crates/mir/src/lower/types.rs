@@ -101,9 +101,12 @@ fn lower_contract(db: &dyn MirDb, contract: analyzer_items::ContractId) -> TypeK
     let name = contract.name(db.upcast());
 
     // Note: contract field types are wrapped in SPtr in TypeId::projection_ty
+    // Transient fields don't live in storage, they are accessed with
+    // `tload`/`tstore` instead.
     let fields = contract
         .fields(db.upcast())
         .iter()
+        .filter(|(_, fid)| !fid.is_transient(db.upcast()))
         .map(|(fname, fid)| {
             let analyzer_type = fid.typ(db.upcast()).unwrap();
             let ty = db.mir_lowered_type(analyzer_type);
@@ -0,0 +1,101 @@
+//! Copy propagation.
+//!
+//! Replaces the reads of a value that is assigned once by a `Bind` with the
+//! source of the `Bind`, as long as the source can't change in between. The
+//! `Bind` itself is left to dead code elimination.
+
+use fxhash::FxHashMap;
+
+use crate::{
+    analysis::{ControlFlowGraph, DomTree},
+    db::MirDb,
+    ir::{inst::InstKind, FunctionBody, InstId, Value, ValueId},
+};
+
+use super::{all_insts, assigned_value, replace_inst_uses, Pass};
+
+pub struct CopyPropagation;
+
+impl Pass for CopyPropagation {
+    fn name(&self) -> &'static str {
+        "copy_prop"
+    }
+
+    fn run(&mut self, _db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let cfg = ControlFlowGraph::compute(body);
+        let domtree = DomTree::compute(&cfg);
+
+        let insts = all_insts(body);
+        let mut positions = FxHashMap::default();
+        let mut assignments: FxHashMap<ValueId, usize> = FxHashMap::default();
+        for (pos, &inst) in insts.iter().enumerate() {
+            positions.insert(inst, pos);
+            if let Some(value) = assigned_value(body, inst) {
+                *assignments.entry(value).or_default() += 1;
+            }
+        }
+        let assignment_num = |value| assignments.get(&value).copied().unwrap_or_default();
+
+        // Returns `true` if `inst1` is executed before `inst2` on every path to
+        // `inst2`.
+        let dominates = |body: &FunctionBody, inst1: InstId, inst2: InstId| {
+            let (block1, block2) = (body.order.inst_block(inst1), body.order.inst_block(inst2));
+            if block1 == block2 {
+                positions[&inst1] < positions[&inst2]
+            } else {
+                domtree.is_reachable(block2) && domtree.dominates(block1, block2)
+            }
+        };
+
+        let mut changed = false;
+        for &bind in &insts {
+            let src = match body.store.inst_data(bind).kind {
+                InstKind::Bind { src } => src,
+                _ => continue,
+            };
+            let dst = match assigned_value(body, bind) {
+                Some(dst) if dst != src && assignment_num(dst) == 1 => dst,
+                _ => continue,
+            };
+            if body.store.value_ty(src) != body.store.value_ty(dst) {
+                continue;
+            }
+
+            // Constants are propagated by `Sccp`, which knows where the Yul
+            // backend treats an immediate operand differently.
+            let is_immutable = match body.store.value_data(src) {
+                Value::Local(local) => local.is_arg && assignment_num(src) == 0,
+                Value::Temporary { .. } => false,
+                _ => continue,
+            };
+
+            let mut replace = |value| (value == dst).then_some(src);
+            if is_immutable {
+                // An argument that is never assigned can't change, so every
+                // read of `dst` after the `Bind` can read it instead.
+                for &user in &insts {
+                    if dominates(body, bind, user) {
+                        changed |= replace_inst_uses(body, user, &mut replace);
+                    }
+                }
+            } else {
+                // The source may be reassigned, so only the reads in the same
+                // block are replaced, up to the next assignment of the source.
+                let mut next = body.order.next_inst(bind);
+                while let Some(user) = next {
+                    changed |= replace_inst_uses(body, user, &mut replace);
+                    let redeclares_src = matches!(
+                        body.store.inst_data(user).kind,
+                        InstKind::Declare { local } if local == src
+                    );
+                    if redeclares_src || assigned_value(body, user) == Some(src) {
+                        break;
+                    }
+                    next = body.order.next_inst(user);
+                }
+            }
+        }
+
+        changed
+    }
+}
@@ -0,0 +1,92 @@
+//! Dead code elimination.
+//!
+//! Removes instructions whose result is never read and whose execution has no
+//! observable effect, together with the declarations of locals that are
+//! neither read nor assigned any more.
+
+use fxhash::FxHashSet;
+
+use crate::{
+    db::MirDb,
+    ir::{
+        inst::{BinOp, InstKind, UnOp},
+        FunctionBody, InstId,
+    },
+};
+
+use super::{all_insts, assigned_value, used_values, Pass};
+
+pub struct DeadCodeElimination;
+
+impl Pass for DeadCodeElimination {
+    fn name(&self) -> &'static str {
+        "dce"
+    }
+
+    fn run(&mut self, _db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let mut changed = false;
+        loop {
+            let used = used_values(body);
+            let assigned: FxHashSet<_> = all_insts(body)
+                .into_iter()
+                .filter_map(|inst| assigned_value(body, inst))
+                .collect();
+
+            let mut removed = false;
+            for inst in all_insts(body) {
+                let is_dead = match &body.store.inst_data(inst).kind {
+                    InstKind::Nop => true,
+                    InstKind::Declare { local } => {
+                        !used.contains(local) && !assigned.contains(local)
+                    }
+                    _ => {
+                        assigned_value(body, inst).is_some_and(|value| !used.contains(&value))
+                            && is_removable(body, inst)
+                    }
+                };
+
+                if is_dead {
+                    body.order.remove_inst(inst);
+                    removed = true;
+                }
+            }
+
+            if !removed {
+                break;
+            }
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+/// Returns `true` if `inst` has no effect besides assigning its result.
+fn is_removable(body: &FunctionBody, inst: InstId) -> bool {
+    let is_imm = |value| body.store.value_data(value).is_imm();
+
+    match &body.store.inst_data(inst).kind {
+        // A negated immediate isn't checked for overflow.
+        InstKind::Unary {
+            op: UnOp::Neg,
+            value,
+        } => is_imm(*value),
+        InstKind::Binary { op, .. } => !matches!(
+            op,
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod | BinOp::Pow
+        ),
+        // A dynamic array index is bounds checked.
+        InstKind::AggregateAccess { indices, .. } => indices.iter().all(|idx| is_imm(*idx)),
+        InstKind::YulIntrinsic { op, .. } => op.is_pure(),
+        InstKind::Unary { .. }
+        | InstKind::Cast { .. }
+        | InstKind::AggregateConstruct { .. }
+        | InstKind::Bind { .. }
+        | InstKind::MemCopy { .. }
+        | InstKind::Load { .. }
+        | InstKind::MapAccess { .. }
+        | InstKind::Keccak256 { .. }
+        | InstKind::AbiEncode { .. } => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,166 @@
+//! Optimization passes over MIR function bodies.
+//!
+//! The passes run before legalization, so they can use the type information
+//! that is lost once a body is lowered to Yul, e.g. to fold a checked
+//! arithmetic operation whose operands are known at compile time.
+
+mod copy_prop;
+mod dce;
+mod sccp;
+mod simplify_cfg;
+
+use fxhash::FxHashSet;
+
+use crate::{
+    db::MirDb,
+    ir::{inst::InstKind, value::AssignableValue, FunctionBody, InstId, ValueId},
+};
+
+pub use copy_prop::CopyPropagation;
+pub use dce::DeadCodeElimination;
+pub use sccp::Sccp;
+pub use simplify_cfg::SimplifyCfg;
+
+/// The maximum number of times the pipeline is run over a body.
+const MAX_ITERATIONS: usize = 8;
+
+pub trait Pass {
+    fn name(&self) -> &'static str;
+
+    /// Transforms `body` in-place, returns `true` if the body was changed.
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool;
+}
+
+/// Runs a pipeline of passes over a function body until none of them changes
+/// the body any more.
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+}
+
+impl PassManager {
+    /// Returns a pass manager without any passes.
+    pub fn new() -> Self {
+        Self { passes: vec![] }
+    }
+
+    pub fn add_pass(&mut self, pass: impl Pass + 'static) -> &mut Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    pub fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) {
+        for _ in 0..MAX_ITERATIONS {
+            let mut changed = false;
+            for pass in &mut self.passes {
+                changed |= pass.run(db, body);
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for PassManager {
+    /// Returns the pipeline used by `fe build --optimize`.
+    fn default() -> Self {
+        let mut manager = Self::new();
+        manager
+            .add_pass(Sccp)
+            .add_pass(CopyPropagation)
+            .add_pass(DeadCodeElimination)
+            .add_pass(SimplifyCfg);
+        manager
+    }
+}
+
+/// Optimizes `body` with the default pipeline.
+pub fn optimize(db: &dyn MirDb, body: &mut FunctionBody) {
+    PassManager::default().run(db, body)
+}
+
+/// Returns all instructions of a body in layout order.
+fn all_insts(body: &FunctionBody) -> Vec<InstId> {
+    body.order
+        .iter_block()
+        .flat_map(|block| body.order.iter_inst(block))
+        .collect()
+}
+
+/// Returns the values read by `inst`. The local of a `Declare` isn't read,
+/// but the base and the indices of an assignment destination are.
+fn inst_uses(body: &FunctionBody, inst: InstId) -> Vec<ValueId> {
+    let data = body.store.inst_data(inst);
+    let mut uses: Vec<_> = match data.kind {
+        InstKind::Declare { .. } => vec![],
+        _ => data.args().collect(),
+    };
+
+    if let Some(result) = body.store.inst_result(inst) {
+        let mut result = result.clone();
+        lvalue_uses_mut(&mut result, &mut |value| uses.push(*value));
+    }
+    uses
+}
+
+/// Returns the values that are read anywhere in a body.
+fn used_values(body: &FunctionBody) -> FxHashSet<ValueId> {
+    all_insts(body)
+        .into_iter()
+        .flat_map(|inst| inst_uses(body, inst))
+        .collect()
+}
+
+/// Replaces the uses of values in `inst` by `f`, see [`inst_uses`].
+fn replace_inst_uses(
+    body: &mut FunctionBody,
+    inst: InstId,
+    f: &mut impl FnMut(ValueId) -> Option<ValueId>,
+) -> bool {
+    let mut changed = false;
+    let mut replace = |value: &mut ValueId| {
+        if let Some(new_value) = f(*value) {
+            changed |= new_value != *value;
+            *value = new_value;
+        }
+    };
+
+    let data = body.store.inst_data_mut(inst);
+    if !matches!(data.kind, InstKind::Declare { .. }) {
+        data.args_mut().for_each(&mut replace);
+    }
+    if let Some(result) = body.store.inst_result_mut(inst) {
+        lvalue_uses_mut(result, &mut replace);
+    }
+    changed
+}
+
+fn lvalue_uses_mut(lvalue: &mut AssignableValue, f: &mut impl FnMut(&mut ValueId)) {
+    match lvalue {
+        AssignableValue::Value(_) => {}
+        AssignableValue::Aggregate { lhs, idx } => {
+            f(idx);
+            lvalue_base_mut(lhs, f);
+        }
+        AssignableValue::Map { lhs, key } => {
+            f(key);
+            lvalue_base_mut(lhs, f);
+        }
+    }
+}
+
+fn lvalue_base_mut(lvalue: &mut AssignableValue, f: &mut impl FnMut(&mut ValueId)) {
+    match lvalue {
+        AssignableValue::Value(value) => f(value),
+        _ => lvalue_uses_mut(lvalue, f),
+    }
+}
+
+/// Returns the value an instruction assigns to as a whole, i.e. not through a
+/// field, an element or a map entry.
+fn assigned_value(body: &FunctionBody, inst: InstId) -> Option<ValueId> {
+    body.store
+        .inst_result(inst)
+        .and_then(AssignableValue::value_id)
+}
@@ -0,0 +1,711 @@
+//! Sparse conditional constant propagation.
+//!
+//! A temporary is assigned by exactly one instruction, so its value is
+//! tracked once for the whole body. Locals can be reassigned, so their values
+//! are tracked per block and merged where control flow joins. Only the edges
+//! that can be taken are followed, which lets a constant branch condition
+//! prune a whole region of the body.
+//!
+//! The checked arithmetic of the Yul backend is folded only if its checks are
+//! known to succeed, so operations that may revert are kept.
+
+use fxhash::{FxHashMap, FxHashSet};
+use num_bigint::BigInt;
+use num_traits::Zero;
+
+use crate::{
+    analysis::ControlFlowGraph,
+    db::MirDb,
+    interpreter::word::{self, Word},
+    ir::{
+        constant::ConstantValue,
+        inst::{BinOp, CastKind, InstKind, UnOp},
+        value::AssignableValue,
+        BasicBlockId, FunctionBody, InstId, TypeId, Value, ValueId,
+    },
+};
+
+use super::{assigned_value, inst_uses, replace_inst_uses, Pass};
+
+const SLOT_SIZE: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Lattice {
+    /// No assignment of the value has been seen yet.
+    Undef,
+    Const(Word),
+    /// The value isn't known at compile time.
+    Overdefined,
+}
+
+impl Lattice {
+    fn meet(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Undef, other) | (other, Self::Undef) => other.clone(),
+            (Self::Const(lhs), Self::Const(rhs)) if lhs == rhs => self.clone(),
+            _ => Self::Overdefined,
+        }
+    }
+
+    fn as_const(&self) -> Option<&Word> {
+        match self {
+            Self::Const(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// The values of the locals at a program point.
+type LocalState = FxHashMap<ValueId, Lattice>;
+
+pub struct Sccp;
+
+impl Pass for Sccp {
+    fn name(&self) -> &'static str {
+        "sccp"
+    }
+
+    fn run(&mut self, db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let mut solver = Solver::new(db, body);
+        solver.solve();
+        let Solver {
+            temps,
+            block_out,
+            executable_edges,
+            executable,
+            cfg,
+            ..
+        } = solver;
+
+        Rewriter {
+            db,
+            body,
+            temps,
+            block_out,
+            executable_edges,
+            cfg,
+        }
+        .rewrite(&executable)
+    }
+}
+
+struct Solver<'a> {
+    db: &'a dyn MirDb,
+    body: &'a FunctionBody,
+    cfg: ControlFlowGraph,
+    temps: FxHashMap<ValueId, Lattice>,
+    block_out: FxHashMap<BasicBlockId, LocalState>,
+    executable_edges: FxHashSet<(BasicBlockId, BasicBlockId)>,
+    executable: FxHashSet<BasicBlockId>,
+    /// Maps a temporary to the blocks reading it.
+    users: FxHashMap<ValueId, Vec<BasicBlockId>>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(db: &'a dyn MirDb, body: &'a FunctionBody) -> Self {
+        let mut users: FxHashMap<_, Vec<_>> = FxHashMap::default();
+        for block in body.order.iter_block() {
+            for inst in body.order.iter_inst(block) {
+                for value in inst_uses(body, inst) {
+                    if matches!(body.store.value_data(value), Value::Temporary { .. }) {
+                        users.entry(value).or_default().push(block);
+                    }
+                }
+            }
+        }
+
+        Self {
+            db,
+            body,
+            cfg: ControlFlowGraph::compute(body),
+            temps: FxHashMap::default(),
+            block_out: FxHashMap::default(),
+            executable_edges: FxHashSet::default(),
+            executable: FxHashSet::default(),
+            users,
+        }
+    }
+
+    fn solve(&mut self) {
+        let entry = self.cfg.entry();
+        self.executable.insert(entry);
+        let mut worklist = vec![entry];
+
+        while let Some(block) = worklist.pop() {
+            let mut state = block_in(
+                self.db,
+                self.body,
+                &self.cfg,
+                &self.block_out,
+                &self.executable_edges,
+                block,
+            );
+
+            let mut changed_temps = vec![];
+            for inst in self.body.order.iter_inst(block) {
+                let eval = Eval {
+                    db: self.db,
+                    body: self.body,
+                    temps: &self.temps,
+                };
+                if let Some((value, lattice)) = eval.transfer(inst, &mut state) {
+                    let old = self.temps.get(&value).cloned().unwrap_or(Lattice::Undef);
+                    let new = old.meet(&lattice);
+                    if new != old {
+                        self.temps.insert(value, new);
+                        changed_temps.push(value);
+                    }
+                }
+            }
+
+            for value in changed_temps {
+                for user in self.users.get(&value).into_iter().flatten() {
+                    if self.executable.contains(user) {
+                        worklist.push(*user);
+                    }
+                }
+            }
+
+            let eval = Eval {
+                db: self.db,
+                body: self.body,
+                temps: &self.temps,
+            };
+            let succs = eval.feasible_succs(block, &state);
+            let out_changed = self.block_out.get(&block) != Some(&state);
+            if out_changed {
+                self.block_out.insert(block, state);
+            }
+
+            for succ in succs {
+                let is_new_edge = self.executable_edges.insert((block, succ));
+                self.executable.insert(succ);
+                if is_new_edge || out_changed {
+                    worklist.push(succ);
+                }
+            }
+        }
+    }
+}
+
+/// Merges the out states of the executable predecessors of `block`.
+fn block_in(
+    db: &dyn MirDb,
+    body: &FunctionBody,
+    cfg: &ControlFlowGraph,
+    block_out: &FxHashMap<BasicBlockId, LocalState>,
+    executable_edges: &FxHashSet<(BasicBlockId, BasicBlockId)>,
+    block: BasicBlockId,
+) -> LocalState {
+    let mut state = if block == cfg.entry() {
+        Some(LocalState::default())
+    } else {
+        None
+    };
+
+    for pred in cfg.preds(block) {
+        if !executable_edges.contains(&(*pred, block)) {
+            continue;
+        }
+        let out = match block_out.get(pred) {
+            Some(out) => out,
+            None => continue,
+        };
+
+        state = Some(match state {
+            None => out.clone(),
+            Some(mut state) => {
+                for (value, lattice) in out {
+                    let current = state
+                        .get(value)
+                        .cloned()
+                        .unwrap_or_else(|| initial_local(db, body, *value));
+                    state.insert(*value, current.meet(lattice));
+                }
+                for (value, lattice) in state.iter_mut() {
+                    if !out.contains_key(value) {
+                        *lattice = lattice.meet(&initial_local(db, body, *value));
+                    }
+                }
+                state
+            }
+        });
+    }
+
+    state.unwrap_or_default()
+}
+
+/// Returns the value of a local before any assignment is seen.
+fn initial_local(db: &dyn MirDb, body: &FunctionBody, value: ValueId) -> Lattice {
+    match body.store.value_data(value) {
+        Value::Local(local) if !local.is_arg && is_tracked(db, local.ty) => Lattice::Undef,
+        _ => Lattice::Overdefined,
+    }
+}
+
+fn is_tracked(db: &dyn MirDb, ty: TypeId) -> bool {
+    ty.is_primitive(db) && !ty.is_unit(db)
+}
+
+/// Evaluates instructions over the lattice.
+struct Eval<'a> {
+    db: &'a dyn MirDb,
+    body: &'a FunctionBody,
+    temps: &'a FxHashMap<ValueId, Lattice>,
+}
+
+impl<'a> Eval<'a> {
+    /// Applies `inst` to `state`. Returns the temporary the instruction
+    /// assigns to with its new value.
+    fn transfer(&self, inst: InstId, state: &mut LocalState) -> Option<(ValueId, Lattice)> {
+        if let InstKind::Declare { local } = &self.body.store.inst_data(inst).kind {
+            let ty = self.body.store.value_ty(*local);
+            let lattice = if is_tracked(self.db, ty) {
+                Lattice::Const(Word::zero())
+            } else {
+                Lattice::Overdefined
+            };
+            state.insert(*local, lattice);
+            return None;
+        }
+
+        let result = assigned_value(self.body, inst)?;
+        let lattice = self.eval(inst, result, state);
+        match self.body.store.value_data(result) {
+            Value::Temporary { .. } => Some((result, lattice)),
+            _ => {
+                state.insert(result, lattice);
+                None
+            }
+        }
+    }
+
+    /// Evaluates the value `inst` assigns to `result`.
+    fn eval(&self, inst: InstId, result: ValueId, state: &LocalState) -> Lattice {
+        let result_ty = self.body.store.value_ty(result);
+        if !is_tracked(self.db, result_ty) {
+            return Lattice::Overdefined;
+        }
+
+        let operands = |values: &[ValueId]| -> Result<Vec<Word>, Lattice> {
+            let mut words = Vec::with_capacity(values.len());
+            let mut undef = false;
+            for value in values {
+                match self.operand(*value, state) {
+                    Lattice::Const(word) => words.push(word),
+                    Lattice::Undef => undef = true,
+                    Lattice::Overdefined => return Err(Lattice::Overdefined),
+                }
+            }
+            if undef {
+                Err(Lattice::Undef)
+            } else {
+                Ok(words)
+            }
+        };
+
+        let value = match &self.body.store.inst_data(inst).kind {
+            InstKind::Unary { op, value } => {
+                let operand = match operands(&[*value]) {
+                    Ok(mut words) => words.pop().unwrap(),
+                    Err(lattice) => return lattice,
+                };
+                match op {
+                    UnOp::Not => word::from_bool(operand.is_zero()),
+                    UnOp::Inv => word::max() ^ operand,
+                    // A negated literal isn't checked at runtime.
+                    UnOp::Neg if self.body.store.value_data(*value).is_imm() => {
+                        word::from_bigint(&-BigInt::from(operand))
+                    }
+                    UnOp::Neg => {
+                        let ty = self.body.store.value_ty(*value);
+                        match self.checked_arith(BinOp::Sub, &Word::zero(), &operand, ty) {
+                            Some(value) => value,
+                            None => return Lattice::Overdefined,
+                        }
+                    }
+                }
+            }
+
+            InstKind::Binary { op, lhs, rhs } => {
+                let is_lhs_signed = self.body.store.value_ty(*lhs).is_signed(self.db);
+                let (lhs, rhs) = match operands(&[*lhs, *rhs]) {
+                    Ok(words) => (words[0].clone(), words[1].clone()),
+                    Err(lattice) => return lattice,
+                };
+                match op {
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod | BinOp::Pow => {
+                        match self.checked_arith(*op, &lhs, &rhs, result_ty) {
+                            Some(value) => value,
+                            None => return Lattice::Overdefined,
+                        }
+                    }
+                    BinOp::Shl => word::shl(&rhs, &lhs),
+                    BinOp::Shr if result_ty.is_signed(self.db) => word::sar(&rhs, &lhs),
+                    BinOp::Shr => word::shr(&rhs, &lhs),
+                    BinOp::BitOr | BinOp::LogicalOr => lhs | rhs,
+                    BinOp::BitXor => lhs ^ rhs,
+                    BinOp::BitAnd | BinOp::LogicalAnd => lhs & rhs,
+                    BinOp::Eq => word::from_bool(lhs == rhs),
+                    BinOp::Ne => word::from_bool(lhs != rhs),
+                    BinOp::Ge | BinOp::Gt | BinOp::Le | BinOp::Lt => {
+                        let ordering = if is_lhs_signed {
+                            word::to_signed(&lhs).cmp(&word::to_signed(&rhs))
+                        } else {
+                            lhs.cmp(&rhs)
+                        };
+                        word::from_bool(match op {
+                            BinOp::Ge => ordering.is_ge(),
+                            BinOp::Gt => ordering.is_gt(),
+                            BinOp::Le => ordering.is_le(),
+                            _ => ordering.is_lt(),
+                        })
+                    }
+                }
+            }
+
+            InstKind::Cast {
+                kind: CastKind::Primitive,
+                value,
+                ..
+            } => {
+                let from_ty = self.body.store.value_ty(*value);
+                match operands(&[*value]) {
+                    Ok(mut words) => self.extend(&words.pop().unwrap(), from_ty),
+                    Err(lattice) => return lattice,
+                }
+            }
+
+            InstKind::Bind { src } => {
+                if self.is_str_constant(*src) {
+                    return Lattice::Overdefined;
+                }
+                match operands(&[*src]) {
+                    Ok(mut words) => words.pop().unwrap(),
+                    Err(lattice) => return lattice,
+                }
+            }
+
+            InstKind::YulIntrinsic { op, args } if op.is_pure() => match operands(args) {
+                Ok(words) => word::intrinsic(*op, &words).unwrap(),
+                Err(lattice) => return lattice,
+            },
+
+            _ => return Lattice::Overdefined,
+        };
+
+        // An assignment extends the value to the type of the destination.
+        Lattice::Const(self.extend(&value, result_ty))
+    }
+
+    fn operand(&self, value: ValueId, state: &LocalState) -> Lattice {
+        match self.body.store.value_data(value) {
+            Value::Immediate { imm, .. } => Lattice::Const(word::from_bigint(imm)),
+            Value::Constant { constant, .. } => match &constant.data(self.db).value {
+                ConstantValue::Immediate(imm) => Lattice::Const(word::from_bigint(imm)),
+                ConstantValue::Bool(value) => Lattice::Const(word::from_bool(*value)),
+                ConstantValue::Str(_) => Lattice::Overdefined,
+            },
+            Value::Temporary { ty, .. } if is_tracked(self.db, *ty) => {
+                self.temps.get(&value).cloned().unwrap_or(Lattice::Undef)
+            }
+            Value::Local(_) => state
+                .get(&value)
+                .cloned()
+                .unwrap_or_else(|| initial_local(self.db, self.body, value)),
+            _ => Lattice::Overdefined,
+        }
+    }
+
+    /// Returns the successors of `block` that can be reached from its
+    /// terminator.
+    fn feasible_succs(&self, block: BasicBlockId, state: &LocalState) -> Vec<BasicBlockId> {
+        let terminator = match self.body.order.terminator(&self.body.store, block) {
+            Some(terminator) => terminator,
+            None => return vec![],
+        };
+
+        match &self.body.store.inst_data(terminator).kind {
+            InstKind::Branch { cond, then, else_ } => match self.operand(*cond, state) {
+                Lattice::Const(cond) if cond.is_zero() => vec![*else_],
+                Lattice::Const(_) => vec![*then],
+                _ => vec![*then, *else_],
+            },
+            InstKind::Switch {
+                disc,
+                table,
+                default,
+            } => match self.switch_dest(*disc, table.iter(), *default, state) {
+                Some(dest) => vec![dest],
+                None => table.iter().map(|(_, dest)| dest).chain(*default).collect(),
+            },
+            _ => self
+                .body
+                .store
+                .branch_info(terminator)
+                .block_iter()
+                .collect(),
+        }
+    }
+
+    /// Returns the destination a switch jumps to if it is known at compile
+    /// time.
+    fn switch_dest(
+        &self,
+        disc: ValueId,
+        table: impl Iterator<Item = (ValueId, BasicBlockId)>,
+        default: Option<BasicBlockId>,
+        state: &LocalState,
+    ) -> Option<BasicBlockId> {
+        let disc = self.operand(disc, state);
+        let disc = disc.as_const()?;
+        let mut dest = None;
+        for (value, block) in table {
+            let value = self.operand(value, state);
+            if value.as_const()? == disc && dest.is_none() {
+                dest = Some(block);
+            }
+        }
+        dest.or(default)
+    }
+
+    fn checked_arith(&self, op: BinOp, lhs: &Word, rhs: &Word, ty: TypeId) -> Option<Word> {
+        let signed = ty.is_signed(self.db);
+        let bits = ty.size_of(self.db, SLOT_SIZE) * 8;
+        word::checked_arith(op, lhs, rhs, signed, bits)
+    }
+
+    fn extend(&self, value: &Word, ty: TypeId) -> Word {
+        let size = ty.size_of(self.db, SLOT_SIZE);
+        if ty.is_signed(self.db) {
+            word::sign_extend(value, size)
+        } else {
+            word::mask(value, size)
+        }
+    }
+
+    fn is_str_constant(&self, value: ValueId) -> bool {
+        match self.body.store.value_data(value) {
+            Value::Constant { constant, .. } => {
+                matches!(constant.data(self.db).value, ConstantValue::Str(_))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Applies the solution of the solver to the body.
+struct Rewriter<'a> {
+    db: &'a dyn MirDb,
+    body: &'a mut FunctionBody,
+    temps: FxHashMap<ValueId, Lattice>,
+    block_out: FxHashMap<BasicBlockId, LocalState>,
+    executable_edges: FxHashSet<(BasicBlockId, BasicBlockId)>,
+    cfg: ControlFlowGraph,
+}
+
+impl<'a> Rewriter<'a> {
+    fn rewrite(mut self, executable: &FxHashSet<BasicBlockId>) -> bool {
+        let mut changed = false;
+        let blocks: Vec<_> = self.body.order.iter_block().collect();
+        for &block in &blocks {
+            if executable.contains(&block) {
+                changed |= self.rewrite_block(block);
+            }
+        }
+
+        for block in blocks {
+            if !executable.contains(&block) {
+                self.body.order.remove_block(block);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn rewrite_block(&mut self, block: BasicBlockId) -> bool {
+        let mut state = block_in(
+            self.db,
+            self.body,
+            &self.cfg,
+            &self.block_out,
+            &self.executable_edges,
+            block,
+        );
+
+        let mut changed = false;
+        let insts: Vec<_> = self.body.order.iter_inst(block).collect();
+        for inst in insts {
+            changed |= self.replace_operands(inst, &state);
+
+            let eval = Eval {
+                db: self.db,
+                body: self.body,
+                temps: &self.temps,
+            };
+            let folded = assigned_value(self.body, inst)
+                .filter(|_| self.is_foldable(inst))
+                .and_then(|result| match eval.eval(inst, result, &state) {
+                    Lattice::Const(value) => Some((result, value)),
+                    _ => None,
+                });
+            eval.transfer(inst, &mut state);
+
+            if let Some((result, value)) = folded {
+                let ty = self.body.store.value_ty(result);
+                let imm = self.make_imm(value, ty);
+                let data = self.body.store.inst_data_mut(inst);
+                let kind = InstKind::Bind { src: imm };
+                if data.kind != kind {
+                    data.kind = kind;
+                    changed = true;
+                }
+            }
+        }
+
+        if let Some(terminator) = self.body.order.terminator(&self.body.store, block) {
+            changed |= self.fold_terminator(terminator, &state);
+        }
+        changed
+    }
+
+    /// Replaces the operands of `inst` that are known at compile time by
+    /// immediates.
+    fn replace_operands(&mut self, inst: InstId, state: &LocalState) -> bool {
+        let excluded = self.excluded_operands(inst, state);
+        let mut imms = FxHashMap::default();
+        for value in inst_uses(self.body, inst) {
+            if excluded.contains(&value) || self.body.store.value_data(value).is_imm() {
+                continue;
+            }
+            if !matches!(
+                self.body.store.value_data(value),
+                Value::Temporary { .. } | Value::Local(_)
+            ) {
+                continue;
+            }
+
+            let eval = Eval {
+                db: self.db,
+                body: self.body,
+                temps: &self.temps,
+            };
+            if let Lattice::Const(word) = eval.operand(value, state) {
+                let ty = self.body.store.value_ty(value);
+                let imm = self.make_imm(word, ty);
+                imms.insert(value, imm);
+            }
+        }
+
+        if imms.is_empty() {
+            return false;
+        }
+        replace_inst_uses(self.body, inst, &mut |value| imms.get(&value).copied())
+    }
+
+    /// Returns the operands of `inst` that must not be replaced by immediates
+    /// because the Yul backend treats an immediate operand differently.
+    fn excluded_operands(&self, inst: InstId, state: &LocalState) -> Vec<ValueId> {
+        let db = self.db;
+        let store = &self.body.store;
+        let eval = Eval {
+            db,
+            body: self.body,
+            temps: &self.temps,
+        };
+        // An immediate array index isn't bounds checked.
+        let is_out_of_bounds = |array_ty: TypeId, idx: ValueId| {
+            let array_ty = array_ty.deref(db);
+            array_ty.is_array(db)
+                && eval.operand(idx, state).as_const().is_some_and(|idx| {
+                    word::to_usize(idx).is_none_or(|idx| idx >= array_ty.aggregate_field_num(db))
+                })
+        };
+
+        let mut excluded = vec![];
+        match &store.inst_data(inst).kind {
+            // A negated immediate isn't checked for overflow.
+            InstKind::Unary {
+                op: UnOp::Neg,
+                value,
+            } => excluded.push(*value),
+
+            InstKind::AggregateAccess { value, indices } => {
+                let mut ty = store.value_ty(*value);
+                for idx in indices {
+                    if is_out_of_bounds(ty, *idx) {
+                        excluded.push(*idx);
+                    }
+                    ty = ty.projection_ty(db, store.value_data(*idx));
+                }
+            }
+            _ => {}
+        }
+
+        let mut lvalue = store.inst_result(inst);
+        while let Some(
+            AssignableValue::Aggregate { lhs, idx } | AssignableValue::Map { lhs, key: idx },
+        ) = lvalue
+        {
+            if matches!(lvalue, Some(AssignableValue::Aggregate { .. }))
+                && is_out_of_bounds(lhs.ty(db, store), *idx)
+            {
+                excluded.push(*idx);
+            }
+            lvalue = Some(lhs.as_ref());
+        }
+        excluded
+    }
+
+    /// Returns `true` if `inst` can be replaced by a `Bind` of its result
+    /// once the result is known.
+    fn is_foldable(&self, inst: InstId) -> bool {
+        match &self.body.store.inst_data(inst).kind {
+            InstKind::Unary { .. }
+            | InstKind::Binary { .. }
+            | InstKind::Cast {
+                kind: CastKind::Primitive,
+                ..
+            } => true,
+            InstKind::Bind { src } => !self.body.store.value_data(*src).is_imm(),
+            InstKind::YulIntrinsic { op, .. } => op.is_pure(),
+            _ => false,
+        }
+    }
+
+    fn fold_terminator(&mut self, terminator: InstId, state: &LocalState) -> bool {
+        let eval = Eval {
+            db: self.db,
+            body: self.body,
+            temps: &self.temps,
+        };
+        let dest = match &self.body.store.inst_data(terminator).kind {
+            InstKind::Branch { cond, then, else_ } => match eval.operand(*cond, state) {
+                Lattice::Const(cond) if cond.is_zero() => Some(*else_),
+                Lattice::Const(_) => Some(*then),
+                _ => None,
+            },
+            InstKind::Switch {
+                disc,
+                table,
+                default,
+            } => eval.switch_dest(*disc, table.iter(), *default, state),
+            _ => None,
+        };
+
+        match dest {
+            Some(dest) => {
+                self.body.store.inst_data_mut(terminator).kind = InstKind::Jump { dest };
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn make_imm(&mut self, value: Word, ty: TypeId) -> ValueId {
+        self.body.store.store_value(Value::Immediate {
+            imm: value.into(),
+            ty,
+        })
+    }
+}
@@ -0,0 +1,164 @@
+//! Control flow graph simplification.
+//!
+//! Turns branches with a single destination into jumps, redirects jumps to
+//! blocks that only jump elsewhere, removes unreachable blocks and merges a
+//! block into its predecessor if the predecessor unconditionally jumps to it
+//! and nothing else does.
+
+use fxhash::{FxHashMap, FxHashSet};
+
+use crate::{
+    analysis::ControlFlowGraph,
+    db::MirDb,
+    ir::{inst::InstKind, BasicBlockId, FunctionBody},
+};
+
+use super::Pass;
+
+pub struct SimplifyCfg;
+
+impl Pass for SimplifyCfg {
+    fn name(&self) -> &'static str {
+        "simplify_cfg"
+    }
+
+    fn run(&mut self, _db: &dyn MirDb, body: &mut FunctionBody) -> bool {
+        let mut changed = false;
+        changed |= fold_trivial_branches(body);
+        changed |= forward_empty_blocks(body);
+        changed |= fold_trivial_branches(body);
+        changed |= remove_unreachable_blocks(body);
+        changed |= merge_blocks(body);
+        changed
+    }
+}
+
+/// Replaces a branch whose destinations are the same block by a jump.
+fn fold_trivial_branches(body: &mut FunctionBody) -> bool {
+    let mut changed = false;
+    let blocks: Vec<_> = body.order.iter_block().collect();
+    for block in blocks {
+        let terminator = match body.order.terminator(&body.store, block) {
+            Some(terminator) => terminator,
+            None => continue,
+        };
+
+        let data = body.store.inst_data_mut(terminator);
+        if let InstKind::Branch { then, else_, .. } = data.kind {
+            if then == else_ {
+                data.kind = InstKind::Jump { dest: then };
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Redirects the jumps and branches to a block that consists of a single jump
+/// to the destination of that jump.
+fn forward_empty_blocks(body: &mut FunctionBody) -> bool {
+    let entry = body.order.entry();
+    let mut forwards = FxHashMap::default();
+    for block in body.order.iter_block() {
+        let first_inst = match body.order.first_inst(block) {
+            Some(inst) if block != entry => inst,
+            _ => continue,
+        };
+        if let InstKind::Jump { dest } = body.store.inst_data(first_inst).kind {
+            if dest != block {
+                forwards.insert(block, dest);
+            }
+        }
+    }
+
+    // Follows a chain of empty blocks, stops if the chain is a cycle.
+    let resolve = |mut block: BasicBlockId| {
+        let mut visited = FxHashSet::default();
+        while let Some(dest) = forwards.get(&block) {
+            if !visited.insert(block) {
+                break;
+            }
+            block = *dest;
+        }
+        block
+    };
+
+    let mut changed = false;
+    let blocks: Vec<_> = body.order.iter_block().collect();
+    for block in blocks {
+        let terminator = match body.order.terminator(&body.store, block) {
+            Some(terminator) => terminator,
+            None => continue,
+        };
+
+        let dests: Vec<_> = match body.store.inst_data(terminator).kind {
+            InstKind::Jump { dest } => vec![dest],
+            InstKind::Branch { then, else_, .. } => vec![then, else_],
+            // Switch tables are left as they are.
+            _ => continue,
+        };
+        for dest in dests {
+            let new_dest = resolve(dest);
+            if new_dest != dest && new_dest != block {
+                body.store.rewrite_branch_dest(terminator, dest, new_dest);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+fn remove_unreachable_blocks(body: &mut FunctionBody) -> bool {
+    let cfg = ControlFlowGraph::compute(body);
+    let mut reachable = FxHashSet::default();
+    let mut worklist = vec![cfg.entry()];
+    while let Some(block) = worklist.pop() {
+        if reachable.insert(block) {
+            worklist.extend(cfg.succs(block));
+        }
+    }
+
+    let unreachable: Vec<_> = body
+        .order
+        .iter_block()
+        .filter(|block| !reachable.contains(block))
+        .collect();
+    for block in &unreachable {
+        body.order.remove_block(*block);
+    }
+    !unreachable.is_empty()
+}
+
+/// Merges a block into its only predecessor if the predecessor ends with a
+/// jump to it.
+fn merge_blocks(body: &mut FunctionBody) -> bool {
+    let mut changed = false;
+    loop {
+        let cfg = ControlFlowGraph::compute(body);
+        let entry = body.order.entry();
+        let mergeable = body.order.iter_block().find_map(|block| {
+            let terminator = body.order.terminator(&body.store, block)?;
+            match body.store.inst_data(terminator).kind {
+                InstKind::Jump { dest }
+                    if dest != block && dest != entry && cfg.preds(dest) == [block] =>
+                {
+                    Some((block, terminator, dest))
+                }
+                _ => None,
+            }
+        });
+
+        let (block, jump, dest) = match mergeable {
+            Some(mergeable) => mergeable,
+            None => return changed,
+        };
+
+        body.order.remove_inst(jump);
+        while let Some(inst) = body.order.first_inst(dest) {
+            body.order.remove_inst(inst);
+            body.order.append_inst(inst, block);
+        }
+        body.order.remove_block(dest);
+        changed = true;
+    }
+}
@@ -176,24 +176,35 @@ impl PrettyPrint for InstId {
                 write!(w, "nop")
             }
 
-            InstKind::Create { value, contract } => {
+            InstKind::Create {
+                value,
+                contract,
+                args,
+            } => {
                 write!(w, "create ")?;
                 let contract_name = contract.name(db.upcast());
                 write!(w, "{contract_name} ")?;
-                value.pretty_print(db, store, w)
+                value.pretty_print(db, store, w)?;
+                write!(w, "(")?;
+                args.as_slice().pretty_print(db, store, w)?;
+                write!(w, ")")
             }
 
             InstKind::Create2 {
                 value,
                 salt,
                 contract,
+                args,
             } => {
                 write!(w, "create2 ")?;
                 let contract_name = contract.name(db.upcast());
                 write!(w, "{contract_name} ")?;
                 value.pretty_print(db, store, w)?;
                 write!(w, " ")?;
-                salt.pretty_print(db, store, w)
+                salt.pretty_print(db, store, w)?;
+                write!(w, "(")?;
+                args.as_slice().pretty_print(db, store, w)?;
+                write!(w, ")")
             }
 
             InstKind::YulIntrinsic { op, args } => {
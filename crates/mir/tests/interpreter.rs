@@ -0,0 +1,1016 @@
+use fe_analyzer::namespace::items::ModuleId;
+use fe_common::utils::keccak;
+use fe_mir::{
+    db::{MirDb, NewDb},
+    interpreter::{self, InterpreterError, Outcome, TEST_ADDRESS},
+};
+
+/// Runs all tests of `src` and returns their outcomes in declaration order.
+fn run_tests(src: &str) -> Vec<Result<Outcome, InterpreterError>> {
+    let mut db = NewDb::default();
+    let module = ModuleId::new_standalone(&mut db, "test.fe", src);
+    assert!(module.diagnostics(&db).is_empty(), "analysis failed");
+
+    module
+        .tests(&db)
+        .iter()
+        .map(|test| interpreter::run_test(&db, db.mir_lowered_func_signature(*test)))
+        .collect()
+}
+
+fn run_test(src: &str) -> Result<Outcome, InterpreterError> {
+    let mut outcomes = run_tests(src);
+    assert_eq!(outcomes.len(), 1);
+    outcomes.pop().unwrap()
+}
+
+fn assert_success(src: &str) {
+    for outcome in run_tests(src) {
+        assert!(
+            matches!(outcome, Ok(Outcome::Success { .. })),
+            "unexpected outcome: {outcome:?}"
+        );
+    }
+}
+
+fn panic_data(code: u8) -> Vec<u8> {
+    let mut data = keccak::full_as_bytes(b"Panic(uint256)")[..4].to_vec();
+    data.extend_from_slice(&[0; 31]);
+    data.push(code);
+    data
+}
+
+#[test]
+fn arithmetic() {
+    assert_success(
+        r#"
+#test
+fn test_arithmetic() {
+    let x: u8 = 200
+    let y: u8 = x + 55
+    assert y == 255
+    assert u256(x) * 2 == 400
+    assert 7 / 2 == 3
+    let z: i256 = -7
+    let two: i256 = 2
+    assert z / two == -3 and z % two == -1
+    assert 2 ** 10 == 1024
+    assert 1 << 4 == 16
+    let w: i16 = -32
+    assert w >> 2 == -8
+    assert ~u8(0) == 255
+}
+"#,
+    );
+}
+
+#[test]
+fn overflow_panics() {
+    let outcome = run_test(
+        r#"
+#test
+fn test_overflow() {
+    let x: u8 = 255
+    let y: u8 = x + 1
+}
+"#,
+    );
+    assert_eq!(
+        outcome,
+        Ok(Outcome::Revert {
+            data: panic_data(0x11)
+        })
+    );
+
+    let outcome = run_test(
+        r#"
+#test
+fn test_div_zero() {
+    let x: u256 = 0
+    let y: u256 = 1 / x
+}
+"#,
+    );
+    assert_eq!(
+        outcome,
+        Ok(Outcome::Revert {
+            data: panic_data(0x12)
+        })
+    );
+}
+
+#[test]
+fn assert_with_string() {
+    let outcome = run_test(
+        r#"
+#test
+fn test_assert() {
+    assert false, "oops"
+}
+"#,
+    );
+
+    let mut data = keccak::full_as_bytes(b"Error(string)")[..4].to_vec();
+    data.extend_from_slice(&[0; 31]);
+    data.push(0x20);
+    data.extend_from_slice(&[0; 31]);
+    data.push(4);
+    data.extend_from_slice(b"oops");
+    data.extend_from_slice(&[0; 28]);
+    assert_eq!(outcome, Ok(Outcome::Revert { data }));
+}
+
+#[test]
+fn structs_arrays_and_enums() {
+    assert_success(
+        r#"
+struct Point {
+    pub x: i32
+    pub y: i32
+}
+
+enum Shape {
+    Dot
+    Circle(Point, u256)
+
+    pub fn area(self) -> u256 {
+        match self {
+            Shape::Dot => {
+                return 0
+            }
+            Shape::Circle(_, r) => {
+                return 3 * r * r
+            }
+        }
+    }
+}
+
+#test
+fn test_aggregates() {
+    let mut p: Point = Point(x: 1, y: -2)
+    p.x += 10
+    assert p.x == 11 and p.y == -2
+
+    let mut arr: Array<u16, 4> = [1, 2, 3, 4]
+    arr[2] = 30
+    let mut sum: u16 = 0
+    for v in arr {
+        sum += v
+    }
+    assert sum == 37
+
+    let (a, b): (u8, bool) = (1, true)
+    assert a == 1 and b
+
+    assert Shape::Dot.area() == 0
+    assert Shape::Circle(p, 2).area() == 12
+}
+"#,
+    );
+}
+
+#[test]
+fn array_index_out_of_bounds() {
+    let outcome = run_test(
+        r#"
+#test
+fn test_oob() {
+    let arr: Array<u256, 3> = [0; 3]
+    let index: u256 = 3
+    let x: u256 = arr[index]
+}
+"#,
+    );
+    assert_eq!(
+        outcome,
+        Ok(Outcome::Revert {
+            data: panic_data(0x32)
+        })
+    );
+}
+
+#[test]
+fn contract_storage_and_calls() {
+    assert_success(
+        r#"
+struct Entry {
+    pub key: u256
+    pub value: String<10>
+}
+
+contract Store {
+    counter: u256
+    balances: Map<address, u256>
+    names: Map<u256, String<10>>
+    entry: Entry
+
+    pub fn __init__(mut self) {
+        self.counter = 100
+    }
+
+    pub fn inc(mut self) -> u256 {
+        self.counter += 1
+        return self.counter
+    }
+
+    pub fn set_balance(mut self, owner: address, value: u256) {
+        self.balances[owner] = value
+    }
+
+    pub fn balance(self, owner: address) -> u256 {
+        return self.balances[owner]
+    }
+
+    pub fn set_entry(mut self, key: u256, value: String<10>) {
+        self.names[key] = value
+        self.entry = Entry(key, value)
+    }
+
+    pub fn name(self, key: u256) -> String<10> {
+        return self.names[key].to_mem()
+    }
+
+    pub fn entry(self) -> Entry {
+        return self.entry.to_mem()
+    }
+
+    pub fn fail(self) {
+        revert
+    }
+}
+
+#test
+fn test_store(mut ctx: Context) {
+    let mut store: Store = Store.create(ctx, 0)
+    assert store.inc() == 101
+    assert store.inc() == 102
+
+    store.set_balance(owner: address(1), value: 26)
+    assert store.balance(owner: address(1)) == 26
+    assert store.balance(owner: address(2)) == 0
+
+    store.set_entry(key: 42, value: "hello")
+    let name: String<10> = store.name(key: 42)
+    let entry: Entry = store.entry()
+    assert entry.key == 42
+
+    let mut other: Store = Store.create(ctx, 0)
+    assert other.inc() == 101
+    assert address(other) != address(store)
+}
+"#,
+    );
+}
+
+#[test]
+fn packed_storage_layout() {
+    assert_success(
+        r#"
+use std::evm
+
+struct Config {
+    pub fee: u16
+    pub limit: u64
+    pub enabled: bool
+}
+
+contract Vault {
+    owner: address
+    paused: bool
+    fee: u16
+    config: Config
+    total: u256
+    flag: bool
+
+    pub fn set(mut self) {
+        self.owner = address(0x42)
+        self.paused = true
+        self.fee = 500
+        self.config = Config(fee: 3, limit: 1000, enabled: true)
+        self.total = 7
+        self.flag = true
+    }
+
+    pub fn config(self) -> Config {
+        return self.config.to_mem()
+    }
+
+    pub fn fee(self) -> u16 {
+        return self.fee
+    }
+
+    pub fn slot(self, index: u256) -> u256 {
+        unsafe {
+            return evm::sload(offset: index)
+        }
+    }
+}
+
+#test
+fn test_packing(mut ctx: Context) {
+    let mut vault: Vault = Vault.create(ctx, 0)
+    vault.set()
+
+    // `owner`, `paused` and `fee` share the first slot from its lower-order end.
+    assert vault.slot(index: 0) == 0x42 + (1 << 160) + (500 << 168)
+    // `Config` starts a new slot and packs its members the same way.
+    assert vault.slot(index: 1) == 3 + (1000 << 16) + (1 << 80)
+    // The field that follows a struct starts a new slot.
+    assert vault.slot(index: 2) == 7
+    assert vault.slot(index: 3) == 1
+
+    let config: Config = vault.config()
+    assert config.fee == 3
+    assert config.limit == 1000
+    assert config.enabled
+    assert vault.fee() == 500
+}
+"#,
+    );
+}
+
+#[test]
+fn storage_layout_change() {
+    // Before fields were packed from the lower-order end of a slot, the
+    // fields of `Registry` were laid out as follows:
+    //
+    // | field     | old slot, bits | new slot, bits |
+    // |-----------|----------------|----------------|
+    // | `owner`   | 0, 96..256     | 0, 0..160      |
+    // | `paused`  | 0, 88..96      | 0, 160..168    |
+    // | `fee`     | 0, 72..88      | 0, 168..184    |
+    // | `members` | 1              | 1              |
+    assert_success(
+        r#"
+use std::evm
+
+contract Registry {
+    owner: address
+    paused: bool
+    fee: u16
+    members: Map<address, bool>
+
+    pub fn set(mut self) {
+        self.owner = address(0x42)
+        self.paused = true
+        self.fee = 500
+        self.members[address(0x42)] = true
+    }
+
+    pub fn is_member(self, _ account: address) -> bool {
+        return self.members[account]
+    }
+
+    pub fn slot(self, index: u256) -> u256 {
+        unsafe {
+            return evm::sload(offset: index)
+        }
+    }
+}
+
+#test
+fn test_layout(mut ctx: Context) {
+    let mut registry: Registry = Registry.create(ctx, 0)
+    registry.set()
+
+    assert registry.slot(index: 0) != (0x42 << 96) + (1 << 88) + (500 << 72)
+    assert registry.slot(index: 0) == 0x42 + (1 << 160) + (500 << 168)
+    // The slot of a map only determines where its entries are stored.
+    assert registry.slot(index: 1) == 0
+    assert registry.is_member(address(0x42))
+    assert not registry.is_member(address(0x43))
+}
+"#,
+    );
+}
+
+#[test]
+fn external_call_revert_propagates() {
+    let outcome = run_test(
+        r#"
+contract Foo {
+    pub fn fail(self) {
+        revert
+    }
+}
+
+#test
+fn test_fail(mut ctx: Context) {
+    let foo: Foo = Foo.create(ctx, 0)
+    foo.fail()
+}
+"#,
+    );
+    assert_eq!(outcome, Ok(Outcome::Revert { data: vec![] }));
+}
+
+#[test]
+fn create_with_init_args() {
+    assert_success(
+        r#"
+contract Foo {
+    owner: address
+    nums: Array<u8, 3>
+    count: u256
+
+    pub fn __init__(mut self, ctx: Context, owner: address, nums: Array<u8, 3>, _ count: u256) {
+        self.owner = owner
+        self.nums = nums
+        self.count = count
+    }
+
+    pub fn owner(self) -> address {
+        return self.owner
+    }
+
+    pub fn sum(self) -> u256 {
+        let nums: Array<u8, 3> = self.nums.to_mem()
+        return u256(nums[0]) + u256(nums[1]) + u256(nums[2]) + self.count
+    }
+}
+
+#test
+fn test_create(mut ctx: Context) {
+    let foo: Foo = Foo.create(ctx, 0, owner: address(26), nums: [1, 2, 3], 4)
+    assert foo.owner() == address(26)
+    assert foo.sum() == 10
+
+    let bar: Foo = Foo.create2(ctx, 0, 7, owner: address(42), nums: [0, 0, 1], 0)
+    assert bar.owner() == address(42)
+    assert bar.sum() == 1
+}
+"#,
+    );
+}
+
+#[test]
+fn events() {
+    let outcome = run_test(
+        r#"
+struct Nums {
+    #indexed
+    pub num1: u256
+    pub num2: u256
+}
+
+contract Foo {
+    pub fn emit_nums(self, mut ctx: Context) {
+        ctx.emit(Nums(num1: 26, num2: 42))
+    }
+}
+
+#test
+fn test_emit(mut ctx: Context) {
+    ctx.emit(Nums(num1: 1, num2: 2))
+    let foo: Foo = Foo.create(ctx, 0)
+    foo.emit_nums(ctx)
+}
+"#,
+    );
+
+    let logs = match outcome {
+        Ok(Outcome::Success { logs }) => logs,
+        _ => panic!("unexpected outcome: {outcome:?}"),
+    };
+    assert_eq!(logs.len(), 2);
+
+    let signature = keccak::full_as_bytes(b"Nums(uint256,uint256)");
+    let word = |value: u8| {
+        let mut word = [0; 32];
+        word[31] = value;
+        word
+    };
+    assert_eq!(logs[0].address, TEST_ADDRESS);
+    assert_eq!(logs[0].topics, vec![signature, word(1)]);
+    assert_eq!(logs[0].data, word(2).to_vec());
+
+    assert_ne!(logs[1].address, TEST_ADDRESS);
+    assert_eq!(logs[1].topics, vec![signature, word(26)]);
+    assert_eq!(logs[1].data, word(42).to_vec());
+}
+
+#[test]
+fn revert_with_error() {
+    let outcome = run_test(
+        r#"
+error Denied(code: u256, reason: String<8>)
+
+#test
+fn test_revert() {
+    revert Denied(code: 7, reason: "nope")
+}
+"#,
+    );
+
+    // The fields are encoded as the arguments of `Denied`, so the string is
+    // referred to by an offset from the start of the arguments.
+    let mut data = keccak::full_as_bytes(b"Denied(uint256,string)")[..4].to_vec();
+    data.extend_from_slice(&[0; 31]);
+    data.push(7);
+    data.extend_from_slice(&[0; 31]);
+    data.push(0x40);
+    data.extend_from_slice(&[0; 31]);
+    data.push(4);
+    data.extend_from_slice(b"nope");
+    data.extend_from_slice(&[0; 28]);
+    assert_eq!(outcome, Ok(Outcome::Revert { data }));
+}
+
+#[test]
+fn generic_struct_and_enum() {
+    assert_success(
+        r#"
+struct Pair<T, U> {
+    pub first: T
+    pub second: U
+
+    pub fn swap(self) -> Pair<U, T> {
+        return Pair<U, T>(first: self.second, second: self.first)
+    }
+}
+
+enum Maybe<T> {
+    Some(T)
+    None
+
+    pub fn unwrap_or(self, _ default: T) -> T {
+        match self {
+            Maybe::Some(value) => {
+                return value
+            }
+            Maybe::None => {
+                return default
+            }
+        }
+    }
+}
+
+fn find(_ values: Array<u8, 3>, _ target: u8) -> Maybe<u256> {
+    let mut i: u256 = 0
+    while i < 3 {
+        if values[i] == target {
+            return Maybe::Some(i)
+        }
+        i += 1
+    }
+    return Maybe::None
+}
+
+#test
+fn test_pair() {
+    let pair: Pair<u8, bool> = Pair(first: 1, second: true)
+    let swapped: Pair<bool, u8> = pair.swap()
+    assert swapped.first and swapped.second == 1
+    let nested: Pair<Pair<u8, bool>, u256> = Pair<Pair<u8, bool>, u256>(first: pair, second: 2)
+    assert nested.first.first == 1 and nested.second == 2
+}
+
+#test
+fn test_option() {
+    let values: Array<u8, 3> = [4, 5, 6]
+    assert find(values, 6).unwrap_or(10) == 2
+    assert find(values, 7).unwrap_or(10) == 10
+    let flag: Maybe<bool> = Maybe::Some(true)
+    assert flag.unwrap_or(false)
+}
+"#,
+    );
+}
+
+#[test]
+fn option_and_result() {
+    let outcomes = run_tests(
+        r#"
+fn checked_div(_ x: u256, _ y: u256) -> Result<u256, u8> {
+    if y == 0 {
+        return Result::Err(1)
+    }
+    return Result::Ok(x / y)
+}
+
+fn halve_quotient(_ x: u256, _ y: u256) -> Result<u256, u8> {
+    let quotient: u256 = checked_div(x, y)?
+    return Result::Ok(quotient / 2)
+}
+
+fn first_even(_ values: Array<u256, 3>) -> Option<u256> {
+    let mut i: u256 = 0
+    while i < 3 {
+        if values[i] % 2 == 0 {
+            return Option::Some(values[i])
+        }
+        i += 1
+    }
+    return Option::None
+}
+
+fn sum_first_evens(_ a: Array<u256, 3>, _ b: Array<u256, 3>) -> Option<u256> {
+    return Option::Some(first_even(a)? + first_even(b)?)
+}
+
+#test
+fn test_result() {
+    assert halve_quotient(20, 2).unwrap() == 5
+    assert halve_quotient(20, 0).unwrap_err() == 1
+    assert halve_quotient(20, 0).is_err()
+    assert halve_quotient(20, 0).unwrap_or(7) == 7
+    assert checked_div(9, 3).ok().unwrap() == 3
+    assert checked_div(9, 0).ok().is_none()
+    assert checked_div(9, 0).err().unwrap() == 1
+}
+
+#test
+fn test_option() {
+    assert sum_first_evens([1, 2, 3], [5, 6, 8]).unwrap() == 8
+    assert sum_first_evens([1, 2, 3], [5, 7, 9]).is_none()
+    assert sum_first_evens([1, 3, 5], [5, 6, 8]).unwrap_or(0) == 0
+    assert first_even([1, 4, 3]).is_some()
+}
+
+#test
+fn test_unwrap_none() {
+    first_even([1, 3, 5]).unwrap()
+}
+"#,
+    );
+
+    assert!(matches!(outcomes[0], Ok(Outcome::Success { .. })));
+    assert!(matches!(outcomes[1], Ok(Outcome::Success { .. })));
+    assert_eq!(outcomes[2], Ok(Outcome::Revert { data: vec![] }));
+}
+
+#[test]
+fn transient_reentrancy_lock() {
+    let outcomes = run_tests(
+        r#"
+use std::evm
+
+contract Guarded {
+    locked: Transient<bool>
+    calls: Transient<u256>
+    total: u256
+
+    pub fn run(mut self, ctx: Context, hook: address, reenter: bool) {
+        if self.locked {
+            revert
+        }
+        self.locked = true
+        self.calls += 1
+        self.total += 1
+        Hook(hook).call_back(ctx, target: ctx.self_address(), reenter)
+        self.locked = false
+    }
+
+    pub fn calls(self) -> u256 {
+        return self.calls
+    }
+
+    pub fn slot(self, index: u256) -> u256 {
+        unsafe {
+            return evm::sload(offset: index)
+        }
+    }
+
+    pub fn transient_slot(self, index: u256) -> u256 {
+        unsafe {
+            return evm::tload(offset: index)
+        }
+    }
+}
+
+contract Hook {
+    pub fn call_back(self, ctx: Context, target: address, reenter: bool) {
+        if reenter {
+            let mut guarded: Guarded = Guarded(target)
+            guarded.run(ctx, hook: ctx.self_address(), reenter: false)
+        }
+    }
+}
+
+#test
+fn test_lock(mut ctx: Context) {
+    let mut guarded: Guarded = Guarded.create(ctx, 0)
+    let hook: Hook = Hook.create(ctx, 0)
+    guarded.run(ctx, hook: address(hook), reenter: false)
+    guarded.run(ctx, hook: address(hook), reenter: false)
+
+    // Transient storage lives until the end of the transaction, which is
+    // the whole test here.
+    assert guarded.calls() == 2
+    assert guarded.transient_slot(index: 0) == 0
+    assert guarded.transient_slot(index: 1) == 2
+
+    // Transient fields take no space in storage.
+    assert guarded.slot(index: 0) == 2
+}
+
+#test
+fn test_reenter(mut ctx: Context) {
+    let mut guarded: Guarded = Guarded.create(ctx, 0)
+    let hook: Hook = Hook.create(ctx, 0)
+    guarded.run(ctx, hook: address(hook), reenter: true)
+}
+"#,
+    );
+
+    assert!(matches!(outcomes[0], Ok(Outcome::Success { .. })));
+    assert_eq!(outcomes[1], Ok(Outcome::Revert { data: vec![] }));
+}
+
+#[test]
+fn receive_and_fallback() {
+    assert_success(
+        r#"
+use std::evm
+
+contract Wallet {
+    receives: u256
+    fallbacks: u256
+
+    pub fn __receive__(mut self) {
+        self.receives += 1
+    }
+
+    pub fn __fallback__(mut self) {
+        self.fallbacks += 1
+    }
+
+    pub fn counts(self) -> (u256, u256) {
+        return (self.receives, self.fallbacks)
+    }
+}
+
+fn raw_call(_ addr: address, input_len: u256) -> bool {
+    unsafe {
+        return evm::call_2(gas: evm::gas_remaining(), addr, value: 0, input_offset: 0, input_len, output_offset: 0, output_len: 0)
+    }
+}
+
+#test
+fn test_entry_points(mut ctx: Context) {
+    let wallet: Wallet = Wallet.create(ctx, 0)
+
+    // Empty call data runs `__receive__`.
+    assert raw_call(address(wallet), input_len: 0)
+
+    // A selector that doesn't match any function runs `__fallback__`.
+    unsafe {
+        evm::mstore(offset: 0, value: 0xdeadbeef << 224)
+    }
+    assert raw_call(address(wallet), input_len: 4)
+    assert raw_call(address(wallet), input_len: 4)
+
+    let (receives, fallbacks): (u256, u256) = wallet.counts()
+    assert receives == 1
+    assert fallbacks == 2
+}
+"#,
+    );
+}
+
+#[test]
+fn interface_calls() {
+    let outcomes = run_tests(
+        r#"
+interface Counter {
+    fn count(self) -> u256;
+    fn add(mut self, amount: u256) -> u256;
+    fn fail(self);
+}
+
+contract CounterImpl {
+    total: u256
+
+    pub fn count(self) -> u256 {
+        return self.total
+    }
+
+    pub fn add(mut self, amount: u256) -> u256 {
+        self.total += amount
+        return self.total
+    }
+
+    pub fn fail(self) {
+        revert
+    }
+}
+
+#test
+fn test_calls(mut ctx: Context) {
+    let target: CounterImpl = CounterImpl.create(ctx, 0)
+    let mut counter: Counter = Counter(address(target))
+    assert counter.add(amount: 5) == 5
+    assert counter.add(amount: 2) == 7
+    assert counter.count() == 7
+    assert target.count() == 7
+}
+
+#test
+fn test_revert(mut ctx: Context) {
+    let target: CounterImpl = CounterImpl.create(ctx, 0)
+    let counter: Counter = Counter(address(target))
+    counter.fail()
+}
+"#,
+    );
+
+    assert!(matches!(outcomes[0], Ok(Outcome::Success { .. })));
+    assert_eq!(outcomes[1], Ok(Outcome::Revert { data: vec![] }));
+}
+
+#[test]
+fn fuzz_test_args() {
+    let mut db = NewDb::default();
+    let src = r#"
+struct Point {
+    pub x: i8
+    pub y: i8
+}
+
+#test
+fn test_args(mut ctx: Context, amount: u256, to: address, p: Point, flags: Array<bool, 2>) {
+    assert amount < 100
+    assert to != address(0)
+    assert p.x + p.y == 1
+    assert flags[0] and not flags[1]
+}
+"#;
+    let module = ModuleId::new_standalone(&mut db, "test.fe", src);
+    assert!(module.diagnostics(&db).is_empty(), "analysis failed");
+    let test = db.mir_lowered_func_signature(module.tests(&db)[0]);
+
+    let word = |value: u8| {
+        let mut word = [0; 32];
+        word[31] = value;
+        word
+    };
+    let calldata = |amount: u8| {
+        let mut data = vec![];
+        data.extend_from_slice(&word(amount));
+        data.extend_from_slice(&word(26));
+        data.extend_from_slice(&[0xff; 32]);
+        data.extend_from_slice(&word(2));
+        data.extend_from_slice(&word(1));
+        data.extend_from_slice(&word(0));
+        data
+    };
+
+    let outcome = interpreter::run_fuzz_test(&db, test, calldata(99));
+    assert!(
+        matches!(outcome, Ok(Outcome::Success { .. })),
+        "unexpected outcome: {outcome:?}"
+    );
+    let outcome = interpreter::run_fuzz_test(&db, test, calldata(100));
+    assert_eq!(
+        outcome,
+        Ok(Outcome::Revert {
+            data: panic_data(1)
+        })
+    );
+}
+
+/// Returns the output of `revert Error(string)` with `reason`.
+fn error_data(reason: &str) -> Vec<u8> {
+    let mut data = keccak::full_as_bytes(b"Error(string)")[..4].to_vec();
+    data.extend_from_slice(&[0; 31]);
+    data.push(0x20);
+    data.extend_from_slice(&[0; 31]);
+    data.push(reason.len() as u8);
+    data.extend_from_slice(reason.as_bytes());
+    data.resize(4 + (data.len() - 4).div_ceil(32) * 32, 0);
+    data
+}
+
+#[test]
+fn cheatcodes() {
+    let outcomes = run_tests(
+        r#"
+use std::testing
+
+contract Wallet {
+    owner: address
+
+    pub fn __init__(mut self, ctx: Context) {
+        self.owner = ctx.msg_sender()
+    }
+
+    pub fn owner(self) -> address {
+        return self.owner
+    }
+
+    #payable
+    pub fn deposit(self, ctx: Context) -> address {
+        return ctx.msg_sender()
+    }
+}
+
+#test
+fn test_env(mut ctx: Context) {
+    assert ctx.block_timestamp() == 1
+    testing::warp(100)
+    testing::roll(7)
+    let id: u256 = testing::snapshot()
+    testing::warp(200)
+    testing::deal(account: ctx.self_address(), balance: 50)
+    testing::revert_to(id)
+    assert ctx.block_timestamp() == 100
+    assert ctx.block_number() == 7
+    assert ctx.self_balance() == 0
+}
+
+#test
+fn test_sender(mut ctx: Context) {
+    testing::prank(address(0xa11ce))
+    let wallet: Wallet = Wallet.create(ctx, 0)
+    assert wallet.owner() == address(0xa11ce)
+    assert wallet.deposit(ctx) == ctx.self_address()
+
+    testing::start_prank(address(0xb0b))
+    testing::deal(account: address(0xb0b), balance: 10)
+    testing::next_call_value(4)
+    assert wallet.deposit(ctx) == address(0xb0b)
+    assert wallet.deposit(ctx) == address(0xb0b)
+    testing::stop_prank()
+    assert ctx.balance_of(address(wallet)) == 4
+    assert ctx.balance_of(address(0xb0b)) == 6
+}
+
+#test
+fn test_unknown_snapshot() {
+    testing::revert_to(5)
+}
+"#,
+    );
+
+    for outcome in &outcomes[..2] {
+        assert!(
+            matches!(outcome, Ok(Outcome::Success { .. })),
+            "unexpected outcome: {outcome:?}"
+        );
+    }
+    assert_eq!(
+        outcomes[2],
+        Ok(Outcome::Revert {
+            data: error_data("unknown snapshot 5")
+        })
+    );
+}
+
+#[test]
+fn expect_emit() {
+    let outcomes = run_tests(
+        r#"
+use std::testing
+
+struct Ping {
+    pub value: u256
+}
+
+contract Pinger {
+    pub fn ping(self, mut ctx: Context, value: u256) {
+        ctx.emit(Ping(value))
+    }
+}
+
+#test
+fn test_emitted(mut ctx: Context) {
+    let pinger: Pinger = Pinger.create(ctx, 0)
+    testing::expect_emit()
+    ctx.emit(Ping(value: 1))
+    pinger.ping(ctx, value: 1)
+}
+
+#test
+fn test_not_emitted(mut ctx: Context) {
+    let pinger: Pinger = Pinger.create(ctx, 0)
+    testing::expect_emit()
+    ctx.emit(Ping(value: 1))
+    pinger.ping(ctx, value: 2)
+}
+
+#test
+fn test_no_call(mut ctx: Context) {
+    testing::expect_emit()
+    ctx.emit(Ping(value: 1))
+}
+"#,
+    );
+
+    assert!(
+        matches!(outcomes[0], Ok(Outcome::Success { .. })),
+        "unexpected outcome: {:?}",
+        outcomes[0]
+    );
+    assert_eq!(
+        outcomes[1],
+        Ok(Outcome::Revert {
+            data: error_data("expected event was not emitted")
+        })
+    );
+    assert_eq!(
+        outcomes[2],
+        Ok(Outcome::Revert {
+            data: error_data("`expect_emit` must be followed by a call")
+        })
+    );
+}
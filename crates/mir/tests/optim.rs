@@ -0,0 +1,253 @@
+use fe_analyzer::namespace::items::{IngotId, ModuleId};
+use fe_common::{files::Utf8Path, utils::files::BuildFiles};
+use fe_mir::{
+    analysis::{ControlFlowGraph, DomTree, LoopTree, PostDomTree},
+    db::{MirDb, NewDb},
+    interpreter,
+    ir::{
+        inst::{BinOp, InstKind},
+        FunctionBody, FunctionId,
+    },
+};
+
+/// Returns the lowered function `name` of `src`.
+fn lower_function(db: &mut NewDb, src: &str, name: &str) -> FunctionId {
+    let module = ModuleId::new_standalone(db, "test.fe", src);
+    assert!(module.diagnostics(db).is_empty(), "analysis failed");
+
+    *db.mir_lower_module_all_functions(module)
+        .iter()
+        .find(|func| func.name(db) == name)
+        .unwrap()
+}
+
+fn inst_kinds(body: &FunctionBody) -> Vec<InstKind> {
+    body.order
+        .iter_block()
+        .flat_map(|block| body.order.iter_inst(block))
+        .map(|inst| body.store.inst_data(inst).kind.clone())
+        .collect()
+}
+
+#[test]
+fn fold_checked_arithmetic() {
+    let mut db = NewDb::default();
+    let func = lower_function(
+        &mut db,
+        r#"
+fn add() -> u8 {
+    let x: u8 = 200
+    let y: u8 = 50
+    return x + y
+}
+"#,
+        "add",
+    );
+
+    let is_add = |kind: &InstKind| matches!(kind, InstKind::Binary { op: BinOp::Add, .. });
+    assert!(inst_kinds(&func.body(&db)).iter().any(is_add));
+    assert!(!inst_kinds(&func.optimized_body(&db)).iter().any(is_add));
+}
+
+#[test]
+fn keep_overflowing_arithmetic() {
+    let mut db = NewDb::default();
+    let func = lower_function(
+        &mut db,
+        r#"
+fn add() -> u8 {
+    let x: u8 = 200
+    let y: u8 = 56
+    return x + y
+}
+"#,
+        "add",
+    );
+
+    let is_add = |kind: &InstKind| matches!(kind, InstKind::Binary { op: BinOp::Add, .. });
+    assert!(inst_kinds(&func.optimized_body(&db)).iter().any(is_add));
+}
+
+#[test]
+fn prune_constant_branch() {
+    let mut db = NewDb::default();
+    let func = lower_function(
+        &mut db,
+        r#"
+fn select(x: u256) -> u256 {
+    let flag: bool = false
+    if flag {
+        return x * 2
+    } else {
+        return x
+    }
+}
+"#,
+        "select",
+    );
+
+    let kinds = inst_kinds(&func.optimized_body(&db));
+    assert!(!kinds
+        .iter()
+        .any(|kind| matches!(kind, InstKind::Branch { .. } | InstKind::Binary { .. })));
+    assert_eq!(func.optimized_body(&db).order.iter_block().count(), 1);
+}
+
+/// Runs the tests of `src` with and without optimizations and checks that the
+/// outcomes are the same.
+fn assert_same_outcomes(src: &str) {
+    let mut db = NewDb::default();
+    let module = ModuleId::new_standalone(&mut db, "test.fe", src);
+    assert!(module.diagnostics(&db).is_empty(), "analysis failed");
+
+    for test in module.tests(&db).iter() {
+        let func = db.mir_lowered_func_signature(*test);
+        let outcome = interpreter::run_test(&db, func).unwrap();
+        let optimized_outcome = interpreter::run_optimized_test(&db, func).unwrap();
+        assert_eq!(outcome, optimized_outcome);
+    }
+}
+
+/// Like `assert_same_outcomes`, but for the tests of a fixture module. Tests
+/// that the interpreter can't run must fail the same way when optimized.
+fn assert_same_module_outcomes(db: &NewDb, module: ModuleId) {
+    for test in module.tests(db).iter() {
+        let func = db.mir_lowered_func_signature(*test);
+        let outcome = interpreter::run_test(db, func);
+        let optimized_outcome = interpreter::run_optimized_test(db, func);
+        assert_eq!(
+            outcome,
+            optimized_outcome,
+            "outcome of `{}` changed",
+            test.name(db)
+        );
+    }
+}
+
+#[test]
+fn preserve_test_outcomes() {
+    assert_same_outcomes(
+        r#"
+fn sum(_ n: u256) -> u256 {
+    let mut total: u256 = 0
+    let mut i: u256 = 0
+    while i < n {
+        total += i
+        i += 1
+    }
+    return total
+}
+
+#test
+fn test_loop() {
+    let limit: u256 = 10
+    assert sum(limit) == 45
+    assert sum(0) == 0
+}
+
+#test
+fn test_overflow() {
+    let x: u8 = 255
+    let one: u8 = 1
+    let y: u8 = x + one
+}
+
+#test
+fn test_division_by_zero() {
+    let zero: i8 = 0
+    let x: i8 = -7 / zero
+}
+
+#test
+fn test_signed() {
+    let x: i8 = -128
+    let y: i16 = i16(x) * 2
+    assert y == -256
+    let array: Array<u8, 3> = [1, 2, 3]
+    let index: u256 = 2
+    assert array[index] == 3
+}
+
+#test
+fn test_out_of_bounds() {
+    let array: Array<u8, 3> = [1, 2, 3]
+    let index: u256 = 3
+    let x: u8 = array[index]
+}
+"#,
+    );
+}
+
+#[test]
+fn preserve_fixture_test_outcomes() {
+    for (path, src) in test_files::new_fixture_dir_files("files") {
+        let mut db = NewDb::default();
+        let file_name = Utf8Path::new(path).file_name().unwrap();
+        let module = ModuleId::new_standalone(&mut db, file_name, src);
+        assert!(
+            module.diagnostics(&db).is_empty(),
+            "analysis of {path} failed"
+        );
+        assert_same_module_outcomes(&db, module);
+    }
+}
+
+#[test]
+fn preserve_ingot_fixture_test_outcomes() {
+    // `git_dependency_ingot` is left out, because its dependency is fetched
+    // from the network.
+    for path in [
+        "ingots/basic_ingot",
+        "ingots/pub_contract_ingot",
+        "ingots/sanity_ingot",
+        "ingots/trait_ingot_check",
+        "ingots/trait_no_ambiguity",
+        "ingots/visibility_ingot",
+    ] {
+        let mut db = NewDb::default();
+        let build_files =
+            BuildFiles::load_static(test_files::new_fixture_dir_files("ingots"), path)
+                .expect("failed to statically load build files");
+        let ingot = IngotId::from_build_files(&mut db, &build_files);
+        assert!(
+            ingot.diagnostics(&db).is_empty(),
+            "analysis of {path} failed"
+        );
+        for module in ingot.all_modules(&db).iter() {
+            assert_same_module_outcomes(&db, *module);
+        }
+    }
+}
+
+macro_rules! test_optimize {
+    ($name:ident, $path:expr) => {
+        #[test]
+        fn $name() {
+            let mut db = NewDb::default();
+
+            let file_name = Utf8Path::new($path).file_name().unwrap();
+            let module = ModuleId::new_standalone(&mut db, file_name, test_files::fixture($path));
+
+            let diags = module.diagnostics(&db);
+            if !diags.is_empty() {
+                panic!("lowering failed")
+            }
+
+            for func in db.mir_lower_module_all_functions(module).iter() {
+                let body = func.optimized_body(&db);
+                let cfg = ControlFlowGraph::compute(&body);
+                let domtree = DomTree::compute(&cfg);
+                LoopTree::compute(&cfg, &domtree);
+                PostDomTree::compute(&body);
+            }
+        }
+    };
+}
+
+test_optimize! { optimize_erc20_token, "demos/erc20_token.fe"}
+test_optimize! { optimize_guest_book, "demos/guest_book.fe"}
+test_optimize! { optimize_uniswap, "demos/uniswap.fe"}
+test_optimize! { optimize_while_loop, "features/while_loop.fe"}
+test_optimize! { optimize_for_loop, "features/for_loop_with_break.fe"}
+test_optimize! { optimize_match, "features/enum_match.fe"}
+test_optimize! { optimize_checked_arithmetic, "features/checked_arithmetic.fe"}
@@ -19,16 +19,27 @@ pub enum ModuleStmt {
     Use(Node<Use>),
     TypeAlias(Node<TypeAlias>),
     Contract(Node<Contract>),
+    Interface(Node<Interface>),
     Constant(Node<ConstantDecl>),
     Struct(Node<Struct>),
+    /// An `error` definition. Its parameters are the fields of the struct.
+    Error(Node<Struct>),
     Enum(Node<Enum>),
     Trait(Node<Trait>),
     Impl(Node<Impl>),
     Function(Node<Function>),
-    Attribute(Node<SmolStr>),
+    Attribute(Node<Attribute>),
     ParseError(Span),
 }
 
+/// An attribute of the item that follows it, e.g. `#test` or
+/// `#test(should_revert)`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Attribute {
+    pub name: Node<SmolStr>,
+    pub args: Option<Node<Vec<Node<CallArg>>>>,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Pragma {
     pub version_requirement: Node<SmolStr>,
@@ -90,9 +101,19 @@ pub struct Contract {
     pub pub_qual: Option<Span>,
 }
 
+/// An `interface` declares the functions of a contract deployed elsewhere,
+/// which are called through the contract ABI.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Interface {
+    pub name: Node<SmolStr>,
+    pub functions: Vec<Node<FunctionSignature>>,
+    pub pub_qual: Option<Span>,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Struct {
     pub name: Node<SmolStr>,
+    pub generic_params: Node<Vec<GenericParameter>>,
     pub fields: Vec<Node<Field>>,
     pub functions: Vec<Node<Function>>,
     pub pub_qual: Option<Span>,
@@ -101,6 +122,7 @@ pub struct Struct {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct Enum {
     pub name: Node<SmolStr>,
+    pub generic_params: Node<Vec<GenericParameter>>,
     pub variants: Vec<Node<Variant>>,
     pub functions: Vec<Node<Function>>,
     pub pub_qual: Option<Span>,
@@ -132,9 +154,7 @@ pub enum TypeDesc {
         items: Vec1<Node<TypeDesc>>,
     },
     Generic {
-        // TODO: when we support user-defined generic types,
-        // this will have to be a `Path`
-        base: Node<SmolStr>,
+        base: Node<Path>,
         args: Node<Vec<GenericArg>>,
     },
     SelfType,
@@ -236,6 +256,8 @@ pub enum VariantKind {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub enum ContractStmt {
     Function(Node<Function>),
+    /// An attribute of the function that follows it, e.g. `#hot`.
+    Attribute(Node<SmolStr>),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
@@ -431,6 +453,11 @@ pub enum Expr {
         value: Box<Node<Expr>>,
         index: Box<Node<Expr>>,
     },
+    /// `value?`, which evaluates to the payload of an `Ok` or `Some` value and
+    /// returns an `Err` or `None` value from the enclosing function.
+    Try {
+        value: Box<Node<Expr>>,
+    },
     Call {
         func: Box<Node<Expr>>,
         generic_args: Option<Node<Vec<GenericArg>>>,
@@ -504,6 +531,12 @@ impl Node<Contract> {
     }
 }
 
+impl Node<Interface> {
+    pub fn name(&self) -> &str {
+        &self.kind.name.kind
+    }
+}
+
 impl Node<Struct> {
     pub fn name(&self) -> &str {
         &self.kind.name.kind
@@ -571,8 +604,10 @@ impl Spanned for ModuleStmt {
             ModuleStmt::Impl(inner) => inner.span,
             ModuleStmt::TypeAlias(inner) => inner.span,
             ModuleStmt::Contract(inner) => inner.span,
+            ModuleStmt::Interface(inner) => inner.span,
             ModuleStmt::Constant(inner) => inner.span,
             ModuleStmt::Struct(inner) => inner.span,
+            ModuleStmt::Error(inner) => inner.span,
             ModuleStmt::Enum(inner) => inner.span,
             ModuleStmt::Function(inner) => inner.span,
             ModuleStmt::Attribute(inner) => inner.span,
@@ -585,6 +620,7 @@ impl Spanned for ContractStmt {
     fn span(&self) -> Span {
         match self {
             ContractStmt::Function(inner) => inner.span,
+            ContractStmt::Attribute(inner) => inner.span,
         }
     }
 }
@@ -619,11 +655,27 @@ impl fmt::Display for ModuleStmt {
             ModuleStmt::Impl(node) => write!(f, "{}", node.kind),
             ModuleStmt::TypeAlias(node) => write!(f, "{}", node.kind),
             ModuleStmt::Contract(node) => write!(f, "{}", node.kind),
+            ModuleStmt::Interface(node) => write!(f, "{}", node.kind),
             ModuleStmt::Constant(node) => write!(f, "{}", node.kind),
             ModuleStmt::Struct(node) => write!(f, "{}", node.kind),
+            ModuleStmt::Error(node) => {
+                let Struct {
+                    name,
+                    fields,
+                    pub_qual,
+                    ..
+                } = &node.kind;
+                if pub_qual.is_some() {
+                    write!(f, "pub ")?;
+                }
+                let fields = fields
+                    .iter()
+                    .map(|field| format!("{}: {}", field.kind.name.kind, field.kind.typ.kind));
+                write!(f, "error {}({})", name.kind, comma_joined(fields))
+            }
             ModuleStmt::Enum(node) => write!(f, "{}", node.kind),
             ModuleStmt::Function(node) => write!(f, "{}", node.kind),
-            ModuleStmt::Attribute(node) => writeln!(f, "#{}", node.kind),
+            ModuleStmt::Attribute(node) => writeln!(f, "{}", node.kind),
             ModuleStmt::ParseError(span) => {
                 write!(f, "# PARSE ERROR: {}..{}", span.start, span.end)
             }
@@ -631,6 +683,16 @@ impl fmt::Display for ModuleStmt {
     }
 }
 
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.name.kind)?;
+        if let Some(args) = &self.args {
+            write!(f, "({})", node_comma_joined(&args.kind))?;
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Display for Pragma {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "pragma {}", self.version_requirement.kind)
@@ -699,6 +761,17 @@ impl fmt::Display for Trait {
     }
 }
 
+impl fmt::Display for Interface {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.pub_qual.is_some() {
+            write!(f, "pub ")?;
+        }
+        writeln!(f, "interface {}:", self.name.kind)?;
+
+        Ok(())
+    }
+}
+
 impl fmt::Display for Impl {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         writeln!(
@@ -747,8 +820,21 @@ impl fmt::Display for Contract {
             writeln!(f)?;
         }
         if !body.is_empty() {
+            // An attribute is printed on the line above the function it
+            // applies to.
+            let mut body_str = String::new();
+            let mut prev: Option<&ContractStmt> = None;
+            for stmt in body {
+                match prev {
+                    Some(ContractStmt::Attribute(_)) => body_str.push('\n'),
+                    Some(_) => body_str.push_str("\n\n"),
+                    None => {}
+                }
+                body_str.push_str(&stmt.to_string());
+                prev = Some(stmt);
+            }
             writeln!(f)?;
-            write!(indented(f), "{}", double_line_joined(body))?;
+            write!(indented(f), "{body_str}")?;
             writeln!(f)?;
         }
         write!(f, "}}")
@@ -759,6 +845,7 @@ impl fmt::Display for Struct {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let Struct {
             name,
+            generic_params,
             fields,
             functions,
             pub_qual,
@@ -767,7 +854,11 @@ impl fmt::Display for Struct {
         if pub_qual.is_some() {
             write!(f, "pub ")?;
         }
-        write!(f, "struct {} ", name.kind)?;
+        write!(f, "struct {}", name.kind)?;
+        if !generic_params.kind.is_empty() {
+            write!(f, "<{}>", comma_joined(generic_params.kind.iter()))?;
+        }
+        write!(f, " ")?;
         write!(f, "{{")?;
         write_nodes_line_wrapped(&mut indented(f), fields)?;
 
@@ -785,6 +876,7 @@ impl fmt::Display for Enum {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let Enum {
             name,
+            generic_params,
             variants,
             functions,
             pub_qual,
@@ -794,7 +886,11 @@ impl fmt::Display for Enum {
             write!(f, "pub ")?;
         }
 
-        write!(f, "enum {} ", name.kind)?;
+        write!(f, "enum {}", name.kind)?;
+        if !generic_params.kind.is_empty() {
+            write!(f, "<{}>", comma_joined(generic_params.kind.iter()))?;
+        }
+        write!(f, " ")?;
         write!(f, "{{")?;
         write_nodes_line_wrapped(&mut indented(f), variants)?;
 
@@ -873,6 +969,7 @@ impl fmt::Display for ContractStmt {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             ContractStmt::Function(node) => write!(f, "{}", node.kind),
+            ContractStmt::Attribute(node) => write!(f, "#{}", node.kind),
         }
     }
 }
@@ -1101,6 +1198,15 @@ impl fmt::Display for Expr {
             }
             Expr::Attribute { value, attr } => write!(f, "{}.{}", value.kind, attr.kind),
             Expr::Subscript { value, index } => write!(f, "{}[{}]", value.kind, index.kind),
+            Expr::Try { value } => {
+                if matches!(value.kind, Expr::Ternary { .. })
+                    || expr_right_binding_power(&value.kind) < u8::MAX
+                {
+                    write!(f, "({})?", value.kind)
+                } else {
+                    write!(f, "{}?", value.kind)
+                }
+            }
             Expr::Call {
                 func,
                 generic_args,
@@ -1296,11 +1402,11 @@ fn double_line_joined(items: &[impl fmt::Display]) -> String {
         .join("\n\n")
 }
 
-trait InfixBindingPower {
+pub(crate) trait InfixBindingPower {
     fn infix_binding_power(&self) -> (u8, u8);
 }
 
-trait PrefixBindingPower {
+pub(crate) trait PrefixBindingPower {
     fn prefix_binding_power(&self) -> u8;
 }
 
@@ -1383,6 +1489,7 @@ fn expr_left_binding_power(expr: &Expr) -> u8 {
         Expr::CompOperation { op, .. } => op.kind.infix_binding_power().0,
         Expr::Attribute { .. } => max_power,
         Expr::Subscript { .. } => max_power,
+        Expr::Try { .. } => max_power,
         Expr::Call { .. } => max_power,
         Expr::List { .. } => max_power,
         Expr::Repeat { .. } => max_power,
@@ -1407,6 +1514,7 @@ fn expr_right_binding_power(expr: &Expr) -> u8 {
         Expr::CompOperation { op, .. } => op.kind.infix_binding_power().1,
         Expr::Attribute { .. } => max_power,
         Expr::Subscript { .. } => max_power,
+        Expr::Try { .. } => max_power,
         Expr::Call { .. } => max_power,
         Expr::List { .. } => max_power,
         Expr::Repeat { .. } => max_power,
@@ -0,0 +1,1310 @@
+//! Canonical source formatting for Fe modules.
+//!
+//! The formatter prints a parsed [`Module`] in a single canonical layout. Since
+//! the AST doesn't contain comments, the comments are collected separately by
+//! lexing the source with trivia enabled, and are re-attached to the items and
+//! statements they precede or trail, based on their position in the source.
+//! Comments between the parameters of a signature, or the arguments of a call
+//! or elements of a list that end a statement, are kept between them. An item
+//! or statement with comments anywhere else (eg. deep inside an expression) is
+//! printed as it appears in the source, so that comments are never moved.
+//! Blank lines that separate groups of statements are preserved (collapsed to
+//! a single blank line).
+
+use crate::ast::{
+    Attribute, CallArg, ConstantDecl, Contract, ContractStmt, Enum, Expr, Field, FuncStmt,
+    Function, FunctionArg, FunctionSignature, GenericArg, GenericParameter, Impl,
+    InfixBindingPower, Interface, LiteralPattern, MatchArm, Module, ModuleStmt, Pattern,
+    PrefixBindingPower, Struct, Trait, TypeAlias, TypeDesc, UnaryOperator, VarDeclTarget, Variant,
+    VariantKind,
+};
+use crate::lexer::{Lexer, TokenKind};
+use crate::node::{Node, Span};
+use fe_common::diagnostics::{Diagnostic, Severity};
+use fe_common::files::SourceFileId;
+use smol_str::SmolStr;
+
+const INDENT: &str = "    ";
+
+/// Function signatures and call statements that don't fit within this width
+/// are broken into one parameter (or argument) per line.
+pub const MAX_WIDTH: usize = 100;
+
+/// Format the given source code.
+///
+/// Returns the parser diagnostics if the source contains syntax errors, in
+/// which case the source can't be formatted.
+pub fn format_source(file_id: SourceFileId, src: &str) -> Result<String, Vec<Diagnostic>> {
+    let (module, diags) = crate::parse_file(file_id, src);
+    if diags.iter().any(|diag| diag.severity == Severity::Error) {
+        return Err(diags);
+    }
+
+    Ok(Formatter::new(file_id, src).format_module(&module))
+}
+
+struct Formatter<'a> {
+    src: &'a str,
+    /// Source ranges of all comments, in source order.
+    comments: Vec<(usize, usize)>,
+    /// Index of the next comment that hasn't been printed yet.
+    next_comment: usize,
+    /// Source ranges of all tokens, excluding newlines and comments.
+    tokens: Vec<(TokenKind, usize, usize)>,
+    out: String,
+    indent: usize,
+    /// End of the source text that has been printed so far. Used to detect
+    /// the blank lines between items.
+    cursor: usize,
+    /// `true` until the first item of the current block has been printed.
+    at_block_start: bool,
+    /// Set when a comment of the member that is being printed couldn't be
+    /// printed where it appears in the source.
+    misplaced: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(file_id: SourceFileId, src: &'a str) -> Self {
+        let mut comments = vec![];
+        let mut tokens = vec![];
+        for tok in Lexer::with_trivia(file_id, src) {
+            match tok.kind {
+                TokenKind::Comment => comments.push((tok.span.start, tok.span.end)),
+                TokenKind::Newline => {}
+                kind => tokens.push((kind, tok.span.start, tok.span.end)),
+            }
+        }
+
+        Self {
+            src,
+            comments,
+            next_comment: 0,
+            tokens,
+            out: String::new(),
+            indent: 0,
+            cursor: 0,
+            at_block_start: true,
+            misplaced: false,
+        }
+    }
+
+    fn format_module(mut self, module: &Module) -> String {
+        let mut prev: Option<&ModuleStmt> = None;
+        for (idx, stmt) in module.body.iter().enumerate() {
+            let region_end = module
+                .body
+                .get(idx + 1)
+                .map(module_stmt_start)
+                .unwrap_or(self.src.len());
+            let force_blank = prev.is_some_and(|prev| needs_blank_line(prev, stmt));
+            self.member(module_stmt_start(stmt), region_end, force_blank, |fmt| {
+                fmt.module_stmt(stmt)
+            });
+            prev = Some(stmt);
+        }
+        self.comments_before(self.src.len());
+        self.out
+    }
+
+    fn module_stmt(&mut self, stmt: &ModuleStmt) {
+        match stmt {
+            ModuleStmt::Pragma(node) => {
+                self.line(&format!("pragma {}", node.kind.version_requirement.kind))
+            }
+            ModuleStmt::Use(node) => self.line(&format!("use {}", node.kind.tree.kind)),
+            ModuleStmt::TypeAlias(node) => self.type_alias(&node.kind),
+            ModuleStmt::Contract(node) => self.contract(&node.kind),
+            ModuleStmt::Interface(node) => self.interface_def(&node.kind),
+            ModuleStmt::Constant(node) => self.constant(&node.kind),
+            ModuleStmt::Struct(node) => self.struct_def(&node.kind),
+            ModuleStmt::Error(node) => self.error_def(&node.kind),
+            ModuleStmt::Enum(node) => self.enum_def(&node.kind),
+            ModuleStmt::Trait(node) => self.trait_def(&node.kind),
+            ModuleStmt::Impl(node) => self.impl_def(&node.kind),
+            ModuleStmt::Function(node) => self.function(node),
+            ModuleStmt::Attribute(node) => self.line(&attribute(&node.kind)),
+            ModuleStmt::ParseError(span) => {
+                let text = self.src[span.start..span.end].to_string();
+                self.line(&text)
+            }
+        }
+    }
+
+    fn type_alias(&mut self, alias: &TypeAlias) {
+        let pub_ = if alias.pub_qual.is_some() { "pub " } else { "" };
+        self.line(&format!(
+            "{}type {} = {}",
+            pub_,
+            alias.name.kind,
+            type_desc(&alias.typ.kind)
+        ))
+    }
+
+    fn constant(&mut self, constant: &ConstantDecl) {
+        let pub_ = if constant.pub_qual.is_some() {
+            "pub "
+        } else {
+            ""
+        };
+        let prefix = format!(
+            "{}const {}: {} = ",
+            pub_,
+            constant.name.kind,
+            type_desc(&constant.typ.kind)
+        );
+        self.expr_line(&prefix, &constant.value)
+    }
+
+    fn contract(&mut self, contract: &Contract) {
+        let pub_ = if contract.pub_qual.is_some() {
+            "pub "
+        } else {
+            ""
+        };
+        let header = format!("{}contract {}", pub_, contract.name.kind);
+        let (open, close) = self.block_after(contract.name.span.end);
+        let is_empty = contract.fields.is_empty() && contract.body.is_empty();
+
+        self.block(&header, open, close, is_empty, |fmt| {
+            let mut members: Vec<(usize, bool, Member)> = contract
+                .fields
+                .iter()
+                .map(|field| (field_start(field), false, Member::Field(field)))
+                .collect();
+            members.extend(contract.body.iter().enumerate().map(|(idx, stmt)| {
+                // A function stays on the line below its attributes.
+                let after_attribute =
+                    idx != 0 && matches!(contract.body[idx - 1], ContractStmt::Attribute(_));
+                let force_blank = (idx != 0 || !contract.fields.is_empty()) && !after_attribute;
+                match stmt {
+                    ContractStmt::Function(func) => {
+                        (func.span.start, force_blank, Member::Function(func))
+                    }
+                    ContractStmt::Attribute(attr) => {
+                        (attr.span.start, force_blank, Member::Attribute(attr))
+                    }
+                }
+            }));
+            fmt.members(&members, close);
+        })
+    }
+
+    fn interface_def(&mut self, interface: &Interface) {
+        let pub_ = if interface.pub_qual.is_some() {
+            "pub "
+        } else {
+            ""
+        };
+        let header = format!("{}interface {}", pub_, interface.name.kind);
+        let (open, close) = self.block_after(interface.name.span.end);
+
+        self.block(
+            &header,
+            open,
+            close,
+            interface.functions.is_empty(),
+            |fmt| {
+                let members: Vec<(usize, bool, Member)> = interface
+                    .functions
+                    .iter()
+                    .map(|sig| (sig.span.start, false, Member::Signature(sig)))
+                    .collect();
+                fmt.members(&members, close);
+            },
+        )
+    }
+
+    fn struct_def(&mut self, struct_: &Struct) {
+        let pub_ = if struct_.pub_qual.is_some() {
+            "pub "
+        } else {
+            ""
+        };
+        let header = format!(
+            "{}struct {}{}",
+            pub_,
+            struct_.name.kind,
+            generic_params(&struct_.generic_params.kind)
+        );
+        let (open, close) = self.block_after(struct_.generic_params.span.end);
+        let is_empty = struct_.fields.is_empty() && struct_.functions.is_empty();
+
+        self.block(&header, open, close, is_empty, |fmt| {
+            let mut members: Vec<(usize, bool, Member)> = struct_
+                .fields
+                .iter()
+                .map(|field| (field_start(field), false, Member::Field(field)))
+                .collect();
+            members.extend(struct_.functions.iter().enumerate().map(|(idx, func)| {
+                let force_blank = idx != 0 || !struct_.fields.is_empty();
+                (func.span.start, force_blank, Member::Function(func))
+            }));
+            fmt.members(&members, close);
+        })
+    }
+
+    fn error_def(&mut self, error: &Struct) {
+        let pub_ = if error.pub_qual.is_some() { "pub " } else { "" };
+        let fields = error.fields.iter().map(|field| {
+            format!(
+                "{}: {}",
+                field.kind.name.kind,
+                type_desc(&field.kind.typ.kind)
+            )
+        });
+        self.line(&format!(
+            "{}error {}({})",
+            pub_,
+            error.name.kind,
+            comma_joined(fields)
+        ))
+    }
+
+    fn enum_def(&mut self, enum_: &Enum) {
+        let pub_ = if enum_.pub_qual.is_some() { "pub " } else { "" };
+        let header = format!(
+            "{}enum {}{}",
+            pub_,
+            enum_.name.kind,
+            generic_params(&enum_.generic_params.kind)
+        );
+        let (open, close) = self.block_after(enum_.generic_params.span.end);
+        let is_empty = enum_.variants.is_empty() && enum_.functions.is_empty();
+
+        self.block(&header, open, close, is_empty, |fmt| {
+            let mut members: Vec<(usize, bool, Member)> = enum_
+                .variants
+                .iter()
+                .map(|variant| (variant.span.start, false, Member::Variant(variant)))
+                .collect();
+            members.extend(enum_.functions.iter().enumerate().map(|(idx, func)| {
+                let force_blank = idx != 0 || !enum_.variants.is_empty();
+                (func.span.start, force_blank, Member::Function(func))
+            }));
+            fmt.members(&members, close);
+        })
+    }
+
+    fn trait_def(&mut self, trait_: &Trait) {
+        let pub_ = if trait_.pub_qual.is_some() {
+            "pub "
+        } else {
+            ""
+        };
+        let header = format!("{}trait {}", pub_, trait_.name.kind);
+        let (open, close) = self.block_after(trait_.name.span.end);
+
+        self.block(&header, open, close, trait_.functions.is_empty(), |fmt| {
+            let members: Vec<(usize, bool, Member)> = trait_
+                .functions
+                .iter()
+                .map(|sig| (sig.span.start, false, Member::Signature(sig)))
+                .collect();
+            fmt.members(&members, close);
+        })
+    }
+
+    fn impl_def(&mut self, impl_: &Impl) {
+        let header = format!(
+            "impl {} for {}",
+            impl_.impl_trait.kind,
+            type_desc(&impl_.receiver.kind)
+        );
+        let (open, close) = self.block_after(impl_.receiver.span.end);
+
+        self.block(&header, open, close, impl_.functions.is_empty(), |fmt| {
+            let members: Vec<(usize, bool, Member)> = impl_
+                .functions
+                .iter()
+                .enumerate()
+                .map(|(idx, func)| (func.span.start, idx != 0, Member::Function(func)))
+                .collect();
+            fmt.members(&members, close);
+        })
+    }
+
+    fn members(&mut self, members: &[(usize, bool, Member)], close: usize) {
+        for (idx, (start, force_blank, member)) in members.iter().enumerate() {
+            let region_end = members
+                .get(idx + 1)
+                .map(|(start, ..)| *start)
+                .unwrap_or(close);
+            self.member(*start, region_end, *force_blank, |fmt| match member {
+                Member::Attribute(attr) => fmt.line(&format!("#{}", attr.kind)),
+                Member::Field(field) => fmt.field(&field.kind),
+                Member::Variant(variant) => fmt.variant(&variant.kind),
+                Member::Function(func) => fmt.function(func),
+                Member::Signature(sig) => {
+                    let sig = fmt.signature(&sig.kind, ";");
+                    fmt.line(&sig)
+                }
+            });
+        }
+    }
+
+    fn field(&mut self, field: &Field) {
+        for attr in &field.attributes {
+            self.line(&format!("#{}", attr.kind));
+        }
+        let mut prefix = String::new();
+        if field.is_pub {
+            prefix.push_str("pub ");
+        }
+        if field.is_const {
+            prefix.push_str("const ");
+        }
+        prefix.push_str(&format!(
+            "{}: {}",
+            field.name.kind,
+            type_desc(&field.typ.kind)
+        ));
+        match &field.value {
+            Some(value) => self.expr_line(&format!("{prefix} = "), value),
+            None => self.line(&prefix),
+        }
+    }
+
+    fn variant(&mut self, variant: &Variant) {
+        match &variant.kind {
+            VariantKind::Unit => self.line(&variant.name.kind),
+            VariantKind::Tuple(elts) => self.line(&format!(
+                "{}({})",
+                variant.name.kind,
+                comma_joined(elts.iter().map(|elt| type_desc(&elt.kind)))
+            )),
+        }
+    }
+
+    fn function(&mut self, func: &Node<Function>) {
+        let sig = &func.kind.sig;
+        let header = self.signature(&sig.kind, " {");
+        let header = header.trim_end_matches(" {");
+        let (open, close) = self.block_after(sig.span.end);
+        self.block(header, open, close, func.kind.body.is_empty(), |fmt| {
+            fmt.stmts(&func.kind.body, close)
+        })
+    }
+
+    /// Returns the function signature text. `suffix` is only used to decide
+    /// whether the parameters need to be broken into multiple lines, and is
+    /// included in the returned text. Parameters with comments between them
+    /// are always broken into multiple lines, to keep the comments in place.
+    fn signature(&mut self, sig: &FunctionSignature, suffix: &str) -> String {
+        let mut head = String::new();
+        if sig.pub_.is_some() {
+            head.push_str("pub ");
+        }
+        if sig.unsafe_.is_some() {
+            head.push_str("unsafe ");
+        }
+        head.push_str("fn ");
+        head.push_str(&sig.name.kind);
+        head.push_str(&generic_params(&sig.generic_params.kind));
+
+        let ret = match &sig.return_type {
+            Some(typ) => format!(" -> {}{}", type_desc(&typ.kind), suffix),
+            None => suffix.to_string(),
+        };
+        let args = sig
+            .args
+            .iter()
+            .map(|arg| {
+                let span = match &arg.kind {
+                    FunctionArg::Self_ { mut_ } => arg.span + *mut_,
+                    FunctionArg::Regular { .. } => arg.span,
+                };
+                (span, function_arg(&arg.kind))
+            })
+            .collect::<Vec<_>>();
+
+        let (open, close) = self.delimited_after(
+            sig.name.span.end,
+            TokenKind::ParenOpen,
+            TokenKind::ParenClose,
+        );
+        if self.has_comment_between(open, close) {
+            if let Some(args) = self.broken_list(open, &args, close) {
+                return format!("{head}(\n{args}){ret}");
+            }
+        }
+
+        let args = args.into_iter().map(|(_, arg)| arg).collect::<Vec<_>>();
+        let single_line = format!("{}({}){}", head, args.join(", "), ret);
+        if args.is_empty() || self.fits(&single_line) {
+            single_line
+        } else {
+            let args = args
+                .iter()
+                .map(|arg| format!("{INDENT}{arg},\n"))
+                .collect::<String>();
+            format!("{head}(\n{args}){ret}")
+        }
+    }
+
+    fn stmts(&mut self, stmts: &[Node<FuncStmt>], close: usize) {
+        for (idx, stmt) in stmts.iter().enumerate() {
+            let region_end = stmts
+                .get(idx + 1)
+                .map(|stmt| stmt.span.start)
+                .unwrap_or(close);
+            self.member(stmt.span.start, region_end, false, |fmt| fmt.stmt(stmt));
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Node<FuncStmt>) {
+        match &stmt.kind {
+            FuncStmt::Return { value } => match value {
+                Some(value) => self.expr_line("return ", value),
+                None => self.line("return"),
+            },
+            FuncStmt::VarDecl {
+                mut_,
+                target,
+                typ,
+                value,
+            } => {
+                let mut_ = if mut_.is_some() { "mut " } else { "" };
+                let decl = format!(
+                    "let {}{}: {}",
+                    mut_,
+                    var_decl_target(&target.kind),
+                    type_desc(&typ.kind)
+                );
+                match value {
+                    Some(value) => self.expr_line(&format!("{decl} = "), value),
+                    None => self.line(&decl),
+                }
+            }
+            FuncStmt::ConstantDecl { name, typ, value } => {
+                let prefix = format!("const {}: {} = ", name.kind, type_desc(&typ.kind));
+                self.expr_line(&prefix, value)
+            }
+            FuncStmt::Assign { target, value } => {
+                self.expr_line(&format!("{} = ", expr(&target.kind)), value)
+            }
+            FuncStmt::AugAssign { target, op, value } => {
+                self.expr_line(&format!("{} {}= ", expr(&target.kind), op.kind), value)
+            }
+            FuncStmt::For { target, iter, body } => {
+                let header = format!("for {} in {}", target.kind, expr(&iter.kind));
+                let (open, close) = self.block_after(iter.span.end);
+                self.block(&header, open, close, body.is_empty(), |fmt| {
+                    fmt.stmts(body, close)
+                })
+            }
+            FuncStmt::While { test, body } => {
+                let header = format!("while {}", expr(&test.kind));
+                let (open, close) = self.block_after(test.span.end);
+                self.block(&header, open, close, body.is_empty(), |fmt| {
+                    fmt.stmts(body, close)
+                })
+            }
+            FuncStmt::If {
+                test,
+                body,
+                or_else,
+            } => self.if_stmt("if", test, body, or_else),
+            FuncStmt::Match { expr: value, arms } => {
+                let header = format!("match {}", expr(&value.kind));
+                let (open, close) = self.block_after(value.span.end);
+                self.block(&header, open, close, arms.is_empty(), |fmt| {
+                    fmt.match_arms(arms, close)
+                })
+            }
+            FuncStmt::Assert { test, msg } => match msg {
+                Some(msg) => self.expr_line(&format!("assert {}, ", expr(&test.kind)), msg),
+                None => self.expr_line("assert ", test),
+            },
+            FuncStmt::Expr { value } => self.expr_line("", value),
+            FuncStmt::Break => self.line("break"),
+            FuncStmt::Continue => self.line("continue"),
+            FuncStmt::Revert { error } => match error {
+                Some(error) => self.expr_line("revert ", error),
+                None => self.line("revert"),
+            },
+            FuncStmt::Unsafe(body) => {
+                let (open, close) = self.block_after(stmt.span.start);
+                self.block("unsafe", open, close, body.is_empty(), |fmt| {
+                    fmt.stmts(body, close)
+                })
+            }
+        }
+    }
+
+    /// Prints an `if` statement, and its `else if` chain.
+    /// An `if` with an `else` branch is always printed on multiple lines.
+    fn if_stmt(
+        &mut self,
+        keyword: &str,
+        test: &Node<Expr>,
+        body: &[Node<FuncStmt>],
+        or_else: &[Node<FuncStmt>],
+    ) {
+        let header = format!("{} {}", keyword, expr(&test.kind));
+        let (open, close) = self.block_after(test.span.end);
+        // An empty `else` block isn't represented in the AST, but it's kept
+        // if it contains comments.
+        let empty_else = or_else.is_empty()
+            && self.token_after(close) == Some(TokenKind::Else)
+            && self.has_comment_before(self.block_after(close + 1).1);
+        if or_else.is_empty() && !empty_else {
+            return self.block(&header, open, close, body.is_empty(), |fmt| {
+                fmt.stmts(body, close)
+            });
+        }
+
+        self.open_block(&header, open);
+        self.stmts(body, close);
+        self.close_block(close);
+
+        match or_else {
+            [Node {
+                kind:
+                    FuncStmt::If {
+                        test,
+                        body,
+                        or_else,
+                    },
+                ..
+            }] => self.if_stmt("} else if", test, body, or_else),
+            _ => {
+                let (open, close) = self.block_after(close + 1);
+                self.open_block("} else", open);
+                self.stmts(or_else, close);
+                self.close_block(close);
+                self.line("}");
+            }
+        }
+    }
+
+    fn match_arms(&mut self, arms: &[Node<MatchArm>], close: usize) {
+        for (idx, arm) in arms.iter().enumerate() {
+            let region_end = arms.get(idx + 1).map(|arm| arm.span.start).unwrap_or(close);
+            self.member(arm.span.start, region_end, false, |fmt| {
+                let header = format!("{} =>", pattern(&arm.kind.pat.kind));
+                let (open, close) = fmt.block_after(arm.kind.pat.span.end);
+                fmt.block(&header, open, close, arm.kind.body.is_empty(), |fmt| {
+                    fmt.stmts(&arm.kind.body, close)
+                })
+            });
+        }
+    }
+
+    /// Prints a member of a block (an item, field, statement, etc.) that
+    /// starts at source position `start`, along with the comments that belong
+    /// to it. `region_end` is the start of the next member, or the position of
+    /// the closing brace of the block.
+    fn member<F>(&mut self, start: usize, region_end: usize, force_blank: bool, print: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let first = match self.pending_comment() {
+            Some((comment_start, _)) if comment_start < start => comment_start,
+            _ => start,
+        };
+        if !self.at_block_start && (force_blank || self.has_blank_line(self.cursor, first)) {
+            self.blank_line();
+        }
+        self.comments_before(start);
+        if !self.at_block_start && self.has_blank_line(self.cursor, start) {
+            self.blank_line();
+        }
+
+        let end = self.last_token_end(region_end).max(start);
+        let mark = self.out.len();
+        let first_comment = self.next_comment;
+        let outer_misplaced = std::mem::take(&mut self.misplaced);
+        self.at_block_start = false;
+        print(self);
+
+        // A comment that can't be printed where it appears (eg. inside an
+        // expression) would have to be moved across syntactic boundaries, so
+        // the member is printed as it appears in the source instead.
+        if self.misplaced || self.has_comment_before(end) {
+            self.out.truncate(mark);
+            self.verbatim(start, end);
+            self.next_comment = first_comment
+                + self.comments[first_comment..].partition_point(|(start, _)| *start < end);
+        }
+        self.misplaced = outer_misplaced;
+        self.cursor = end;
+        self.trailing_comment(end);
+    }
+
+    /// Prints a brace-delimited block. `open` and `close` are the source
+    /// positions of the braces.
+    fn block<F>(&mut self, header: &str, open: usize, close: usize, is_empty: bool, body: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        if is_empty && !self.has_comment_before(close) {
+            self.header_comments(open);
+            self.line(&format!("{header} {{}}"));
+            self.cursor = close + 1;
+        } else {
+            self.open_block(header, open);
+            body(self);
+            self.close_block(close);
+            self.line("}");
+        }
+    }
+
+    fn open_block(&mut self, header: &str, open: usize) {
+        self.header_comments(open);
+        self.line(&format!("{header} {{"));
+        self.indent += 1;
+        self.cursor = open + 1;
+        self.trailing_comment(open + 1);
+        self.at_block_start = true;
+    }
+
+    /// Prints the remaining comments in the block, and dedents. The closing
+    /// brace is left to the caller.
+    fn close_block(&mut self, close: usize) {
+        self.comments_before(close);
+        self.indent -= 1;
+        self.cursor = close + 1;
+        self.at_block_start = false;
+    }
+
+    /// Comments that appear in a block header (eg. in the condition of an
+    /// `if`) can't be kept in place, so the member that contains the block
+    /// is printed as it appears in the source.
+    fn header_comments(&mut self, open: usize) {
+        if self.has_comment_before(open) {
+            self.misplaced = true;
+        }
+    }
+
+    /// Appends the comment that follows position `pos` on the same line, if
+    /// any, to the last printed line.
+    fn trailing_comment(&mut self, pos: usize) {
+        if let Some((start, end)) = self.pending_comment() {
+            if start >= pos && !self.src[pos..start].contains('\n') {
+                self.out.pop();
+                self.out.push(' ');
+                self.out.push_str(self.src[start..end].trim_end());
+                self.out.push('\n');
+                self.next_comment += 1;
+                self.cursor = end;
+            }
+        }
+    }
+
+    /// Prints all unprinted comments that start before `pos` on their own
+    /// lines, preserving blank lines between them.
+    fn comments_before(&mut self, pos: usize) {
+        while let Some((start, end)) = self.pending_comment() {
+            if start >= pos {
+                break;
+            }
+            if !self.at_block_start && self.has_blank_line(self.cursor, start) {
+                self.blank_line();
+            }
+            let text = self.src[start..end].trim_end().to_string();
+            self.line(&text);
+            self.next_comment += 1;
+            self.cursor = end;
+            self.at_block_start = false;
+        }
+    }
+
+    /// Prints a line that ends with an expression. Calls and lists that don't
+    /// fit on one line, or that have comments between their elements, have
+    /// their elements broken into one per line.
+    fn expr_line(&mut self, prefix: &str, value: &Node<Expr>) {
+        let single_line = format!("{}{}", prefix, expr(&value.kind));
+        let has_comments = self.has_comment_between(value.span.start, value.span.end);
+        if self.fits(&single_line) && !has_comments {
+            return self.line(&single_line);
+        }
+
+        let (mut text, open, items, close, closing) = match &value.kind {
+            Expr::Call {
+                func,
+                generic_args,
+                args,
+            } if !args.kind.is_empty() => {
+                let mut text = format!("{}{}", prefix, postfix_operand(&func.kind));
+                if let Some(generic_args) = generic_args {
+                    text.push_str(&format!(
+                        "<{}>",
+                        comma_joined(generic_args.kind.iter().map(generic_arg))
+                    ));
+                }
+                text.push_str("(\n");
+                let items = args
+                    .kind
+                    .iter()
+                    .map(|arg| (arg.span, call_arg(&arg.kind)))
+                    .collect::<Vec<_>>();
+                (text, args.span.start, items, args.span.end - 1, ')')
+            }
+            Expr::List { elts } if !elts.is_empty() && has_comments => {
+                let text = format!("{prefix}[\n");
+                let items = elts
+                    .iter()
+                    .map(|elt| (elt.span, expr(&elt.kind)))
+                    .collect::<Vec<_>>();
+                (text, value.span.start, items, value.span.end - 1, ']')
+            }
+            _ => return self.line(&single_line),
+        };
+
+        // Comments elsewhere in the expression are left to the caller.
+        if self.has_comment_before(open) {
+            return self.line(&single_line);
+        }
+        match self.broken_list(open, &items, close) {
+            Some(items) => {
+                text.push_str(&items);
+                text.push(closing);
+                self.line(&text)
+            }
+            None => self.line(&single_line),
+        }
+    }
+
+    /// Returns the items of a bracketed list on separate lines, each followed
+    /// by a comma, along with the comments between them. `open` and `close`
+    /// are the source positions of the brackets, and `items` holds the span
+    /// and text of each item. Returns `None` if a comment is inside an item,
+    /// where it can't be kept.
+    fn broken_list(
+        &mut self,
+        open: usize,
+        items: &[(Span, String)],
+        close: usize,
+    ) -> Option<String> {
+        let inside_item = self.comments[self.next_comment..]
+            .iter()
+            .take_while(|(start, _)| *start < close)
+            .any(|(start, _)| {
+                items
+                    .iter()
+                    .any(|(span, _)| span.start <= *start && *start < span.end)
+            });
+        if inside_item || self.has_comment_before(open) {
+            return None;
+        }
+
+        let mut text = String::new();
+        for (idx, (span, item)) in items.iter().enumerate() {
+            while let Some((start, end)) = self.pending_comment() {
+                if start >= span.start {
+                    break;
+                }
+                text.push_str(&format!("{INDENT}{}\n", self.src[start..end].trim_end()));
+                self.next_comment += 1;
+            }
+
+            text.push_str(&format!("{INDENT}{item},"));
+            // A comment on the same line as the item stays after it.
+            let next = items.get(idx + 1).map_or(close, |(span, _)| span.start);
+            if let Some((start, end)) = self.pending_comment() {
+                if start < next && !self.src[span.end..start].contains('\n') {
+                    text.push(' ');
+                    text.push_str(self.src[start..end].trim_end());
+                    self.next_comment += 1;
+                }
+            }
+            text.push('\n');
+        }
+
+        while let Some((start, end)) = self.pending_comment() {
+            if start >= close {
+                break;
+            }
+            text.push_str(&format!("{INDENT}{}\n", self.src[start..end].trim_end()));
+            self.next_comment += 1;
+        }
+        Some(text)
+    }
+
+    /// Prints the source text from `start` to `end` unchanged, except for
+    /// its indentation, which is shifted to the current indentation.
+    fn verbatim(&mut self, start: usize, end: usize) {
+        let line_start = self.src[..start].rfind('\n').map_or(0, |pos| pos + 1);
+        let line = &self.src[line_start..];
+        let base_indent = line.len() - line.trim_start().len();
+
+        let lines = self.src[start..end]
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                if idx == 0 {
+                    return line.trim_end();
+                }
+                let indent = line.len() - line.trim_start().len();
+                line[indent.min(base_indent)..].trim_end()
+            })
+            .collect::<Vec<_>>();
+        self.line(&lines.join("\n"))
+    }
+
+    /// Appends the (possibly multi-line) text at the current indentation.
+    fn line(&mut self, text: &str) {
+        let text = self.indented(text);
+        self.out.push_str(&text);
+    }
+
+    fn indented(&self, text: &str) -> String {
+        let mut out = String::new();
+        for line in text.split('\n') {
+            if !line.is_empty() {
+                for _ in 0..self.indent {
+                    out.push_str(INDENT);
+                }
+                out.push_str(line);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn blank_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn fits(&self, text: &str) -> bool {
+        !text.contains('\n') && self.indent * INDENT.len() + text.len() <= MAX_WIDTH
+    }
+
+    fn pending_comment(&self) -> Option<(usize, usize)> {
+        self.comments.get(self.next_comment).copied()
+    }
+
+    fn has_comment_before(&self, pos: usize) -> bool {
+        matches!(self.pending_comment(), Some((start, _)) if start < pos)
+    }
+
+    /// Returns `true` if an unprinted comment starts between `from` and
+    /// `to`.
+    fn has_comment_between(&self, from: usize, to: usize) -> bool {
+        self.comments[self.next_comment..]
+            .iter()
+            .take_while(|(start, _)| *start < to)
+            .any(|(start, _)| *start >= from)
+    }
+
+    fn has_blank_line(&self, from: usize, to: usize) -> bool {
+        from < to && self.src[from..to].matches('\n').count() > 1
+    }
+
+    /// Returns the end of the last token that starts before `pos`.
+    fn last_token_end(&self, pos: usize) -> usize {
+        let idx = self.tokens.partition_point(|(_, start, _)| *start < pos);
+        idx.checked_sub(1).map_or(0, |idx| self.tokens[idx].2)
+    }
+
+    /// Returns the kind of the first token after position `pos`.
+    fn token_after(&self, pos: usize) -> Option<TokenKind> {
+        let idx = self.tokens.partition_point(|(_, start, _)| *start <= pos);
+        self.tokens.get(idx).map(|(kind, ..)| *kind)
+    }
+
+    /// Returns the positions of the first `{` at or after `pos`, and of its
+    /// matching `}`.
+    fn block_after(&self, pos: usize) -> (usize, usize) {
+        self.delimited_after(pos, TokenKind::BraceOpen, TokenKind::BraceClose)
+    }
+
+    /// Returns the positions of the first `open` delimiter at or after `pos`,
+    /// and of its matching `close` delimiter.
+    fn delimited_after(&self, pos: usize, open: TokenKind, close: TokenKind) -> (usize, usize) {
+        let first = self.tokens.partition_point(|(_, start, _)| *start < pos);
+        let open_idx = (first..self.tokens.len())
+            .find(|idx| self.tokens[*idx].0 == open)
+            .expect("missing delimiter");
+
+        let mut depth = 0;
+        for (kind, start, _) in &self.tokens[open_idx..] {
+            if *kind == open {
+                depth += 1;
+            } else if *kind == close {
+                depth -= 1;
+                if depth == 0 {
+                    return (self.tokens[open_idx].1, *start);
+                }
+            }
+        }
+        panic!("unclosed delimiter")
+    }
+}
+
+enum Member<'a> {
+    Attribute(&'a Node<SmolStr>),
+    Field(&'a Node<Field>),
+    Variant(&'a Node<Variant>),
+    Function(&'a Node<Function>),
+    Signature(&'a Node<FunctionSignature>),
+}
+
+fn start_of(span: Span, pub_qual: Option<Span>) -> usize {
+    pub_qual.map_or(span.start, |pub_qual| pub_qual.start.min(span.start))
+}
+
+fn module_stmt_start(stmt: &ModuleStmt) -> usize {
+    match stmt {
+        ModuleStmt::Pragma(node) => node.span.start,
+        ModuleStmt::Use(node) => node.span.start,
+        ModuleStmt::TypeAlias(node) => start_of(node.span, node.kind.pub_qual),
+        ModuleStmt::Contract(node) => start_of(node.span, node.kind.pub_qual),
+        ModuleStmt::Interface(node) => start_of(node.span, node.kind.pub_qual),
+        ModuleStmt::Constant(node) => start_of(node.span, node.kind.pub_qual),
+        ModuleStmt::Struct(node) => start_of(node.span, node.kind.pub_qual),
+        ModuleStmt::Error(node) => start_of(node.span, node.kind.pub_qual),
+        ModuleStmt::Enum(node) => start_of(node.span, node.kind.pub_qual),
+        ModuleStmt::Trait(node) => start_of(node.span, node.kind.pub_qual),
+        ModuleStmt::Impl(node) => node.span.start,
+        ModuleStmt::Function(node) => node.span.start,
+        ModuleStmt::Attribute(node) => node.span.start,
+        ModuleStmt::ParseError(span) => span.start,
+    }
+}
+
+fn field_start(field: &Node<Field>) -> usize {
+    field
+        .kind
+        .attributes
+        .first()
+        .map_or(field.span.start, |attr| {
+            attr.span.start.min(field.span.start)
+        })
+}
+
+/// Items are separated by a blank line, except for consecutive one-line items
+/// of the same kind (eg. a group of `use` statements), and items following an
+/// attribute.
+fn needs_blank_line(prev: &ModuleStmt, next: &ModuleStmt) -> bool {
+    !matches!(
+        (prev, next),
+        (ModuleStmt::Attribute(_), _)
+            | (ModuleStmt::Pragma(_), ModuleStmt::Pragma(_))
+            | (ModuleStmt::Use(_), ModuleStmt::Use(_))
+            | (ModuleStmt::Constant(_), ModuleStmt::Constant(_))
+            | (ModuleStmt::TypeAlias(_), ModuleStmt::TypeAlias(_))
+            | (ModuleStmt::Error(_), ModuleStmt::Error(_))
+    )
+}
+
+fn comma_joined(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+/// Formats a generic parameter list, or nothing if there are no parameters.
+fn generic_params(params: &[GenericParameter]) -> String {
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", comma_joined(params.iter().map(generic_param)))
+    }
+}
+
+fn generic_param(param: &GenericParameter) -> String {
+    match param {
+        GenericParameter::Unbounded(name) => name.kind.to_string(),
+        GenericParameter::Bounded { name, bound } => {
+            format!("{}: {}", name.kind, type_desc(&bound.kind))
+        }
+    }
+}
+
+fn function_arg(arg: &FunctionArg) -> String {
+    match arg {
+        FunctionArg::Regular {
+            mut_,
+            label,
+            name,
+            typ,
+        } => {
+            let mut text = String::new();
+            if mut_.is_some() {
+                text.push_str("mut ");
+            }
+            if let Some(label) = label {
+                text.push_str(&format!("{} ", label.kind));
+            }
+            text.push_str(&format!("{}: {}", name.kind, type_desc(&typ.kind)));
+            text
+        }
+        FunctionArg::Self_ { mut_ } => {
+            if mut_.is_some() {
+                "mut self".to_string()
+            } else {
+                "self".to_string()
+            }
+        }
+    }
+}
+
+fn type_desc(typ: &TypeDesc) -> String {
+    match typ {
+        TypeDesc::Unit => "()".to_string(),
+        TypeDesc::Base { base } => base.to_string(),
+        TypeDesc::Path(path) => path.to_string(),
+        TypeDesc::Tuple { items } => {
+            if items.len() == 1 {
+                format!("({},)", type_desc(&items[0].kind))
+            } else {
+                format!(
+                    "({})",
+                    comma_joined(items.iter().map(|item| type_desc(&item.kind)))
+                )
+            }
+        }
+        TypeDesc::Generic { base, args } => format!(
+            "{}<{}>",
+            base.kind,
+            comma_joined(args.kind.iter().map(generic_arg))
+        ),
+        TypeDesc::SelfType => "Self".to_string(),
+    }
+}
+
+fn generic_arg(arg: &GenericArg) -> String {
+    match arg {
+        GenericArg::TypeDesc(node) => type_desc(&node.kind),
+        GenericArg::Int(node) => node.kind.to_string(),
+        GenericArg::ConstExpr(node) => format!("{{ {} }}", expr(&node.kind)),
+    }
+}
+
+fn var_decl_target(target: &VarDeclTarget) -> String {
+    match target {
+        VarDeclTarget::Name(name) => name.to_string(),
+        VarDeclTarget::Tuple(elts) => {
+            let elts = elts
+                .iter()
+                .map(|elt| var_decl_target(&elt.kind))
+                .collect::<Vec<_>>();
+            if elts.len() == 1 {
+                format!("({},)", elts[0])
+            } else {
+                format!("({})", elts.join(", "))
+            }
+        }
+    }
+}
+
+fn pattern(pat: &Pattern) -> String {
+    match pat {
+        Pattern::WildCard => "_".to_string(),
+        Pattern::Rest => "..".to_string(),
+        Pattern::Literal(lit) => match lit.kind {
+            LiteralPattern::Bool(value) => value.to_string(),
+        },
+        Pattern::Tuple(elts) => format!("({})", patterns(elts)),
+        Pattern::Path(path) => path.kind.to_string(),
+        Pattern::PathTuple(path, elts) => format!("{}({})", path.kind, patterns(elts)),
+        Pattern::PathStruct {
+            path,
+            fields,
+            has_rest,
+        } => {
+            let mut fields = fields
+                .iter()
+                .map(|(name, pat)| format!("{}: {}", name.kind, pattern(&pat.kind)))
+                .collect::<Vec<_>>();
+            if *has_rest {
+                fields.push("..".to_string());
+            }
+            if fields.is_empty() {
+                format!("{} {{}}", path.kind)
+            } else {
+                format!("{} {{ {} }}", path.kind, fields.join(", "))
+            }
+        }
+        Pattern::Or(pats) => pats
+            .iter()
+            .map(|pat| pattern(&pat.kind))
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+fn patterns(pats: &[Node<Pattern>]) -> String {
+    comma_joined(pats.iter().map(|pat| pattern(&pat.kind)))
+}
+
+/// Returns the canonical text of an expression, with only the parentheses
+/// that are required to preserve its meaning.
+fn expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Ternary {
+            if_expr,
+            test,
+            else_expr,
+        } => format!(
+            "{} if {} else {}",
+            ternary_operand(&if_expr.kind),
+            self::expr(&test.kind),
+            self::expr(&else_expr.kind)
+        ),
+        Expr::BoolOperation { left, op, right } => {
+            infix(&op.kind, &left.kind, &op.kind.to_string(), &right.kind)
+        }
+        Expr::BinOperation { left, op, right } => {
+            infix(&op.kind, &left.kind, &op.kind.to_string(), &right.kind)
+        }
+        Expr::CompOperation { left, op, right } => {
+            infix(&op.kind, &left.kind, &op.kind.to_string(), &right.kind)
+        }
+        Expr::UnaryOperation { op, operand } => {
+            let operand_text = if op.kind.prefix_binding_power() > left_binding_power(&operand.kind)
+            {
+                format!("({})", self::expr(&operand.kind))
+            } else {
+                self::expr(&operand.kind)
+            };
+            if op.kind == UnaryOperator::Not {
+                format!("{} {}", op.kind, operand_text)
+            } else {
+                format!("{}{}", op.kind, operand_text)
+            }
+        }
+        Expr::Attribute { value, attr } => {
+            format!("{}.{}", postfix_operand(&value.kind), attr.kind)
+        }
+        Expr::Subscript { value, index } => format!(
+            "{}[{}]",
+            postfix_operand(&value.kind),
+            self::expr(&index.kind)
+        ),
+        Expr::Try { value } => format!("{}?", postfix_operand(&value.kind)),
+        Expr::Call {
+            func,
+            generic_args,
+            args,
+        } => {
+            let mut text = postfix_operand(&func.kind);
+            if let Some(generic_args) = generic_args {
+                text.push_str(&format!(
+                    "<{}>",
+                    comma_joined(generic_args.kind.iter().map(generic_arg))
+                ));
+            }
+            text.push_str(&format!(
+                "({})",
+                comma_joined(args.kind.iter().map(|arg| call_arg(&arg.kind)))
+            ));
+            text
+        }
+        Expr::List { elts } => format!("[{}]", exprs(elts)),
+        Expr::Repeat { value, len } => {
+            let len = match &len.kind {
+                GenericArg::ConstExpr(len) => self::expr(&len.kind),
+                arg => generic_arg(arg),
+            };
+            format!("[{}; {}]", self::expr(&value.kind), len)
+        }
+        Expr::Tuple { elts } => {
+            if elts.len() == 1 {
+                format!("({},)", self::expr(&elts[0].kind))
+            } else {
+                format!("({})", exprs(elts))
+            }
+        }
+        Expr::Bool(value) => value.to_string(),
+        Expr::Name(name) => name.to_string(),
+        Expr::Path(path) => path.to_string(),
+        Expr::Num(num) => num.to_string(),
+        Expr::Str(text) => format!("\"{}\"", escape(text)),
+        Expr::Unit => "()".to_string(),
+    }
+}
+
+fn exprs(exprs: &[Node<Expr>]) -> String {
+    comma_joined(exprs.iter().map(|elt| expr(&elt.kind)))
+}
+
+fn attribute(attr: &Attribute) -> String {
+    match &attr.args {
+        Some(args) => format!(
+            "#{}({})",
+            attr.name.kind,
+            comma_joined(args.kind.iter().map(|arg| call_arg(&arg.kind)))
+        ),
+        None => format!("#{}", attr.name.kind),
+    }
+}
+
+fn call_arg(arg: &CallArg) -> String {
+    match &arg.label {
+        Some(label) if arg.value.kind == Expr::Name(label.kind.clone()) => label.kind.to_string(),
+        Some(label) => format!("{}: {}", label.kind, expr(&arg.value.kind)),
+        None => expr(&arg.value.kind),
+    }
+}
+
+fn infix(op: &impl InfixBindingPower, left: &Expr, op_text: &str, right: &Expr) -> String {
+    let (left_bp, right_bp) = op.infix_binding_power();
+    let left = if right_binding_power(left) < left_bp {
+        format!("({})", expr(left))
+    } else {
+        expr(left)
+    };
+    let right = if right_bp > left_binding_power(right) {
+        format!("({})", expr(right))
+    } else {
+        expr(right)
+    };
+    format!("{left} {op_text} {right}")
+}
+
+/// The operand of `.`, `[]`, or a call must be wrapped in parentheses unless
+/// it's an atom or another postfix expression.
+fn postfix_operand(operand: &Expr) -> String {
+    if left_binding_power(operand) == u8::MAX && right_binding_power(operand) == u8::MAX {
+        expr(operand)
+    } else {
+        format!("({})", expr(operand))
+    }
+}
+
+fn ternary_operand(operand: &Expr) -> String {
+    if matches!(operand, Expr::Ternary { .. }) {
+        format!("({})", expr(operand))
+    } else {
+        expr(operand)
+    }
+}
+
+fn left_binding_power(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Ternary { .. } => 0,
+        Expr::BoolOperation { op, .. } => op.kind.infix_binding_power().0,
+        Expr::BinOperation { op, .. } => op.kind.infix_binding_power().0,
+        Expr::CompOperation { op, .. } => op.kind.infix_binding_power().0,
+        Expr::UnaryOperation { op, .. } => op.kind.prefix_binding_power(),
+        _ => u8::MAX,
+    }
+}
+
+fn right_binding_power(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Ternary { .. } => 0,
+        Expr::BoolOperation { op, .. } => op.kind.infix_binding_power().1,
+        Expr::BinOperation { op, .. } => op.kind.infix_binding_power().1,
+        Expr::CompOperation { op, .. } => op.kind.infix_binding_power().1,
+        Expr::UnaryOperation { op, .. } => op.kind.prefix_binding_power(),
+        _ => u8::MAX,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
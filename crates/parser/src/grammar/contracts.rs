@@ -1,8 +1,8 @@
-use super::functions::parse_fn_def;
+use super::functions::{parse_fn_def, parse_fn_sig};
 use super::types::{parse_field, parse_opt_qualifier};
 
-use crate::ast::{Contract, ContractStmt};
-use crate::node::{Node, Span};
+use crate::ast::{Contract, ContractStmt, Interface};
+use crate::node::{Node, Span, Spanned};
 use crate::{ParseFailed, ParseResult, Parser, TokenKind};
 
 // Rule: all "statement" level parse functions consume their trailing
@@ -33,6 +33,16 @@ pub fn parse_contract_def(
 
     loop {
         par.eat_newlines();
+        if let Some(attr) = par.optional(TokenKind::Hash) {
+            let attr_name = par.expect_with_notes(TokenKind::Name, "failed to parse attribute definition", |_|
+                vec!["Note: an attribute name must start with a letter or underscore, and contain letters, numbers, or underscores".into()])?;
+            defs.push(ContractStmt::Attribute(Node::new(
+                attr_name.text.into(),
+                attr.span + attr_name.span,
+            )));
+            continue;
+        }
+
         let mut pub_qual = parse_opt_qualifier(par, TokenKind::Pub);
         let const_qual = parse_opt_qualifier(par, TokenKind::Const);
         if pub_qual.is_none() && const_qual.is_some() && par.peek() == Some(TokenKind::Pub) {
@@ -46,7 +56,13 @@ pub fn parse_contract_def(
         match par.peek_or_err()? {
             TokenKind::Name => {
                 let field = parse_field(par, vec![], pub_qual, const_qual)?;
-                if !defs.is_empty() {
+                if matches!(defs.last(), Some(ContractStmt::Attribute(_))) {
+                    let attr = defs.pop().unwrap();
+                    par.error(
+                        attr.span(),
+                        "attributes can only be applied to contract functions",
+                    );
+                } else if !defs.is_empty() {
                     par.error(
                         field.span,
                         "contract field definitions must come before any function definitions",
@@ -64,6 +80,12 @@ pub fn parse_contract_def(
                 defs.push(ContractStmt::Function(parse_fn_def(par, pub_qual)?));
             }
             TokenKind::BraceClose => {
+                if let Some(ContractStmt::Attribute(attr)) = defs.last() {
+                    par.error(
+                        attr.span,
+                        "expected a function definition after the attribute",
+                    );
+                }
                 span += par.next()?.span;
                 break;
             }
@@ -89,3 +111,59 @@ pub fn parse_contract_def(
         span,
     ))
 }
+
+/// Parse an interface definition, e.g.
+/// `interface Token { fn balance_of(self, owner: address) -> u256; }`.
+/// # Panics
+/// Panics if the next token isn't `interface`.
+pub fn parse_interface_def(
+    par: &mut Parser,
+    pub_qual: Option<Span>,
+) -> ParseResult<Node<Interface>> {
+    let interface_tok = par.assert(TokenKind::Name);
+    let interface_name = par.expect_with_notes(
+        TokenKind::Name,
+        "failed to parse interface definition",
+        |_| vec!["Note: `interface` must be followed by a name, which must start with a letter and contain only letters, numbers, or underscores".into()],
+    )?;
+
+    let mut span = interface_tok.span + interface_name.span;
+    let mut functions = vec![];
+    par.enter_block(span, "interface definition")?;
+
+    loop {
+        match par.peek_or_err()? {
+            TokenKind::Fn => {
+                functions.push(parse_fn_sig(par, None)?);
+                par.expect_with_notes(
+                    TokenKind::Semi,
+                    "failed to parse interface definition",
+                    |_| vec!["Note: interface functions must appear without body and followed by a semicolon.".into()],
+                )?;
+                par.eat_newlines();
+            }
+            TokenKind::BraceClose => {
+                span += par.next()?.span;
+                break;
+            }
+            _ => {
+                let tok = par.next()?;
+                par.unexpected_token_error(
+                    &tok,
+                    "failed to parse interface definition body",
+                    vec![],
+                );
+                return Err(ParseFailed);
+            }
+        };
+    }
+
+    Ok(Node::new(
+        Interface {
+            name: Node::new(interface_name.text.into(), interface_name.span),
+            functions,
+            pub_qual,
+        },
+        span + pub_qual,
+    ))
+}
@@ -1,11 +1,12 @@
-use super::expressions::parse_expr;
+use super::contracts::{parse_contract_def, parse_interface_def};
+use super::expressions::{parse_call_args, parse_expr};
 use super::functions::parse_fn_def;
+use super::types::parse_enum_def;
 use super::types::{
-    parse_impl_def, parse_path_tail, parse_struct_def, parse_trait_def, parse_type_alias,
-    parse_type_desc,
+    parse_error_def, parse_impl_def, parse_path_tail, parse_struct_def, parse_trait_def,
+    parse_type_alias, parse_type_desc,
 };
-use super::{contracts::parse_contract_def, types::parse_enum_def};
-use crate::ast::{ConstantDecl, Module, ModuleStmt, Pragma, Use, UseTree};
+use crate::ast::{Attribute, ConstantDecl, Module, ModuleStmt, Pragma, Use, UseTree};
 use crate::node::{Node, Span};
 use crate::{Label, ParseFailed, ParseResult, Parser, TokenKind};
 
@@ -48,6 +49,10 @@ pub fn parse_module_stmt(par: &mut Parser) -> ParseResult<ModuleStmt> {
         TokenKind::Impl => ModuleStmt::Impl(parse_impl_def(par)?),
         TokenKind::Type => ModuleStmt::TypeAlias(parse_type_alias(par, None)?),
         TokenKind::Const => ModuleStmt::Constant(parse_constant(par, None)?),
+        TokenKind::Name if is_error_def(par) => ModuleStmt::Error(parse_error_def(par, None)?),
+        TokenKind::Name if is_interface_def(par) => {
+            ModuleStmt::Interface(parse_interface_def(par, None)?)
+        }
         TokenKind::Pub => {
             let pub_span = par.next()?.span;
             match par.peek_or_err()? {
@@ -59,6 +64,12 @@ pub fn parse_module_stmt(par: &mut Parser) -> ParseResult<ModuleStmt> {
                 TokenKind::Trait => ModuleStmt::Trait(parse_trait_def(par, Some(pub_span))?),
                 TokenKind::Type => ModuleStmt::TypeAlias(parse_type_alias(par, Some(pub_span))?),
                 TokenKind::Const => ModuleStmt::Constant(parse_constant(par, Some(pub_span))?),
+                TokenKind::Name if is_error_def(par) => {
+                    ModuleStmt::Error(parse_error_def(par, Some(pub_span))?)
+                }
+                TokenKind::Name if is_interface_def(par) => {
+                    ModuleStmt::Interface(parse_interface_def(par, Some(pub_span))?)
+                }
                 TokenKind::Contract => {
                     ModuleStmt::Contract(parse_contract_def(par, Some(pub_span))?)
                 }
@@ -78,14 +89,21 @@ pub fn parse_module_stmt(par: &mut Parser) -> ParseResult<ModuleStmt> {
             let attr = par.expect(TokenKind::Hash, "expected `#`")?;
             let attr_name = par.expect_with_notes(TokenKind::Name, "failed to parse attribute definition", |_|
                 vec!["Note: an attribute name must start with a letter or underscore, and contain letters, numbers, or underscores".into()])?;
-            ModuleStmt::Attribute(Node::new(attr_name.text.into(), attr.span + attr_name.span))
+            let name = Node::new(attr_name.text.into(), attr_name.span);
+            let args = if par.peek() == Some(TokenKind::ParenOpen) {
+                Some(parse_call_args(par)?)
+            } else {
+                None
+            };
+            let span = attr.span + name.span + args.as_ref();
+            ModuleStmt::Attribute(Node::new(Attribute { name, args }, span))
         }
         _ => {
             let tok = par.next()?;
             par.unexpected_token_error(
                 &tok,
                 "failed to parse module",
-                vec!["Note: expected import, contract, struct, type or const".into()],
+                vec!["Note: expected import, contract, struct, error, type or const".into()],
             );
             return Err(ParseFailed);
         }
@@ -93,6 +111,23 @@ pub fn parse_module_stmt(par: &mut Parser) -> ParseResult<ModuleStmt> {
     Ok(stmt)
 }
 
+/// Returns `true` if the next tokens are `error` followed by a name. `error`
+/// is not a keyword, so that it can still be used as an identifier elsewhere
+/// (eg. `std::error`).
+fn is_error_def(par: &mut Parser) -> bool {
+    let mut bt_par = par.as_bt_parser();
+    matches!(bt_par.next(), Ok(tok) if tok.text == "error")
+        && bt_par.peek() == Some(TokenKind::Name)
+}
+
+/// Returns `true` if the next tokens are `interface` followed by a name. Like
+/// `error`, `interface` is not a keyword.
+fn is_interface_def(par: &mut Parser) -> bool {
+    let mut bt_par = par.as_bt_parser();
+    matches!(bt_par.next(), Ok(tok) if tok.text == "interface")
+        && bt_par.peek() == Some(TokenKind::Name)
+}
+
 /// Parse a constant, e.g. `const MAGIC_NUMBER: u256 = 4711`.
 /// # Panics
 /// Panics if the next token isn't `const`.
@@ -2,7 +2,7 @@ use crate::ast::{
     self, Enum, Field, GenericArg, Impl, Path, Trait, TypeAlias, TypeDesc, Variant, VariantKind,
 };
 use crate::grammar::expressions::parse_expr;
-use crate::grammar::functions::{parse_fn_def, parse_fn_sig};
+use crate::grammar::functions::{parse_fn_def, parse_fn_sig, parse_generic_params};
 use crate::node::{Node, Span};
 use crate::Token;
 use crate::{ParseFailed, ParseResult, Parser, TokenKind};
@@ -23,7 +23,13 @@ pub fn parse_struct_def(
         vec!["Note: a struct name must start with a letter or underscore, and contain letters, numbers, or underscores".into()]
     })?;
 
-    let mut span = struct_tok.span + name.span;
+    let generic_params = if par.peek() == Some(TokenKind::Lt) {
+        parse_generic_params(par)?
+    } else {
+        Node::new(vec![], name.span)
+    };
+
+    let mut span = struct_tok.span + name.span + generic_params.span;
     let mut fields = vec![];
     let mut functions = vec![];
     par.enter_block(span, "struct body must start with `{`")?;
@@ -71,6 +77,7 @@ pub fn parse_struct_def(
     Ok(Node::new(
         ast::Struct {
             name: name.into(),
+            generic_params,
             fields,
             functions,
             pub_qual,
@@ -79,6 +86,89 @@ pub fn parse_struct_def(
     ))
 }
 
+/// Parse a [`ModuleStmt::Error`], e.g.
+/// `error InsufficientBalance(needed: u256, have: u256)`. The error is
+/// represented as a struct with public fields and no functions.
+/// # Panics
+/// Panics if the next token isn't a [`TokenKind::Name`].
+pub fn parse_error_def(par: &mut Parser, pub_qual: Option<Span>) -> ParseResult<Node<ast::Struct>> {
+    let error_tok = par.assert(TokenKind::Name);
+    let name = par.expect_with_notes(TokenKind::Name, "failed to parse error definition", |_| {
+        vec!["Note: an error name must start with a letter or underscore, and contain letters, numbers, or underscores".into()]
+    })?;
+
+    let mut span = error_tok.span + name.span;
+    let mut fields = vec![];
+    par.expect_with_notes(
+        TokenKind::ParenOpen,
+        "failed to parse error definition",
+        |_| {
+            vec![
+                "Note: an error name must be followed by a parenthesized list of fields".into(),
+                format!("Example: `error {}(needed: u256, have: u256)`", name.text),
+            ]
+        },
+    )?;
+    loop {
+        match par.peek_or_err()? {
+            TokenKind::ParenClose => {
+                span += par.next()?.span;
+                break;
+            }
+            TokenKind::Name => {
+                let field_name = par.next()?;
+                par.expect_with_notes(TokenKind::Colon, "failed to parse error field", |_| {
+                    vec![
+                        "Note: field name must be followed by a colon and a type description"
+                            .into(),
+                        format!("Example: `{}: u256`", field_name.text),
+                    ]
+                })?;
+                let typ = parse_type_desc(par)?;
+                let field_span = field_name.span + typ.span;
+                fields.push(Node::new(
+                    Field {
+                        is_pub: true,
+                        is_const: false,
+                        attributes: vec![],
+                        name: field_name.into(),
+                        typ,
+                        value: None,
+                    },
+                    field_span,
+                ));
+
+                if par.peek() == Some(TokenKind::Comma) {
+                    par.next()?;
+                } else {
+                    span += par
+                        .expect(
+                            TokenKind::ParenClose,
+                            "unexpected token while parsing error fields",
+                        )?
+                        .span;
+                    break;
+                }
+            }
+            _ => {
+                let tok = par.next()?;
+                par.unexpected_token_error(&tok, "failed to parse error definition", vec![]);
+                return Err(ParseFailed);
+            }
+        }
+    }
+    Ok(Node::new(
+        ast::Struct {
+            generic_params: Node::new(vec![], name.span),
+            name: name.into(),
+            fields,
+            functions: vec![],
+            pub_qual,
+        },
+        span,
+    ))
+}
+
 #[allow(clippy::unnecessary_literal_unwrap)]
 /// Parse a [`ModuleStmt::Enum`].
 /// # Panics
@@ -91,7 +181,13 @@ pub fn parse_enum_def(par: &mut Parser, pub_qual: Option<Span>) -> ParseResult<N
         |_| vec!["Note: `enum` must be followed by a name, which must start with a letter and contain only letters, numbers, or underscores".into()],
     )?;
 
-    let mut span = enum_tok.span + name.span;
+    let generic_params = if par.peek() == Some(TokenKind::Lt) {
+        parse_generic_params(par)?
+    } else {
+        Node::new(vec![], name.span)
+    };
+
+    let mut span = enum_tok.span + name.span + generic_params.span;
     let mut variants = vec![];
     let mut functions = vec![];
 
@@ -146,6 +242,7 @@ pub fn parse_enum_def(par: &mut Parser, pub_qual: Option<Span>) -> ParseResult<N
     Ok(Node::new(
         ast::Enum {
             name: name.into(),
+            generic_params,
             variants,
             functions,
             pub_qual,
@@ -546,18 +643,26 @@ pub fn parse_type_desc(par: &mut Parser) -> ParseResult<Node<TypeDesc>> {
                         );
                         return Err(ParseFailed);
                     }
-                    Node::new(TypeDesc::Path(path), span)
+                    if par.peek() == Some(Lt) {
+                        let base = Node::new(path, span);
+                        let args = parse_generic_args(par)?;
+                        let span = base.span + args.span;
+                        Node::new(TypeDesc::Generic { base, args }, span)
+                    } else {
+                        Node::new(TypeDesc::Path(path), span)
+                    }
                 }
                 Some(Lt) => {
-                    let args = parse_generic_args(par)?;
-                    let span = name.span + args.span;
-                    Node::new(
-                        TypeDesc::Generic {
-                            base: name.into(),
-                            args,
+                    let base_span = name.span;
+                    let base = Node::new(
+                        Path {
+                            segments: vec![name.into()],
                         },
-                        span,
-                    )
+                        base_span,
+                    );
+                    let args = parse_generic_args(par)?;
+                    let span = base.span + args.span;
+                    Node::new(TypeDesc::Generic { base, args }, span)
                 }
                 _ => Node::new(
                     TypeDesc::Base {
@@ -646,7 +751,12 @@ pub fn parse_type_desc(par: &mut Parser) -> ParseResult<Node<TypeDesc>> {
                 );
                 typ = Node::new(
                     TypeDesc::Generic {
-                        base: Node::new("Array".into(), typ.span),
+                        base: Node::new(
+                            Path {
+                                segments: vec![Node::new("Array".into(), typ.span)],
+                            },
+                            typ.span,
+                        ),
                         args: Node::new(vec![
                             GenericArg::TypeDesc(
                                 Node::new(typ.kind, typ.span)
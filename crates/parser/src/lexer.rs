@@ -8,14 +8,28 @@ pub use token::{Token, TokenKind};
 pub struct Lexer<'a> {
     file_id: SourceFileId,
     inner: logos::Lexer<'a, TokenKind>,
+    keep_trivia: bool,
 }
 
 impl<'a> Lexer<'a> {
     /// Create a new lexer with the given source code string.
-    pub fn new(file_id: SourceFileId, src: &'a str) -> Lexer {
+    /// Trivia tokens (comments) are skipped.
+    pub fn new(file_id: SourceFileId, src: &'a str) -> Lexer<'a> {
         Lexer {
             file_id,
             inner: TokenKind::lexer(src),
+            keep_trivia: false,
+        }
+    }
+
+    /// Create a new lexer that also yields trivia tokens (comments).
+    /// Used by tools that need to reproduce the source text, like the
+    /// formatter.
+    pub fn with_trivia(file_id: SourceFileId, src: &'a str) -> Lexer<'a> {
+        Lexer {
+            file_id,
+            inner: TokenKind::lexer(src),
+            keep_trivia: true,
         }
     }
 
@@ -29,7 +43,10 @@ impl<'a> Iterator for Lexer<'a> {
     type Item = Token<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let kind = self.inner.next()?;
+        let mut kind = self.inner.next()?;
+        while kind.is_trivia() && !self.keep_trivia {
+            kind = self.inner.next()?;
+        }
         let text = self.inner.slice();
         let span = self.inner.span();
         Some(Token {
@@ -99,4 +116,25 @@ mod tests {
             &[Newline, Contract, Newline, Name, Newline],
         );
     }
+
+    #[test]
+    fn trivia() {
+        let src = "// header\nfn f() { // trailing\n}";
+        let kinds = Lexer::with_trivia(SourceFileId::dummy_file(), src)
+            .map(|t| t.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                Comment, Newline, Fn, Name, ParenOpen, ParenClose, BraceOpen, Comment, Newline,
+                BraceClose
+            ]
+        );
+
+        let comment = Lexer::with_trivia(SourceFileId::dummy_file(), src)
+            .find(|t| t.kind == Comment)
+            .unwrap();
+        assert_eq!(comment.text, "// header");
+        assert_eq!((comment.span.start, comment.span.end), (0, 9));
+    }
 }
@@ -27,13 +27,16 @@ impl<'a> Add<&Token<'a>> for Span {
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Logos)]
 pub enum TokenKind {
-    // Ignoring comments and spaces/tabs for now.
-    // If we implement an auto-formatting tool, we'll probably want to change this.
-    #[regex(r"//[^\n]*", logos::skip)]
+    // Spaces and tabs are ignored. Comments are lexed as trivia tokens, which
+    // are filtered out by the default `Lexer` and only surface when trivia is
+    // requested (eg. by the formatter).
     #[regex("[ \t]+", logos::skip)]
     #[error]
     Error,
 
+    #[regex(r"//[^\n]*")]
+    Comment,
+
     #[regex(r"\n[ \t]*")]
     Newline,
 
@@ -143,6 +146,8 @@ pub enum TokenKind {
     Comma,
     #[token("#")]
     Hash,
+    #[token("?")]
+    Question,
     #[token(";")]
     Semi,
     #[token("+")]
@@ -216,6 +221,12 @@ pub enum TokenKind {
 }
 
 impl TokenKind {
+    /// Returns `true` if the token carries no syntactic meaning, and is
+    /// skipped by the parser.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, TokenKind::Comment)
+    }
+
     /// Return a user-friendly description of the token kind. E.g.
     /// TokenKind::Newline => "a newline"
     /// TokenKind::Colon => "`:`"
@@ -223,6 +234,7 @@ impl TokenKind {
         use TokenKind::*;
         match self {
             Newline => "a newline",
+            Comment => "a comment",
             Name => "a name",
             Int => "a number",
             Hex => "a hexadecimal number",
@@ -274,6 +286,7 @@ impl TokenKind {
             ColonColon => "symbol `::`",
             Comma => "symbol `,`",
             Hash => "symbol `#`",
+            Question => "symbol `?`",
             Semi => "symbol `;`",
             Plus => "symbol `+`",
             Minus => "symbol `-`",
@@ -1,4 +1,5 @@
 pub mod ast;
+pub mod formatter;
 pub mod grammar;
 pub mod lexer;
 pub use lexer::{Token, TokenKind};
@@ -66,6 +66,8 @@ contract C {
 }
 
 test_parse_err! { type_desc_path_number, module::parse_module, "type Foo = some::mod::Foo::5000" }
+test_parse_err! { contract_field_attr, module::parse_module, "contract C {\n #hot\n x: u8\n}" }
+test_parse_err! { contract_dangling_attr, module::parse_module, "contract C {\n fn f() {}\n #hot\n}" }
 test_parse_err! { contract_const_pub, module::parse_module, "contract C {\n const pub x: u8\n}" }
 test_parse_err! { contract_const_fn, module::parse_module, "contract C {\n const fn f() {}\n}" }
 test_parse_err! { expr_bad_prefix, expressions::parse_expr, "*x + 1" }
@@ -84,6 +86,9 @@ test_parse_err! { use_bad_name, module::parse_use, "use x as 123" }
 test_parse_err! { module_bad_stmt, module::parse_module, "if x { y }" }
 test_parse_err! { module_nonsense, module::parse_module, "))" }
 test_parse_err! { struct_bad_field_name, module::parse_module, "struct f {\n pub type }" }
+test_parse_err! { error_def_missing_parens, module::parse_module, "error Unauthorized" }
+test_parse_err! { error_def_bad_field, module::parse_module, "error Unauthorized(caller)" }
+test_parse_err! { interface_def_fn_body, module::parse_module, "interface Token {\n fn total_supply(self) -> u256 {}\n}" }
 test_parse_err! { stmt_vardecl_attr, functions::parse_stmt, "f.s : u" }
 test_parse_err! { stmt_vardecl_tuple, functions::parse_stmt, "(a, x+1) : u256" }
 test_parse_err! { stmt_vardecl_tuple_empty, functions::parse_stmt, "(a, ()) : u256" }
@@ -0,0 +1,89 @@
+use fe_common::db::TestDb;
+use fe_common::diagnostics::print_diagnostics;
+use fe_common::SourceFileId;
+use fe_parser::formatter::format_source;
+use fe_parser::parse_file;
+use fe_test_files::{fixture, fixture_dir_files};
+use insta::assert_snapshot;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn format(path: &str, src: &str) -> String {
+    let mut db = TestDb::default();
+    let id = SourceFileId::new_local(&mut db, path, src.into());
+
+    match format_source(id, src) {
+        Ok(formatted) => formatted,
+        Err(diags) => {
+            print_diagnostics(&db, &diags);
+            panic!("parse error");
+        }
+    }
+}
+
+fn print_ast(path: &str, src: &str) -> String {
+    let mut db = TestDb::default();
+    let id = SourceFileId::new_local(&mut db, path, src.into());
+    let (module, diags) = parse_file(id, src);
+    assert!(diags.is_empty(), "failed to parse formatted `{path}`");
+    format!("{module}")
+}
+
+/// Formatting must not change the meaning of the code, and formatting the
+/// formatted code must not change it again.
+fn check_roundtrip(path: &str, src: &str) {
+    let formatted = format(path, src);
+    assert_eq!(
+        print_ast(path, src),
+        print_ast(path, &formatted),
+        "formatting changed the AST of `{path}`"
+    );
+    assert_eq!(
+        formatted,
+        format(path, &formatted),
+        "formatting `{path}` is not idempotent"
+    );
+}
+
+macro_rules! test_format {
+    ($name:ident, $path:expr) => {
+        #[test]
+        #[wasm_bindgen_test]
+        fn $name() {
+            let src = fixture($path);
+            check_roundtrip($path, src);
+            if cfg!(target_arch = "wasm32") {
+                fe_common::assert_snapshot_wasm!(
+                    concat!("snapshots/cases__format__", stringify!($name), ".snap"),
+                    format($path, src)
+                );
+            } else {
+                assert_snapshot!(format($path, src))
+            }
+        }
+    };
+}
+
+test_format! { comments, "printing/comments.fe" }
+test_format! { comments_in_place, "printing/comments_in_place.fe" }
+test_format! { defs, "printing/defs.fe" }
+test_format! { expr_parens, "printing/expr_parens.fe" }
+test_format! { erc20, "demos/erc20_token.fe" }
+
+macro_rules! test_format_dir {
+    ($name:ident, $path:expr) => {
+        #[test]
+        fn $name() {
+            for (path, src) in fixture_dir_files($path) {
+                if path.ends_with(".fe") {
+                    check_roundtrip(path, src);
+                }
+            }
+        }
+    };
+}
+
+test_format_dir! { format_demos, "demos" }
+test_format_dir! { format_features, "features" }
+test_format_dir! { format_stress, "stress" }
+test_format_dir! { format_differential, "differential" }
+test_format_dir! { format_abi, "abi" }
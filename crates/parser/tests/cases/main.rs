@@ -1,3 +1,4 @@
 mod errors;
+mod format;
 mod parse_ast;
 mod print_ast;
@@ -89,6 +89,7 @@ test_parse! { expr_tuple1, expressions::parse_expr, "(1,)" }
 test_parse! { expr_tuple2, expressions::parse_expr, "(1, 2, \n 3)" }
 test_parse! { expr_tuple3, expressions::parse_expr, "(1, (2 + 3), (3 * 4, 5))" }
 test_parse! { expr_unit, expressions::parse_expr, "()" }
+test_parse! { expr_try, expressions::parse_expr, "-foo(x)?.bar[0]?" }
 
 test_parse! { ops_not, expressions::parse_expr, "x and not y" }
 test_parse! { ops_math, expressions::parse_expr, "a + b * -c ** d / e % f" }
@@ -174,6 +175,7 @@ test_parse! { type_3d, types::parse_type_desc, "Array<Array<Array<u256, 4>, 4>,
 test_parse! { type_string, types::parse_type_desc, "string<100>" }
 test_parse! { type_generic, types::parse_type_desc, "foo<a, b<c>, Array<d, 10>>" }
 test_parse! { type_generic_int, types::parse_type_desc, "foo<1, 2>" }
+test_parse! { type_generic_path, types::parse_type_desc, "foo::Pair<u8, bar::Baz>" }
 test_parse! { type_map1, types::parse_type_desc, "Map<address, u256>" }
 test_parse! { type_map2, types::parse_type_desc, "Map<address, Map<u8, u256>>" }
 test_parse! { type_map3, types::parse_type_desc, "Map<address, Map<u8, Map<u8, u8>>>" }
@@ -217,6 +219,20 @@ test_parse! { struct_def, try_parse_module, r#"struct S {
   unsafe fn bar() {}
 }"# }
 test_parse! { empty_struct_def, try_parse_module, "struct S {}" }
+test_parse! { struct_def_generic, try_parse_module, r#"pub struct Pair<T, U> {
+  pub first: T
+  pub second: U
+
+  pub fn swap(self) -> Pair<U, T> {
+    return Pair<U, T>(first: self.second, second: self.first)
+  }
+}"# }
+test_parse! { error_def, try_parse_module, "error InsufficientBalance(needed: u256,\n have: Array<u8, 2>,)" }
+test_parse! { error_def_pub, try_parse_module, "pub error Unauthorized()" }
+test_parse! { interface_def, try_parse_module, r#"pub interface Token {
+  fn balance_of(self, owner: address) -> u256;
+  fn transfer(mut self, to: address, amount: u256) -> bool;
+}"# }
 
 test_parse! { enum_def, try_parse_module, r#"enum E {
     Unit1
@@ -225,6 +241,10 @@ test_parse! { enum_def, try_parse_module, r#"enum E {
     Tuple2(Array<u32, 10>, u256)
 }"# }
 test_parse! { enum_enum_def, try_parse_module, r#"enum E {}"# }
+test_parse! { enum_def_generic, try_parse_module, r#"enum Option<T> {
+    Some(T)
+    None
+}"# }
 
 test_parse! { contract_def, try_parse_module, r#"contract Foo {
   x: address
@@ -239,6 +259,21 @@ test_parse! { contract_def, try_parse_module, r#"contract Foo {
 
 test_parse! { empty_contract_def, try_parse_module, "contract Foo {}" }
 
+test_parse! { contract_fn_attributes, try_parse_module, r#"contract Foo {
+  #hot
+  pub fn foo() {}
+
+  pub fn bar() {}
+}
+"# }
+
+test_parse! { module_fn_attributes, try_parse_module, r#"#test
+fn test_foo() {}
+
+#test(should_revert: Error(code: 42))
+fn test_bar() {}
+"# }
+
 test_parse! { pub_contract_def, try_parse_module, r#"
 pub contract Foo {
     pub fn foo() -> u8 {
@@ -0,0 +1,304 @@
+//! The cheatcodes of `std::testing`, which change the environment that a test
+//! runs in. Cheatcodes are calls to [`CHEATCODE_ADDRESS`], which the
+//! [`Cheatcodes`] inspector answers instead of the EVM.
+
+use ethabi::{ParamType, Token};
+use revm::interpreter::{
+    CallInputs, CallOutcome, CreateInputs, CreateOutcome, Gas, InstructionResult, InterpreterResult,
+};
+use revm::primitives::{address, Address, BlockEnv, Bytes, Log, LogData, B256, U256};
+use revm::{Database, EvmContext, Inspector, JournaledState};
+
+/// The address of the cheatcodes, the same as in Foundry.
+pub const CHEATCODE_ADDRESS: Address = address!("7109709ecfa91a80626ff3989d68f67f5b1dd12d");
+
+/// The address that tests are deployed to.
+pub const TEST_ADDRESS: Address = address!("0000000000000000000000000000000000000042");
+
+/// `warp(uint256)`
+const WARP: [u8; 4] = [0xe5, 0xd6, 0xbf, 0x02];
+/// `roll(uint256)`
+const ROLL: [u8; 4] = [0x1f, 0x7b, 0x4f, 0x30];
+/// `deal(address,uint256)`
+const DEAL: [u8; 4] = [0xc8, 0x8a, 0x5e, 0x6d];
+/// `prank(address)`
+const PRANK: [u8; 4] = [0xca, 0x66, 0x9f, 0xa7];
+/// `startPrank(address)`
+const START_PRANK: [u8; 4] = [0x06, 0x44, 0x7d, 0x56];
+/// `stopPrank()`
+const STOP_PRANK: [u8; 4] = [0x90, 0xc5, 0x01, 0x3b];
+/// `nextCallValue(uint256)`
+const NEXT_CALL_VALUE: [u8; 4] = [0xe3, 0xf2, 0x61, 0xb8];
+/// `snapshot()`
+const SNAPSHOT: [u8; 4] = [0x97, 0x11, 0x71, 0x5a];
+/// `revertTo(uint256)`
+const REVERT_TO: [u8; 4] = [0x44, 0xd7, 0xf0, 0xa4];
+/// `expectEmit()`
+const EXPECT_EMIT: [u8; 4] = [0x44, 0x0e, 0xd1, 0x0d];
+
+/// An inspector that executes the cheatcodes called by a test, and applies
+/// their effects to the calls and contract creations of the test.
+#[derive(Debug, Default)]
+pub struct Cheatcodes {
+    prank: Option<Prank>,
+    next_call_value: Option<U256>,
+    snapshots: Vec<Snapshot>,
+    expected_emit: Option<ExpectedEmit>,
+}
+
+/// The `msg.sender` of calls made by the test.
+#[derive(Debug)]
+struct Prank {
+    sender: Address,
+    /// Whether the prank ends after the next call, as opposed to when
+    /// `stop_prank` is called.
+    single_call: bool,
+}
+
+/// An event that the next call or contract creation of the test must emit.
+#[derive(Debug, Default)]
+struct ExpectedEmit {
+    /// The event, which is the next one that the test emits itself after
+    /// calling `expect_emit`.
+    log: Option<LogData>,
+    /// The call depth of the test while it makes the call that must emit the
+    /// event.
+    depth: Option<u64>,
+    found: bool,
+}
+
+#[derive(Debug)]
+struct Snapshot {
+    /// The call depth of the frame that took the snapshot. Reverting from
+    /// another frame would leave the journal of the EVM inconsistent.
+    depth: u64,
+    state: JournaledState,
+    block: BlockEnv,
+}
+
+impl Cheatcodes {
+    /// Executes the cheatcode encoded in `input`, and returns its output or
+    /// the reason it failed.
+    fn execute<DB: Database>(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        input: &[u8],
+    ) -> Result<Bytes, String> {
+        if input.len() < 4 {
+            return Err("invalid cheatcode call".into());
+        }
+        let (selector, args) = input.split_at(4);
+        let selector = <[u8; 4]>::try_from(selector).unwrap();
+        let words = |count: usize| -> Result<Vec<U256>, String> {
+            if args.len() != count * 32 {
+                return Err("invalid cheatcode arguments".into());
+            }
+            Ok(args.chunks(32).map(U256::from_be_slice).collect())
+        };
+
+        match selector {
+            WARP => context.env.block.timestamp = words(1)?[0],
+            ROLL => context.env.block.number = words(1)?[0],
+            DEAL => {
+                let words = words(2)?;
+                let address = to_address(words[0]);
+                let (account, _) = context
+                    .load_account(address)
+                    .map_err(|_| format!("unable to load account {address}"))?;
+                account.info.balance = words[1];
+                context.journaled_state.touch(&address);
+            }
+            PRANK | START_PRANK => {
+                self.prank = Some(Prank {
+                    sender: to_address(words(1)?[0]),
+                    single_call: selector == PRANK,
+                })
+            }
+            STOP_PRANK => {
+                words(0)?;
+                self.prank = None
+            }
+            NEXT_CALL_VALUE => self.next_call_value = Some(words(1)?[0]),
+            SNAPSHOT => {
+                words(0)?;
+                self.snapshots.push(Snapshot {
+                    depth: context.journaled_state.depth(),
+                    state: context.journaled_state.clone(),
+                    block: context.env.block.clone(),
+                });
+                let id = U256::from(self.snapshots.len() - 1);
+                return Ok(Bytes::copy_from_slice(&id.to_be_bytes::<32>()));
+            }
+            REVERT_TO => {
+                let id = words(1)?[0];
+                let snapshot = usize::try_from(id)
+                    .ok()
+                    .and_then(|id| self.snapshots.get(id))
+                    .ok_or_else(|| format!("unknown snapshot {id}"))?;
+                if snapshot.depth != context.journaled_state.depth() {
+                    return Err(format!(
+                        "snapshot {id} must be reverted to by the function that took it"
+                    ));
+                }
+                context.journaled_state = snapshot.state.clone();
+                context.env.block = snapshot.block.clone();
+            }
+            EXPECT_EMIT => {
+                words(0)?;
+                self.expected_emit = Some(ExpectedEmit::default())
+            }
+            _ => return Err(format!("unknown cheatcode 0x{}", hex::encode(selector))),
+        }
+        Ok(Bytes::new())
+    }
+
+    /// Returns the sender of the next call or contract creation of the test,
+    /// if it's pranked.
+    fn take_prank_sender(&mut self) -> Option<Address> {
+        let prank = self.prank.as_ref()?;
+        let sender = prank.sender;
+        if prank.single_call {
+            self.prank = None
+        }
+        Some(sender)
+    }
+
+    /// Starts looking for the expected event in the call or contract
+    /// creation that the test, at call depth `depth`, is about to make.
+    fn start_expected_emit(&mut self, depth: u64) {
+        if let Some(expected) = &mut self.expected_emit {
+            if expected.log.is_some() && expected.depth.is_none() {
+                expected.depth = Some(depth)
+            }
+        }
+    }
+
+    /// Checks that the expected event was emitted by the call or contract
+    /// creation that ended at call depth `depth`. At the end of the test,
+    /// i.e. at depth 0, every `expect_emit` must have been checked.
+    fn check_expected_emit(&mut self, depth: u64) -> Result<(), String> {
+        let expected = match &self.expected_emit {
+            Some(expected) if expected.depth == Some(depth) || depth == 0 => {
+                self.expected_emit.take().unwrap()
+            }
+            _ => return Ok(()),
+        };
+        if expected.found {
+            Ok(())
+        } else if expected.log.is_none() {
+            Err("`expect_emit` must be followed by the expected event".into())
+        } else if expected.depth.is_none() {
+            Err("`expect_emit` must be followed by a call".into())
+        } else {
+            Err("expected event was not emitted".into())
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for Cheatcodes {
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if inputs.contract == CHEATCODE_ADDRESS {
+            let (result, output) = match self.execute(context, &inputs.input) {
+                Ok(output) => (InstructionResult::Return, output),
+                Err(reason) => (InstructionResult::Revert, revert_reason(reason)),
+            };
+            return Some(CallOutcome::new(
+                InterpreterResult {
+                    result,
+                    output,
+                    gas: Gas::new(inputs.gas_limit),
+                },
+                inputs.return_memory_offset.clone(),
+            ));
+        }
+
+        if inputs.context.caller == TEST_ADDRESS {
+            self.start_expected_emit(context.journaled_state.depth());
+            if let Some(sender) = self.take_prank_sender() {
+                inputs.context.caller = sender;
+                inputs.transfer.source = sender;
+            }
+            if let Some(value) = self.next_call_value.take() {
+                inputs.context.apparent_value = value;
+                inputs.transfer.value = value;
+            }
+        }
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        mut outcome: CallOutcome,
+    ) -> CallOutcome {
+        if inputs.contract != CHEATCODE_ADDRESS {
+            let checked = self.check_expected_emit(context.journaled_state.depth());
+            if let (Err(reason), true) = (checked, outcome.result.is_ok()) {
+                outcome.result.result = InstructionResult::Revert;
+                outcome.result.output = revert_reason(reason);
+            }
+        }
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        if inputs.caller == TEST_ADDRESS {
+            self.start_expected_emit(context.journaled_state.depth());
+            if let Some(sender) = self.take_prank_sender() {
+                inputs.caller = sender;
+            }
+            if let Some(value) = self.next_call_value.take() {
+                inputs.value = value;
+            }
+        }
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        mut outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        let checked = self.check_expected_emit(context.journaled_state.depth());
+        if let (Err(reason), true) = (checked, outcome.result.is_ok()) {
+            outcome.result.result = InstructionResult::Revert;
+            outcome.result.output = revert_reason(reason);
+            outcome.address = None;
+        }
+        outcome
+    }
+
+    fn log(&mut self, _context: &mut EvmContext<DB>, log: &Log) {
+        if let Some(expected) = &mut self.expected_emit {
+            match &expected.log {
+                None if log.address == TEST_ADDRESS => expected.log = Some(log.data.clone()),
+                Some(data) if expected.depth.is_some() && log.data == *data => {
+                    expected.found = true
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn to_address(word: U256) -> Address {
+    Address::from_word(B256::from(word))
+}
+
+/// Encodes `reason` as the output of `revert Error(string)`, as solc does.
+fn revert_reason(reason: String) -> Bytes {
+    let selector = ethabi::short_signature("Error", &[ParamType::String]);
+    let mut output = selector.to_vec();
+    output.extend(ethabi::encode(&[Token::String(reason)]));
+    output.into()
+}
@@ -0,0 +1,252 @@
+//! Fuzzing of tests that take arguments. The arguments are generated at random
+//! and, if the test fails, shrunk to a minimal counterexample.
+
+use ethabi::{AbiError, Param, ParamType, Token, Uint};
+use proptest::prelude::*;
+use proptest::strategy::{NewTree, ValueTree};
+use proptest::test_runner::{Config, RngAlgorithm, TestCaseError, TestError, TestRng, TestRunner};
+
+use crate::{failure, format_token, ExpectedRevert, TestOutcome, TestSink};
+
+/// The number of random inputs that a fuzz test is run with, and the seed
+/// they're generated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuzzConfig {
+    pub runs: u32,
+    pub seed: u64,
+}
+
+impl FuzzConfig {
+    /// The number of runs if none is given.
+    pub const DEFAULT_RUNS: u32 = 256;
+
+    /// Returns the config for `runs` runs, with a random seed if `seed` is
+    /// `None`.
+    pub fn new(runs: u32, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            let mut bytes = [0; 8];
+            getrandom::getrandom(&mut bytes).expect("failed to generate a fuzz seed");
+            u64::from_le_bytes(bytes)
+        });
+        Self { runs, seed }
+    }
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_RUNS, None)
+    }
+}
+
+/// Runs the test `name` with `config.runs` random arguments for `params`,
+/// records its outcome in `sink` and returns whether it passed. `run`
+/// executes the test with the ABI encoded arguments, or fails with a reason.
+///
+/// If an input fails the test, the arguments are shrunk to the smallest ones
+/// that still fail it, which are reported along with the seed.
+pub fn fuzz(
+    name: &str,
+    params: &[Param],
+    errors: &[AbiError],
+    expected_revert: Option<&ExpectedRevert>,
+    config: FuzzConfig,
+    sink: &mut TestSink,
+    run: impl Fn(Vec<u8>) -> Result<TestOutcome, String>,
+) -> bool {
+    let strategy: Vec<_> = params.iter().map(|param| strategy(&param.kind)).collect();
+    let mut runner = runner(config);
+    let result = runner.run(&strategy, |args| {
+        let outcome = run(ethabi::encode(&args)).map_err(TestCaseError::fail)?;
+        match failure(errors, expected_revert, &outcome) {
+            Some(reason) => Err(TestCaseError::fail(reason)),
+            None => Ok(()),
+        }
+    });
+
+    let reason = match result {
+        Ok(()) => {
+            sink.inc_success_count();
+            return true;
+        }
+        Err(TestError::Fail(reason, args)) => {
+            let args: Vec<_> = params
+                .iter()
+                .zip(&args)
+                .map(|(param, arg)| format!("{}: {}", param.name, format_token(arg)))
+                .collect();
+            format!(
+                "{}\n  counterexample: ({})\n  seed: {}",
+                reason.message(),
+                args.join(", "),
+                config.seed
+            )
+        }
+        Err(TestError::Abort(reason)) => format!("  fuzzing aborted: {}", reason.message()),
+    };
+    sink.insert_failure(name, &reason);
+    false
+}
+
+/// Returns `config.runs` ABI encoded random arguments for `params`, generated
+/// like the ones of `fuzz` but without running anything, so that the same
+/// inputs can be given to several implementations of a test.
+pub fn fuzz_inputs(params: &[Param], config: FuzzConfig) -> Vec<Vec<u8>> {
+    let strategy: Vec<_> = params.iter().map(|param| strategy(&param.kind)).collect();
+    let mut runner = runner(config);
+    (0..config.runs)
+        .map(|_| {
+            let args = strategy
+                .new_tree(&mut runner)
+                .expect("failed to generate fuzz arguments")
+                .current();
+            ethabi::encode(&args)
+        })
+        .collect()
+}
+
+/// Returns the runner that generates `config.runs` inputs from `config.seed`.
+fn runner(config: FuzzConfig) -> TestRunner {
+    let runner_config = Config {
+        cases: config.runs,
+        failure_persistence: None,
+        ..Config::default()
+    };
+    let mut seed = [0; 32];
+    seed[..8].copy_from_slice(&config.seed.to_le_bytes());
+    let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &seed);
+    TestRunner::new_with_rng(runner_config, rng)
+}
+
+/// Returns the strategy that generates values of type `kind`. Integers
+/// shrink toward zero, addresses toward the zero address and booleans toward
+/// `false`.
+fn strategy(kind: &ParamType) -> BoxedStrategy<Token> {
+    match kind {
+        ParamType::Uint(bits) => int(*bits, false).prop_map(Token::Uint).boxed(),
+        ParamType::Int(bits) => int(*bits, true).prop_map(Token::Int).boxed(),
+        ParamType::Address => any::<[u8; 20]>()
+            .prop_map(|address| Token::Address(address.into()))
+            .boxed(),
+        ParamType::Bool => any::<bool>().prop_map(Token::Bool).boxed(),
+        ParamType::FixedArray(elem, len) => proptest::collection::vec(strategy(elem), *len)
+            .prop_map(Token::FixedArray)
+            .boxed(),
+        ParamType::Tuple(fields) => fields
+            .iter()
+            .map(strategy)
+            .collect::<Vec<_>>()
+            .prop_map(Token::Tuple)
+            .boxed(),
+        // The analyzer only accepts test parameters of integer, `bool` and
+        // `address` types, and arrays, tuples and structs of them. Arrays of
+        // `u8`, which are encoded as bytes, and strings are rejected.
+        ParamType::Bytes | ParamType::FixedBytes(_) | ParamType::String | ParamType::Array(_) => {
+            unreachable!("test parameters of type `{kind}` are rejected by the analyzer")
+        }
+    }
+}
+
+/// Returns the strategy that generates `bits` bit integers, in two's
+/// complement if `signed`.
+fn int(bits: usize, signed: bool) -> IntStrategy {
+    let max = if bits == 256 {
+        Uint::MAX
+    } else {
+        (Uint::one() << bits) - 1
+    };
+    IntStrategy { bits, max, signed }
+}
+
+/// Generates integers of up to `bits` bits, whose maximum unsigned value is
+/// `max`. The bit patterns of all zeros and all ones are generated more often
+/// than others, since they're the most likely to hit edge cases.
+#[derive(Debug, Clone, Copy)]
+struct IntStrategy {
+    bits: usize,
+    max: Uint,
+    signed: bool,
+}
+
+impl Strategy for IntStrategy {
+    type Tree = IntValueTree;
+    type Value = Uint;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let rng = runner.rng();
+        let value = match rng.next_u32() % 10 {
+            0 => Uint::zero(),
+            1 => self.max,
+            _ => {
+                let mut bytes = [0; 32];
+                rng.fill_bytes(&mut bytes);
+                Uint::from_big_endian(&bytes) & self.max
+            }
+        };
+
+        // Negative values are shrunk by their magnitude, so that they shrink
+        // toward zero rather than toward the minimum value.
+        let negative = self.signed && value.bit(self.bits - 1);
+        let magnitude = if negative {
+            (self.max - value) + 1
+        } else {
+            value
+        };
+        Ok(IntValueTree {
+            lo: Uint::zero(),
+            curr: magnitude,
+            hi: magnitude,
+            negative,
+        })
+    }
+}
+
+/// Shrinks the magnitude of an integer toward zero by binary search, like
+/// the value trees of the integer strategies of proptest. The value is
+/// sign extended to 256 bits if it's negative.
+#[derive(Debug, Clone, Copy)]
+struct IntValueTree {
+    lo: Uint,
+    curr: Uint,
+    hi: Uint,
+    negative: bool,
+}
+
+impl IntValueTree {
+    fn reposition(&mut self) -> bool {
+        let mid = self.lo + (self.hi - self.lo) / 2;
+        if mid == self.curr {
+            false
+        } else {
+            self.curr = mid;
+            true
+        }
+    }
+}
+
+impl ValueTree for IntValueTree {
+    type Value = Uint;
+
+    fn current(&self) -> Uint {
+        if self.negative {
+            Uint::zero().overflowing_sub(self.curr).0
+        } else {
+            self.curr
+        }
+    }
+
+    fn simplify(&mut self) -> bool {
+        if self.hi <= self.lo {
+            return false;
+        }
+        self.hi = self.curr;
+        self.reposition()
+    }
+
+    fn complicate(&mut self) -> bool {
+        if self.hi <= self.lo {
+            return false;
+        }
+        self.lo = self.curr + 1;
+        self.reposition()
+    }
+}
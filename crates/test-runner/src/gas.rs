@@ -0,0 +1,338 @@
+//! Gas measurements of tests, which `fe test --gas-report` prints and
+//! `fe test --snapshot` compares against the `.gas-snapshot` file.
+
+use indexmap::IndexMap;
+use revm::interpreter::{CallInputs, CallOutcome};
+use revm::{Database, EvmContext, Inspector};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::{CHEATCODE_ADDRESS, TEST_ADDRESS};
+
+/// The gas used by a test and by the contract functions it called.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestGas {
+    /// The gas used by the whole test transaction.
+    pub gas_used: u64,
+    pub calls: Vec<CallGas>,
+}
+
+/// The gas used by a call to a contract function, including the calls that
+/// the function made in turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallGas {
+    pub selector: [u8; 4],
+    pub gas_used: u64,
+}
+
+/// An inspector that records the gas used by the calls of a test.
+#[derive(Debug, Default)]
+pub(crate) struct GasTracker {
+    pub(crate) calls: Vec<CallGas>,
+}
+
+impl<DB: Database> Inspector<DB> for GasTracker {
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        if inputs.contract != CHEATCODE_ADDRESS
+            && inputs.contract != TEST_ADDRESS
+            && inputs.input.len() >= 4
+        {
+            let gas = &outcome.result.gas;
+            self.calls.push(CallGas {
+                selector: inputs.input[..4].try_into().unwrap(),
+                gas_used: gas.limit() - gas.remaining(),
+            })
+        }
+        outcome
+    }
+}
+
+/// The gas used by all tests, and by the contract functions they called.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasReport {
+    tests: IndexMap<String, u64>,
+    functions: IndexMap<String, FunctionGas>,
+}
+
+/// The gas used by the calls to a contract function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FunctionGas {
+    calls: u64,
+    min: u64,
+    max: u64,
+    total: u64,
+}
+
+impl GasReport {
+    /// Adds the gas used by `test`. The called functions are reported under
+    /// the names returned by `function_name`.
+    pub fn insert(&mut self, test: &str, gas: &TestGas, function_name: impl Fn([u8; 4]) -> String) {
+        self.tests.insert(test.to_string(), gas.gas_used);
+        for call in &gas.calls {
+            let gas_used = call.gas_used;
+            self.functions
+                .entry(function_name(call.selector))
+                .and_modify(|function| {
+                    function.calls += 1;
+                    function.min = function.min.min(gas_used);
+                    function.max = function.max.max(gas_used);
+                    function.total += gas_used;
+                })
+                .or_insert(FunctionGas {
+                    calls: 1,
+                    min: gas_used,
+                    max: gas_used,
+                    total: gas_used,
+                });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tests.is_empty()
+    }
+
+    /// Returns the gas used by each test.
+    pub fn snapshot(&self) -> GasSnapshot {
+        GasSnapshot {
+            tests: self.tests.clone(),
+        }
+    }
+}
+
+impl Display for GasReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .tests
+            .keys()
+            .chain(self.functions.keys())
+            .map(String::len)
+            .max()
+            .unwrap_or_default()
+            .max("function".len());
+
+        writeln!(f, "{:width$}  {:>10}", "test", "gas")?;
+        for (test, gas_used) in &self.tests {
+            writeln!(f, "{test:width$}  {gas_used:>10}")?;
+        }
+
+        if !self.functions.is_empty() {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{:width$}  {:>6}  {:>10}  {:>10}  {:>10}",
+                "function", "calls", "min", "avg", "max"
+            )?;
+            for (name, function) in &self.functions {
+                writeln!(
+                    f,
+                    "{name:width$}  {:>6}  {:>10}  {:>10}  {:>10}",
+                    function.calls,
+                    function.min,
+                    function.total / function.calls,
+                    function.max
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The gas used by each test, as written to a `.gas-snapshot` file, with
+/// one `<test> (gas: <gas>)` line per test.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasSnapshot {
+    tests: IndexMap<String, u64>,
+}
+
+/// A test that uses more gas than it did in a previous snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasRegression {
+    pub test: String,
+    pub previous: u64,
+    pub current: u64,
+}
+
+impl GasSnapshot {
+    /// Returns the tests that use more gas than in `previous`, by more than
+    /// `tolerance` percent. Tests that are new to this snapshot are ignored.
+    pub fn regressions(&self, previous: &GasSnapshot, tolerance: f64) -> Vec<GasRegression> {
+        self.tests
+            .iter()
+            .filter_map(|(test, &current)| {
+                let previous = *previous.tests.get(test)?;
+                let limit = previous as f64 * (1. + tolerance / 100.);
+                (current as f64 > limit).then(|| GasRegression {
+                    test: test.clone(),
+                    previous,
+                    current,
+                })
+            })
+            .collect()
+    }
+
+    /// Adds the tests of `other`, replacing the entries of the tests that are
+    /// in both snapshots.
+    pub fn extend(&mut self, other: GasSnapshot) {
+        self.tests.extend(other.tests)
+    }
+}
+
+impl Display for GasSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (test, gas_used) in &self.tests {
+            writeln!(f, "{test} (gas: {gas_used})")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GasSnapshot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tests = IndexMap::new();
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let entry = line
+                .strip_suffix(')')
+                .and_then(|line| line.rsplit_once(" (gas: "))
+                .and_then(|(test, gas_used)| Some((test, gas_used.parse().ok()?)));
+            match entry {
+                Some((test, gas_used)) => tests.insert(test.to_string(), gas_used),
+                None => return Err(format!("invalid gas snapshot entry `{line}`")),
+            };
+        }
+        Ok(Self { tests })
+    }
+}
+
+impl Display for GasRegression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let increase = (self.current - self.previous) as f64 / self.previous as f64 * 100.;
+        write!(
+            f,
+            "{}: {} -> {} (+{increase:.2}%)",
+            self.test, self.previous, self.current
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(tests: &[(&str, u64)]) -> GasSnapshot {
+        GasSnapshot {
+            tests: tests
+                .iter()
+                .map(|(test, gas_used)| (test.to_string(), *gas_used))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parse_snapshot() {
+        let src = "main::test_a (gas: 21000)\n\nfoo::test_b (gas: 48213)\n";
+        let parsed = src.parse::<GasSnapshot>().unwrap();
+        assert_eq!(
+            parsed,
+            snapshot(&[("main::test_a", 21000), ("foo::test_b", 48213)])
+        );
+        assert_eq!(parsed.to_string(), src.replace("\n\n", "\n"));
+
+        assert_eq!(
+            "main::test_a (gas: 21000)\nmain::test_b 48213\n".parse::<GasSnapshot>(),
+            Err("invalid gas snapshot entry `main::test_b 48213`".to_string())
+        );
+        assert_eq!(
+            "main::test_a (gas: lots)".parse::<GasSnapshot>(),
+            Err("invalid gas snapshot entry `main::test_a (gas: lots)`".to_string())
+        );
+    }
+
+    #[test]
+    fn regressions_within_tolerance() {
+        let previous = snapshot(&[("main::test_a", 1000)]);
+
+        // 10% more gas is allowed by a tolerance of 10%, but not 1 more.
+        assert!(snapshot(&[("main::test_a", 1100)])
+            .regressions(&previous, 10.)
+            .is_empty());
+        assert_eq!(
+            snapshot(&[("main::test_a", 1101)]).regressions(&previous, 10.),
+            vec![GasRegression {
+                test: "main::test_a".into(),
+                previous: 1000,
+                current: 1101,
+            }]
+        );
+        assert!(snapshot(&[("main::test_a", 999)])
+            .regressions(&previous, 0.)
+            .is_empty());
+    }
+
+    #[test]
+    fn regressions_ignore_new_tests() {
+        let previous = snapshot(&[("main::test_a", 1000)]);
+        let current = snapshot(&[("main::test_a", 1000), ("main::test_b", 5000)]);
+        assert!(current.regressions(&previous, 0.).is_empty());
+    }
+
+    #[test]
+    fn same_test_name_in_different_modules() {
+        let mut report = GasReport::default();
+        let gas = |gas_used| TestGas {
+            gas_used,
+            calls: vec![],
+        };
+        report.insert("foo::test_a", &gas(100), |_| unreachable!());
+        report.insert("bar::test_a", &gas(200), |_| unreachable!());
+        assert_eq!(
+            report.snapshot(),
+            snapshot(&[("foo::test_a", 100), ("bar::test_a", 200)])
+        );
+    }
+
+    #[test]
+    fn report_table() {
+        let mut report = GasReport::default();
+        let call = |selector, gas_used| CallGas { selector, gas_used };
+        let name = |selector: [u8; 4]| match selector {
+            [0, 0, 0, 1] => "Token.transfer".to_string(),
+            _ => "Token.balance_of".to_string(),
+        };
+        report.insert(
+            "main::test_transfer",
+            &TestGas {
+                gas_used: 50000,
+                calls: vec![call([0, 0, 0, 1], 30000), call([0, 0, 0, 2], 2000)],
+            },
+            name,
+        );
+        report.insert(
+            "main::test_transfer_twice",
+            &TestGas {
+                gas_used: 70000,
+                calls: vec![call([0, 0, 0, 1], 30000), call([0, 0, 0, 1], 10000)],
+            },
+            name,
+        );
+
+        assert_eq!(
+            report.to_string(),
+            "\
+test                              gas
+main::test_transfer             50000
+main::test_transfer_twice       70000
+
+function                    calls         min         avg         max
+Token.transfer                  3       10000       23333       30000
+Token.balance_of                1        2000        2000        2000
+"
+        );
+    }
+}
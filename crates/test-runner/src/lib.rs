@@ -1,12 +1,25 @@
 use colored::Colorize;
-use ethabi::{Event, Hash, RawLog};
+use ethabi::{AbiError, Event, Hash, Param, ParamType, RawLog, Token, Uint};
+use fe_common::EvmVersion;
 use indexmap::IndexMap;
+use revm::interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome};
 use revm::primitives::{
-    AccountInfo, Address, Bytecode, Bytes, Env, ExecutionResult, TransactTo, B256, U256,
+    AccountInfo, Address, Bytecode, Bytes, Env, ExecutionResult, Log, SpecId, TransactTo, B256,
+    U256,
 };
-use std::{fmt::Display, str::FromStr};
+use revm::{Database, EvmContext, Inspector};
+use std::fmt::Display;
 
+pub use cheatcodes::{Cheatcodes, CHEATCODE_ADDRESS, TEST_ADDRESS};
 pub use ethabi;
+pub use fuzz::{fuzz, fuzz_inputs, FuzzConfig};
+pub use gas::{CallGas, GasRegression, GasReport, GasSnapshot, TestGas};
+
+use gas::GasTracker;
+
+mod cheatcodes;
+mod fuzz;
+mod gas;
 
 #[derive(Debug)]
 pub struct TestSink {
@@ -14,6 +27,7 @@ pub struct TestSink {
     failure_details: Vec<String>,
     logs_details: Vec<String>,
     collect_logs: bool,
+    gas_report: GasReport,
 }
 
 impl TestSink {
@@ -23,6 +37,7 @@ impl TestSink {
             failure_details: vec![],
             logs_details: vec![],
             collect_logs,
+            gas_report: GasReport::default(),
         }
     }
 
@@ -61,6 +76,20 @@ impl TestSink {
         self.success_count += 1
     }
 
+    /// Records the gas used by the test `name`, see [`GasReport::insert`].
+    pub fn insert_gas(
+        &mut self,
+        name: &str,
+        gas: &TestGas,
+        function_name: impl Fn([u8; 4]) -> String,
+    ) {
+        self.gas_report.insert(name, gas, function_name)
+    }
+
+    pub fn gas_report(&self) -> &GasReport {
+        &self.gas_report
+    }
+
     pub fn failure_details(&self) -> String {
         self.failure_details.join("\n")
     }
@@ -108,84 +137,398 @@ impl Display for TestSink {
     }
 }
 
-pub fn execute(name: &str, events: &[Event], bytecode: &str, sink: &mut TestSink) -> bool {
-    let events: IndexMap<_, _> = events
+/// A log emitted by a test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestLog {
+    pub address: [u8; 20],
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+}
+
+/// The result of a test execution, independent of the backend that ran it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Success {
+        logs: Vec<TestLog>,
+    },
+    Revert {
+        output: Vec<u8>,
+    },
+    /// The test stopped without returning or reverting, e.g. because it ran
+    /// out of gas or executed an invalid instruction.
+    Halt {
+        reason: String,
+    },
+}
+
+/// The revert that a test marked `#test(should_revert)` expects. The test
+/// fails if it returns, or if it reverts with a different error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpectedRevert {
+    /// Any revert passes the test.
+    Any,
+    /// Only a revert with `error`, whose fields have the values `args`,
+    /// passes the test.
+    Error { error: AbiError, args: Vec<Token> },
+}
+
+impl ExpectedRevert {
+    /// Returns the `Panic(uint256)` revert with `code`, which the compiler
+    /// emits on e.g. an arithmetic overflow or a failed `assert`.
+    pub fn panic(code: Uint) -> Self {
+        ExpectedRevert::Error {
+            error: panic_error(),
+            args: vec![Token::Uint(code)],
+        }
+    }
+
+    fn matches(&self, output: &[u8]) -> bool {
+        match self {
+            ExpectedRevert::Any => true,
+            ExpectedRevert::Error { error, args } => {
+                output.len() >= 4
+                    && output[..4] == error.signature()[..4]
+                    && output[4..] == ethabi::encode(args)
+            }
+        }
+    }
+}
+
+impl Display for ExpectedRevert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpectedRevert::Any => write!(f, "any revert"),
+            ExpectedRevert::Error { error, args } => {
+                // Structs that aren't `error`s are encoded as a single tuple.
+                let args = match args.as_slice() {
+                    [Token::Tuple(fields)] if error.inputs.len() != 1 => fields,
+                    _ => args,
+                };
+                write!(f, "{}", format_error(error, args))
+            }
+        }
+    }
+}
+
+fn panic_error() -> AbiError {
+    AbiError {
+        name: "Panic".into(),
+        inputs: vec![Param {
+            name: "code".into(),
+            kind: ParamType::Uint(256),
+            internal_type: None,
+        }],
+    }
+}
+
+/// The `Error` struct of the standard library.
+fn std_error() -> AbiError {
+    AbiError {
+        name: "Error".into(),
+        inputs: vec![Param {
+            name: "code".into(),
+            kind: ParamType::Uint(256),
+            internal_type: None,
+        }],
+    }
+}
+
+/// Formats an error as it's written in Fe, e.g. `Error(code: 42)`.
+fn format_error(error: &AbiError, args: &[Token]) -> String {
+    let args: Vec<_> = error
+        .inputs
         .iter()
-        .map(|event| (event.signature(), event))
+        .zip(args)
+        .map(|(param, arg)| match arg {
+            Token::Uint(code) if error.name == "Panic" => format!("{}: 0x{code:02x}", param.name),
+            _ => format!("{}: {}", param.name, format_token(arg)),
+        })
         .collect();
+    format!("{}({})", error.name, args.join(", "))
+}
+
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Uint(value) => value.to_string(),
+        Token::Int(value) if value.bit(255) => {
+            format!("-{}", (!*value).overflowing_add(1.into()).0)
+        }
+        Token::Int(value) => value.to_string(),
+        Token::Address(address) => format!("{address:?}"),
+        Token::String(text) => format!("{text:?}"),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Array(tokens) | Token::FixedArray(tokens) => {
+            let tokens: Vec<_> = tokens.iter().map(format_token).collect();
+            format!("[{}]", tokens.join(", "))
+        }
+        Token::Tuple(tokens) => {
+            let tokens: Vec<_> = tokens.iter().map(format_token).collect();
+            format!("({})", tokens.join(", "))
+        }
+        Token::Bool(value) => value.to_string(),
+    }
+}
+
+/// The `Error(string)` revert of solc, which failed cheatcodes revert with.
+fn string_error() -> AbiError {
+    AbiError {
+        name: "Error".into(),
+        inputs: vec![Param {
+            name: "reason".into(),
+            kind: ParamType::String,
+            internal_type: None,
+        }],
+    }
+}
+
+/// Decodes the output of a revert with the first of `errors`, `Panic` or
+/// `Error` whose selector matches, or formats it as hex if none does.
+fn format_revert_output(errors: &[AbiError], output: &[u8]) -> String {
+    if output.len() >= 4 {
+        let known = errors
+            .iter()
+            .cloned()
+            .chain([panic_error(), std_error(), string_error()]);
+        for error in known {
+            if output[..4] != error.signature()[..4] {
+                continue;
+            }
+            if let Ok(args) = error.decode(&output[4..]) {
+                return format_error(&error, &args);
+            }
+        }
+    }
+    format!("0x{}", hex::encode(output))
+}
+
+/// The inspector of a test execution, which runs the cheatcodes and measures
+/// the gas used by the calls of the test.
+#[derive(Debug, Default)]
+struct TestInspector {
+    cheatcodes: Cheatcodes,
+    gas_tracker: GasTracker,
+}
+
+impl<DB: Database> Inspector<DB> for TestInspector {
+    fn log(&mut self, context: &mut EvmContext<DB>, log: &Log) {
+        self.cheatcodes.log(context, log)
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.cheatcodes.call(context, inputs)
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        let outcome = self.cheatcodes.call_end(context, inputs, outcome);
+        self.gas_tracker.call_end(context, inputs, outcome)
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.cheatcodes.create(context, inputs)
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.cheatcodes.create_end(context, inputs, outcome)
+    }
+}
+
+/// Executes the test in `bytecode`, records its outcome in `sink` and returns
+/// whether it passed, along with the gas it used.
+pub fn execute(
+    name: &str,
+    events: &[Event],
+    errors: &[AbiError],
+    expected_revert: Option<&ExpectedRevert>,
+    bytecode: &str,
+    evm_version: EvmVersion,
+    sink: &mut TestSink,
+) -> (bool, TestGas) {
+    let (outcome, gas) = run(bytecode, vec![], evm_version);
+    let passed = report(name, events, errors, expected_revert, outcome, sink);
+    (passed, gas)
+}
+
+/// Runs the test in `bytecode` with `calldata`, which holds the ABI encoded
+/// arguments of a fuzz test, and returns its outcome and the gas it used.
+pub fn run(bytecode: &str, calldata: Vec<u8>, evm_version: EvmVersion) -> (TestOutcome, TestGas) {
     let bytecode = Bytecode::new_raw(Bytes::copy_from_slice(&hex::decode(bytecode).unwrap()));
 
     let mut database = revm::InMemoryDB::default();
-    let test_address = Address::from_str("0000000000000000000000000000000000000042").unwrap();
     let test_info = AccountInfo::new(U256::ZERO, 0, B256::default(), bytecode);
-    database.insert_account_info(test_address, test_info);
+    database.insert_account_info(TEST_ADDRESS, test_info);
 
     let mut env = Env::default();
-    env.tx.transact_to = TransactTo::Call(test_address);
+    env.tx.transact_to = TransactTo::Call(TEST_ADDRESS);
+    env.tx.data = calldata.into();
 
     let builder = revm::EvmBuilder::default()
         .with_db(database)
-        .with_env(Box::new(env));
+        .with_external_context(TestInspector::default())
+        .with_env(Box::new(env))
+        .with_spec_id(spec_id(evm_version))
+        .append_handler_register(revm::inspector_handle_register);
     let mut evm = builder.build();
     let result = evm.transact_commit().expect("evm failure");
+    let gas = TestGas {
+        gas_used: result.gas_used(),
+        calls: std::mem::take(&mut evm.context.external.gas_tracker.calls),
+    };
 
-    if let ExecutionResult::Success { logs, .. } = result {
-        let logs: Vec<_> = logs
-            .iter()
-            .map(|log| {
-                if let Some(Some(event)) = log
-                    .topics()
-                    .first()
-                    .map(|sig| events.get(&Hash::from_slice(sig.as_slice())))
-                {
-                    let topics = log
-                        .topics()
-                        .iter()
-                        .map(|topic| Hash::from_slice(topic.as_slice()))
-                        .collect();
-                    let data = log.data.data.clone().to_vec();
-                    let raw_log = RawLog { topics, data };
-                    if let Ok(parsed_event) = event.parse_log(raw_log) {
-                        format!(
-                            "  {} emitted by {} with the following parameters [{}]",
-                            event.name,
-                            log.address,
-                            parsed_event
-                                .params
-                                .iter()
-                                .map(|param| format!("{}: {}", param.name, param.value))
-                                .collect::<Vec<String>>()
-                                .join(", "),
-                        )
-                    } else {
-                        format!("  {:?}", log)
-                    }
-                } else {
-                    format!("  {:?}", log)
+    let outcome = match result {
+        ExecutionResult::Success { logs, .. } => TestOutcome::Success {
+            logs: logs
+                .iter()
+                .map(|log| TestLog {
+                    address: log.address.into_array(),
+                    topics: log.topics().iter().map(|topic| topic.0).collect(),
+                    data: log.data.data.to_vec(),
+                })
+                .collect(),
+        },
+        ExecutionResult::Revert { output, .. } => TestOutcome::Revert {
+            output: output.to_vec(),
+        },
+        ExecutionResult::Halt { reason, .. } => TestOutcome::Halt {
+            reason: format!("{reason:?}"),
+        },
+    };
+    (outcome, gas)
+}
+
+/// Returns the revm hardfork that matches the EVM version the test is
+/// compiled for.
+pub fn spec_id(evm_version: EvmVersion) -> SpecId {
+    match evm_version {
+        EvmVersion::London => SpecId::LONDON,
+        EvmVersion::Paris => SpecId::MERGE,
+        EvmVersion::Shanghai => SpecId::SHANGHAI,
+        EvmVersion::Cancun => SpecId::CANCUN,
+    }
+}
+
+/// Records the outcome of a test in `sink` and returns whether it passed.
+/// Revert outputs are decoded with `errors` for display.
+pub fn report(
+    name: &str,
+    events: &[Event],
+    errors: &[AbiError],
+    expected_revert: Option<&ExpectedRevert>,
+    outcome: TestOutcome,
+    sink: &mut TestSink,
+) -> bool {
+    match failure(errors, expected_revert, &outcome) {
+        Some(reason) => {
+            sink.insert_failure(name, &reason);
+            false
+        }
+        None => {
+            if let TestOutcome::Success { logs } = outcome {
+                let logs = format_logs(events, logs);
+                if !logs.is_empty() {
+                    sink.insert_logs(name, &logs.join("\n"))
                 }
-            })
-            .collect();
+            }
+            sink.inc_success_count();
+            true
+        }
+    }
+}
 
-        if !logs.is_empty() {
-            sink.insert_logs(name, &logs.join("\n"))
+/// Returns why `outcome` fails the test, or `None` if the test passed.
+fn failure(
+    errors: &[AbiError],
+    expected_revert: Option<&ExpectedRevert>,
+    outcome: &TestOutcome,
+) -> Option<String> {
+    match (outcome, expected_revert) {
+        (TestOutcome::Success { .. }, None) => None,
+        (TestOutcome::Success { .. }, Some(ExpectedRevert::Any)) => {
+            Some("  expected a revert, but the test succeeded".to_string())
+        }
+        (TestOutcome::Success { .. }, Some(expected)) => Some(format!(
+            "  expected a revert with {expected}, but the test succeeded"
+        )),
+        (TestOutcome::Revert { output }, Some(expected)) if expected.matches(output) => None,
+        (TestOutcome::Revert { output }, Some(expected)) => Some(format!(
+            "  expected a revert with {expected}, but reverted with {}",
+            format_revert_output(errors, output)
+        )),
+        (TestOutcome::Revert { output }, None) if output.is_empty() => {
+            Some("  reverted".to_string())
         }
+        (TestOutcome::Revert { output }, None) => Some(format!(
+            "  reverted with {}",
+            format_revert_output(errors, output)
+        )),
+        // A halt undoes the state changes of the test like a revert does, but
+        // has no output that could match an error.
+        (TestOutcome::Halt { .. }, Some(ExpectedRevert::Any)) => None,
+        (TestOutcome::Halt { reason }, Some(expected)) => Some(format!(
+            "  expected a revert with {expected}, but halted with {reason}"
+        )),
+        (TestOutcome::Halt { reason }, None) => Some(format!("  halted with {reason}")),
+    }
+}
+
+fn format_logs(events: &[Event], logs: Vec<TestLog>) -> Vec<String> {
+    let events: IndexMap<_, _> = events
+        .iter()
+        .map(|event| (event.signature(), event))
+        .collect();
 
-        sink.inc_success_count();
-        true
-    } else if let ExecutionResult::Revert { output, .. } = result {
-        sink.insert_failure(
-            name,
-            &if output.is_empty() {
-                "  reverted".to_string()
+    logs.into_iter()
+        .map(|log| {
+            let address = Address::from_slice(&log.address);
+            if let Some(Some(event)) = log
+                .topics
+                .first()
+                .map(|sig| events.get(&Hash::from_slice(sig)))
+            {
+                let topics = log
+                    .topics
+                    .iter()
+                    .map(|topic| Hash::from_slice(topic))
+                    .collect();
+                let raw_log = RawLog {
+                    topics,
+                    data: log.data.clone(),
+                };
+                if let Ok(parsed_event) = event.parse_log(raw_log) {
+                    format!(
+                        "  {} emitted by {} with the following parameters [{}]",
+                        event.name,
+                        address,
+                        parsed_event
+                            .params
+                            .iter()
+                            .map(|param| format!("{}: {}", param.name, param.value))
+                            .collect::<Vec<String>>()
+                            .join(", "),
+                    )
+                } else {
+                    format!("  {:?}", log)
+                }
             } else {
-                format!(
-                    "  reverted with the following output: {}",
-                    hex::encode(output)
-                )
-            },
-        );
-        false
-    } else {
-        panic!("test halted")
-    }
+                format!("  {:?}", log)
+            }
+        })
+        .collect()
 }
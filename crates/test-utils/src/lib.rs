@@ -1,6 +1,7 @@
 use evm_runtime::{ExitReason, Handler};
 use fe_common::diagnostics::print_diagnostics;
 use fe_common::utils::keccak;
+use fe_common::EvmVersion;
 use fe_driver as driver;
 use primitive_types::{H160, U256};
 use std::cell::RefCell;
@@ -247,6 +248,18 @@ impl ContractHarness {
     }
 }
 
+/// The EVM version that the executor of [`with_executor`] implements.
+pub const EVM_VERSION: EvmVersion = EvmVersion::London;
+
+/// Returns a database that compiles contracts for [`EVM_VERSION`].
+fn new_db() -> driver::Db {
+    use fe_analyzer::AnalyzerDb;
+
+    let mut db = driver::Db::default();
+    db.set_evm_version(EVM_VERSION);
+    db
+}
+
 #[allow(dead_code)]
 pub fn with_executor(test: &dyn Fn(Executor)) {
     let vicinity = evm::backend::MemoryVicinity {
@@ -335,7 +348,7 @@ pub fn deploy_contract(
     contract_name: &str,
     init_params: &[ethabi::Token],
 ) -> ContractHarness {
-    let mut db = driver::Db::default();
+    let mut db = new_db();
     let compiled_module = match driver::compile_single_file(
         &mut db,
         fixture,
@@ -376,7 +389,7 @@ pub fn deploy_contract_from_ingot(
 
     let files = test_files::fixture_dir_files("ingots");
     let build_files = BuildFiles::load_static(files, path).expect("failed to load build files");
-    let mut db = driver::Db::default();
+    let mut db = new_db();
     let compiled_module = match driver::compile_ingot(&mut db, &build_files, true, false, true) {
         Ok(module) => module,
         Err(error) => {
@@ -398,6 +411,54 @@ pub fn deploy_contract_from_ingot(
     )
 }
 
+/// Deploys a contract whose bytecode is compiled by the native assembler
+/// instead of solc.
+#[allow(dead_code)]
+#[cfg(feature = "solc-backend")]
+pub fn deploy_contract_with_native_assembler(
+    executor: &mut Executor,
+    fixture: &str,
+    contract_name: &str,
+    init_params: &[ethabi::Token],
+) -> ContractHarness {
+    use driver::CodegenDb;
+
+    let mut db = new_db();
+    db.set_codegen_memory_guard(true);
+    let compiled_module = match driver::compile_single_file(
+        &mut db,
+        fixture,
+        test_files::fixture(fixture),
+        false,
+        false,
+        true,
+    ) {
+        Ok(module) => module,
+        Err(error) => {
+            fe_common::diagnostics::print_diagnostics(&db, &error.0);
+            panic!("failed to compile module: {fixture}")
+        }
+    };
+
+    let compiled_contract = compiled_module
+        .contracts
+        .get(contract_name)
+        .expect("could not find contract in fixture");
+
+    let yul = fe_driver::unescape_yul(&compiled_contract.yul);
+    let name = db.codegen_contract_deployer_symbol_name(compiled_contract.origin);
+    let bytecode = fe_evm_asm::compile_single_contract(name.as_str(), &yul, EVM_VERSION, false)
+        .unwrap_or_else(|err| panic!("failed to assemble {fixture}: {err}"))
+        .bytecode;
+
+    _deploy_contract(
+        executor,
+        &bytecode,
+        &compiled_contract.json_abi,
+        init_params,
+    )
+}
+
 #[allow(dead_code)]
 #[cfg(feature = "solc-backend")]
 pub fn deploy_solidity_contract(
@@ -587,7 +648,7 @@ pub fn compile_solidity_contract(
 
 #[allow(dead_code)]
 pub fn load_contract(address: H160, fixture: &str, contract_name: &str) -> ContractHarness {
-    let mut db = driver::Db::default();
+    let mut db = new_db();
     let compiled_module = driver::compile_single_file(
         &mut db,
         fixture,
@@ -719,8 +780,9 @@ impl ExecutionOutput {
 #[cfg(feature = "solc-backend")]
 fn execute_runtime_functions(executor: &mut Executor, runtime: &Runtime) -> (ExitReason, Vec<u8>) {
     let yul_code = runtime.to_yul().to_string().replace('"', "\\\"");
-    let contract_bytecode = fe_yulc::compile_single_contract("Contract", &yul_code, false, false)
-        .expect("failed to compile Yul");
+    let contract_bytecode =
+        fe_yulc::compile_single_contract("Contract", &yul_code, false, EVM_VERSION, false)
+            .expect("failed to compile Yul");
     let bytecode = hex::decode(contract_bytecode.bytecode).expect("failed to decode bytecode");
 
     if let evm::Capture::Exit((reason, _, output)) = executor.create(
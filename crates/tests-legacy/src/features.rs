@@ -139,6 +139,19 @@ fn test_revert() {
                 &[uint_token(1), bool_token(true)],
             ),
         );
+
+        validate_revert(
+            harness.capture_call(&mut executor, "revert_insufficient_balance", &[]),
+            &encode_revert(
+                "InsufficientBalance(uint256,uint256)",
+                &[uint_token(10), uint_token(3)],
+            ),
+        );
+
+        validate_revert(
+            harness.capture_call(&mut executor, "revert_denied", &[]),
+            &encode_revert("Denied(string)", &[string_token("not the owner")]),
+        );
     })
 }
 
@@ -430,8 +443,6 @@ fn return_builtin_attributes() {
             deploy_contract(&mut executor, "return_builtin_attributes.fe", "Foo", &[]);
         let sender = address_token("1234000000000000000000000000000000005678");
         harness.caller = sender.clone().into_address().unwrap();
-        let value = 55555;
-        harness.value = U256::from(value);
         harness.test_function(&mut executor, "base_fee", &[], Some(&uint_token(basefee)));
         harness.test_function(&mut executor, "coinbase", &[], Some(&block_coinbase));
         harness.test_function(
@@ -454,7 +465,10 @@ fn return_builtin_attributes() {
         );
         harness.test_function(&mut executor, "chainid", &[], Some(&uint_token(chain_id)));
         harness.test_function(&mut executor, "sender", &[], Some(&sender));
+        let value = 55555;
+        harness.value = U256::from(value);
         harness.test_function(&mut executor, "value", &[], Some(&uint_token(value)));
+        harness.value = U256::zero();
         harness.test_function(&mut executor, "origin", &[], Some(&origin));
         harness.test_function(
             &mut executor,
@@ -1918,6 +1932,28 @@ fn signext_int_array2(method: &str, value: ethabi::Token) {
     })
 }
 
+#[test]
+fn storage_layout() {
+    let mut db = fe_driver::Db::default();
+    let path = "features/storage_layout.fe";
+    let module = match fe_driver::compile_single_file(
+        &mut db,
+        path,
+        test_files::fixture(path),
+        false,
+        false,
+        true,
+    ) {
+        Ok(module) => module,
+        Err(error) => {
+            fe_common::diagnostics::print_diagnostics(&db, &error.0);
+            panic!("failed to compile module: {path}")
+        }
+    };
+
+    assert_snapshot!(module.contracts["Foo"].storage_layout);
+}
+
 #[test]
 fn ctx_param_simple() {
     with_executor(&|mut executor| {
@@ -1940,6 +1976,112 @@ fn ctx_param_internal_func_call() {
     });
 }
 
+#[test]
+fn dispatcher() {
+    with_executor(&|mut executor| {
+        let harness = deploy_contract(&mut executor, "dispatcher.fe", "Foo", &[]);
+
+        // The ten functions are dispatched with a binary search over their
+        // selectors, `get_hot` is checked before all of them.
+        for i in 0..10 {
+            harness.test_function(&mut executor, &format!("get{i}"), &[], Some(&uint_token(i)));
+        }
+        harness.test_function(&mut executor, "get_hot", &[], Some(&uint_token(100)));
+
+        // A selector that doesn't match any function returns nothing.
+        match harness.capture_call_raw_bytes(&mut executor, vec![0xff; 4]) {
+            Capture::Exit((ExitReason::Succeed(_), return_bytes)) => {
+                assert!(return_bytes.is_empty())
+            }
+            Capture::Exit(_) => panic!("call didn't succeed"),
+            Capture::Trap(_) => panic!("trapped!"),
+        }
+    });
+}
+
+#[test]
+fn payable() {
+    with_executor(&|mut executor| {
+        let mut harness = deploy_contract(&mut executor, "payable.fe", "Foo", &[]);
+
+        harness.value = U256::from(100);
+        harness.test_function(&mut executor, "deposit", &[], Some(&uint_token(100)));
+
+        // Functions that aren't `#payable` revert if they're sent a value.
+        validate_revert(harness.capture_call(&mut executor, "balance", &[]), &[]);
+        validate_revert(harness.capture_call(&mut executor, "reset", &[]), &[]);
+
+        harness.value = U256::zero();
+        harness.test_function(&mut executor, "deposit", &[], Some(&uint_token(100)));
+        harness.test_function(&mut executor, "balance", &[], Some(&uint_token(100)));
+        harness.test_function(&mut executor, "reset", &[], None);
+        harness.test_function(&mut executor, "balance", &[], Some(&uint_token(0)));
+    });
+}
+
+#[test]
+fn receive_fallback() {
+    fn call(executor: &mut Executor, harness: &ContractHarness, input: Vec<u8>) {
+        match harness.capture_call_raw_bytes(executor, input) {
+            Capture::Exit((ExitReason::Succeed(_), return_bytes)) => {
+                assert!(return_bytes.is_empty())
+            }
+            Capture::Exit(_) => panic!("call didn't succeed"),
+            Capture::Trap(_) => panic!("trapped!"),
+        }
+    }
+
+    with_executor(&|mut executor| {
+        let mut harness = deploy_contract(&mut executor, "receive_fallback.fe", "Foo", &[]);
+
+        // Empty call data runs `__receive__`, which is always payable.
+        harness.value = U256::from(100);
+        call(&mut executor, &harness, vec![]);
+
+        // `__fallback__` runs if no selector matches, and isn't payable.
+        assert!(matches!(
+            harness.capture_call_raw_bytes(&mut executor, vec![0xff; 4]),
+            Capture::Exit((ExitReason::Revert(_), _))
+        ));
+        harness.value = U256::zero();
+        call(&mut executor, &harness, vec![0xff; 4]);
+        call(&mut executor, &harness, vec![0xff; 4]);
+
+        harness.test_function(&mut executor, "receives", &[], Some(&uint_token(1)));
+        harness.test_function(&mut executor, "fallbacks", &[], Some(&uint_token(2)));
+    });
+}
+
+#[test]
+fn interface() {
+    with_executor(&|mut executor| {
+        let foo_harness = deploy_contract(&mut executor, "interface.fe", "Foo", &[]);
+        let proxy_harness = deploy_contract(&mut executor, "interface.fe", "FooProxy", &[]);
+        let foo_address = ethabi::Token::Address(foo_harness.address);
+
+        proxy_harness.test_function(
+            &mut executor,
+            "add",
+            &[foo_address.clone(), uint_token(5)],
+            Some(&uint_token(5)),
+        );
+        proxy_harness.test_function(
+            &mut executor,
+            "count",
+            &[foo_address.clone()],
+            Some(&uint_token(5)),
+        );
+
+        // `bump` is called with `staticcall`, which reverts if it writes to
+        // storage.
+        validate_revert(
+            proxy_harness.capture_call(&mut executor, "bump", &[foo_address]),
+            &[],
+        );
+        foo_harness.test_function(&mut executor, "count", &[], Some(&uint_token(5)));
+    });
+}
+
 #[test]
 fn ctx_init_in_call() {
     with_executor(&|mut executor| {
@@ -1958,6 +2100,93 @@ fn ctx_init_in_call() {
     });
 }
 
+#[test]
+fn source_map() {
+    use fe_driver::CodegenDb;
+
+    let fixture = "features/return_u256.fe";
+    let mut db = fe_driver::Db::default();
+    db.set_codegen_source_map(true);
+    let module = fe_driver::compile_single_file(
+        &mut db,
+        fixture,
+        test_files::fixture(fixture),
+        true,
+        true,
+        false,
+    )
+    .unwrap();
+
+    let contract = &module.contracts["Foo"];
+    assert!(contract.yul.contains("@use-src 0:"));
+    assert!(contract.yul.contains("@src 0:"));
+    assert!(contract
+        .source_map
+        .contains(r#""file": "features/return_u256.fe""#));
+}
+
+#[test]
+fn memory_guard() {
+    use fe_driver::CodegenDb;
+
+    let fixture = "features/return_array.fe";
+    let compile = |db: &mut fe_driver::Db| {
+        let module = fe_driver::compile_single_file(
+            db,
+            fixture,
+            test_files::fixture(fixture),
+            false,
+            false,
+            true,
+        )
+        .unwrap();
+        module.contracts["Foo"].yul.clone()
+    };
+
+    // The Yul that solc compiles doesn't call `memoryguard`, which would let
+    // solc assume that the code is memory-safe.
+    let mut db = fe_driver::Db::default();
+    assert!(!compile(&mut db).contains("memoryguard"));
+
+    db.set_codegen_memory_guard(true);
+    assert!(compile(&mut db).contains("memoryguard("));
+}
+
+#[test]
+fn code_size_limit() {
+    use fe_driver::CodegenDb;
+
+    // The string is stored in the runtime object, which exceeds the limit of
+    // EIP-170.
+    let len = 0x6000;
+    let src = format!(
+        "contract Big {{\n    pub fn text() -> String<{len}> {{\n        return \"{}\"\n    }}\n}}\n",
+        "a".repeat(len)
+    );
+
+    let mut db = fe_driver::Db::default();
+    let error = fe_driver::compile_single_file(&mut db, "big.fe", &src, true, true, true)
+        .err()
+        .expect("oversized contract was compiled");
+    assert_eq!(error.0.len(), 1);
+    let diag = &error.0[0];
+    assert!(diag
+        .message
+        .starts_with("the runtime bytecode of contract `Big` is"));
+    assert!(diag
+        .message
+        .ends_with("exceeds the limit of 24576 bytes of EIP-170"));
+    assert!(diag.notes[0]
+        .starts_with("the largest Yul functions, by their estimated size before optimization:"));
+    assert!(diag.notes[0].contains(" bytes (estimate)"));
+
+    let mut db = fe_driver::Db::default();
+    db.set_codegen_allow_oversize(true);
+    let module = fe_driver::compile_single_file(&mut db, "big.fe", &src, true, true, true)
+        .unwrap_or_else(|_| panic!("failed to compile with `allow_oversize`"));
+    assert!(module.contracts["Big"].runtime_bytecode.len() / 2 > len);
+}
+
 // These tests are expected to make assertions in Fe only
 #[rstest(
     fixture_file,
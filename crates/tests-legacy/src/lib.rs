@@ -13,6 +13,10 @@ mod differential;
 #[cfg(test)]
 mod features;
 #[cfg(test)]
+mod mcopy;
+#[cfg(test)]
+mod native_assembler;
+#[cfg(test)]
 mod solidity;
 #[cfg(test)]
 mod stress;
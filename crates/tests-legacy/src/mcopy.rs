@@ -0,0 +1,292 @@
+//! Gas benchmarks for memory-to-memory copies. Every contract is compiled for
+//! Shanghai, where copies are word-by-word loops, and for Cancun, where they
+//! are `mcopy` instructions. Both builds run on a Cancun EVM, so they must
+//! return the same values, and the `mcopy` build must not use more gas.
+//!
+//! Run the tests with `--nocapture` to print the gas used by each transaction.
+#![cfg(all(feature = "solc-backend", not(target_arch = "wasm32")))]
+
+use fe_common::EvmVersion;
+use fe_compiler_test_utils::*;
+use fe_driver::AnalyzerDb;
+use revm::primitives::{Address, ExecutionResult, Output, SpecId, TransactTo};
+use revm::{Evm, InMemoryDB};
+
+/// A Cancun chain that deploys contracts compiled for `evm_version`.
+struct Chain {
+    evm: Evm<'static, (), InMemoryDB>,
+    evm_version: EvmVersion,
+    caller: Address,
+    records: Vec<Record>,
+}
+
+/// The outcome of a transaction on a [`Chain`].
+struct Record {
+    description: String,
+    output: Vec<u8>,
+    gas_used: u64,
+}
+
+/// A contract deployed on a [`Chain`].
+struct Contract {
+    address: Address,
+    abi: ethabi::Contract,
+}
+
+impl Chain {
+    fn new(evm_version: EvmVersion) -> Self {
+        let evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .with_spec_id(SpecId::CANCUN)
+            .build();
+
+        Chain {
+            evm,
+            evm_version,
+            caller: Address::from(address(DEFAULT_CALLER).0),
+            records: vec![],
+        }
+    }
+
+    fn compile(&self, fixture: &str, contract_name: &str) -> (Vec<u8>, ethabi::Contract) {
+        let mut db = fe_driver::Db::default();
+        db.set_evm_version(self.evm_version);
+        let module = match fe_driver::compile_single_file(
+            &mut db,
+            fixture,
+            test_files::fixture(fixture),
+            true,
+            false,
+            true,
+        ) {
+            Ok(module) => module,
+            Err(error) => {
+                fe_common::diagnostics::print_diagnostics(&db, &error.0);
+                panic!("failed to compile module: {fixture}")
+            }
+        };
+
+        let contract = module
+            .contracts
+            .get(contract_name)
+            .expect("could not find contract in fixture");
+        let abi =
+            ethabi::Contract::load(contract.json_abi.as_bytes()).expect("unable to load the ABI");
+        let bytecode = hex::decode(&contract.bytecode).expect("failed to decode bytecode");
+        (bytecode, abi)
+    }
+
+    fn deploy(
+        &mut self,
+        fixture: &str,
+        contract_name: &str,
+        init_params: &[ethabi::Token],
+    ) -> Contract {
+        let (mut bytecode, abi) = self.compile(fixture, contract_name);
+        if let Some(constructor) = &abi.constructor {
+            bytecode = constructor.encode_input(bytecode, init_params).unwrap()
+        }
+
+        let description = format!("deploy {contract_name}");
+        match self.transact(TransactTo::create(), bytecode, description) {
+            Output::Create(_, Some(address)) => Contract { address, abi },
+            output => panic!("unable to retrieve contract address: {output:?}"),
+        }
+    }
+
+    /// Returns the contract at `address`, which was deployed by another
+    /// contract.
+    fn load(&self, address: &ethabi::Token, fixture: &str, contract_name: &str) -> Contract {
+        let address = address.clone().into_address().expect("not an address");
+        Contract {
+            address: Address::from(address.0),
+            abi: self.compile(fixture, contract_name).1,
+        }
+    }
+
+    fn call(
+        &mut self,
+        contract: &Contract,
+        name: &str,
+        input: &[ethabi::Token],
+    ) -> Vec<ethabi::Token> {
+        let function = contract.abi.function(name).expect("function not in ABI");
+        let data = function
+            .encode_input(input)
+            .expect("unable to encode input");
+        let description = format!("{name}({input:?})");
+        let output = self.transact(TransactTo::Call(contract.address), data, description);
+        function
+            .decode_output(output.data())
+            .expect("unable to decode output")
+    }
+
+    fn transact(&mut self, to: TransactTo, data: Vec<u8>, description: String) -> Output {
+        let tx = self.evm.tx_mut();
+        tx.caller = self.caller;
+        tx.transact_to = to;
+        tx.data = data.into();
+
+        match self.evm.transact_commit().expect("evm failure") {
+            ExecutionResult::Success {
+                output, gas_used, ..
+            } => {
+                // The deployed code differs between the builds, so only the
+                // address of a new contract is compared.
+                let compared_output = match &output {
+                    Output::Call(data) => data.to_vec(),
+                    Output::Create(_, address) => address.map(|a| a.to_vec()).unwrap_or_default(),
+                };
+                self.records.push(Record {
+                    description,
+                    output: compared_output,
+                    gas_used,
+                });
+                output
+            }
+            result => panic!("{description} failed: {result:?}"),
+        }
+    }
+}
+
+/// Runs `scenario` on a chain with the loop build and on a chain with the
+/// `mcopy` build, and prints the gas used by both.
+fn compare_gas(name: &str, scenario: impl Fn(&mut Chain)) {
+    let mut loops = Chain::new(EvmVersion::Shanghai);
+    scenario(&mut loops);
+    let mut mcopy = Chain::new(EvmVersion::Cancun);
+    scenario(&mut mcopy);
+
+    println!("{name}:");
+    for (loops, mcopy) in loops.records.iter().zip(&mcopy.records) {
+        assert_eq!(
+            loops.output, mcopy.output,
+            "`{}` returns different values with `mcopy`",
+            loops.description
+        );
+        println!(
+            "  {}: {} gas with loops, {} gas with mcopy",
+            loops.description, loops.gas_used, mcopy.gas_used
+        );
+    }
+
+    let total = |chain: &Chain| chain.records.iter().map(|r| r.gas_used).sum::<u64>();
+    let (loops_total, mcopy_total) = (total(&loops), total(&mcopy));
+    println!("  total: {loops_total} gas with loops, {mcopy_total} gas with mcopy");
+    assert!(
+        mcopy_total <= loops_total,
+        "{name} uses more gas with `mcopy`: {mcopy_total} > {loops_total}"
+    );
+}
+
+#[test]
+fn structs() {
+    compare_gas("structs", |chain| {
+        let foo = chain.deploy("features/structs.fe", "Foo", &[]);
+        let house = ethabi::Token::Tuple(vec![
+            uint_token(1000000),
+            uint_token(250),
+            uint_token(6),
+            bool_token(true),
+        ]);
+
+        chain.call(&foo, "create_house", &[]);
+        chain.call(&foo, "bar", &[]);
+        chain.call(&foo, "set_house", &[house]);
+        chain.call(&foo, "get_house", &[]);
+        chain.call(&foo, "encode_house", &[]);
+        chain.call(&foo, "hashed_house", &[]);
+        chain.call(&foo, "create_mixed", &[]);
+        chain.call(&foo, "complex_struct_in_memory", &[]);
+        chain.call(&foo, "complex_struct_in_storage", &[]);
+    })
+}
+
+#[test]
+fn guest_book() {
+    compare_gas("guest_book", |chain| {
+        let guest_book = chain.deploy("demos/guest_book.fe", "GuestBook", &[]);
+        let msg = string_token("little echo");
+
+        chain.call(&guest_book, "sign", &[msg]);
+        chain.call(&guest_book, "get_msg", &[address_token(DEFAULT_CALLER)]);
+    })
+}
+
+#[test]
+fn uniswap() {
+    compare_gas("uniswap", |chain| {
+        let alice = address_token(DEFAULT_CALLER);
+        let bob = address_token("42");
+
+        let token0 = chain.deploy(
+            "demos/erc20_token.fe",
+            "ERC20",
+            &[string_token("Fe Coin"), string_token("fe")],
+        );
+        let token1 = chain.deploy(
+            "demos/erc20_token.fe",
+            "ERC20",
+            &[string_token("Maker"), string_token("mkr")],
+        );
+        chain.call(
+            &token1,
+            "transfer",
+            &[
+                bob.clone(),
+                uint_token_from_dec_str("500000000000000000000000"),
+            ],
+        );
+
+        let factory = chain.deploy(
+            "demos/uniswap.fe",
+            "UniswapV2Factory",
+            &[address_token("0")],
+        );
+        let token0_address = ethabi::Token::Address(token0.address.into_array().into());
+        let token1_address = ethabi::Token::Address(token1.address.into_array().into());
+        let pair_address = chain
+            .call(&factory, "create_pair", &[token0_address, token1_address])
+            .remove(0);
+        let pair = chain.load(&pair_address, "demos/uniswap.fe", "UniswapV2Pair");
+
+        // Alice adds liquidity.
+        chain.call(
+            &token0,
+            "transfer",
+            &[
+                pair_address.clone(),
+                uint_token_from_dec_str("200000000000000000000"),
+            ],
+        );
+        chain.call(
+            &token1,
+            "transfer",
+            &[
+                pair_address.clone(),
+                uint_token_from_dec_str("100000000000000000000"),
+            ],
+        );
+        let alices_liquidity = chain.call(&pair, "mint", &[alice.clone()]).remove(0);
+        chain.call(&pair, "get_reserves", &[]);
+
+        // Bob swaps token1 for token0.
+        chain.caller = Address::from(bob.clone().into_address().unwrap().0);
+        chain.call(
+            &token1,
+            "transfer",
+            &[pair_address.clone(), uint_token(1000)],
+        );
+        chain.call(
+            &pair,
+            "swap",
+            &[uint_token(1993), uint_token(0), bob.clone()],
+        );
+
+        // Alice removes her liquidity.
+        chain.caller = Address::from(address(DEFAULT_CALLER).0);
+        chain.call(&pair, "transfer", &[pair_address, alices_liquidity]);
+        chain.call(&pair, "burn", &[alice]);
+        chain.call(&token0, "balanceOf", &[bob]);
+    })
+}
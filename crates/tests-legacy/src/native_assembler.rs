@@ -0,0 +1,90 @@
+//! Tests that contracts assembled without solc behave like the contracts
+//! compiled by solc.
+#![cfg(feature = "solc-backend")]
+
+use fe_compiler_test_utils::*;
+use rstest::rstest;
+
+/// Deploys the contract with both backends, and checks that every call has
+/// the same outcome on both deployments.
+fn assert_same_behavior(
+    fixture: &str,
+    contract_name: &str,
+    init_params: &[ethabi::Token],
+    calls: &[(&str, Vec<ethabi::Token>)],
+) {
+    with_executor(&|mut executor| {
+        let solc = deploy_contract(&mut executor, fixture, contract_name, init_params);
+        let native = deploy_contract_with_native_assembler(
+            &mut executor,
+            fixture,
+            contract_name,
+            init_params,
+        );
+
+        for (name, input) in calls {
+            assert_eq!(
+                solc.capture_call(&mut executor, name, input),
+                native.capture_call(&mut executor, name, input),
+                "`{name}` behaves differently with input {input:?}"
+            );
+        }
+    })
+}
+
+#[rstest(
+    fixture_file,
+    input,
+    case("for_loop_with_break.fe", vec![]),
+    case("while_loop_with_continue.fe", vec![]),
+    case("if_statement.fe", vec![uint_token(3)]),
+    case("if_statement.fe", vec![uint_token(6)])
+)]
+fn features(fixture_file: &str, input: Vec<ethabi::Token>) {
+    assert_same_behavior(
+        &format!("features/{fixture_file}"),
+        "Foo",
+        &[],
+        &[("bar", input)],
+    );
+}
+
+#[test]
+fn erc20_token() {
+    let alice = address_token(DEFAULT_CALLER);
+    let bob = address_token("2000000000000000000000000000000000000002");
+
+    assert_same_behavior(
+        "demos/erc20_token.fe",
+        "ERC20",
+        &[string_token("Fe Coin"), string_token("fe")],
+        &[
+            ("name", vec![]),
+            ("symbol", vec![]),
+            ("decimals", vec![]),
+            ("totalSupply", vec![]),
+            ("balanceOf", vec![alice.clone()]),
+            ("transfer", vec![bob.clone(), uint_token(42)]),
+            ("balanceOf", vec![bob.clone()]),
+            (
+                "transferFrom",
+                vec![bob.clone(), alice.clone(), uint_token(1)],
+            ),
+            ("approve", vec![bob.clone(), uint_token(10)]),
+            ("allowance", vec![alice, bob]),
+        ],
+    );
+}
+
+#[test]
+fn guest_book() {
+    assert_same_behavior(
+        "demos/guest_book.fe",
+        "GuestBook",
+        &[],
+        &[
+            ("sign", vec![string_token("hello world")]),
+            ("get_msg", vec![address_token(DEFAULT_CALLER)]),
+        ],
+    );
+}
@@ -1,12 +1,26 @@
-#![cfg(feature = "solc-backend")]
 #![allow(dead_code)]
 use std::path::Path;
 
 use dir_test::{dir_test, Fixture};
 use fe_common::diagnostics::print_diagnostics;
 use fe_common::utils::files::BuildFiles;
-use fe_test_runner::TestSink;
+use fe_driver::{InterpretedTest, InterpreterError};
+use fe_test_runner::{FuzzConfig, TestSink};
 
+/// The tests that rely on features the interpreter doesn't model, by the
+/// file name of their fixture. They fail if they're run by the interpreter
+/// after all, so that they're removed from the list once it supports them.
+const INTERPRETER_UNSUPPORTED: &[(&str, &str)] = &[
+    ("precompiles.fe", "test_ec_recover"),
+    ("precompiles.fe", "test_sha2_256"),
+    ("precompiles.fe", "test_ripemd_160"),
+    ("precompiles.fe", "test_ec_add"),
+    ("precompiles.fe", "test_ec_mul"),
+    ("precompiles.fe", "test_ec_pairing"),
+    ("precompiles.fe", "test_blake2f"),
+];
+
+#[cfg(feature = "solc-backend")]
 #[dir_test(dir: "$CARGO_MANIFEST_DIR/fixtures/files", glob: "*.fe")]
 fn single_file_test_run(fixture: Fixture<&str>) {
     let mut db = fe_driver::Db::default();
@@ -27,7 +41,7 @@ fn single_file_test_run(fixture: Fixture<&str>) {
     let mut test_sink = TestSink::new(true);
 
     for test in tests {
-        test.execute(&mut test_sink);
+        test.execute(&mut test_sink, fuzz_config());
     }
 
     if test_sink.failure_count() != 0 {
@@ -35,6 +49,7 @@ fn single_file_test_run(fixture: Fixture<&str>) {
     }
 }
 
+#[cfg(feature = "solc-backend")]
 #[dir_test(dir: "$CARGO_MANIFEST_DIR/fixtures/ingots/", glob: "**/fe.toml")]
 fn ingot_test_run(fixture: Fixture<&str>) {
     let input_path = fixture.path().trim_end_matches("/fe.toml");
@@ -53,7 +68,63 @@ fn ingot_test_run(fixture: Fixture<&str>) {
             let mut sink = TestSink::new(true);
             for (_, tests) in test_batches {
                 for test in tests {
-                    test.execute(&mut sink);
+                    test.execute(&mut sink, fuzz_config());
+                }
+                if sink.failure_count() != 0 {
+                    panic!("{}", sink)
+                }
+            }
+        }
+        Err(error) => {
+            print_diagnostics(&db, &error.0);
+            panic!("Unable to compile {input_path}.");
+        }
+    }
+}
+
+#[dir_test(dir: "$CARGO_MANIFEST_DIR/fixtures/files", glob: "*.fe")]
+fn single_file_test_interpret(fixture: Fixture<&str>) {
+    let mut db = fe_driver::Db::default();
+    let tests = match fe_driver::lower_single_file_tests(&mut db, fixture.path(), fixture.content())
+    {
+        Ok((_, tests)) => tests,
+        Err(error) => {
+            eprintln!("Unable to compile {}.", fixture.path());
+            print_diagnostics(&db, &error.0);
+            panic!("failed to compile tests")
+        }
+    };
+
+    let mut test_sink = TestSink::new(true);
+
+    let fixture_name = file_name(fixture.path());
+    for test in tests {
+        interpret(&db, fixture_name, &test, &mut test_sink);
+    }
+
+    if test_sink.failure_count() != 0 {
+        panic!("{}", test_sink)
+    }
+}
+
+#[dir_test(dir: "$CARGO_MANIFEST_DIR/fixtures/ingots/", glob: "**/fe.toml")]
+fn ingot_test_interpret(fixture: Fixture<&str>) {
+    let input_path = fixture.path().trim_end_matches("/fe.toml");
+
+    if !Path::new(input_path).exists() {
+        panic!("Input directory does not exist: `{input_path}`.");
+    }
+
+    let build_files =
+        BuildFiles::load_fs(input_path).expect("failed to load build files from file system");
+
+    let mut db = fe_driver::Db::default();
+    match fe_driver::lower_ingot_tests(&mut db, &build_files) {
+        Ok(test_batches) => {
+            let mut sink = TestSink::new(true);
+            for (_, tests) in test_batches {
+                for test in tests {
+                    interpret(&db, file_name(input_path), &test, &mut sink);
                 }
                 if sink.failure_count() != 0 {
                     panic!("{}", sink)
@@ -66,3 +137,170 @@ fn ingot_test_run(fixture: Fixture<&str>) {
         }
     }
 }
+
+#[cfg(feature = "solc-backend")]
+#[dir_test(dir: "$CARGO_MANIFEST_DIR/fixtures/files", glob: "*.fe")]
+fn single_file_test_differential(fixture: Fixture<&str>) {
+    let mut compile_db = fe_driver::Db::default();
+    let compiled = match fe_driver::compile_single_file_tests(
+        &mut compile_db,
+        fixture.path(),
+        fixture.content(),
+        true,
+    ) {
+        Ok((_, tests)) => tests,
+        Err(error) => {
+            print_diagnostics(&compile_db, &error.0);
+            panic!("Unable to compile {}.", fixture.path());
+        }
+    };
+
+    let mut lower_db = fe_driver::Db::default();
+    let interpreted = match fe_driver::lower_single_file_tests(
+        &mut lower_db,
+        fixture.path(),
+        fixture.content(),
+    ) {
+        Ok((_, tests)) => tests,
+        Err(error) => {
+            print_diagnostics(&lower_db, &error.0);
+            panic!("Unable to lower {}.", fixture.path());
+        }
+    };
+
+    let mismatches = compare(
+        &lower_db,
+        file_name(fixture.path()),
+        &compiled,
+        &interpreted,
+    );
+    if !mismatches.is_empty() {
+        panic!("{}", mismatches.join("\n"))
+    }
+}
+
+#[cfg(feature = "solc-backend")]
+#[dir_test(dir: "$CARGO_MANIFEST_DIR/fixtures/ingots/", glob: "**/fe.toml")]
+fn ingot_test_differential(fixture: Fixture<&str>) {
+    let input_path = fixture.path().trim_end_matches("/fe.toml");
+    let build_files =
+        BuildFiles::load_fs(input_path).expect("failed to load build files from file system");
+
+    let mut compile_db = fe_driver::Db::default();
+    let compiled = match fe_driver::compile_ingot_tests(&mut compile_db, &build_files, true) {
+        Ok(test_batches) => test_batches,
+        Err(error) => {
+            print_diagnostics(&compile_db, &error.0);
+            panic!("Unable to compile {input_path}.");
+        }
+    };
+
+    let mut lower_db = fe_driver::Db::default();
+    let interpreted = match fe_driver::lower_ingot_tests(&mut lower_db, &build_files) {
+        Ok(test_batches) => test_batches,
+        Err(error) => {
+            print_diagnostics(&lower_db, &error.0);
+            panic!("Unable to lower {input_path}.");
+        }
+    };
+
+    let mut mismatches = vec![];
+    for ((_, compiled), (_, interpreted)) in compiled.iter().zip(&interpreted) {
+        mismatches.extend(compare(
+            &lower_db,
+            file_name(input_path),
+            compiled,
+            interpreted,
+        ));
+    }
+    if !mismatches.is_empty() {
+        panic!("{}", mismatches.join("\n"))
+    }
+}
+
+/// Runs each test with revm and with the interpreter, on the same arguments
+/// if it's a fuzz test, and returns a description of every run whose
+/// outcomes differ. The tests in [`INTERPRETER_UNSUPPORTED`] are skipped.
+#[cfg(feature = "solc-backend")]
+fn compare(
+    db: &fe_driver::Db,
+    fixture_name: &str,
+    compiled: &[fe_driver::CompiledTest],
+    interpreted: &[InterpretedTest],
+) -> Vec<String> {
+    let mut mismatches = vec![];
+    for (compiled, interpreted) in compiled.iter().zip(interpreted) {
+        assert_eq!(compiled.name, interpreted.name);
+        if is_unsupported(fixture_name, &interpreted.name) {
+            eprintln!(
+                "skipping {}: not supported by the interpreter",
+                interpreted.name
+            );
+            continue;
+        }
+
+        let inputs = if interpreted.is_fuzz_test() {
+            fe_test_runner::fuzz_inputs(interpreted.params(), fuzz_config())
+        } else {
+            vec![vec![]]
+        };
+        for calldata in inputs {
+            let expected = compiled.run(calldata.clone());
+            match interpreted.run_with(db, calldata.clone()) {
+                Ok(outcome) if outcome == expected => {}
+                result => mismatches.push(format!(
+                    "{}::{} (calldata 0x{})\n  revm: {expected:?}\n  interpreter: {result:?}",
+                    fixture_name,
+                    interpreted.name,
+                    calldata
+                        .iter()
+                        .map(|byte| format!("{byte:02x}"))
+                        .collect::<String>(),
+                )),
+            }
+        }
+    }
+    mismatches
+}
+
+/// The fuzz tests of the fixtures are run with a fixed seed, so that their
+/// outcome doesn't vary between runs.
+fn fuzz_config() -> FuzzConfig {
+    FuzzConfig::new(FuzzConfig::DEFAULT_RUNS, Some(0))
+}
+
+/// Runs a test of the fixture `fixture_name` with the interpreter. A test
+/// that relies on a feature the interpreter doesn't model fails, unless it's
+/// listed in [`INTERPRETER_UNSUPPORTED`].
+fn interpret(db: &fe_driver::Db, fixture_name: &str, test: &InterpretedTest, sink: &mut TestSink) {
+    if test.is_fuzz_test() {
+        test.execute(db, sink, fuzz_config());
+        return;
+    }
+
+    let result = test.run(db);
+    let unsupported = is_unsupported(fixture_name, &test.name);
+    match &result {
+        Err(InterpreterError::Unsupported(feature)) if unsupported => {
+            eprintln!("skipping {}: {feature} is not supported", test.name);
+        }
+        Ok(_) if unsupported => sink.insert_failure(
+            &test.name,
+            "  the test is listed as unsupported by the interpreter, but ran",
+        ),
+        _ => {
+            test.report(result, sink);
+        }
+    }
+}
+
+fn is_unsupported(fixture_name: &str, test_name: &str) -> bool {
+    INTERPRETER_UNSUPPORTED.contains(&(fixture_name, test_name))
+}
+
+fn file_name(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path)
+}
@@ -1,11 +1,20 @@
+use fe_common::EvmVersion;
 use indexmap::map::IndexMap;
 
+mod source_map;
+
+pub use source_map::{decode_source_map, InstructionSource};
+
 #[derive(Debug)]
 pub struct YulcError(pub String);
 
 pub struct ContractBytecode {
     pub bytecode: String,
     pub runtime_bytecode: String,
+    /// The source map of `bytecode` in the compressed format of solc, which
+    /// refers to the `@src` comments of the Yul code. See [`decode_source_map`].
+    pub source_map: String,
+    pub runtime_source_map: String,
 }
 
 /// Compile a map of Yul contracts to a map of bytecode contracts.
@@ -14,10 +23,11 @@ pub struct ContractBytecode {
 pub fn compile(
     contracts: impl Iterator<Item = (impl AsRef<str>, impl AsRef<str>)>,
     optimize: bool,
+    evm_version: EvmVersion,
 ) -> Result<IndexMap<String, ContractBytecode>, YulcError> {
     contracts
         .map(|(name, yul_src)| {
-            compile_single_contract(name.as_ref(), yul_src.as_ref(), optimize, true)
+            compile_single_contract(name.as_ref(), yul_src.as_ref(), optimize, evm_version, true)
                 .map(|bytecode| (name.as_ref().to_string(), bytecode))
         })
         .collect()
@@ -29,24 +39,31 @@ pub fn compile_single_contract(
     name: &str,
     yul_src: &str,
     optimize: bool,
+    evm_version: EvmVersion,
     verify_runtime_bytecode: bool,
 ) -> Result<ContractBytecode, YulcError> {
     let solc_temp = include_str!("solc_temp.json");
     let input = solc_temp
         .replace("{optimizer_enabled}", &optimize.to_string())
+        .replace("{evm_version}", evm_version.as_str())
         .replace("{src}", yul_src);
     let raw_output = solc::compile(&input);
     let output: serde_json::Value = serde_json::from_str(&raw_output)
         .map_err(|_| YulcError("JSON serialization error".into()))?;
 
-    let bytecode = output["contracts"]["input.yul"][name]["evm"]["bytecode"]["object"]
-        .to_string()
-        .replace('"', "");
-
-    let runtime_bytecode = output["contracts"]["input.yul"][name]["evm"]["deployedBytecode"]
-        ["object"]
+    let evm = &output["contracts"]["input.yul"][name]["evm"];
+    let bytecode = evm["bytecode"]["object"].to_string().replace('"', "");
+    let runtime_bytecode = evm["deployedBytecode"]["object"]
         .to_string()
         .replace('"', "");
+    let source_map = evm["bytecode"]["sourceMap"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let runtime_source_map = evm["deployedBytecode"]["sourceMap"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
 
     if bytecode == "null" {
         return Err(YulcError(output.to_string()));
@@ -58,6 +75,8 @@ pub fn compile_single_contract(
     Ok(ContractBytecode {
         bytecode,
         runtime_bytecode,
+        source_map,
+        runtime_source_map,
     })
 }
 
@@ -67,6 +86,7 @@ pub fn compile_single_contract(
     _name: &str,
     _yul_src: &str,
     _optimize: bool,
+    _evm_version: EvmVersion,
     _verify_runtime_bytecode: bool,
 ) -> Result<ContractBytecode, YulcError> {
     // This is ugly, but required (as far as I can tell) to make
@@ -81,6 +101,7 @@ fn test_solc_sanity() {
     let solc_temp = include_str!("solc_temp.json");
     let input = solc_temp
         .replace("{optimizer_enabled}", "false")
+        .replace("{evm_version}", EvmVersion::London.as_str())
         .replace("{src}", yul_src);
 
     let raw_output = solc::compile(&input);
@@ -92,5 +113,6 @@ fn test_solc_sanity() {
 
     // solc 0.8.4: push1 0; push1 0; sstore  "6000600055"
     // solc 0.8.7: push1 0; dup1;    sstore  "60008055"
+    // `PUSH0` would replace `push1 0` from Shanghai on.
     assert_eq!(bytecode, "60008055", "incorrect bytecode",);
 }
@@ -0,0 +1,128 @@
+/// The source location of an instruction in the bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionSource {
+    /// The offset of the instruction in the bytecode.
+    pub pc: usize,
+    /// The index of the source file, as given in the `@use-src` comment of the
+    /// Yul object.
+    pub source_index: usize,
+    /// A byte offset specifying the inclusive start of the location.
+    pub start: usize,
+    /// A byte offset specifying the exclusive end of the location.
+    pub end: usize,
+}
+
+/// Decodes a source map in the compressed format of solc, and returns the
+/// source location of each instruction in `bytecode` that has one.
+///
+/// The source map has an entry `s:l:f:j:m` for each instruction, where empty
+/// or missing fields repeat the value of the previous entry. See
+/// <https://docs.soliditylang.org/en/latest/internals/source_mappings.html>.
+pub fn decode_source_map(bytecode: &str, source_map: &str) -> Vec<InstructionSource> {
+    let bytecode = decode_hex(bytecode);
+    let mut sources = vec![];
+    let mut pc = 0;
+    // The start, length and source index of the previous entry.
+    let mut location: [i64; 3] = [-1, -1, -1];
+
+    for entry in source_map.split(';') {
+        if pc >= bytecode.len() {
+            break;
+        }
+
+        for (field, value) in entry.split(':').take(3).enumerate() {
+            if let Ok(value) = value.parse() {
+                location[field] = value;
+            }
+        }
+
+        let [start, length, source_index] = location;
+        if start >= 0 && length >= 0 && source_index >= 0 {
+            sources.push(InstructionSource {
+                pc,
+                source_index: source_index as usize,
+                start: start as usize,
+                end: (start + length) as usize,
+            });
+        }
+
+        pc += instruction_size(bytecode[pc]);
+    }
+
+    sources
+}
+
+/// Returns the size of an instruction with `opcode`, including the immediate
+/// value of a `PUSH`.
+fn instruction_size(opcode: u8) -> usize {
+    match opcode {
+        // PUSH1 to PUSH32
+        0x60..=0x7f => (opcode - 0x5f) as usize + 1,
+        _ => 1,
+    }
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    (0..hex.len() / 2)
+        .map_while(|idx| u8::from_str_radix(hex.get(idx * 2..idx * 2 + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() {
+        // PUSH1 0x80, PUSH1 0x40, MSTORE, CALLVALUE, PUSH2 0x0010, JUMPI
+        let bytecode = "6080604052346100105700";
+        let source_map = "-1:-1:-1;;;10:5:0;:8;3:::-;1:2:1";
+
+        assert_eq!(
+            decode_source_map(bytecode, source_map),
+            vec![
+                InstructionSource {
+                    pc: 5,
+                    source_index: 0,
+                    start: 10,
+                    end: 15,
+                },
+                InstructionSource {
+                    pc: 6,
+                    source_index: 0,
+                    start: 10,
+                    end: 18,
+                },
+                InstructionSource {
+                    pc: 9,
+                    source_index: 0,
+                    start: 3,
+                    end: 11,
+                },
+                InstructionSource {
+                    pc: 10,
+                    source_index: 1,
+                    start: 1,
+                    end: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_source() {
+        let bytecode = "60016002";
+        let source_map = "4:2:0;-1:-1:-1";
+
+        assert_eq!(
+            decode_source_map(bytecode, source_map),
+            vec![InstructionSource {
+                pc: 0,
+                source_index: 0,
+                start: 4,
+                end: 6,
+            }]
+        );
+    }
+}